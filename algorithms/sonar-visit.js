@@ -46,8 +46,10 @@
     const center = { x: 0.5, y: 0.5 };
     angleBuckets.forEach((bucketPoints, key) => {
       bucketPoints.sort((a, b) => {
-        const distA = Math.hypot(a.x - center.x, a.y - center.y);
-        const distB = Math.hypot(b.x - center.x, b.y - center.y);
+        const ax = a.x - center.x, ay = a.y - center.y;
+        const bx = b.x - center.x, by = b.y - center.y;
+        const distA = Math.sqrt(ax * ax + ay * ay);
+        const distB = Math.sqrt(bx * bx + by * by);
         return distA - distB;
       });
     });
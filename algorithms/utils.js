@@ -23,7 +23,10 @@
    * @returns {number} Distance between points
    */
   function distance(start, end) {
-    return Math.hypot(end.y - start.y, end.x - start.x);
+    // Same operations as the Rust version so equal grid distances tie identically
+    const dx = end.x - start.x;
+    const dy = end.y - start.y;
+    return Math.sqrt(dx * dx + dy * dy);
   }
 
   /**
@@ -115,10 +118,11 @@
    * @returns {Array} Array of point objects with x, y, angle, and id
    */
   function generateNormalizedPoints(numPoints, gridSize = 40, seed = 12345) {
-    // Simple LCG for reproducible results
+    // Simple LCG for reproducible results; Math.imul keeps the product exact
+    // (a float multiply would round past 2^53), matching the Rust Lcg
     let currentSeed = seed;
     const rng = () => {
-      currentSeed = (currentSeed * 1103515245 + 12345) % (2 ** 31);
+      currentSeed = (Math.imul(currentSeed, 1103515245) + 12345) & 0x7fffffff;
       return currentSeed / (2 ** 31);
     };
 
@@ -15,7 +15,9 @@
   'use strict';
 
   const distance = TSPUtils ? TSPUtils.distance : function(start, end) {
-    return Math.hypot(end.y - start.y, end.x - start.x);
+    const dx = end.x - start.x;
+    const dy = end.y - start.y;
+    return Math.sqrt(dx * dx + dy * dy);
   };
 
   /**
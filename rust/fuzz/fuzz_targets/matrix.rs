@@ -0,0 +1,48 @@
+//! Arbitrary distance matrices into every matrix-based solver
+//! Matrices may be asymmetric, contain NaN, negative or infinite entries; each
+//! solver must return an error or a valid tour, never panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::time::Duration;
+use tsp_algorithms::error::{validate_tour, SonarError};
+use tsp_algorithms::sanitize::{sanitize_matrix, Limits};
+use tsp_algorithms::{
+    branch_and_bound, brute_force, genetic, greedy_edge, nearest_neighbor, or_one, simulated_annealing,
+    two_h_opt, two_opt,
+};
+
+/// Keeps each input fast enough for the fuzzer to make progress
+const MAX_CITIES: usize = 24;
+
+fuzz_target!(|input: (Vec<f64>, u8)| {
+    let (values, iterations) = input;
+    let n = (values.len() as f64).sqrt() as usize;
+    let n = n.min(MAX_CITIES);
+    let graph: Vec<Vec<f64>> = (0..n).map(|i| values[i * n..(i + 1) * n].to_vec()).collect();
+    let iterations = Limits::default().clamp_iterations(iterations as usize);
+    let sanitized = sanitize_matrix(&graph, &Limits::default()).is_ok();
+
+    // Matrices that fail sanitization must be rejected by every solver too
+    let check = |name: &str, result: Result<Vec<usize>, SonarError>| match result {
+        Ok(tour) => {
+            assert!(sanitized, "{} accepted a matrix sanitization rejects", name);
+            validate_tour(&tour, n).unwrap_or_else(|e| panic!("{}: {}", name, e));
+        }
+        Err(_) => {}
+    };
+    let identity: Vec<usize> = (0..n).collect();
+    check("nearest-neighbor", nearest_neighbor::generate_tour(n, &graph, 0));
+    check("greedy-edge", greedy_edge::generate_tour(n, &graph));
+    check("two-opt", two_opt::improve(&identity, &graph, iterations));
+    check("two-h-opt", two_h_opt::improve(&identity, &graph, 8, iterations));
+    check("or-one", or_one::improve(&identity, &graph, 8, iterations));
+    check("simulated-annealing", simulated_annealing::optimize(&graph, &identity, iterations * 10, 1.0, 0.99));
+    check("genetic", genetic::optimize(&graph, n, 8, iterations.min(20), 0.1));
+    if n <= 10 {
+        check("held-karp", brute_force::held_karp(&graph, n).map(|(tour, _)| tour));
+    }
+    let limit = Some(Duration::from_millis(50));
+    check("branch-and-bound", branch_and_bound::solve(&graph, limit).map(|(tour, ..)| tour));
+});
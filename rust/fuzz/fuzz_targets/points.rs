@@ -0,0 +1,38 @@
+//! Arbitrary point sets into every registered solver
+//! Raw points must be rejected or solved without panicking; sanitized ones must
+//! be solved by every algorithm into a valid tour.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tsp_algorithms::error::validate_tour;
+use tsp_algorithms::problem::{Metric, ProblemInstance};
+use tsp_algorithms::registry;
+use tsp_algorithms::sanitize::{sanitize_points, Limits};
+use tsp_algorithms::utils::Point;
+use tsp_algorithms::{angular_sort, karp_partition, quadtree, sonar_visit};
+
+/// Keeps each input fast enough for the fuzzer to make progress
+const MAX_POINTS: usize = 48;
+
+fuzz_target!(|coords: Vec<(f64, f64, f64)>| {
+    let raw: Vec<Point> = coords
+        .iter()
+        .take(MAX_POINTS)
+        .enumerate()
+        .map(|(id, &(x, y, angle))| Point { x, y, angle, id })
+        .collect();
+    let _ = angular_sort::generate_tour(&raw);
+    let _ = sonar_visit::generate_tour(&raw, 40);
+    let _ = karp_partition::generate_tour(&raw, 8);
+    let _ = quadtree::generate_tour(&raw, 8);
+
+    let Ok(points) = sanitize_points(&raw, &Limits::default()) else { return };
+    let n = points.len();
+    let instance = ProblemInstance::new(points, Metric::Euclidean).unwrap();
+    // Exact solvers only on inputs small enough to finish quickly
+    for algorithm in registry::ALGORITHMS.iter().filter(|a| a.max_n.is_none_or(|max| n <= max.min(9))) {
+        let tour = (algorithm.solve)(&instance).unwrap_or_else(|e| panic!("{}: {}", algorithm.key, e));
+        validate_tour(&tour, n).unwrap_or_else(|e| panic!("{}: {}", algorithm.key, e));
+    }
+});
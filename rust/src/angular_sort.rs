@@ -1,14 +1,82 @@
 //! Angular Sort algorithm - O(n log n) sorting-based approach
 
-use crate::utils::Point;
+use alloc::vec::Vec;
+use crate::error::{validate_points, SonarError};
+use crate::math;
+use crate::utils::{distance_coords, Point};
 
 /// Generate a tour by sorting points by their angle from center
 /// Points are connected sequentially after sorting
 /// Time complexity: O(n log n) dominated by the sort
-pub fn generate_tour(points: &[Point]) -> Vec<usize> {
+pub fn generate_tour(points: &[Point]) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
     let mut sorted_points: Vec<&Point> = points.iter().collect();
-    sorted_points.sort_by(|a, b| a.angle.partial_cmp(&b.angle).unwrap());
-    sorted_points.iter().map(|p| p.id).collect()
+    // Ties are broken by id so the order never depends on input layout
+    sorted_points.sort_by(|a, b| a.angle.total_cmp(&b.angle).then(a.id.cmp(&b.id)));
+    Ok(sorted_points.iter().map(|p| p.id).collect())
+}
+
+/// Generate a tour by angular sort around several centres instead of one
+/// The bounding square is cut into `grid_size` x `grid_size` cells and the
+/// points of each non-empty cell form a cluster. Clusters are visited in the
+/// angular order of their centroids around the centroid of all points, and
+/// inside a cluster points are sorted by angle around its own centroid,
+/// starting from the one nearest to where the previous cluster was left. On
+/// clustered instances a single centre sweeps back and forth between
+/// clusters; here each is swept once. Points are grouped by sorting them by
+/// cell, so memory stays O(n) however fine the grid.
+/// Time complexity: O(n log n) dominated by the sorts
+pub fn generate_tour_multi_center(points: &[Point], grid_size: usize) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
+    if grid_size == 0 {
+        return Err(SonarError::InvalidParameter { name: "grid_size", reason: "must be positive" });
+    }
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for p in points {
+        min_x = min_x.min(p.x);
+        min_y = min_y.min(p.y);
+        max_x = max_x.max(p.x);
+        max_y = max_y.max(p.y);
+    }
+    let size = (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+    let to_cell = |v: f64, min: f64| (((v - min) / size * grid_size as f64) as usize).min(grid_size - 1);
+
+    // Sorted by (row, column), the points of each non-empty cell form one run
+    let mut by_cell: Vec<((usize, usize), usize)> =
+        points.iter().enumerate().map(|(i, p)| ((to_cell(p.y, min_y), to_cell(p.x, min_x)), i)).collect();
+    by_cell.sort_unstable();
+    let cells = by_cell.chunk_by(|a, b| a.0 == b.0).map(|run| run.iter().map(|&(_, i)| i).collect::<Vec<usize>>());
+    let centroid = |members: &[usize]| {
+        let (x, y) = members.iter().fold((0.0, 0.0), |(x, y), &i| (x + points[i].x, y + points[i].y));
+        (x / members.len() as f64, y / members.len() as f64)
+    };
+    // Order the clusters around the overall centroid, ties by cell
+    let all: Vec<usize> = (0..points.len()).collect();
+    let (cx, cy) = centroid(&all);
+    let mut clusters: Vec<(f64, (f64, f64), Vec<usize>)> = cells
+        .map(|members| {
+            let (x, y) = centroid(&members);
+            (math::atan2(y - cy, x - cx), (x, y), members)
+        })
+        .collect();
+    clusters.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut tour: Vec<usize> = Vec::with_capacity(points.len());
+    for (_, (x, y), members) in clusters {
+        let mut keyed: Vec<(f64, usize)> =
+            members.into_iter().map(|i| (math::atan2(points[i].y - y, points[i].x - x), i)).collect();
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        let mut members: Vec<usize> = keyed.into_iter().map(|(_, i)| i).collect();
+        if let Some(&last) = tour.last() {
+            let (x, y) = (points[last].x, points[last].y);
+            let gap = |i: usize| distance_coords(x, y, points[i].x, points[i].y);
+            let start = (0..members.len()).min_by(|&a, &b| gap(members[a]).total_cmp(&gap(members[b])));
+            members.rotate_left(start.unwrap_or(0));
+        }
+        tour.extend(members);
+    }
+    Ok(tour)
 }
 
 #[cfg(test)]
@@ -25,7 +93,7 @@ mod tests {
             Point { x: 0.0, y: 1.0, angle: PI / 2.0, id: 3 },
         ];
 
-        let tour = generate_tour(&points);
+        let tour = generate_tour(&points).unwrap();
 
         assert_eq!(tour.len(), points.len());
 
@@ -35,4 +103,23 @@ mod tests {
             assert!(seen.insert(id));
         }
     }
+
+    #[test]
+    fn test_multi_center_sweeps_each_cluster_once() {
+        use crate::error::validate_tour;
+        use crate::layouts::{self, Layout};
+        use crate::utils::{calculate_tour_length, create_distance_matrix};
+
+        let points = layouts::generate(Layout::TwoClusters, 400, 2);
+        let graph = create_distance_matrix(&points);
+        let single = calculate_tour_length(&generate_tour(&points).unwrap(), &graph);
+        let multi = generate_tour_multi_center(&points, 4).unwrap();
+        validate_tour(&multi, points.len()).unwrap();
+        assert!(calculate_tour_length(&multi, &graph) < 0.7 * single);
+        // One cell is one centre, the plain angular sort around the centroid
+        validate_tour(&generate_tour_multi_center(&points, 1).unwrap(), points.len()).unwrap();
+        assert!(generate_tour_multi_center(&points, 0).is_err());
+        // A grid far finer than the points only has as many cells as points
+        validate_tour(&generate_tour_multi_center(&points, 1 << 30).unwrap(), points.len()).unwrap();
+    }
 }
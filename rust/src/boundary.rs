@@ -0,0 +1,271 @@
+//! Model-guided search for the largest n a solver handles within a time limit
+//!
+//! Every probe near the limit costs about the limit itself, so instead of
+//! doubling and then bisecting, the search fits a time model to the probes so
+//! far and probes where the model puts the boundary. Once the boundary is
+//! bracketed the model's guesses refine it like a secant method; a guess that
+//! fails to halve the bracket is followed by a plain bisection step, so the
+//! search never needs more than about twice the probes of binary search.
+
+use crate::complexity::MIN_TIME_MS;
+use std::time::Duration;
+
+/// Furthest the search jumps past the largest passing n before any probe
+/// fails, as a multiple of it
+const GROWTH: usize = 8;
+
+/// Share of the limit aimed at before any probe fails, so a model that
+/// underestimates the growth does not overshoot by a whole extra limit
+const AIM: f64 = 0.8;
+
+/// Probes, largest n first, the model is fitted to; smaller ones are dominated
+/// by fixed overheads that do not grow with n
+const FIT_RUNS: usize = 4;
+
+/// Runtime as a function of n, fitted to timed runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeModel {
+    /// time = exp(a) * n^k
+    Power { a: f64, k: f64 },
+    /// time = exp(a + b * n), as for the exact solvers
+    Exponential { a: f64, b: f64 },
+}
+
+impl TimeModel {
+    /// The better of a power law and an exponential fitted by least squares to
+    /// log(time) of the largest `(n, time_ms)` runs
+    /// Needs two usable runs at distinct sizes; with only two, both models fit
+    /// exactly and the exponential is kept, as it predicts the slower growth
+    /// ahead and so the shorter, safer jump.
+    /// Time complexity: O(m log m) for m runs
+    pub fn fit(runs: &[(usize, f64)]) -> Option<TimeModel> {
+        let mut usable: Vec<(usize, f64)> =
+            runs.iter().copied().filter(|&(n, ms)| n > 0 && ms >= MIN_TIME_MS && ms.is_finite()).collect();
+        usable.sort_by_key(|run| std::cmp::Reverse(run.0));
+        usable.dedup_by_key(|run| run.0);
+        // A run slower than a larger one is dominated by overheads
+        let mut fastest = f64::INFINITY;
+        usable.retain(|&(_, ms)| {
+            let growing = ms < fastest;
+            fastest = fastest.min(ms);
+            growing
+        });
+        usable.truncate(FIT_RUNS);
+        if usable.len() < 2 {
+            return None;
+        }
+
+        let power = least_squares(usable.iter().map(|&(n, ms)| ((n as f64).ln(), ms.ln())));
+        let exponential = least_squares(usable.iter().map(|&(n, ms)| (n as f64, ms.ln())));
+        let ((a, k, power_error), (c, b, exponential_error)) = (power?, exponential?);
+        if power_error < exponential_error * 0.999 {
+            Some(TimeModel::Power { a, k })
+        } else {
+            Some(TimeModel::Exponential { a: c, b })
+        }
+    }
+
+    /// Predicted time in ms at `n`
+    pub fn time_ms(&self, n: usize) -> f64 {
+        match *self {
+            TimeModel::Power { a, k } => (a + k * (n as f64).ln()).exp(),
+            TimeModel::Exponential { a, b } => (a + b * n as f64).exp(),
+        }
+    }
+
+    /// Largest n predicted to take at most `ms`, or `None` if the model does not
+    /// grow with n
+    pub fn n_within(&self, ms: f64) -> Option<f64> {
+        let n = match *self {
+            TimeModel::Power { a, k } if k > 0.0 => ((ms.ln() - a) / k).exp(),
+            TimeModel::Exponential { a, b } if b > 0.0 => (ms.ln() - a) / b,
+            _ => return None,
+        };
+        n.is_finite().then_some(n)
+    }
+}
+
+/// (intercept, slope, sum of squared residuals) of the least squares line
+fn least_squares(points: impl Iterator<Item = (f64, f64)> + Clone) -> Option<(f64, f64, f64)> {
+    let m = points.clone().count() as f64;
+    let mean_x = points.clone().map(|p| p.0).sum::<f64>() / m;
+    let mean_y = points.clone().map(|p| p.1).sum::<f64>() / m;
+    let sxx: f64 = points.clone().map(|p| (p.0 - mean_x).powi(2)).sum();
+    if sxx <= 0.0 {
+        return None;
+    }
+    let slope = points.clone().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum::<f64>() / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let residuals = points.map(|p| (p.1 - intercept - slope * p.0).powi(2)).sum();
+    Some((intercept, slope, residuals))
+}
+
+/// State of a search for the largest n in `[min_n, max_n]` whose run stays
+/// within the limit
+/// Call `next` for the n to probe and report its outcome with `passed` or
+/// `failed`, until `next` returns `None`; `best` is then the answer. The
+/// smallest n is taken to pass even when it does not, as there is nothing
+/// smaller to fall back to.
+#[derive(Debug, Clone)]
+pub struct BoundarySearch {
+    limit_ms: f64,
+    min_n: usize,
+    max_n: usize,
+    /// Largest n known to pass
+    low: usize,
+    /// Smallest n known to fail, if any has
+    high: Option<usize>,
+    /// (n, time in ms) of every probe
+    runs: Vec<(usize, f64)>,
+    /// Whether the last model guess left more than half the bracket
+    bisect: bool,
+    probed: bool,
+}
+
+impl BoundarySearch {
+    pub fn new(min_n: usize, max_n: usize, limit: Duration) -> Self {
+        BoundarySearch {
+            limit_ms: limit.as_secs_f64() * 1000.0,
+            min_n,
+            max_n: max_n.max(min_n),
+            low: min_n,
+            high: None,
+            runs: Vec::new(),
+            bisect: false,
+            probed: false,
+        }
+    }
+
+    /// The n to probe next, or `None` once the boundary is found
+    pub fn next(&self) -> Option<usize> {
+        if !self.probed {
+            return Some(self.min_n);
+        }
+        let model = TimeModel::fit(&self.runs);
+        match self.high {
+            None if self.low >= self.max_n => None,
+            None => {
+                // Doubling until the runs are long enough to fit a model, then
+                // to `AIM` of the limit, and once about there just past the
+                // predicted boundary to bracket it, by at most half again as
+                // noisy timings can flatten the model into a far jump
+                let low_ms = self.runs.iter().rev().find(|run| run.0 == self.low).map_or(0.0, |run| run.1);
+                let close = low_ms >= self.limit_ms * AIM * AIM;
+                let growth = if close { self.low + self.low / 2 } else { self.low.saturating_mul(GROWTH) };
+                let furthest = growth.min(self.max_n);
+                let share = if close { 1.0 } else { AIM };
+                // A model that does not grow, from timings still dominated by
+                // noise, says nothing about how far it is safe to jump
+                let guess = match model.and_then(|model| model.n_within(self.limit_ms * share)) {
+                    Some(n) => n.min(furthest as f64) as usize + close as usize,
+                    None => self.low.saturating_mul(2).min(furthest),
+                };
+                Some(guess.clamp(self.low + 1, furthest.max(self.low + 1)))
+            }
+            Some(high) if high <= self.low + 1 => None,
+            Some(high) => {
+                let middle = self.low + (high - self.low) / 2;
+                let guess = match model.and_then(|model| model.n_within(self.limit_ms)) {
+                    Some(n) if !self.bisect => n as usize,
+                    _ => middle,
+                };
+                Some(guess.clamp(self.low + 1, high - 1))
+            }
+        }
+    }
+
+    /// `n` ran within the limit in `ms`
+    pub fn passed(&mut self, n: usize, ms: f64) {
+        self.runs.push((n, ms));
+        self.narrow(n, true);
+    }
+
+    /// `n` did not pass: it ran over the limit in `ms`, or gave no usable
+    /// timing (an invalid tour, or too little memory to run it)
+    pub fn failed(&mut self, n: usize, ms: Option<f64>) {
+        if let Some(ms) = ms {
+            self.runs.push((n, ms));
+        }
+        self.narrow(n, false);
+    }
+
+    fn narrow(&mut self, n: usize, passed: bool) {
+        let before = self.high.map(|high| high - self.low);
+        if !self.probed {
+            self.probed = true;
+            if !passed {
+                // Nothing smaller to try
+                self.high = Some(self.min_n);
+                return;
+            }
+        }
+        if passed {
+            self.low = self.low.max(n);
+        } else {
+            self.high = Some(self.high.map_or(n, |high| high.min(n)));
+        }
+        let after = self.high.map(|high| high.saturating_sub(self.low));
+        self.bisect = match (before, after) {
+            (Some(before), Some(after)) => !self.bisect && after * 2 > before,
+            _ => false,
+        };
+    }
+
+    /// Largest n known to pass
+    pub fn best(&self) -> usize {
+        self.low
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Probes `search` takes against `time_ms(n)` and its answer
+    fn run(mut search: BoundarySearch, time_ms: impl Fn(usize) -> f64) -> (usize, usize) {
+        let mut probes = 0;
+        while let Some(n) = search.next() {
+            probes += 1;
+            let ms = time_ms(n);
+            if ms <= 1000.0 {
+                search.passed(n, ms);
+            } else {
+                search.failed(n, Some(ms));
+            }
+        }
+        (search.best(), probes)
+    }
+
+    #[test]
+    fn test_search_finds_the_boundary_in_few_probes() {
+        let second = Duration::from_secs(1);
+        // Quadratic: 1000 ms at n = 10_000
+        let quadratic = |n: usize| (n * n) as f64 / 100_000.0;
+        let (best, probes) = run(BoundarySearch::new(10, 1_000_000, second), quadratic);
+        assert_eq!(best, 10_000);
+        // Doubling from 10 and bisecting would take about 25 probes
+        assert!(probes <= 14, "{} probes", probes);
+
+        // Exponential: 2^n microseconds, 1000 ms just under n = 20
+        let exponential = |n: usize| 2f64.powi(n as i32) / 1000.0;
+        assert_eq!(run(BoundarySearch::new(4, 40, second), exponential).0, 19);
+        // Capped by max_n, or stuck at a min_n that is already too slow
+        assert_eq!(run(BoundarySearch::new(10, 500, second), quadratic).0, 500);
+        assert_eq!(run(BoundarySearch::new(20_000, 50_000, second), quadratic), (20_000, 1));
+
+        let model = TimeModel::fit(&[(100, 1.0), (200, 4.0), (400, 16.0)]).unwrap();
+        assert!(matches!(model, TimeModel::Power { k, .. } if (k - 2.0).abs() < 1e-9));
+        assert!((model.n_within(100.0).unwrap() - 1000.0).abs() < 1e-6);
+        assert!((model.time_ms(800) - 64.0).abs() < 1e-9);
+        assert_eq!(TimeModel::fit(&[(100, 1.0), (100, 2.0)]), None);
+
+        // Times falling with n, as fixed overheads can make them, give no jump
+        // and are left out of later fits
+        let mut search = BoundarySearch::new(4, 23, second);
+        search.passed(4, 0.3);
+        search.passed(8, 0.15);
+        assert_eq!(search.next(), Some(16));
+        let rising = TimeModel::fit(&[(8, 0.15), (16, 40.0)]).unwrap();
+        assert_eq!(TimeModel::fit(&[(4, 0.3), (8, 0.15), (16, 40.0)]), Some(rising));
+    }
+}
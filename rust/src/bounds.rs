@@ -0,0 +1,146 @@
+//! Lower bounds on the optimal tour length, for gap estimates where no exact
+//! method can run
+
+use alloc::{vec, vec::Vec};
+use crate::error::{validate_graph, SonarError};
+use crate::utils::{calculate_tour_length, minimum_spanning_tree, minimum_spanning_tree_by};
+
+/// Step size as a fraction of the gap to the double-tree tour, which is loose
+/// by up to a factor of two, so the first steps stay small
+const INITIAL_STEP_SCALE: f64 = 0.1;
+
+/// Iterations without a better bound before the step scale is halved
+const PATIENCE: usize = 10;
+
+/// Lowest step scale before the ascent is considered converged
+const MIN_STEP_SCALE: f64 = 1e-6;
+
+/// Held-Karp lower bound by Lagrangian (subgradient) ascent over 1-trees
+/// Each iteration builds a minimum 1-tree (an MST over cities 1..n plus the
+/// two cheapest edges at city 0) under edge costs `d(i, j) + pi_i + pi_j`,
+/// whose cost minus `2 * sum(pi)` never exceeds the optimal tour length. The
+/// penalties `pi` then move along the degree excess `degree - 2`, with a step
+/// sized by the gap to a double-tree tour; the step is halved whenever the
+/// bound stops improving for a while. The best bound seen is returned, and the
+/// ascent stops early once the 1-tree is itself a tour, which is then optimal.
+/// At least one 1-tree is evaluated, so `iterations = 0` gives the plain
+/// 1-tree bound.
+/// Time complexity: O(iterations * n^2)
+pub fn held_karp_ascent(graph: &[Vec<f64>], iterations: usize) -> Result<f64, SonarError> {
+    validate_graph(graph)?;
+    let n = graph.len();
+    if n < 3 {
+        return Ok(calculate_tour_length(&(0..n).collect::<Vec<_>>(), graph));
+    }
+
+    let upper = calculate_tour_length(&double_tree_tour(graph), graph);
+    let mut penalties = vec![0.0; n];
+    let mut best = f64::NEG_INFINITY;
+    let mut step_scale = INITIAL_STEP_SCALE;
+    let mut stalled = 0;
+    for _ in 0..iterations.max(1) {
+        let (bound, degrees) = one_tree(graph, &penalties);
+        if bound > best {
+            best = bound;
+            stalled = 0;
+        } else {
+            stalled += 1;
+            if stalled == PATIENCE {
+                step_scale /= 2.0;
+                stalled = 0;
+            }
+        }
+        let norm: f64 = degrees.iter().map(|&d| (d as f64 - 2.0) * (d as f64 - 2.0)).sum();
+        if norm == 0.0 || !upper.is_finite() || step_scale < MIN_STEP_SCALE {
+            break;
+        }
+        let step = step_scale * (upper - bound).max(0.0) / norm;
+        if step == 0.0 {
+            break;
+        }
+        for (penalty, &degree) in penalties.iter_mut().zip(&degrees) {
+            *penalty += step * (degree as f64 - 2.0);
+        }
+    }
+    Ok(best)
+}
+
+/// Cost of the minimum 1-tree under `penalties`, minus `2 * sum(penalties)`,
+/// and the degree of every city in it
+fn one_tree(graph: &[Vec<f64>], penalties: &[f64]) -> (f64, Vec<usize>) {
+    let n = graph.len();
+    let cost = |i: usize, j: usize| graph[i][j] + penalties[i] + penalties[j];
+    let tree = minimum_spanning_tree_by(n - 1, |a, b| cost(a + 1, b + 1));
+    let mut degrees = vec![0; n];
+    for &(a, b) in &tree.edges {
+        degrees[a + 1] += 1;
+        degrees[b + 1] += 1;
+    }
+
+    // The two cheapest edges at city 0: (cost, city)
+    let (mut first, mut second) = ((f64::INFINITY, 1), (f64::INFINITY, 2));
+    for j in 1..n {
+        let c = cost(0, j);
+        if c < first.0 {
+            second = first;
+            first = (c, j);
+        } else if c < second.0 {
+            second = (c, j);
+        }
+    }
+    degrees[0] = 2;
+    degrees[first.1] += 1;
+    degrees[second.1] += 1;
+
+    let total = tree.weight + first.0 + second.0;
+    (total - 2.0 * penalties.iter().sum::<f64>(), degrees)
+}
+
+/// Preorder walk of the minimum spanning tree, at most twice the optimal length
+/// under the triangle inequality
+fn double_tree_tour(graph: &[Vec<f64>]) -> Vec<usize> {
+    let n = graph.len();
+    let mut children = vec![Vec::new(); n];
+    for (parent, child) in minimum_spanning_tree(graph).edges {
+        children[parent].push(child);
+    }
+    let mut tour = Vec::with_capacity(n);
+    let mut stack = vec![0];
+    while let Some(city) = stack.pop() {
+        tour.push(city);
+        stack.extend(children[city].iter().rev());
+    }
+    tour
+}
+
+#[cfg(all(test, feature = "exact", feature = "constructive", feature = "local-search"))]
+mod tests {
+    use super::*;
+    use crate::branch_and_bound::one_tree_bound;
+    use crate::brute_force;
+    use crate::utils::{create_distance_matrix, generate_normalized_points, Point};
+
+    #[test]
+    fn test_ascent_tightens_the_one_tree_bound() {
+        for seed in [1, 2, 3] {
+            let graph = create_distance_matrix(&generate_normalized_points(10, 20, seed));
+            let (_, optimal) = brute_force::held_karp(&graph, 10).unwrap();
+            let plain = held_karp_ascent(&graph, 0).unwrap();
+            let ascended = held_karp_ascent(&graph, 200).unwrap();
+            assert!((plain - one_tree_bound(&graph).unwrap()).abs() < 1e-9);
+            assert!(plain <= ascended && ascended <= optimal + 1e-9, "{} {} {}", plain, ascended, optimal);
+            assert!(ascended >= 0.95 * optimal, "{} vs {}", ascended, optimal);
+        }
+
+        // On a convex polygon the bound reaches the optimum, the perimeter
+        let hexagon: Vec<Point> = (0..6)
+            .map(|i| {
+                let angle = i as f64 * core::f64::consts::PI / 3.0;
+                Point { x: angle.cos(), y: angle.sin(), angle, id: i }
+            })
+            .collect();
+        let bound = held_karp_ascent(&create_distance_matrix(&hexagon), 100).unwrap();
+        assert!((bound - 6.0).abs() < 1e-6, "{}", bound);
+        assert!(held_karp_ascent(&[], 10).is_err());
+    }
+}
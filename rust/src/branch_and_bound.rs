@@ -0,0 +1,210 @@
+//! Branch and Bound exact solver with 1-tree bounds and an optimality certificate
+
+use crate::error::{validate_graph, SonarError};
+use crate::utils::calculate_tour_length;
+use crate::{nearest_neighbor, two_opt};
+use std::time::{Duration, Instant};
+
+/// 1-tree lower bound: MST over cities 1..n plus the two cheapest edges at city 0
+/// Every tour is a 1-tree, so this never exceeds the optimal tour length
+pub fn one_tree_bound(graph: &[Vec<f64>]) -> Result<f64, SonarError> {
+    validate_graph(graph)?;
+    let n = graph.len();
+    if n < 3 {
+        return Ok(calculate_tour_length(&(0..n).collect::<Vec<_>>(), graph));
+    }
+
+    let others: Vec<usize> = (1..n).collect();
+    let mut edges_at_zero: Vec<f64> = (1..n).map(|j| graph[0][j]).collect();
+    edges_at_zero.sort_by(|a, b| a.total_cmp(b));
+
+    Ok(mst_weight(graph, &others) + edges_at_zero[0] + edges_at_zero[1])
+}
+
+/// Weight of the minimum spanning tree over a subset of cities (Prim, O(m^2))
+fn mst_weight(graph: &[Vec<f64>], cities: &[usize]) -> f64 {
+    let m = cities.len();
+    if m < 2 {
+        return 0.0;
+    }
+
+    let mut in_tree = vec![false; m];
+    let mut min_edge = vec![f64::INFINITY; m];
+    min_edge[0] = 0.0;
+    let mut weight = 0.0;
+
+    for _ in 0..m {
+        let mut u = usize::MAX;
+        for k in 0..m {
+            if !in_tree[k] && (u == usize::MAX || min_edge[k] < min_edge[u]) {
+                u = k;
+            }
+        }
+        in_tree[u] = true;
+        weight += min_edge[u];
+        for k in 0..m {
+            let d = graph[cities[u]][cities[k]];
+            if !in_tree[k] && d < min_edge[k] {
+                min_edge[k] = d;
+            }
+        }
+    }
+
+    weight
+}
+
+/// Search state shared across the recursion
+struct Search<'a> {
+    graph: &'a [Vec<f64>],
+    deadline: Option<Instant>,
+    timed_out: bool,
+    best_tour: Vec<usize>,
+    best_length: f64,
+}
+
+impl Search<'_> {
+    /// Lower bound on any completion of `path`: its cost plus an MST over the
+    /// unvisited cities and both path end points (the rest of the tour is a
+    /// Hamiltonian path through them, hence a spanning tree)
+    fn bound(&self, path: &[usize], cost: f64, visited: &[bool]) -> f64 {
+        let last = *path.last().unwrap();
+        let mut cities: Vec<usize> = (0..visited.len()).filter(|&c| !visited[c]).collect();
+        cities.push(last);
+        if last != path[0] {
+            cities.push(path[0]);
+        }
+        cost + mst_weight(self.graph, &cities)
+    }
+
+    /// Depth-first search; returns the smallest lower bound among subtrees left
+    /// unexplored because the deadline passed, or None if the subtree was finished
+    fn explore(&mut self, path: &mut Vec<usize>, cost: f64, visited: &mut [bool]) -> Option<f64> {
+        let n = visited.len();
+        let last = *path.last().unwrap();
+
+        if path.len() == n {
+            let length = cost + self.graph[last][path[0]];
+            if length < self.best_length {
+                self.best_length = length;
+                self.best_tour = path.clone();
+            }
+            return None;
+        }
+
+        let mut children: Vec<usize> = (0..n).filter(|&c| !visited[c]).collect();
+        children.sort_by(|&a, &b| self.graph[last][a].total_cmp(&self.graph[last][b]));
+
+        let mut unexplored: Option<f64> = None;
+        for next in children {
+            let child_cost = cost + self.graph[last][next];
+            path.push(next);
+            visited[next] = true;
+
+            let bound = self.bound(path, child_cost, visited);
+            if self.timed_out {
+                unexplored = Some(unexplored.map_or(bound, |u| u.min(bound)));
+            } else if bound < self.best_length - 1e-9 {
+                if let Some(left) = self.explore(path, child_cost, visited) {
+                    unexplored = Some(unexplored.map_or(left, |u| u.min(left)));
+                }
+            }
+
+            visited[next] = false;
+            path.pop();
+
+            if !self.timed_out && self.deadline.is_some_and(|d| Instant::now() >= d) {
+                self.timed_out = true;
+            }
+        }
+
+        unexplored
+    }
+}
+
+/// Solve TSP exactly with depth-first Branch and Bound
+/// The incumbent starts from nearest neighbor + 2-opt; subtrees are pruned with
+/// an MST-based bound. If `time_limit` expires the best tour found so far is
+/// returned together with a certified lower bound.
+/// Returns (tour, length, proven_optimal, gap) where gap is the relative
+/// distance between the tour length and the lower bound (0 when proven optimal)
+/// Infinite edges are absent: if no tour avoids them it fails with
+/// `SonarError::Unreachable`; if the deadline passes before a tour avoiding
+/// them is found, the tour returned is infinitely long with a gap of 1.
+/// Time complexity: exponential in the worst case, practical for n <= ~30
+pub fn solve(
+    graph: &[Vec<f64>],
+    time_limit: Option<Duration>,
+) -> Result<(Vec<usize>, f64, bool, f64), SonarError> {
+    validate_graph(graph)?;
+    let n = graph.len();
+    if n < 4 {
+        let tour: Vec<usize> = (0..n).collect();
+        let length = calculate_tour_length(&tour, graph);
+        return Ok((tour, length, true, 0.0));
+    }
+
+    // Nearest neighbor can run into a dead end that another order avoids; the
+    // search then starts without a finite incumbent
+    let initial = match nearest_neighbor::generate_tour(n, graph, 0) {
+        Ok(tour) => two_opt::improve(&tour, graph, 1_000)?,
+        Err(SonarError::Unreachable { .. }) => (0..n).collect(),
+        Err(error) => return Err(error),
+    };
+    let mut search = Search {
+        graph,
+        deadline: time_limit.map(|limit| Instant::now() + limit),
+        timed_out: false,
+        best_length: calculate_tour_length(&initial, graph),
+        best_tour: initial,
+    };
+
+    let mut path = vec![0];
+    let mut visited = vec![false; n];
+    visited[0] = true;
+    let unexplored = search.explore(&mut path, 0.0, &mut visited);
+
+    let length = search.best_length;
+    match unexplored {
+        None if length.is_infinite() => Err(SonarError::Unreachable { from: 0 }),
+        None => Ok((search.best_tour, length, true, 0.0)),
+        Some(bound) => {
+            let lower_bound = bound.max(one_tree_bound(graph)?).min(length);
+            let gap = match length {
+                length if length.is_infinite() => 1.0,
+                length if length > 0.0 => (length - lower_bound) / length,
+                _ => 0.0,
+            };
+            Ok((search.best_tour, length, gap <= 0.0, gap))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brute_force;
+    use crate::utils::{create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_branch_and_bound_matches_held_karp() {
+        let points = generate_normalized_points(11, 20, 17);
+        let graph = create_distance_matrix(&points);
+
+        let (tour, length, proven_optimal, gap) = solve(&graph, None).unwrap();
+        let (_, optimal) = brute_force::held_karp(&graph, points.len()).unwrap();
+
+        assert_eq!(tour.len(), points.len());
+        assert!(proven_optimal);
+        assert_eq!(gap, 0.0);
+        assert!((length - optimal).abs() < 1e-9);
+        assert!(one_tree_bound(&graph).unwrap() <= optimal + 1e-9);
+
+        // With no time at all the answer is the incumbent plus an honest gap
+        let big = generate_normalized_points(40, 40, 17);
+        let big_graph = create_distance_matrix(&big);
+        let (_, length, proven_optimal, gap) = solve(&big_graph, Some(Duration::ZERO)).unwrap();
+        assert!(!proven_optimal);
+        assert!(gap > 0.0 && gap < 1.0);
+        assert!(length > 0.0);
+    }
+}
@@ -1,12 +1,38 @@
 //! Brute Force and Held-Karp exact algorithms for TSP
 
+use crate::error::{validate_graph, validate_size, SonarError};
 use crate::utils::calculate_tour_length;
-use std::collections::HashMap;
+use rayon::prelude::*;
+
+/// City every tour from `brute_force_exact` and `held_karp` starts at; the
+/// searches fix it first and permute the rest
+pub const START_CITY: usize = 0;
+
+/// Largest instance `held_karp` accepts; n = 23 already needs ~0.8GB of DP tables
+pub const HELD_KARP_MAX_N: usize = 23;
+
+/// Cities left to place at which `brute_force_exact_cancellable` checks for
+/// cancellation, once per (CANCEL_CHECK_DEPTH - 1)! permutations
+const CANCEL_CHECK_DEPTH: usize = 9;
 
 /// Find optimal TSP tour using exhaustive permutation search
 /// Time complexity: O(n!)
 /// Only practical for n <= 10
-pub fn brute_force_exact(graph: &[Vec<f64>], n: usize) -> (Vec<usize>, f64) {
+/// Fails with `SonarError::Unreachable` when every tour needs an infinite edge
+pub fn brute_force_exact(graph: &[Vec<f64>], n: usize) -> Result<(Vec<usize>, f64), SonarError> {
+    brute_force_exact_cancellable(graph, n, &|| false)
+}
+
+/// `brute_force_exact`, checking `cancelled` every 8! permutations and failing
+/// with `SonarError::Cancelled` once it returns true
+pub fn brute_force_exact_cancellable(
+    graph: &[Vec<f64>],
+    n: usize,
+    cancelled: &dyn Fn() -> bool,
+) -> Result<(Vec<usize>, f64), SonarError> {
+    validate_graph(graph)?;
+    validate_size(n, graph)?;
+
     let mut cities: Vec<usize> = (0..n).collect();
     let mut best_tour = cities.clone();
     let mut best_length = calculate_tour_length(&best_tour, graph);
@@ -18,119 +44,167 @@ pub fn brute_force_exact(graph: &[Vec<f64>], n: usize) -> (Vec<usize>, f64) {
         graph: &[Vec<f64>],
         best_tour: &mut Vec<usize>,
         best_length: &mut f64,
-    ) {
+        cancelled: &dyn Fn() -> bool,
+    ) -> Result<(), SonarError> {
         if start == arr.len() - 1 {
             let length = calculate_tour_length(arr, graph);
             if length < *best_length {
                 *best_length = length;
                 *best_tour = arr.clone();
             }
-            return;
+            return Ok(());
+        }
+        if arr.len() - start == CANCEL_CHECK_DEPTH && cancelled() {
+            return Err(SonarError::Cancelled);
         }
 
         for i in start..arr.len() {
             arr.swap(start, i);
-            permute(arr, start + 1, graph, best_tour, best_length);
+            permute(arr, start + 1, graph, best_tour, best_length, cancelled)?;
             arr.swap(start, i);
         }
+        Ok(())
     }
 
     // Fix first city to reduce permutations from n! to (n-1)!
-    permute(&mut cities, 1, graph, &mut best_tour, &mut best_length);
+    permute(&mut cities, 1, graph, &mut best_tour, &mut best_length, cancelled)?;
+    if best_length.is_infinite() {
+        return Err(SonarError::Unreachable { from: 0 });
+    }
+
+    Ok((best_tour, best_length))
+}
 
-    (best_tour, best_length)
+/// Bytes of DP tables `held_karp` allocates for n cities
+/// Time complexity: O(1)
+pub fn held_karp_memory(n: usize) -> usize {
+    if n < 2 {
+        return 0;
+    }
+    // One f64 distance and one u8 parent per (subset, last city) entry
+    1usize.checked_shl(n as u32 - 1).unwrap_or(usize::MAX).saturating_mul(n - 1).saturating_mul(9)
 }
 
 /// Find optimal TSP tour using Held-Karp dynamic programming
-/// Time complexity: O(2^n * n^2)
-/// Practical for n <= 20
-pub fn held_karp(graph: &[Vec<f64>], n: usize) -> (Vec<usize>, f64) {
-    // dp[mask][i] = minimum distance to reach city i, having visited cities in mask
-    // mask is a bitmask where bit j is set if city j has been visited
-    let mut dp: HashMap<(usize, usize), f64> = HashMap::new();
-    let mut parent: HashMap<(usize, usize), usize> = HashMap::new();
+/// DP tables are flat arrays indexed by (subset of cities 1..n-1, last city).
+/// Every subset of a given size depends only on subsets one smaller, so each
+/// layer is computed in parallel with rayon.
+/// Time complexity: O(2^n * n^2), memory O(2^n * n)
+/// Fails with `SonarError::TooLarge` for n > `HELD_KARP_MAX_N`, before allocating
+/// (memory grows as 9 * 2^(n-1) * (n-1) bytes)
+/// Fails with `SonarError::Unreachable` when every tour needs an infinite edge
+pub fn held_karp(graph: &[Vec<f64>], n: usize) -> Result<(Vec<usize>, f64), SonarError> {
+    held_karp_cancellable(graph, n, &|| false)
+}
+
+/// `held_karp`, checking `cancelled` before each subset size of the DP and
+/// failing with `SonarError::Cancelled` once it returns true
+pub fn held_karp_cancellable(
+    graph: &[Vec<f64>],
+    n: usize,
+    cancelled: &(dyn Fn() -> bool + Sync),
+) -> Result<(Vec<usize>, f64), SonarError> {
+    validate_graph(graph)?;
+    validate_size(n, graph)?;
+    if n > HELD_KARP_MAX_N {
+        return Err(SonarError::TooLarge { n, max: HELD_KARP_MAX_N });
+    }
+    if n == 1 {
+        return Ok((vec![0], 0.0));
+    }
+
+    // Subset masks use bit c for city c (c >= 1); `mask >> 1` is the table row.
+    // dp[row * m + (last - 1)] = minimum distance from city 0 through the
+    // subset, ending at `last`; parent holds the city visited before `last`.
+    let m = n - 1;
+    let rows = 1usize << m;
+    let mut dp = vec![f64::INFINITY; rows * m];
+    let mut parent = vec![0u8; rows * m];
 
     // Base case: start from city 0, visit each other city directly
     for i in 1..n {
-        let mask = 1 << i;
-        dp.insert((mask, i), graph[0][i]);
-        parent.insert((mask, i), 0);
+        dp[((1 << i) >> 1) * m + (i - 1)] = graph[0][i];
     }
 
-    // Fill DP table for increasing subset sizes
+    // Fill DP table for increasing subset sizes, one parallel layer at a time
     for size in 2..n {
-        // Generate all subsets of given size from cities 1 to n-1
+        if cancelled() {
+            return Err(SonarError::Cancelled);
+        }
         let subsets = generate_subsets(n, size);
+        let table = &dp;
 
-        for mask in subsets {
-            for last in 1..n {
-                // Skip if last city is not in the current subset
-                if (mask & (1 << last)) == 0 {
-                    continue;
-                }
-
-                let prev_mask = mask ^ (1 << last);
-                if prev_mask == 0 {
-                    continue;
-                }
-
-                let mut best_dist = f64::INFINITY;
-                let mut best_prev = 0;
-
-                // Try all possible previous cities
-                for prev in 1..n {
-                    if (prev_mask & (1 << prev)) == 0 {
-                        continue;
+        let layer: Vec<(f64, u8)> = subsets
+            .par_iter()
+            .flat_map_iter(|&mask| {
+                (1..n).map(move |last| {
+                    // Skip if last city is not in the current subset
+                    if (mask & (1 << last)) == 0 {
+                        return (f64::INFINITY, 0);
                     }
 
-                    if let Some(&dist) = dp.get(&(prev_mask, prev)) {
-                        let new_dist = dist + graph[prev][last];
+                    let prev_row = (mask ^ (1 << last)) >> 1;
+                    let mut best_dist = f64::INFINITY;
+                    let mut best_prev = 0;
+
+                    // Try all possible previous cities
+                    for prev in 1..n {
+                        if (mask & (1 << prev)) == 0 || prev == last {
+                            continue;
+                        }
+                        let new_dist = table[prev_row * m + (prev - 1)] + graph[prev][last];
                         if new_dist < best_dist {
                             best_dist = new_dist;
                             best_prev = prev;
                         }
                     }
-                }
 
-                if best_dist < f64::INFINITY {
-                    dp.insert((mask, last), best_dist);
-                    parent.insert((mask, last), best_prev);
+                    (best_dist, best_prev as u8)
+                })
+            })
+            .collect();
+
+        for (k, &mask) in subsets.iter().enumerate() {
+            let row = (mask >> 1) * m;
+            for j in 0..m {
+                let (dist, prev) = layer[k * m + j];
+                if dist < f64::INFINITY {
+                    dp[row + j] = dist;
+                    parent[row + j] = prev;
                 }
             }
         }
     }
 
     // Find the minimum tour by trying all last cities and returning to 0
-    let full_mask = ((1 << n) - 1) ^ 1; // All cities except 0 (bits 1 to n-1)
+    let full_row = rows - 1; // All cities except 0
     let mut min_length = f64::INFINITY;
     let mut last_city = 0;
 
     for i in 1..n {
-        if let Some(&dist) = dp.get(&(full_mask, i)) {
-            let total_dist = dist + graph[i][0];
-            if total_dist < min_length {
-                min_length = total_dist;
-                last_city = i;
-            }
+        let total_dist = dp[full_row * m + (i - 1)] + graph[i][0];
+        if total_dist < min_length {
+            min_length = total_dist;
+            last_city = i;
         }
     }
+    if last_city == 0 {
+        return Err(SonarError::Unreachable { from: 0 });
+    }
 
     // Reconstruct the tour
     let mut tour = vec![0];
-    let mut current_mask = full_mask;
+    let mut current_row = full_row;
     let mut current = last_city;
 
     while current != 0 {
         tour.push(current);
-        if let Some(&prev) = parent.get(&(current_mask, current)) {
-            current_mask ^= 1 << current;
-            current = prev;
-        } else {
-            break;
-        }
+        let prev = parent[current_row * m + (current - 1)] as usize;
+        current_row ^= 1 << (current - 1);
+        current = prev;
     }
 
-    (tour, min_length)
+    Ok((tour, min_length))
 }
 
 /// Generate all subsets of a given size from cities 1 to n-1
@@ -153,23 +227,23 @@ fn generate_subsets(n: usize, size: usize) -> Vec<usize> {
 
 /// Find optimal TSP solution using the best available method for the given size
 /// - n <= 10: Use brute force O(n!)
-/// - n <= 20: Use Held-Karp O(2^n * n^2)
-/// - n > 20: Returns None (exact solution infeasible)
-pub fn find_optimal(graph: &[Vec<f64>], n: usize) -> Option<(Vec<usize>, f64)> {
-    if n > 20 {
-        return None;
+/// - n <= `HELD_KARP_MAX_N`: Use Held-Karp O(2^n * n^2)
+/// - larger n: Fails with `SonarError::TooLarge` (exact solution infeasible)
+pub fn find_optimal(graph: &[Vec<f64>], n: usize) -> Result<(Vec<usize>, f64), SonarError> {
+    if n > get_max_feasible_n() {
+        return Err(SonarError::TooLarge { n, max: get_max_feasible_n() });
     }
 
     if n <= 10 {
-        Some(brute_force_exact(graph, n))
+        brute_force_exact(graph, n)
     } else {
-        Some(held_karp(graph, n))
+        held_karp(graph, n)
     }
 }
 
 /// Get maximum number of cities for which exact solution is feasible
 pub fn get_max_feasible_n() -> usize {
-    20
+    HELD_KARP_MAX_N
 }
 
 #[cfg(test)]
@@ -187,7 +261,7 @@ mod tests {
             Point { x: 0.0, y: 1.0, angle: PI / 2.0, id: 3 },
         ];
         let graph = create_distance_matrix(&points);
-        let (tour, length) = brute_force_exact(&graph, 4);
+        let (tour, length) = brute_force_exact(&graph, 4).unwrap();
 
         // Square perimeter should be 4
         assert!((length - 4.0).abs() < 0.0001);
@@ -203,10 +277,16 @@ mod tests {
             Point { x: 0.0, y: 1.0, angle: PI / 2.0, id: 3 },
         ];
         let graph = create_distance_matrix(&points);
-        let (tour, length) = held_karp(&graph, 4);
+        let (tour, length) = held_karp(&graph, 4).unwrap();
 
         // Square perimeter should be 4
         assert!((length - 4.0).abs() < 0.0001);
         assert_eq!(tour.len(), 4);
+        assert_eq!(held_karp_cancellable(&graph, 4, &|| true), Err(SonarError::Cancelled));
+
+        // Too large an instance is refused from its size, before any table is allocated
+        let n = HELD_KARP_MAX_N + 1;
+        let graph = vec![vec![1.0; n]; n];
+        assert_eq!(held_karp(&graph, n), Err(SonarError::TooLarge { n, max: HELD_KARP_MAX_N }));
     }
 }
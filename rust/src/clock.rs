@@ -0,0 +1,125 @@
+//! Time sources for benchmark measurements
+//!
+//! Wall time is what a caller waits for; CPU time is the work done, summed
+//! over every thread of the process. A solver spreading its work over eight
+//! threads takes about an eighth of the wall time of a sequential one doing
+//! the same work, but as much CPU time, so measured by CPU time the parallel
+//! entries of a comparison no longer look free. Like the RNG kind in
+//! `determinism`, the source is chosen once for the whole process.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Index of the chosen source in `TimeSource::ALL`
+static TIME_SOURCE: AtomicU8 = AtomicU8::new(0);
+
+/// What measured times are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeSource {
+    /// Elapsed real time
+    #[default]
+    Wall,
+    /// CPU time of every thread of the process together, idle pool threads
+    /// included, so runs should not overlap with other work of the process;
+    /// the benchmark runs nothing while an abandoned run is still going
+    Cpu,
+}
+
+impl TimeSource {
+    pub const ALL: [TimeSource; 2] = [TimeSource::Wall, TimeSource::Cpu];
+
+    /// Name for command lines and manifests
+    pub fn name(self) -> &'static str {
+        match self {
+            TimeSource::Wall => "wall",
+            TimeSource::Cpu => "cpu",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TimeSource> {
+        TimeSource::ALL.into_iter().find(|source| source.name() == name)
+    }
+
+    /// Whether this platform reports the source
+    pub fn is_available(self) -> bool {
+        match self {
+            TimeSource::Wall => true,
+            TimeSource::Cpu => process_cpu_time().is_some(),
+        }
+    }
+
+    /// Time since a fixed origin; only differences between readings mean anything
+    /// CPU time reads as zero where the platform does not report it.
+    pub fn now(self) -> Duration {
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        match self {
+            TimeSource::Wall => ORIGIN.get_or_init(Instant::now).elapsed(),
+            TimeSource::Cpu => process_cpu_time().unwrap_or_default(),
+        }
+    }
+}
+
+/// Choose the source every measurement of the process reads
+pub fn set_time_source(source: TimeSource) {
+    let index = TimeSource::ALL.iter().position(|&s| s == source).unwrap();
+    TIME_SOURCE.store(index as u8, Ordering::SeqCst);
+}
+
+/// The source measurements read, wall time unless changed
+pub fn time_source() -> TimeSource {
+    TimeSource::ALL[TIME_SOURCE.load(Ordering::SeqCst) as usize]
+}
+
+/// CPU time used by all threads of the process so far, where the platform reports it
+#[cfg(unix)]
+pub fn process_cpu_time() -> Option<Duration> {
+    cpu_clock(libc::CLOCK_PROCESS_CPUTIME_ID)
+}
+
+/// Reading of the CPU-time clock `id`
+#[cfg(unix)]
+fn cpu_clock(id: libc::clockid_t) -> Option<Duration> {
+    let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: `time` is a valid timespec for the call to fill in
+    let status = unsafe { libc::clock_gettime(id, &mut time) };
+    (status == 0).then(|| Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+/// CPU time used by all threads of the process so far, where the platform reports it
+#[cfg(not(unix))]
+pub fn process_cpu_time() -> Option<Duration> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_time_counts_work_not_sleep() {
+        for source in TimeSource::ALL {
+            assert_eq!(TimeSource::from_name(source.name()), Some(source));
+        }
+        assert_eq!(TimeSource::from_name("gpu"), None);
+        if !TimeSource::Cpu.is_available() {
+            return;
+        }
+        let wall = TimeSource::Wall.now();
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(TimeSource::Wall.now() - wall >= Duration::from_millis(50));
+        // Tests run in parallel and may be descheduled, so the work is counted
+        // by this thread's own CPU time, which the process time must cover
+        #[cfg(unix)]
+        {
+            let thread = || cpu_clock(libc::CLOCK_THREAD_CPUTIME_ID).unwrap();
+            let (cpu, own) = (TimeSource::Cpu.now(), thread());
+            let mut sum = 0u64;
+            while thread() - own < Duration::from_millis(20) {
+                sum = std::hint::black_box(sum.wrapping_add(1));
+            }
+            let spent = thread() - own;
+            assert!(TimeSource::Cpu.now() - cpu >= spent);
+        }
+    }
+}
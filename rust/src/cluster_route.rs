@@ -0,0 +1,163 @@
+//! Cluster-first, route-second solver - k-means clusters routed separately, then joined
+
+use crate::determinism;
+use crate::error::{validate_points, validate_tour, SonarError};
+use crate::nearest_neighbor;
+use crate::sector_partition::open_cycle;
+use crate::two_opt;
+use crate::utils::{create_distance_matrix, Point};
+use rand::Rng;
+use rayon::prelude::*;
+
+/// Generate a tour by k-means clustering the points and routing each cluster
+/// `solve_cluster` receives one cluster's points (ids renumbered from 0) and
+/// returns a tour over them; clusters are solved in parallel. The clusters are
+/// visited in the order of a nearest neighbor + 2-opt tour over their
+/// centroids, and each cluster's cycle is opened at the edge cheapest to enter
+/// from the previous cluster.
+/// Time complexity: O(n * clusters * kmeans_iterations) plus the cluster solves
+pub fn generate_tour<F>(
+    points: &[Point],
+    clusters: usize,
+    kmeans_iterations: usize,
+    solve_cluster: F,
+) -> Result<Vec<usize>, SonarError>
+where
+    F: Fn(&[Point]) -> Result<Vec<usize>, SonarError> + Sync,
+{
+    validate_points(points)?;
+    if clusters == 0 {
+        return Err(SonarError::InvalidParameter { name: "clusters", reason: "must be positive" });
+    }
+
+    let (centroids, members) = kmeans(points, clusters.min(points.len()), kmeans_iterations);
+
+    let cycles: Vec<Vec<usize>> = members
+        .par_iter()
+        .map(|indices| {
+            let local: Vec<Point> = indices
+                .iter()
+                .enumerate()
+                .map(|(id, &i)| Point { id, ..points[i].clone() })
+                .collect();
+            let tour = solve_cluster(&local)?;
+            validate_tour(&tour, local.len())?;
+            Ok(tour.into_iter().map(|i| indices[i]).collect())
+        })
+        .collect::<Result<_, _>>()?;
+
+    let centroid_graph = create_distance_matrix(&centroids);
+    let order = nearest_neighbor::generate_tour(centroids.len(), &centroid_graph, 0)?;
+    let order = two_opt::improve(&order, &centroid_graph, 100)?;
+
+    let mut tour: Vec<usize> = Vec::with_capacity(points.len());
+    for c in order {
+        tour.extend(open_cycle(points, &cycles[c], tour.last().copied()));
+    }
+    Ok(tour)
+}
+
+/// Route a cluster with grid nearest neighbor followed by 2-opt
+/// The default `solve_cluster` for `generate_tour`.
+/// Time complexity: O(m^2 * max_iterations) for m points
+pub fn nearest_neighbor_two_opt(points: &[Point], max_iterations: usize) -> Result<Vec<usize>, SonarError> {
+    let initial = nearest_neighbor::generate_tour_grid(points, 0)?;
+    two_opt::improve(&initial, &create_distance_matrix(points), max_iterations)
+}
+
+/// Lloyd's k-means with k-means++ seeding
+/// Returns the centroids and the point indices of each cluster; empty clusters are dropped.
+fn kmeans(points: &[Point], k: usize, iterations: usize) -> (Vec<Point>, Vec<Vec<usize>>) {
+    let squared = |p: &Point, c: &(f64, f64)| (p.x - c.0) * (p.x - c.0) + (p.y - c.1) * (p.y - c.1);
+    let mut rng = determinism::rng(0);
+
+    // k-means++: each new centre is drawn with probability proportional to the
+    // squared distance to the closest centre chosen so far
+    let first = &points[rng.gen_range(0..points.len())];
+    let mut centers = vec![(first.x, first.y)];
+    let mut closest: Vec<f64> = points.iter().map(|p| squared(p, &centers[0])).collect();
+    while centers.len() < k {
+        let total: f64 = closest.iter().sum();
+        let next = if total > 0.0 {
+            let mut r = rng.gen::<f64>() * total;
+            closest.iter().position(|&d| {
+                r -= d;
+                r <= 0.0
+            })
+            .unwrap_or(points.len() - 1)
+        } else {
+            // Every point coincides with a centre; extra centres stay empty
+            rng.gen_range(0..points.len())
+        };
+        let center = (points[next].x, points[next].y);
+        for (d, p) in closest.iter_mut().zip(points) {
+            *d = d.min(squared(p, &center));
+        }
+        centers.push(center);
+    }
+
+    let nearest = |p: &Point, centers: &[(f64, f64)]| {
+        (0..centers.len())
+            .min_by(|&a, &b| squared(p, &centers[a]).total_cmp(&squared(p, &centers[b])))
+            .unwrap()
+    };
+    let mut assignment: Vec<usize> = points.par_iter().map(|p| nearest(p, &centers)).collect();
+    for _ in 0..iterations {
+        let mut sums = vec![(0.0, 0.0, 0usize); k];
+        for (p, &c) in points.iter().zip(&assignment) {
+            sums[c].0 += p.x;
+            sums[c].1 += p.y;
+            sums[c].2 += 1;
+        }
+        for (center, &(x, y, count)) in centers.iter_mut().zip(&sums) {
+            if count > 0 {
+                *center = (x / count as f64, y / count as f64);
+            }
+        }
+
+        let next: Vec<usize> = points.par_iter().map(|p| nearest(p, &centers)).collect();
+        if next == assignment {
+            break;
+        }
+        assignment = next;
+    }
+
+    let mut members = vec![Vec::new(); k];
+    for (i, &c) in assignment.iter().enumerate() {
+        members[c].push(i);
+    }
+    let (centroids, members): (Vec<Point>, Vec<Vec<usize>>) = members
+        .into_iter()
+        .zip(&centers)
+        .filter(|(indices, _)| !indices.is_empty())
+        .enumerate()
+        .map(|(id, (indices, &(x, y)))| (Point { x, y, angle: 0.0, id }, indices))
+        .unzip();
+    (centroids, members)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{calculate_tour_length, generate_normalized_points};
+
+    #[test]
+    fn test_cluster_tour_visits_all_and_beats_random() {
+        let points = generate_normalized_points(300, 40, 13);
+        let tour = generate_tour(&points, 10, 20, |cluster| nearest_neighbor_two_opt(cluster, 50)).unwrap();
+
+        assert_eq!(tour.len(), points.len());
+        let mut seen = std::collections::HashSet::new();
+        for &id in &tour {
+            assert!(seen.insert(id));
+        }
+
+        let graph = create_distance_matrix(&points);
+        let random = crate::random_tour::generate_tour(points.len(), 1);
+        assert!(calculate_tour_length(&tour, &graph) < 0.3 * calculate_tour_length(&random, &graph));
+
+        // A sub-solver returning a bad tour is reported, not stitched
+        let broken = generate_tour(&points, 4, 5, |_| Ok(vec![0]));
+        assert!(matches!(broken, Err(SonarError::SizeMismatch { found: 1, .. })));
+    }
+}
@@ -0,0 +1,81 @@
+//! Empirical complexity - fit runtime ~ c * n^k to measured (n, time) pairs
+
+/// Runs faster than this are dropped; timer resolution and fixed overhead dominate them
+pub const MIN_TIME_MS: f64 = 0.05;
+
+/// Estimated exponent k of time ~ c * n^k with its 95% confidence interval
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExponentFit {
+    pub exponent: f64,
+    pub low: f64,
+    pub high: f64,
+    /// Runs the fit used, after dropping the ones under `MIN_TIME_MS`
+    pub samples: usize,
+}
+
+/// Least squares fit of log(time) = log(c) + k * log(n) over `(n, time_ms)` runs
+/// Needs at least three usable runs over at least two distinct sizes. The
+/// interval is slope +- t * standard error with Student's t at n - 2 degrees of freedom.
+/// Time complexity: O(m) for m runs
+pub fn fit_exponent(runs: &[(usize, f64)]) -> Option<ExponentFit> {
+    let points: Vec<(f64, f64)> = runs
+        .iter()
+        .filter(|&&(n, ms)| n > 0 && ms >= MIN_TIME_MS && ms.is_finite())
+        .map(|&(n, ms)| ((n as f64).ln(), ms.ln()))
+        .collect();
+    let m = points.len();
+    if m < 3 {
+        return None;
+    }
+
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / m as f64;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / m as f64;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    if sxx <= 0.0 {
+        return None;
+    }
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let exponent = sxy / sxx;
+    let intercept = mean_y - exponent * mean_x;
+
+    let residuals: f64 = points.iter().map(|p| (p.1 - intercept - exponent * p.0).powi(2)).sum();
+    let standard_error = (residuals / (m - 2) as f64 / sxx).sqrt();
+    let margin = t_975(m - 2) * standard_error;
+    Some(ExponentFit { exponent, low: exponent - margin, high: exponent + margin, samples: m })
+}
+
+/// Two-sided 95% quantile of Student's t distribution
+fn t_975(degrees_of_freedom: usize) -> f64 {
+    const TABLE: [f64; 10] = [12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228];
+    match degrees_of_freedom {
+        0 => f64::INFINITY,
+        1..=10 => TABLE[degrees_of_freedom - 1],
+        11..=20 => 2.086,
+        21..=40 => 2.021,
+        _ => 1.96,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_recovers_exponent() {
+        // time = 0.001 * n^2 with a +-5% wobble
+        let runs: Vec<(usize, f64)> = [100, 200, 400, 800, 1600, 3200]
+            .iter()
+            .enumerate()
+            .map(|(i, &n)| (n, 0.001 * (n * n) as f64 * if i % 2 == 0 { 1.05 } else { 0.95 }))
+            .collect();
+        let fit = fit_exponent(&runs).unwrap();
+        assert!((fit.exponent - 2.0).abs() < 0.05);
+        assert!(fit.low < 2.0 && 2.0 < fit.high);
+        assert_eq!(fit.samples, 6);
+
+        // Sub-resolution timings are ignored, leaving too few runs
+        assert_eq!(fit_exponent(&[(10, 0.001), (20, 0.002), (40, 1.0), (80, 2.0)]), None);
+        // A single size says nothing about scaling
+        assert_eq!(fit_exponent(&[(50, 1.0), (50, 1.1), (50, 0.9)]), None);
+    }
+}
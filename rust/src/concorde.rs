@@ -0,0 +1,80 @@
+//! Optional wrapper around an external Concorde binary
+//!
+//! Concorde is not bundled. The binary is taken from the `CONCORDE` environment
+//! variable, falling back to `concorde` on the PATH.
+
+use crate::tsplib::{parse_sol, write_tsp};
+use crate::utils::Point;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Coordinate scale applied before handing points to Concorde's integer metric
+pub const DEFAULT_SCALE: f64 = 1_000_000.0;
+
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn binary() -> PathBuf {
+    std::env::var_os("CONCORDE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("concorde"))
+}
+
+/// Whether a Concorde binary can be started
+pub fn is_available() -> bool {
+    Command::new(binary())
+        .arg("-h")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// Solve the instance optimally with Concorde and return the tour
+/// Coordinates are multiplied by `scale` first (see `tsplib::write_tsp`).
+/// Fails with `io::ErrorKind::NotFound` when no Concorde binary is installed.
+pub fn solve(points: &[Point], scale: f64) -> io::Result<Vec<usize>> {
+    let run = RUN_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("sonar-concorde-{}-{}", std::process::id(), run));
+    std::fs::create_dir_all(&dir)?;
+
+    let result = (|| {
+        std::fs::write(dir.join("instance.tsp"), write_tsp("instance", points, scale))?;
+
+        // Concorde writes auxiliary files to the working directory; -x removes them
+        let output = Command::new(binary())
+            .current_dir(&dir)
+            .args(["-x", "-o", "instance.sol", "instance.tsp"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "concorde failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let solution = std::fs::read_to_string(dir.join("instance.sol"))?;
+        parse_sol(&solution).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    })();
+
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_concorde_solves_when_installed() {
+        let points = generate_normalized_points(12, 20, 2);
+        match solve(&points, DEFAULT_SCALE) {
+            Ok(tour) => assert_eq!(tour.len(), points.len()),
+            Err(e) => assert!(!is_available() || e.kind() != io::ErrorKind::NotFound),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Live terminal dashboard of a benchmark run
+//!
+//! The benchmark reports what it is doing as `Event`s; `Progress` keeps the
+//! state they describe and `draw` lays it out: overall progress over the
+//! algorithms, the algorithm and n being timed, elapsed and estimated
+//! remaining time, a sparkline of the current algorithm's run times and the
+//! latest log lines. `Dashboard` redraws it on the alternate screen of stderr
+//! from a thread of its own, so the clock keeps moving during long runs and
+//! stdout stays free for results.
+
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::{cursor, execute, terminal};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Gauge, Paragraph, Sparkline};
+use ratatui::{Frame, Terminal};
+use std::collections::VecDeque;
+use std::io;
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Log lines kept for the log pane
+const LOG_LINES: usize = 200;
+
+/// Time between redraws
+const REFRESH: Duration = Duration::from_millis(200);
+
+/// Something the benchmark did
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// An algorithm's search for its max N started
+    Started(String),
+    /// A timed run at n points finished
+    Probed { n: usize, time: Duration },
+    /// The current algorithm finished with this max N
+    Finished { max_n: usize },
+    /// A line of the benchmark's text output
+    Log(String),
+}
+
+/// State of a benchmark run, as shown by the dashboard
+#[derive(Debug, Clone)]
+pub struct Progress {
+    /// Algorithms the run benchmarks
+    pub algorithms: usize,
+    /// Algorithms finished so far
+    pub finished: usize,
+    /// Algorithm being benchmarked, if any
+    pub current: Option<String>,
+    /// n of the latest timed run of the current algorithm
+    pub n: Option<usize>,
+    /// Times of the current algorithm's runs, in order
+    pub probes: Vec<Duration>,
+    /// Largest max N found so far, and by which algorithm
+    pub best: Option<(String, usize)>,
+    pub log: VecDeque<String>,
+    pub started: Instant,
+}
+
+impl Progress {
+    pub fn new(algorithms: usize) -> Self {
+        Progress {
+            algorithms,
+            finished: 0,
+            current: None,
+            n: None,
+            probes: Vec::new(),
+            best: None,
+            log: VecDeque::new(),
+            started: Instant::now(),
+        }
+    }
+
+    pub fn apply(&mut self, event: Event) {
+        match event {
+            Event::Started(name) => {
+                self.current = Some(name);
+                self.n = None;
+                self.probes.clear();
+            }
+            Event::Probed { n, time } => {
+                self.n = Some(n);
+                self.probes.push(time);
+            }
+            Event::Finished { max_n } => {
+                self.finished += 1;
+                if let Some(name) = self.current.take() {
+                    if self.best.as_ref().is_none_or(|&(_, best)| max_n > best) {
+                        self.best = Some((name, max_n));
+                    }
+                }
+            }
+            Event::Log(line) => {
+                if self.log.len() == LOG_LINES {
+                    self.log.pop_front();
+                }
+                self.log.push_back(line);
+            }
+        }
+    }
+
+    /// Remaining time at the mean time per finished algorithm so far
+    pub fn remaining(&self) -> Option<Duration> {
+        if self.finished == 0 {
+            return None;
+        }
+        let left = self.algorithms.saturating_sub(self.finished) as u32;
+        Some(self.started.elapsed() / self.finished as u32 * left)
+    }
+}
+
+/// `duration` as `h:mm:ss`
+fn clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+/// Lay `progress` out over the whole frame
+pub fn draw(frame: &mut Frame, progress: &Progress) {
+    let [overall, status, sparkline, log] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(5),
+        Constraint::Length(6),
+        Constraint::Min(3),
+    ])
+    .areas(frame.area());
+
+    let ratio = if progress.algorithms == 0 { 0.0 } else { progress.finished as f64 / progress.algorithms as f64 };
+    let gauge = Gauge::default()
+        .block(Block::bordered().title(" Algorithms "))
+        .ratio(ratio.min(1.0))
+        .label(format!("{} / {}", progress.finished, progress.algorithms));
+    frame.render_widget(gauge, overall);
+
+    let remaining = progress.remaining().map_or_else(|| "-".to_string(), clock);
+    let best = match &progress.best {
+        Some((name, max_n)) => format!("{} (n={})", name, max_n),
+        None => "-".to_string(),
+    };
+    let lines = vec![
+        Line::from(format!(
+            "{}  n={}",
+            progress.current.as_deref().unwrap_or("-"),
+            progress.n.map_or_else(|| "-".to_string(), |n| n.to_string())
+        )),
+        Line::from(format!("elapsed {}  remaining ~{}", clock(progress.started.elapsed()), remaining)),
+        Line::from(format!("largest max N so far: {}", best)),
+    ];
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" Running ")), status);
+
+    // The most recent runs that fit, in microseconds
+    let width = sparkline.width.saturating_sub(2) as usize;
+    let skip = progress.probes.len().saturating_sub(width);
+    let data: Vec<u64> = progress.probes[skip..].iter().map(|time| time.as_micros() as u64).collect();
+    let title = match progress.probes.last() {
+        Some(time) => format!(" Run times (last {:.2} ms) ", time.as_secs_f64() * 1000.0),
+        None => " Run times ".to_string(),
+    };
+    frame.render_widget(Sparkline::default().block(Block::bordered().title(title)).data(&data), sparkline);
+
+    let height = log.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = progress.log.iter().skip(progress.log.len().saturating_sub(height)).map(Line::raw).collect();
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" Log ")), log);
+}
+
+/// A dashboard drawn on stderr's alternate screen until `finish`
+pub struct Dashboard {
+    progress: Arc<Mutex<Progress>>,
+    stop: Arc<AtomicBool>,
+    painter: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl Dashboard {
+    /// Switch stderr to the alternate screen and start redrawing
+    /// The terminal stays in cooked mode, so Ctrl-C still stops the run.
+    pub fn start(algorithms: usize) -> io::Result<Self> {
+        let mut stderr = io::stderr();
+        execute!(stderr, terminal::EnterAlternateScreen, cursor::Hide)?;
+        // A panic message would be lost on the alternate screen
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let _ = execute!(io::stderr(), cursor::Show, terminal::LeaveAlternateScreen);
+            hook(info);
+        }));
+        let mut terminal = Terminal::new(CrosstermBackend::new(stderr))?;
+        let progress = Arc::new(Mutex::new(Progress::new(algorithms)));
+        let stop = Arc::new(AtomicBool::new(false));
+        let painter = {
+            let (progress, stop) = (Arc::clone(&progress), Arc::clone(&stop));
+            thread::spawn(move || -> io::Result<()> {
+                while !stop.load(Ordering::Relaxed) {
+                    let progress = progress.lock().unwrap().clone();
+                    terminal.draw(|frame| draw(frame, &progress))?;
+                    thread::sleep(REFRESH);
+                }
+                Ok(())
+            })
+        };
+        Ok(Dashboard { progress, stop, painter: Some(painter) })
+    }
+
+    pub fn report(&self, event: Event) {
+        self.progress.lock().unwrap().apply(event);
+    }
+
+    /// Stop redrawing and give the terminal back as it was
+    pub fn finish(mut self) -> io::Result<()> {
+        self.restore()
+    }
+
+    fn restore(&mut self) -> io::Result<()> {
+        let Some(painter) = self.painter.take() else {
+            return Ok(());
+        };
+        self.stop.store(true, Ordering::Relaxed);
+        let painted = painter.join().unwrap_or(Ok(()));
+        execute!(io::stderr(), cursor::Show, terminal::LeaveAlternateScreen)?;
+        painted
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        let _ = self.restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn test_progress_is_drawn() {
+        let mut progress = Progress::new(4);
+        progress.apply(Event::Started("NearestNeighbor".to_string()));
+        progress.apply(Event::Probed { n: 10, time: Duration::from_millis(1) });
+        progress.apply(Event::Probed { n: 20, time: Duration::from_millis(3) });
+        progress.apply(Event::Log("  n=20: 3.00ms".to_string()));
+        assert_eq!((progress.n, progress.probes.len(), progress.remaining()), (Some(20), 2, None));
+        progress.apply(Event::Finished { max_n: 20 });
+        progress.apply(Event::Started("TwoOpt".to_string()));
+        assert_eq!(progress.best, Some(("NearestNeighbor".to_string(), 20)));
+        assert!(progress.probes.is_empty() && progress.remaining().is_some());
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|frame| draw(frame, &progress)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        for text in ["1 / 4", "TwoOpt  n=-", "NearestNeighbor (n=20)", "n=20: 3.00ms"] {
+            assert!(screen.contains(text), "{} missing", text);
+        }
+        assert_eq!(clock(Duration::from_secs(3723)), "1:02:03");
+    }
+}
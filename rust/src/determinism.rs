@@ -0,0 +1,134 @@
+//! Global deterministic mode
+//!
+//! When enabled, every stochastic algorithm draws from RNGs seeded from one
+//! global seed, and the rayon pool is pinned to a single thread, so repeated
+//! runs produce bit-identical tours (useful for debugging and JS parity).
+//! Which generator the RNGs are is chosen with `set_rng_kind`.
+
+#[cfg(feature = "metaheuristics")]
+use crate::rng::{BoxedRng, UnboxedRng};
+use crate::rng::RngKind;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+
+/// Seed used in deterministic mode unless overridden with `set_seed`
+pub const DEFAULT_SEED: u64 = 12345;
+
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+static SEED: AtomicU64 = AtomicU64::new(DEFAULT_SEED);
+/// Index of the chosen generator in `RngKind::ALL`
+static RNG_KIND: AtomicU8 = AtomicU8::new(RngKind::DEFAULT_INDEX as u8);
+
+/// Enable or disable deterministic mode for the whole process
+/// Enabling it also fixes the global rayon pool to one thread; that part only
+/// takes effect if the pool has not been used yet.
+pub fn set_deterministic(enabled: bool) {
+    DETERMINISTIC.store(enabled, Ordering::SeqCst);
+    if enabled {
+        let _ = rayon::ThreadPoolBuilder::new().num_threads(1).build_global();
+    }
+}
+
+/// Whether deterministic mode is enabled
+pub fn is_deterministic() -> bool {
+    DETERMINISTIC.load(Ordering::SeqCst)
+}
+
+/// Set the global seed used in deterministic mode
+pub fn set_seed(seed: u64) {
+    SEED.store(seed, Ordering::SeqCst);
+}
+
+/// The global seed used in deterministic mode
+pub fn seed() -> u64 {
+    SEED.load(Ordering::SeqCst)
+}
+
+/// Choose the generator every RNG of the process is created as
+pub fn set_rng_kind(kind: RngKind) {
+    RNG_KIND.store(kind.index() as u8, Ordering::SeqCst);
+}
+
+/// The generator RNGs are created as, `RngKind::default()` unless changed
+pub fn rng_kind() -> RngKind {
+    RngKind::ALL[RNG_KIND.load(Ordering::SeqCst) as usize]
+}
+
+/// RNG of the chosen kind seeded with `seed`, for runs that carry their own seed
+#[cfg(feature = "metaheuristics")]
+pub fn seeded(seed: u64) -> BoxedRng {
+    rng_kind().seeded(seed)
+}
+
+/// RNG for one independent random stream of an algorithm
+/// Deterministic mode derives it from the global seed and `stream`, so threads
+/// that use distinct streams stay reproducible; otherwise it is seeded from entropy.
+#[cfg(feature = "metaheuristics")]
+pub fn rng(stream: u64) -> BoxedRng {
+    rng_kind().seeded(stream_seed(stream))
+}
+
+/// `rng` held inline, for runs that must not allocate
+#[cfg(feature = "metaheuristics")]
+pub fn unboxed_rng(stream: u64) -> UnboxedRng {
+    rng_kind().unboxed(stream_seed(stream))
+}
+
+/// Seed of the RNG for `stream`
+#[cfg(feature = "metaheuristics")]
+fn stream_seed(stream: u64) -> u64 {
+    if is_deterministic() {
+        seed() ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    } else {
+        rand::random()
+    }
+}
+
+#[cfg(all(test, feature = "metaheuristics"))]
+mod tests {
+    use super::*;
+    use crate::genetic;
+    use crate::utils::{create_distance_matrix, generate_normalized_points};
+
+    /// Deterministic mode until dropped, then the previous mode again
+    /// Unlike `set_deterministic` it leaves the rayon pool alone, which the
+    /// other tests of the process share.
+    struct Deterministic(bool);
+
+    impl Deterministic {
+        fn enable() -> Self {
+            Deterministic(DETERMINISTIC.swap(true, Ordering::SeqCst))
+        }
+    }
+
+    impl Drop for Deterministic {
+        fn drop(&mut self) {
+            DETERMINISTIC.store(self.0, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_deterministic_mode_repeats_results() {
+        let draw = |mut rng: BoxedRng| -> Vec<u64> { (0..8).map(|_| rng.next_u64()).collect() };
+        assert_eq!(draw(seeded(7)), draw(seeded(7)));
+        assert_ne!(draw(seeded(7)), draw(seeded(8)));
+
+        let points = generate_normalized_points(30, 20, 1);
+        let graph = create_distance_matrix(&points);
+        let previous = is_deterministic();
+        {
+            let _deterministic = Deterministic::enable();
+            assert_eq!(draw(rng(3)), draw(rng(3)));
+            assert_ne!(draw(rng(3)), draw(rng(4)));
+            let first = genetic::optimize(&graph, points.len(), 20, 20, 0.1).unwrap();
+            let second = genetic::optimize(&graph, points.len(), 20, 20, 0.1).unwrap();
+            assert_eq!(first, second);
+        }
+        assert_eq!(is_deterministic(), previous);
+    }
+
+    #[test]
+    fn test_rng_kind_defaults_until_set() {
+        // No test of the process sets a kind
+        assert_eq!(rng_kind(), RngKind::default());
+    }
+}
@@ -0,0 +1,132 @@
+//! Drill paths: coincident points grouped before solving
+//!
+//! PCB drill files and similar job lists often repeat a location, exactly or
+//! within machine tolerance: a via drilled by two tools, a pad listed twice.
+//! Handing those to a solver wastes work on zero-length edges and can confuse
+//! the moves that compare them. Here points within `epsilon` of each other are
+//! grouped first, the tour is solved over one representative per group, and
+//! each group is expanded in place so its points are visited back to back.
+
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+use crate::error::{validate_points, validate_tour, SonarError};
+use crate::math;
+use crate::utils::{distance, Point};
+
+/// Points grouped by `group_coincident`
+#[derive(Debug, Clone)]
+pub struct Groups {
+    /// The first point of each group, with ids renumbered from 0
+    pub representatives: Vec<Point>,
+    /// Indices of the original points in each group, first point first
+    pub members: Vec<Vec<usize>>,
+}
+
+impl Groups {
+    /// Number of groups
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Turn a tour over the representatives into one over all original points,
+    /// each group's points visited consecutively in index order
+    /// Time complexity: O(n)
+    pub fn expand(&self, tour: &[usize]) -> Vec<usize> {
+        tour.iter().flat_map(|&group| self.members[group].iter().copied()).collect()
+    }
+}
+
+/// Group points lying within `epsilon` of a group's first point
+/// Points are taken in index order; each joins the first earlier group whose
+/// first point is within `epsilon`, or starts a group of its own. Groups are
+/// therefore at most 2 * `epsilon` across, however densely points are packed.
+/// `epsilon` = 0 groups exact duplicates only.
+/// Time complexity: O(n log n) when groups are small and far apart relative to `epsilon`
+pub fn group_coincident(points: &[Point], epsilon: f64) -> Result<Groups, SonarError> {
+    validate_points(points)?;
+    if epsilon.is_nan() || epsilon < 0.0 || epsilon.is_infinite() {
+        return Err(SonarError::InvalidParameter { name: "epsilon", reason: "must be finite and non-negative" });
+    }
+    // Cells at least `epsilon` wide, so a group's first point is in a neighboring cell
+    let cell = if epsilon > 0.0 { epsilon } else { 1.0 };
+    let key = |p: &Point| (math::floor(p.x / cell) as i64, math::floor(p.y / cell) as i64);
+    let mut leaders: BTreeMap<(i64, i64), Vec<usize>> = BTreeMap::new();
+    let mut representatives: Vec<Point> = Vec::new();
+    let mut members: Vec<Vec<usize>> = Vec::new();
+
+    for (i, p) in points.iter().enumerate() {
+        let (cx, cy) = key(p);
+        let nearby = (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (cx.saturating_add(dx), cy.saturating_add(dy))));
+        let found = nearby
+            .filter_map(|k| leaders.get(&k))
+            .flatten()
+            .copied()
+            .filter(|&group| distance(&representatives[group], p) <= epsilon)
+            .min();
+        match found {
+            Some(group) => members[group].push(i),
+            None => {
+                let group = members.len();
+                leaders.entry((cx, cy)).or_default().push(group);
+                representatives.push(Point { id: group, ..p.clone() });
+                members.push(vec![i]);
+            }
+        }
+    }
+    Ok(Groups { representatives, members })
+}
+
+/// Group coincident points, tour the representatives with `solve` and expand
+/// `solve` receives one point per group (ids renumbered from 0) and returns a
+/// tour over them.
+/// Time complexity: that of `group_coincident`, plus `solve` over the groups
+pub fn generate_tour<F>(points: &[Point], epsilon: f64, solve: F) -> Result<Vec<usize>, SonarError>
+where
+    F: FnOnce(&[Point]) -> Result<Vec<usize>, SonarError>,
+{
+    let groups = group_coincident(points, epsilon)?;
+    let tour = solve(&groups.representatives)?;
+    validate_tour(&tour, groups.len())?;
+    Ok(groups.expand(&tour))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_repeated_holes_are_drilled_back_to_back() {
+        // 30 holes, each listed three times: exactly, and jittered by 1e-7
+        let holes = generate_normalized_points(30, 40, 4);
+        let mut points: Vec<Point> = Vec::new();
+        for copy in 0..3 {
+            for hole in &holes {
+                let jitter = if copy == 2 { 1e-7 } else { 0.0 };
+                points.push(Point { x: hole.x + jitter, y: hole.y - jitter, angle: 0.0, id: points.len() });
+            }
+        }
+
+        let groups = group_coincident(&points, 1e-6).unwrap();
+        assert_eq!(groups.len(), 30);
+        assert!(groups.members.iter().enumerate().all(|(g, m)| *m == [g, g + 30, g + 60]));
+        assert_eq!(group_coincident(&points, 0.0).unwrap().len(), 60);
+
+        let tour = generate_tour(&points, 1e-6, |representatives| {
+            assert_eq!(representatives.len(), 30);
+            Ok((0..representatives.len()).rev().collect())
+        })
+        .unwrap();
+        validate_tour(&tour, 90).unwrap();
+        assert_eq!(&tour[..3], &[29, 59, 89]);
+        let short = tour.iter().zip(tour.iter().skip(1)).filter(|&(&a, &b)| distance(&points[a], &points[b]) < 1e-6);
+        assert_eq!(short.count(), 60);
+
+        assert!(group_coincident(&points, -1.0).is_err());
+        assert!(generate_tour(&points, 1e-6, |_| Ok(vec![0])).is_err());
+    }
+}
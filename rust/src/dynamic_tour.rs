@@ -0,0 +1,189 @@
+//! Dynamic tour maintenance - insert and remove points without re-solving
+
+use alloc::vec::Vec;
+use crate::error::{validate_points, validate_tour, SonarError};
+use crate::math;
+use crate::utils::{distance, Point};
+use core::f64::consts::PI;
+
+/// Tour positions on each side of a change that the 2-opt repair may touch
+const REPAIR_WINDOW: usize = 12;
+
+/// A tour that follows a changing point set
+/// Points keep their index for life; removed points stay in `points()` but
+/// leave the tour. Every change is followed by 2-opt restricted to a window
+/// of tour positions around it, so updates cost O(n) instead of a re-solve.
+#[derive(Debug, Clone, Default)]
+pub struct DynamicTour {
+    points: Vec<Point>,
+    tour: Vec<usize>,
+}
+
+impl DynamicTour {
+    /// Start from an existing tour over `points`
+    pub fn new(points: Vec<Point>, tour: Vec<usize>) -> Result<Self, SonarError> {
+        validate_points(&points)?;
+        validate_tour(&tour, points.len())?;
+        Ok(DynamicTour { points, tour })
+    }
+
+    /// Every point ever added, including removed ones
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// Indices of the points currently in the tour
+    pub fn tour(&self) -> &[usize] {
+        &self.tour
+    }
+
+    /// Number of points currently in the tour
+    pub fn len(&self) -> usize {
+        self.tour.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tour.is_empty()
+    }
+
+    /// Length of the closed tour
+    /// Time complexity: O(n)
+    pub fn length(&self) -> f64 {
+        let n = self.tour.len();
+        (0..n).map(|i| self.edge(i, (i + 1) % n)).sum()
+    }
+
+    /// Add a point at its cheapest insertion position and repair around it
+    /// Returns the new point's index.
+    /// Time complexity: O(n + REPAIR_WINDOW^2) per repair pass
+    pub fn insert_point(&mut self, x: f64, y: f64) -> Result<usize, SonarError> {
+        let id = self.points.len();
+        if !(x.is_finite() && y.is_finite()) {
+            return Err(SonarError::NonFiniteCoordinate { index: id });
+        }
+        let mut angle = math::atan2(y - 0.5, x - 0.5);
+        if angle < 0.0 {
+            angle += 2.0 * PI;
+        }
+        self.points.push(Point { x, y, angle, id });
+
+        let n = self.tour.len();
+        if n < 3 {
+            self.tour.push(id);
+            return Ok(id);
+        }
+
+        let point = &self.points[id];
+        let mut best = (f64::INFINITY, 0);
+        for i in 0..n {
+            let (a, b) = (&self.points[self.tour[i]], &self.points[self.tour[(i + 1) % n]]);
+            let cost = distance(a, point) + distance(point, b) - distance(a, b);
+            if cost < best.0 {
+                best = (cost, i + 1);
+            }
+        }
+        self.tour.insert(best.1, id);
+        self.repair(best.1);
+        Ok(id)
+    }
+
+    /// Take a point out of the tour, join its neighbours and repair around the gap
+    /// Time complexity: O(n + REPAIR_WINDOW^2) per repair pass
+    pub fn remove_point(&mut self, id: usize) -> Result<(), SonarError> {
+        let position = self
+            .tour
+            .iter()
+            .position(|&city| city == id)
+            .ok_or(SonarError::InvalidParameter { name: "id", reason: "is not in the tour" })?;
+        self.tour.remove(position);
+        if !self.tour.is_empty() {
+            self.repair(position % self.tour.len());
+        }
+        Ok(())
+    }
+
+    /// Distance between the cities at tour positions i and j
+    fn edge(&self, i: usize, j: usize) -> f64 {
+        distance(&self.points[self.tour[i]], &self.points[self.tour[j]])
+    }
+
+    /// 2-opt restricted to moves whose both removed edges start within
+    /// `REPAIR_WINDOW` positions of `center`, repeated until no move improves
+    fn repair(&mut self, center: usize) {
+        let n = self.tour.len();
+        if n < 4 {
+            return;
+        }
+        let window = (2 * REPAIR_WINDOW + 1).min(n);
+        let start = if window == n { 0 } else { (center + n - REPAIR_WINDOW) % n };
+        let at = |k: usize| (start + k) % n;
+
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for a in 0..window {
+                for b in a + 2..window {
+                    // With the whole tour in the window these two edges are adjacent
+                    if window == n && a == 0 && b == n - 1 {
+                        continue;
+                    }
+                    let (i, i_next, j, j_next) = (at(a), at(a + 1), at(b), at(b + 1));
+                    let delta = self.edge(i, j) + self.edge(i_next, j_next)
+                        - self.edge(i, i_next)
+                        - self.edge(j, j_next);
+                    if delta < -1e-12 {
+                        // Reverse positions a+1..=b of the window
+                        let (mut lo, mut hi) = (a + 1, b);
+                        while lo < hi {
+                            self.tour.swap(at(lo), at(hi));
+                            lo += 1;
+                            hi -= 1;
+                        }
+                        improved = true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_insert_and_remove_keep_a_valid_short_tour() {
+        let square = vec![
+            Point { x: 0.0, y: 0.0, angle: 0.0, id: 0 },
+            Point { x: 1.0, y: 0.0, angle: 0.0, id: 1 },
+            Point { x: 1.0, y: 1.0, angle: 0.0, id: 2 },
+            Point { x: 0.0, y: 1.0, angle: 0.0, id: 3 },
+        ];
+        let mut dynamic = DynamicTour::new(square, vec![0, 1, 2, 3]).unwrap();
+        // A point on an edge costs nothing to insert
+        let mid = dynamic.insert_point(0.5, 0.0).unwrap();
+        assert_eq!(mid, 4);
+        assert!((dynamic.length() - 4.0).abs() < 1e-12);
+        dynamic.remove_point(mid).unwrap();
+        assert_eq!(dynamic.tour().len(), 4);
+        assert!(dynamic.remove_point(mid).is_err());
+
+        // Stream points in and out; the tour stays a permutation of the live points
+        let mut streamed = DynamicTour::default();
+        for p in generate_normalized_points(300, 40, 21) {
+            streamed.insert_point(p.x, p.y).unwrap();
+        }
+        for id in (0..300).step_by(3) {
+            streamed.remove_point(id).unwrap();
+        }
+        let mut live: Vec<usize> = streamed.tour().to_vec();
+        live.sort_unstable();
+        assert_eq!(live, (0..300).filter(|id| id % 3 != 0).collect::<Vec<_>>());
+
+        let sweep = crate::angular_sort::generate_tour(streamed.points()).unwrap();
+        let sweep_length = DynamicTour::new(streamed.points().to_vec(), sweep).unwrap().length();
+        // Two thirds of the points in the sweep, yet the maintained tour is still shorter
+        assert!(streamed.length() < sweep_length);
+    }
+}
@@ -0,0 +1,149 @@
+//! Maximum edge length constraint
+//!
+//! Some routes may not take any step longer than a limit L, such as a robot
+//! that must stay within communication range of the city it just left. The
+//! constructive heuristics enforce the limit strictly by treating longer
+//! edges as forbidden (infinite). When no tour within the limit may exist,
+//! simulated annealing and the genetic algorithm search a penalized matrix
+//! instead: every edge over the limit costs `penalty` on top of its length,
+//! so they return the tour with the fewest violations they find, and then the
+//! shortest of those. Check the result with `error::validate_max_edge`.
+
+use crate::error::{validate_graph, SonarError};
+use crate::utils::DistanceMatrix;
+use crate::{genetic, greedy_edge, nearest_neighbor, simulated_annealing};
+
+fn validate_limit(max_edge: f64) -> Result<(), SonarError> {
+    if max_edge.is_nan() || max_edge < 0.0 {
+        return Err(SonarError::InvalidParameter { name: "max_edge", reason: "must be a non-negative length" });
+    }
+    Ok(())
+}
+
+/// Copy of `graph` with every edge longer than `max_edge` made infinite
+/// Time complexity: O(n^2)
+pub fn limited_matrix(graph: &[Vec<f64>], max_edge: f64) -> Result<DistanceMatrix, SonarError> {
+    validate_graph(graph)?;
+    validate_limit(max_edge)?;
+    Ok(graph.iter().map(|row| row.iter().map(|&d| if d > max_edge { f64::INFINITY } else { d }).collect()).collect())
+}
+
+/// Copy of `graph` with `penalty` added to every edge longer than `max_edge`
+/// A penalty above the length of any tour makes one violation fewer always
+/// worth more than any saving in length.
+/// Time complexity: O(n^2)
+pub fn penalized_matrix(graph: &[Vec<f64>], max_edge: f64, penalty: f64) -> Result<DistanceMatrix, SonarError> {
+    validate_graph(graph)?;
+    validate_limit(max_edge)?;
+    if !(penalty >= 0.0 && penalty.is_finite()) {
+        return Err(SonarError::InvalidParameter { name: "penalty", reason: "must be non-negative and finite" });
+    }
+    Ok(graph.iter().map(|row| row.iter().map(|&d| if d > max_edge { d + penalty } else { d }).collect()).collect())
+}
+
+/// Number of tour edges longer than `max_edge`, the closing edge included
+/// Time complexity: O(n)
+pub fn violations(tour: &[usize], graph: &[Vec<f64>], max_edge: f64) -> usize {
+    let n = tour.len();
+    if n < 2 {
+        return 0;
+    }
+    (0..n).filter(|&i| graph[tour[i]][tour[(i + 1) % n]] > max_edge).count()
+}
+
+/// A tour with no edge longer than `max_edge`, built by greedy edge over the
+/// edges within the limit, or failing that by nearest neighbor from each start
+/// Fails with `SonarError::Unreachable` if neither closes such a tour, which
+/// does not prove that none exists.
+/// Time complexity: O(n^2 log n) for greedy edge, O(n^3) if every nearest neighbor start is tried
+pub fn generate_tour(graph: &[Vec<f64>], max_edge: f64) -> Result<Vec<usize>, SonarError> {
+    let limited = limited_matrix(graph, max_edge)?;
+    let n = limited.len();
+    let mut failure = match greedy_edge::generate_tour(n, &limited) {
+        Ok(tour) => return Ok(tour),
+        Err(error) => error,
+    };
+    for start in 0..n {
+        match nearest_neighbor::generate_tour(n, &limited, start) {
+            Ok(tour) => return Ok(tour),
+            Err(error) => failure = error,
+        }
+    }
+    Err(failure)
+}
+
+/// Simulated annealing on the penalized matrix, from `initial_tour`
+/// Time complexity: O(n^2 + n * iterations)
+pub fn optimize_annealing(
+    graph: &[Vec<f64>],
+    max_edge: f64,
+    penalty: f64,
+    initial_tour: &[usize],
+    max_iterations: usize,
+    initial_temperature: f64,
+    cooling_rate: f64,
+) -> Result<Vec<usize>, SonarError> {
+    let penalized = penalized_matrix(graph, max_edge, penalty)?;
+    simulated_annealing::optimize(&penalized, initial_tour, max_iterations, initial_temperature, cooling_rate)
+}
+
+/// Genetic algorithm on the penalized matrix
+/// Time complexity: O(n^2 + population_size * generations * n)
+pub fn optimize_genetic(
+    graph: &[Vec<f64>],
+    max_edge: f64,
+    penalty: f64,
+    population_size: usize,
+    generations: usize,
+    mutation_rate: f64,
+) -> Result<Vec<usize>, SonarError> {
+    let penalized = penalized_matrix(graph, max_edge, penalty)?;
+    genetic::optimize(&penalized, penalized.len(), population_size, generations, mutation_rate)
+}
+
+/// Penalty that puts one violation above any saving in length: a tour leaves
+/// every city once, so it is never longer than the sum of each city's longest
+/// finite edge
+/// Time complexity: O(n^2)
+pub fn default_penalty(graph: &[Vec<f64>]) -> f64 {
+    let longest = |row: &Vec<f64>| row.iter().copied().filter(|d| d.is_finite()).fold(0.0, f64::max);
+    graph.iter().map(longest).sum::<f64>() + 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::validate_max_edge;
+    use crate::utils::{create_distance_matrix, Point};
+
+    #[test]
+    fn test_tours_respect_the_maximum_edge_length() {
+        // A 4 x 4 grid at unit spacing has tours of unit steps only
+        let mut points: Vec<Point> =
+            (0..16).map(|id| Point { x: (id % 4) as f64, y: (id / 4) as f64, angle: 0.0, id }).collect();
+        let graph = create_distance_matrix(&points);
+        let tour = generate_tour(&graph, 1.0).unwrap();
+        validate_max_edge(&tour, &graph, 1.0).unwrap();
+        assert_eq!(violations(&tour, &graph, 1.0), 0);
+
+        let order: Vec<usize> = (0..16).collect();
+        assert_eq!(violations(&order, &graph, 1.0), 4);
+        assert_eq!(validate_max_edge(&order, &graph, 1.0), Err(SonarError::EdgeTooLong { from: 3, to: 4 }));
+        let penalty = default_penalty(&graph);
+        let annealed = optimize_annealing(&graph, 1.0, penalty, &order, 200_000, 10.0, 0.9999).unwrap();
+        assert_eq!(violations(&annealed, &graph, 1.0), 0);
+
+        // An outpost beyond the range of every other city: two violations are
+        // unavoidable, the heuristics fail and the penalized searches get there
+        points.push(Point { x: 10.0, y: 10.0, angle: 0.0, id: 16 });
+        let graph = create_distance_matrix(&points);
+        assert!(matches!(generate_tour(&graph, 1.0), Err(SonarError::Unreachable { .. })));
+        let penalty = default_penalty(&graph);
+        let order: Vec<usize> = (0..17).collect();
+        let annealed = optimize_annealing(&graph, 1.0, penalty, &order, 200_000, 10.0, 0.9999).unwrap();
+        assert_eq!(violations(&annealed, &graph, 1.0), 2);
+        let evolved = optimize_genetic(&graph, 1.0, penalty, 50, 500, 0.1).unwrap();
+        assert!(violations(&evolved, &graph, 1.0) < violations(&order, &graph, 1.0));
+        assert!(limited_matrix(&graph, f64::NAN).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! Error type and input validation for the public solver API
+
+use alloc::vec::Vec;
+use crate::invariants::is_permutation;
+use crate::utils::Point;
+use core::fmt;
+
+/// Why a solver rejected its input
+#[derive(Debug, Clone, PartialEq)]
+pub enum SonarError {
+    /// No points or cities were given
+    EmptyInput,
+    /// A point has a NaN or infinite coordinate
+    NonFiniteCoordinate { index: usize },
+    /// A point's coordinate exceeds the magnitude allowed by `sanitize::Limits`
+    CoordinateOutOfRange { index: usize },
+    /// Row `row` of the distance matrix has `len` entries instead of `expected`
+    NonSquareMatrix { row: usize, len: usize, expected: usize },
+    /// A distance is NaN or negative (+inf is allowed and marks a missing edge)
+    InvalidDistance { from: usize, to: usize },
+    /// The tour travels the forbidden (infinite) edge from `from` to `to`
+    ForbiddenEdge { from: usize, to: usize },
+    /// The tour's edge from `from` to `to` is longer than the maximum allowed
+    EdgeTooLong { from: usize, to: usize },
+    /// The tour visits `after` before `before`, against a precedence pair
+    PrecedenceViolated { before: usize, after: usize },
+    /// Two inputs disagree on the number of cities
+    SizeMismatch { expected: usize, found: usize },
+    /// The tour is not a permutation of the cities
+    InvalidTour,
+    /// A parameter is outside its valid range
+    InvalidParameter { name: &'static str, reason: &'static str },
+    /// The instance is too large for an exact method
+    TooLarge { n: usize, max: usize },
+    /// No unvisited city can be reached from `from` over a finite edge
+    Unreachable { from: usize },
+    /// A hardware backend such as the GPU cannot be used on this machine
+    BackendUnavailable { reason: &'static str },
+    /// The caller cancelled the run before it finished
+    Cancelled,
+}
+
+impl fmt::Display for SonarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SonarError::EmptyInput => write!(f, "no points or cities given"),
+            SonarError::NonFiniteCoordinate { index } => {
+                write!(f, "point {} has a non-finite coordinate", index)
+            }
+            SonarError::CoordinateOutOfRange { index } => {
+                write!(f, "point {} has a coordinate beyond the allowed magnitude", index)
+            }
+            SonarError::NonSquareMatrix { row, len, expected } => {
+                write!(f, "distance matrix row {} has {} entries, expected {}", row, len, expected)
+            }
+            SonarError::InvalidDistance { from, to } => {
+                write!(f, "distance from {} to {} is NaN or negative", from, to)
+            }
+            SonarError::ForbiddenEdge { from, to } => {
+                write!(f, "tour uses the forbidden edge from {} to {}", from, to)
+            }
+            SonarError::EdgeTooLong { from, to } => {
+                write!(f, "tour edge from {} to {} exceeds the maximum edge length", from, to)
+            }
+            SonarError::PrecedenceViolated { before, after } => {
+                write!(f, "tour visits {} before {}, which must come first", after, before)
+            }
+            SonarError::SizeMismatch { expected, found } => {
+                write!(f, "expected {} cities, found {}", expected, found)
+            }
+            SonarError::InvalidTour => write!(f, "tour is not a permutation of the cities"),
+            SonarError::InvalidParameter { name, reason } => write!(f, "invalid {}: {}", name, reason),
+            SonarError::TooLarge { n, max } => {
+                write!(f, "{} cities exceed the limit of {} for this method", n, max)
+            }
+            SonarError::Unreachable { from } => {
+                write!(f, "no unvisited city is reachable from city {}", from)
+            }
+            SonarError::BackendUnavailable { reason } => write!(f, "backend unavailable: {}", reason),
+            SonarError::Cancelled => write!(f, "cancelled before it finished"),
+        }
+    }
+}
+
+impl core::error::Error for SonarError {}
+
+/// Check that there is at least one point and every coordinate is finite
+/// Time complexity: O(n)
+pub fn validate_points(points: &[Point]) -> Result<(), SonarError> {
+    if points.is_empty() {
+        return Err(SonarError::EmptyInput);
+    }
+    match points.iter().position(|p| !p.x.is_finite() || !p.y.is_finite()) {
+        Some(index) => Err(SonarError::NonFiniteCoordinate { index }),
+        None => Ok(()),
+    }
+}
+
+/// Check that the distance matrix is non-empty and square, with no NaN or
+/// negative entries
+/// Time complexity: O(n^2)
+pub fn validate_graph(graph: &[Vec<f64>]) -> Result<(), SonarError> {
+    let n = graph.len();
+    if n == 0 {
+        return Err(SonarError::EmptyInput);
+    }
+    for (from, row) in graph.iter().enumerate() {
+        if row.len() != n {
+            return Err(SonarError::NonSquareMatrix { row: from, len: row.len(), expected: n });
+        }
+        if let Some(to) = row.iter().position(|d| d.is_nan() || *d < 0.0) {
+            return Err(SonarError::InvalidDistance { from, to });
+        }
+    }
+    Ok(())
+}
+
+/// Check that `n` matches the number of cities in the matrix
+pub fn validate_size(n: usize, graph: &[Vec<f64>]) -> Result<(), SonarError> {
+    if n != graph.len() {
+        return Err(SonarError::SizeMismatch { expected: graph.len(), found: n });
+    }
+    Ok(())
+}
+
+/// Check that `tour` visits each of the cities 0..n exactly once
+/// Time complexity: O(n)
+pub fn validate_tour(tour: &[usize], n: usize) -> Result<(), SonarError> {
+    if tour.len() != n {
+        return Err(SonarError::SizeMismatch { expected: n, found: tour.len() });
+    }
+    if !is_permutation(tour, n) {
+        return Err(SonarError::InvalidTour);
+    }
+    Ok(())
+}
+
+/// Check that `tour` visits every city of the matrix exactly once and never
+/// travels an infinite, i.e. forbidden, edge
+/// Time complexity: O(n)
+pub fn validate_tour_edges(tour: &[usize], graph: &[Vec<f64>]) -> Result<(), SonarError> {
+    validate_tour(tour, graph.len())?;
+    if tour.len() < 2 {
+        return Ok(());
+    }
+    let edges = tour.iter().zip(tour.iter().cycle().skip(1));
+    match edges.map(|(&from, &to)| (from, to)).find(|&(from, to)| graph[from][to].is_infinite()) {
+        Some((from, to)) => Err(SonarError::ForbiddenEdge { from, to }),
+        None => Ok(()),
+    }
+}
+
+/// Check that `tour` visits every city of the matrix exactly once and has no
+/// edge longer than `max_edge`
+/// Time complexity: O(n)
+pub fn validate_max_edge(tour: &[usize], graph: &[Vec<f64>], max_edge: f64) -> Result<(), SonarError> {
+    validate_tour(tour, graph.len())?;
+    if tour.len() < 2 {
+        return Ok(());
+    }
+    let edges = tour.iter().zip(tour.iter().cycle().skip(1));
+    match edges.map(|(&from, &to)| (from, to)).find(|&(from, to)| graph[from][to] > max_edge) {
+        Some((from, to)) => Err(SonarError::EdgeTooLong { from, to }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation_rejects_bad_input() {
+        let nan = Point { x: f64::NAN, y: 0.0, angle: 0.0, id: 0 };
+        assert_eq!(validate_points(&[]), Err(SonarError::EmptyInput));
+        assert_eq!(validate_points(&[nan]), Err(SonarError::NonFiniteCoordinate { index: 0 }));
+
+        assert_eq!(validate_graph(&[]), Err(SonarError::EmptyInput));
+        assert_eq!(
+            validate_graph(&[vec![0.0, 1.0], vec![1.0]]),
+            Err(SonarError::NonSquareMatrix { row: 1, len: 1, expected: 2 })
+        );
+        assert_eq!(
+            validate_graph(&[vec![0.0, -1.0], vec![1.0, 0.0]]),
+            Err(SonarError::InvalidDistance { from: 0, to: 1 })
+        );
+        assert!(validate_graph(&[vec![0.0, f64::INFINITY], vec![f64::INFINITY, 0.0]]).is_ok());
+
+        assert_eq!(validate_tour(&[0, 0], 2), Err(SonarError::InvalidTour));
+        assert_eq!(validate_tour(&[0], 2), Err(SonarError::SizeMismatch { expected: 2, found: 1 }));
+        let inf = f64::INFINITY;
+        let one_way = [vec![0.0, 1.0, 1.0], vec![1.0, 0.0, inf], vec![1.0, inf, 0.0]];
+        assert_eq!(validate_tour_edges(&[0, 1, 2], &one_way), Err(SonarError::ForbiddenEdge { from: 1, to: 2 }));
+        assert_eq!(validate_tour_edges(&[0, 2], &one_way), Err(SonarError::SizeMismatch { expected: 3, found: 2 }));
+        assert_eq!(validate_tour_edges(&[0], &[vec![0.0]]), Ok(()));
+        assert_eq!(
+            SonarError::TooLarge { n: 30, max: 20 }.to_string(),
+            "30 cities exceed the limit of 20 for this method"
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "constructive", feature = "local-search", feature = "exact"))]
+    fn test_solvers_return_errors_instead_of_panicking() {
+        use crate::{angular_sort, brute_force, karp_partition, quadtree, sonar_visit, two_opt};
+
+        let mut points = crate::utils::generate_normalized_points(6, 10, 1);
+        points[3].x = f64::NAN;
+        let nan_point = Err(SonarError::NonFiniteCoordinate { index: 3 });
+        assert_eq!(angular_sort::generate_tour(&points), nan_point);
+        assert_eq!(sonar_visit::generate_tour(&points, 10), nan_point);
+        assert_eq!(karp_partition::generate_tour(&points, 4), nan_point);
+        assert_eq!(quadtree::generate_tour(&points, 2), nan_point);
+        assert_eq!(angular_sort::generate_tour(&[]), Err(SonarError::EmptyInput));
+
+        let graph = vec![vec![0.0, 1.0, f64::NAN], vec![1.0, 0.0, 1.0], vec![1.0, 1.0, 0.0]];
+        assert_eq!(two_opt::improve(&[0, 1, 2], &graph, 10), Err(SonarError::InvalidDistance { from: 0, to: 2 }));
+        assert_eq!(brute_force::find_optimal(&[], 0), Err(SonarError::EmptyInput));
+        assert!(matches!(brute_force::find_optimal(&graph, 30), Err(SonarError::TooLarge { .. })));
+    }
+
+    #[test]
+    #[cfg(all(feature = "constructive", feature = "local-search", feature = "exact"))]
+    fn test_solvers_avoid_forbidden_edges() {
+        use crate::utils::{create_distance_matrix, forbid_edges, generate_normalized_points};
+        use crate::{branch_and_bound, brute_force, greedy_edge, nearest_neighbor, two_opt};
+
+        let points = generate_normalized_points(8, 20, 3);
+        let mut graph = create_distance_matrix(&points);
+        // Forbid every edge of the unconstrained optimum
+        let (optimal, _) = brute_force::held_karp(&graph, 8).unwrap();
+        let mut forbidden = vec![vec![false; 8]; 8];
+        for i in 0..8 {
+            forbidden[optimal[i]][optimal[(i + 1) % 8]] = true;
+        }
+        forbid_edges(&mut graph, &forbidden).unwrap();
+        let first_edge = SonarError::ForbiddenEdge { from: optimal[0], to: optimal[1] };
+        assert_eq!(validate_tour_edges(&optimal, &graph), Err(first_edge));
+
+        let (tour, length) = brute_force::brute_force_exact(&graph, 8).unwrap();
+        validate_tour_edges(&tour, &graph).unwrap();
+        assert_eq!(brute_force::held_karp(&graph, 8).unwrap().1, length);
+        let (tour, bb_length, proven_optimal, _) = branch_and_bound::solve(&graph, None).unwrap();
+        validate_tour_edges(&tour, &graph).unwrap();
+        assert!(proven_optimal && (bb_length - length).abs() < 1e-9);
+        // Heuristics may fail to close a tour, but never close one over a forbidden edge
+        let heuristic = [nearest_neighbor::generate_tour(8, &graph, 0), greedy_edge::generate_tour(8, &graph)];
+        for tour in heuristic.into_iter().flatten() {
+            validate_tour_edges(&two_opt::improve(&tour, &graph, 100).unwrap(), &graph).unwrap();
+        }
+
+        // City 5 cut off entirely: no tour at all
+        forbidden[5] = vec![true; 8];
+        forbid_edges(&mut graph, &forbidden).unwrap();
+        assert!(matches!(brute_force::brute_force_exact(&graph, 8), Err(SonarError::Unreachable { .. })));
+        assert!(matches!(brute_force::held_karp(&graph, 8), Err(SonarError::Unreachable { .. })));
+        assert!(matches!(branch_and_bound::solve(&graph, None), Err(SonarError::Unreachable { .. })));
+        assert!(matches!(greedy_edge::generate_tour(8, &graph), Err(SonarError::Unreachable { .. })));
+        assert!(matches!(forbid_edges(&mut graph, &forbidden[..7]), Err(SonarError::SizeMismatch { .. })));
+    }
+}
@@ -0,0 +1,76 @@
+//! Live progress of long-running solvers, streamed over a channel
+
+use crate::stepwise::SteppableSolver;
+use crate::utils::calculate_tour_length;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+
+/// Sent whenever a solver's best tour gets shorter
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImprovementEvent {
+    /// Number of steps taken when the tour was found
+    pub iteration: usize,
+    /// Length of the new best tour
+    pub length: f64,
+    /// Time since the run started
+    pub elapsed: Duration,
+}
+
+/// Step `solver` to completion, sending an event on `events` for each improvement
+/// The first event reports the starting tour at iteration 0. Events are sent
+/// while the solver runs, so a receiver on another thread sees them live; if
+/// the receiver is gone the run still completes.
+/// Time complexity: the solver's own, plus O(n) per step to measure the tour
+pub fn run_with_events<S: SteppableSolver>(
+    solver: &mut S,
+    graph: &[Vec<f64>],
+    events: &Sender<ImprovementEvent>,
+) -> Vec<usize> {
+    let start = Instant::now();
+    let mut best_length = calculate_tour_length(solver.tour(), graph);
+    let _ = events.send(ImprovementEvent { iteration: 0, length: best_length, elapsed: start.elapsed() });
+
+    let mut iteration = 0;
+    while !solver.is_finished() {
+        iteration += 1;
+        let length = calculate_tour_length(solver.step(), graph);
+        if length < best_length {
+            best_length = length;
+            let _ = events.send(ImprovementEvent { iteration, length, elapsed: start.elapsed() });
+        }
+    }
+    solver.tour().to_vec()
+}
+
+#[cfg(all(test, feature = "local-search"))]
+mod tests {
+    use super::*;
+    use crate::two_opt::TwoOptSolver;
+    use crate::utils::{create_distance_matrix, generate_normalized_points};
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_events_stream_strictly_improving_lengths() {
+        let points = generate_normalized_points(80, 20, 6);
+        let graph = create_distance_matrix(&points);
+        let initial: Vec<usize> = (0..points.len()).collect();
+        let (sender, receiver) = mpsc::channel();
+        let (graph, initial) = (&graph, &initial);
+
+        // Consume events on this thread while the solver runs on another
+        let (tour, events) = std::thread::scope(|scope| {
+            let solver = scope.spawn(move || {
+                let mut solver = TwoOptSolver::new(initial, graph, 100).unwrap();
+                run_with_events(&mut solver, graph, &sender)
+            });
+            let events: Vec<ImprovementEvent> = receiver.iter().collect();
+            (solver.join().unwrap(), events)
+        });
+
+        assert_eq!(events[0].iteration, 0);
+        assert!(events.len() > 1);
+        assert!(events.windows(2).all(|w| w[1].length < w[0].length && w[1].iteration > w[0].iteration));
+        let final_length = calculate_tour_length(&tour, graph);
+        assert!((events.last().unwrap().length - final_length).abs() < 1e-12);
+    }
+}
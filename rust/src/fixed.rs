@@ -0,0 +1,207 @@
+//! Fixed-point scalar for results that are bit-identical on every platform
+//!
+//! `Fixed` is an `i64` with `FRAC_BITS` fractional bits. Every operation,
+//! including the square root, is integer arithmetic, so distances, matrices
+//! and tour lengths come out the same on x86, ARM and WASM whatever the
+//! platform's float library does. Sums are exact, hence independent of the
+//! order they are taken in. Use it as the `Scalar` of `Point` and
+//! `DistanceMatrix`, or through `Metric::Fixed` to keep the solvers' `f64`
+//! matrices but fill them with fixed-point distances.
+//!
+//! A product is only representable below 2^31, and a distance squares the
+//! coordinate differences first, so fixed-point distances are exact only
+//! between points less than `MAX_DISTANCE` apart. `validate_range` checks a
+//! point set against that, and `ProblemInstance` runs it for `Metric::Fixed`.
+
+use alloc::vec::Vec;
+use crate::error::SonarError;
+use crate::math;
+use crate::scalar::Scalar;
+use crate::utils::Point;
+use core::num::ParseIntError;
+use core::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use num_traits::{Num, One, Zero};
+
+/// Fractional bits: a resolution of 2^-32, about 2.3e-10, and values, products
+/// included, within +-2^31
+pub const FRAC_BITS: u32 = 32;
+
+/// Points closer than this have a squared distance below 2^31, so `distance`
+/// on fixed-point coordinates does not overflow; sqrt(2^31) rounded down
+pub const MAX_DISTANCE: f64 = 46_340.0;
+
+/// A fixed-point number, `raw / 2^FRAC_BITS`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Fixed(pub i64);
+
+impl Fixed {
+    /// The nearest fixed-point value to `x`
+    pub fn from_f64(x: f64) -> Fixed {
+        Fixed(math::round(x * (1u64 << FRAC_BITS) as f64) as i64)
+    }
+
+    /// The value as `f64`; exact below 2^21 in magnitude
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / (1u64 << FRAC_BITS) as f64
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+    fn add(self, other: Fixed) -> Fixed {
+        Fixed(self.0 + other.0)
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+    fn sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0 - other.0)
+    }
+}
+
+/// Rounds toward negative infinity; panics if the product is 2^31 or more in magnitude
+impl Mul for Fixed {
+    type Output = Fixed;
+    fn mul(self, other: Fixed) -> Fixed {
+        let product = (self.0 as i128 * other.0 as i128) >> FRAC_BITS;
+        Fixed(i64::try_from(product).expect("fixed-point product out of range"))
+    }
+}
+
+/// Rounds toward zero; panics if the quotient is 2^31 or more in magnitude
+impl Div for Fixed {
+    type Output = Fixed;
+    fn div(self, other: Fixed) -> Fixed {
+        let quotient = ((self.0 as i128) << FRAC_BITS) / other.0 as i128;
+        Fixed(i64::try_from(quotient).expect("fixed-point quotient out of range"))
+    }
+}
+
+impl Rem for Fixed {
+    type Output = Fixed;
+    fn rem(self, other: Fixed) -> Fixed {
+        Fixed(self.0 % other.0)
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+    fn neg(self) -> Fixed {
+        Fixed(-self.0)
+    }
+}
+
+impl Zero for Fixed {
+    fn zero() -> Fixed {
+        Fixed(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl One for Fixed {
+    fn one() -> Fixed {
+        Fixed(1 << FRAC_BITS)
+    }
+}
+
+/// Parses whole numbers only
+impl Num for Fixed {
+    type FromStrRadixErr = ParseIntError;
+
+    fn from_str_radix(text: &str, radix: u32) -> Result<Fixed, ParseIntError> {
+        i32::from_str_radix(text, radix).map(|whole| Fixed((whole as i64) << FRAC_BITS))
+    }
+}
+
+impl Scalar for Fixed {
+    /// Integer square root, rounded down; negative values give zero
+    fn sqrt(self) -> Fixed {
+        let raw = self.0.max(0) as u128;
+        Fixed((raw << FRAC_BITS).isqrt() as i64)
+    }
+}
+
+/// Check that fixed-point distances between any two of `points` are exact:
+/// the diagonal of their bounding box must be shorter than `MAX_DISTANCE`
+/// Fails with `SonarError::CoordinateOutOfRange` naming the point farthest
+/// from the first one.
+/// Time complexity: O(n)
+pub fn validate_range(points: &[Point]) -> Result<(), SonarError> {
+    let Some(first) = points.first() else { return Ok(()) };
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (first.x, first.y, first.x, first.y);
+    let mut farthest = (0, 0.0);
+    for (index, p) in points.iter().enumerate() {
+        min_x = min_x.min(p.x);
+        min_y = min_y.min(p.y);
+        max_x = max_x.max(p.x);
+        max_y = max_y.max(p.y);
+        let (dx, dy) = (p.x - first.x, p.y - first.y);
+        let spread = dx * dx + dy * dy;
+        if spread > farthest.1 {
+            farthest = (index, spread);
+        }
+    }
+    let (width, height) = (max_x - min_x, max_y - min_y);
+    // Coordinates are checked finite before, so the comparison is never with NaN
+    if width * width + height * height >= MAX_DISTANCE * MAX_DISTANCE {
+        return Err(SonarError::CoordinateOutOfRange { index: farthest.0 });
+    }
+    Ok(())
+}
+
+/// `point` with fixed-point coordinates and angle
+pub fn to_fixed_point(point: &Point) -> Point<Fixed> {
+    let Point { x, y, angle, id } = *point;
+    Point { x: Fixed::from_f64(x), y: Fixed::from_f64(y), angle: Fixed::from_f64(angle), id }
+}
+
+/// `points` with fixed-point coordinates and angles
+pub fn to_fixed(points: &[Point]) -> Vec<Point<Fixed>> {
+    points.iter().map(to_fixed_point).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{calculate_tour_length, create_distance_matrix, distance, generate_normalized_points};
+
+    #[test]
+    fn test_fixed_point_distances() {
+        let two = Fixed::from_f64(2.0);
+        assert_eq!(two * two, Fixed::from_f64(4.0));
+        assert_eq!((two / Fixed::from_f64(8.0)).to_f64(), 0.25);
+        assert_eq!(Fixed::from_f64(9.0).sqrt(), Fixed::from_f64(3.0));
+        assert_eq!(Fixed::from_str_radix("-3", 10), Ok(Fixed::from_f64(-3.0)));
+
+        let points = generate_normalized_points(50, 20, 8);
+        let fixed = to_fixed(&points);
+        let graph = create_distance_matrix(&fixed);
+        for (i, j) in [(0, 1), (7, 30), (49, 2)] {
+            assert!((graph[i][j].to_f64() - distance(&points[i], &points[j])).abs() < 1e-8);
+        }
+        // Exact sums: every rotation of a tour has the same length
+        let tour: Vec<usize> = (0..50).collect();
+        let mut rotated = tour.clone();
+        rotated.rotate_left(17);
+        assert_eq!(calculate_tour_length(&tour, &graph), calculate_tour_length(&rotated, &graph));
+    }
+
+    #[test]
+    fn test_distances_are_exact_up_to_the_range_limit() {
+        let at = |x: f64| Point { x, y: 0.0, angle: 0.0, id: 0 };
+        let (origin, edge) = (to_fixed_point(&at(0.0)), to_fixed_point(&at(MAX_DISTANCE)));
+        assert_eq!(distance(&origin, &edge).to_f64(), MAX_DISTANCE);
+        assert!(validate_range(&[at(0.0), at(MAX_DISTANCE - 1.0)]).is_ok());
+        // Diagonal of the bounding box, not the coordinates alone
+        let corner = Point { y: MAX_DISTANCE * 0.8, ..at(MAX_DISTANCE * 0.8) };
+        assert_eq!(validate_range(&[at(0.0), corner]), Err(SonarError::CoordinateOutOfRange { index: 1 }));
+        assert!(validate_range(&[at(0.0), at(50_000.0)]).is_err());
+        // Past the limit the product no longer fits, which is a panic, never a wrapped result
+        let far = to_fixed_point(&at(50_000.0));
+        assert!(std::panic::catch_unwind(|| distance(&origin, &far)).is_err());
+    }
+}
@@ -1,55 +1,64 @@
 //! Genetic Algorithm for TSP
 
+use crate::determinism;
+use crate::error::{validate_graph, validate_size, SonarError};
+use crate::stepwise::SteppableSolver;
 use crate::utils::calculate_tour_length;
+use crate::rng::BoxedRng;
+use crate::scratch::{reset, SolverScratch};
 use rand::Rng;
-use std::collections::HashSet;
+use std::mem;
 
 /// Create a random tour permutation using Fisher-Yates shuffle
 fn random_tour(n: usize, rng: &mut impl Rng) -> Vec<usize> {
-    let mut tour: Vec<usize> = (0..n).collect();
+    let mut tour = Vec::with_capacity(n);
+    shuffle_into(&mut tour, n, rng);
+    tour
+}
+
+/// Overwrite `tour` with a random permutation of n cities
+fn shuffle_into(tour: &mut Vec<usize>, n: usize, rng: &mut impl Rng) {
+    tour.clear();
+    tour.extend(0..n);
     for i in (1..n).rev() {
         let j = rng.gen_range(0..=i);
         tour.swap(i, j);
     }
-    tour
 }
 
-/// Order Crossover (OX) operator
-fn crossover(parent1: &[usize], parent2: &[usize], rng: &mut impl Rng) -> Vec<usize> {
+/// Order Crossover (OX) operator, writing the child into `child`
+/// `used` holds the flags of cities copied from `parent1`.
+fn crossover(parent1: &[usize], parent2: &[usize], rng: &mut impl Rng, used: &mut Vec<bool>, child: &mut Vec<usize>) {
     let n = parent1.len();
     let start = rng.gen_range(0..n);
     let end = start + rng.gen_range(0..n - start);
 
-    let mut child = vec![usize::MAX; n];
-    let mut used: HashSet<usize> = HashSet::new();
+    reset(child, n, usize::MAX);
+    reset(used, n, false);
 
     // Copy segment from parent1
     for i in start..=end {
         child[i] = parent1[i];
-        used.insert(parent1[i]);
+        used[parent1[i]] = true;
     }
 
     // Fill rest from parent2 in order
     let mut idx = (end + 1) % n;
     for i in 0..n {
         let city = parent2[(end + 1 + i) % n];
-        if !used.contains(&city) {
+        if !used[city] {
             child[idx] = city;
             idx = (idx + 1) % n;
         }
     }
-
-    child
 }
 
 /// Mutation by swapping two random cities
-fn mutate(tour: &[usize], rng: &mut impl Rng) -> Vec<usize> {
-    let mut new_tour = tour.to_vec();
+fn mutate(tour: &mut [usize], rng: &mut impl Rng) {
     let n = tour.len();
     let i = rng.gen_range(0..n);
     let j = rng.gen_range(0..n);
-    new_tour.swap(i, j);
-    new_tour
+    tour.swap(i, j);
 }
 
 /// Optimize TSP using a Genetic Algorithm
@@ -61,24 +70,170 @@ pub fn optimize(
     population_size: usize,
     generations: usize,
     mutation_rate: f64,
-) -> Vec<usize> {
-    let mut rng = rand::thread_rng();
+) -> Result<Vec<usize>, SonarError> {
+    Ok(GeneticSolver::new(graph, n, population_size, generations, mutation_rate)?.run())
+}
 
-    // Initialize population
-    let mut population: Vec<Vec<usize>> = (0..population_size)
-        .map(|_| random_tour(n, &mut rng))
-        .collect();
+/// `optimize` into `tour`, with the population and its offspring kept in `scratch`
+/// Allocates nothing once both have held a run of the same population size and n.
+pub fn optimize_with(
+    graph: &[Vec<f64>],
+    n: usize,
+    population_size: usize,
+    generations: usize,
+    mutation_rate: f64,
+    scratch: &mut SolverScratch,
+    tour: &mut Vec<usize>,
+) -> Result<(), SonarError> {
+    let rng = determinism::unboxed_rng(0);
+    let mut solver = GeneticSolver::from_scratch(graph, n, population_size, generations, mutation_rate, scratch, rng)?;
+    while !solver.is_finished() {
+        solver.step();
+    }
+    tour.clone_from(&solver.best);
+    solver.into_scratch(scratch);
+    Ok(())
+}
+
+/// Genetic algorithm run one generation per `step`
+/// Same search as `optimize`, which runs it to the end; stepping two solvers
+/// with equally seeded RNGs (`with_rng`) gives the same tour.
+pub struct GeneticSolver<'a, R: Rng = BoxedRng> {
+    graph: &'a [Vec<f64>],
+    rng: R,
+    population: Vec<Vec<usize>>,
+    best: Vec<usize>,
+    mutation_rate: f64,
+    remaining: usize,
+    // Buffers of `step`, kept between generations
+    offspring: Vec<Vec<usize>>,
+    fitnesses: Vec<f64>,
+    used: Vec<bool>,
+}
+
+impl<'a> GeneticSolver<'a> {
+    pub fn new(
+        graph: &'a [Vec<f64>],
+        n: usize,
+        population_size: usize,
+        generations: usize,
+        mutation_rate: f64,
+    ) -> Result<Self, SonarError> {
+        let scratch = &mut SolverScratch::new();
+        Self::from_scratch(graph, n, population_size, generations, mutation_rate, scratch, determinism::rng(0))
+    }
+}
+
+impl<'a, R: Rng> GeneticSolver<'a, R> {
+    /// `new` on the buffers of `scratch`, which `into_scratch` gives back, drawing from `rng`
+    fn from_scratch(
+        graph: &'a [Vec<f64>],
+        n: usize,
+        population_size: usize,
+        generations: usize,
+        mutation_rate: f64,
+        scratch: &mut SolverScratch,
+        mut rng: R,
+    ) -> Result<Self, SonarError> {
+        validate_graph(graph)?;
+        validate_size(n, graph)?;
+        validate_population(population_size)?;
+
+        // Initialize population
+        let mut population = mem::take(&mut scratch.population);
+        population.resize_with(population_size, Vec::new);
+        for tour in &mut population {
+            shuffle_into(tour, n, &mut rng);
+        }
+        let mut best = mem::take(&mut scratch.best);
+        best.clone_from(fittest(&population, graph));
+
+        Ok(GeneticSolver {
+            graph,
+            rng,
+            population,
+            best,
+            mutation_rate,
+            remaining: generations,
+            offspring: mem::take(&mut scratch.offspring),
+            fitnesses: mem::take(&mut scratch.fitnesses),
+            used: mem::take(&mut scratch.visited),
+        })
+    }
+
+    /// Draw random numbers from `rng` instead of the default stream
+    /// The initial population is regenerated from `rng`.
+    pub fn with_rng<S: Rng>(self, mut rng: S) -> GeneticSolver<'a, S> {
+        let n = self.population[0].len();
+        let mut population = self.population;
+        for tour in &mut population {
+            shuffle_into(tour, n, &mut rng);
+        }
+        let mut best = self.best;
+        best.clone_from(fittest(&population, self.graph));
+        GeneticSolver {
+            graph: self.graph,
+            rng,
+            population,
+            best,
+            mutation_rate: self.mutation_rate,
+            remaining: self.remaining,
+            offspring: self.offspring,
+            fitnesses: self.fitnesses,
+            used: self.used,
+        }
+    }
+
+    pub fn rng(&self) -> &R {
+        &self.rng
+    }
+
+    /// Hand the buffers back to `scratch` for the next run
+    fn into_scratch(self, scratch: &mut SolverScratch) {
+        scratch.population = self.population;
+        scratch.best = self.best;
+        scratch.offspring = self.offspring;
+        scratch.fitnesses = self.fitnesses;
+        scratch.visited = self.used;
+    }
+}
+
+/// Shortest tour of a population, the first one on ties
+fn fittest<'p>(population: &'p [Vec<usize>], graph: &[Vec<f64>]) -> &'p Vec<usize> {
+    let mut best_tour = &population[0];
+    let mut best_length = calculate_tour_length(best_tour, graph);
+
+    for tour in population {
+        let length = calculate_tour_length(tour, graph);
+        if length < best_length {
+            best_tour = tour;
+            best_length = length;
+        }
+    }
+    best_tour
+}
+
+impl<R: Rng> SteppableSolver for GeneticSolver<'_, R> {
+    /// One generation of selection, crossover and mutation
+    /// Time complexity: O(populationSize * n)
+    fn step(&mut self) -> &[usize] {
+        if self.remaining == 0 {
+            return &self.best;
+        }
+        self.remaining -= 1;
+
+        let graph = self.graph;
+        let population = &self.population;
+        let population_size = population.len();
 
-    for _ in 0..generations {
         // Calculate fitness (inverse of tour length)
-        let fitnesses: Vec<f64> = population
-            .iter()
-            .map(|tour| 1.0 / calculate_tour_length(tour, graph))
-            .collect();
+        self.fitnesses.clear();
+        self.fitnesses.extend(population.iter().map(|tour| 1.0 / calculate_tour_length(tour, graph)));
+        let fitnesses = &self.fitnesses;
         let total_fitness: f64 = fitnesses.iter().sum();
 
         // Roulette wheel selection
-        let select = |rng: &mut rand::rngs::ThreadRng| -> &Vec<usize> {
+        let select = |rng: &mut R| -> &Vec<usize> {
             let mut r = rng.gen::<f64>() * total_fitness;
             for (i, &fitness) in fitnesses.iter().enumerate() {
                 r -= fitness;
@@ -89,54 +244,151 @@ pub fn optimize(
             &population[population_size - 1]
         };
 
-        // Create new population
-        let mut new_population: Vec<Vec<usize>> = Vec::with_capacity(population_size);
+        // Create new population in the offspring buffers
+        let new_population = &mut self.offspring;
+        new_population.resize_with(population_size, Vec::new);
 
         // Elitism: keep the best individual
         let best_idx = fitnesses
             .iter()
             .enumerate()
-            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
             .map(|(i, _)| i)
             .unwrap_or(0);
-        new_population.push(population[best_idx].clone());
+        new_population[0].clone_from(&population[best_idx]);
 
         // Generate rest of population through selection and crossover
-        while new_population.len() < population_size {
-            let parent1 = select(&mut rng);
-            let parent2 = select(&mut rng);
-            let mut child = crossover(parent1, parent2, &mut rng);
+        for child in &mut new_population[1..] {
+            let parent1 = select(&mut self.rng);
+            let parent2 = select(&mut self.rng);
+            crossover(parent1, parent2, &mut self.rng, &mut self.used, child);
 
             // Apply mutation with given probability
-            if rng.gen::<f64>() < mutation_rate {
-                child = mutate(&child, &mut rng);
+            if self.rng.gen::<f64>() < self.mutation_rate {
+                mutate(child, &mut self.rng);
             }
-
-            new_population.push(child);
         }
 
-        population = new_population;
+        mem::swap(&mut self.population, &mut self.offspring);
+        self.best.clone_from(fittest(&self.population, graph));
+        &self.best
     }
 
-    // Return the best tour from final population
-    let mut best_tour = population[0].clone();
-    let mut best_length = calculate_tour_length(&best_tour, graph);
+    /// Best tour of the current population
+    fn tour(&self) -> &[usize] {
+        &self.best
+    }
 
-    for tour in &population {
-        let length = calculate_tour_length(tour, graph);
-        if length < best_length {
-            best_tour = tour.clone();
-            best_length = length;
+    fn is_finished(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+/// Reverse the cyclic range of positions from `i` to `j` inclusive, keeping `position` in sync
+fn invert(tour: &mut [usize], position: &mut [usize], mut i: usize, mut j: usize) {
+    let n = tour.len();
+    let length = (j + n - i) % n + 1;
+    for _ in 0..length / 2 {
+        tour.swap(i, j);
+        position[tour[i]] = i;
+        position[tour[j]] = j;
+        i = (i + 1) % n;
+        j = (j + n - 1) % n;
+    }
+}
+
+/// Optimize TSP using the Inver-over evolutionary operator (Tao & Michalewicz)
+/// Instead of crossover + mutation, every individual is improved by a chain of
+/// inversions whose end points are guided by the adjacency found in other
+/// individuals (or chosen at random with probability `random_inversion_rate`).
+/// The offspring replaces its parent only if it is not longer.
+/// Time complexity: O(populationSize * generations * n^2) worst case
+pub fn optimize_inver_over(
+    graph: &[Vec<f64>],
+    n: usize,
+    population_size: usize,
+    generations: usize,
+    random_inversion_rate: f64,
+) -> Result<Vec<usize>, SonarError> {
+    validate_graph(graph)?;
+    validate_size(n, graph)?;
+    validate_population(population_size)?;
+
+    let mut rng = determinism::rng(0);
+
+    let mut population: Vec<Vec<usize>> = (0..population_size)
+        .map(|_| random_tour(n, &mut rng))
+        .collect();
+    let mut lengths: Vec<f64> = population
+        .iter()
+        .map(|tour| calculate_tour_length(tour, graph))
+        .collect();
+
+    if n >= 4 {
+        let mut position = vec![0; n];
+
+        for _ in 0..generations {
+            for i in 0..population_size {
+                let mut offspring = population[i].clone();
+                for (p, &city) in offspring.iter().enumerate() {
+                    position[city] = p;
+                }
+
+                let mut c = offspring[rng.gen_range(0..n)];
+                loop {
+                    let c_next = if rng.gen::<f64>() < random_inversion_rate {
+                        let mut other = offspring[rng.gen_range(0..n)];
+                        while other == c {
+                            other = offspring[rng.gen_range(0..n)];
+                        }
+                        other
+                    } else {
+                        // Take the successor of c in a randomly chosen other individual
+                        let guide = &population[rng.gen_range(0..population_size)];
+                        let k = guide.iter().position(|&city| city == c).unwrap();
+                        guide[(k + 1) % n]
+                    };
+
+                    let p = position[c];
+                    if offspring[(p + 1) % n] == c_next || offspring[(p + n - 1) % n] == c_next {
+                        break;
+                    }
+
+                    // Invert the section from next(c) to c_next so c and c_next become adjacent
+                    let q = position[c_next];
+                    invert(&mut offspring, &mut position, (p + 1) % n, q);
+                    c = c_next;
+                }
+
+                let length = calculate_tour_length(&offspring, graph);
+                if length <= lengths[i] {
+                    population[i] = offspring;
+                    lengths[i] = length;
+                }
+            }
         }
     }
 
-    best_tour
+    let best_idx = lengths
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    Ok(population.swap_remove(best_idx))
+}
+
+fn validate_population(population_size: usize) -> Result<(), SonarError> {
+    if population_size == 0 {
+        return Err(SonarError::InvalidParameter { name: "population_size", reason: "must be positive" });
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::utils::{create_distance_matrix, Point};
+    use crate::utils::{calculate_tour_length, create_distance_matrix, Point};
     use std::f64::consts::PI;
 
     #[test]
@@ -148,7 +400,7 @@ mod tests {
             Point { x: 0.0, y: 1.0, angle: PI / 2.0, id: 3 },
         ];
         let graph = create_distance_matrix(&points);
-        let tour = optimize(&graph, 4, 50, 100, 0.1);
+        let tour = optimize(&graph, 4, 50, 100, 0.1).unwrap();
 
         assert_eq!(tour.len(), points.len());
 
@@ -157,5 +409,33 @@ mod tests {
         for &id in &tour {
             assert!(seen.insert(id));
         }
+
+        // Stepping one generation at a time replays the run of an equally seeded solver
+        let graph = create_distance_matrix(&crate::utils::generate_normalized_points(30, 20, 5));
+        let seeded = || GeneticSolver::new(&graph, 30, 20, 50, 0.1).unwrap().with_rng(determinism::seeded(7));
+        let mut stepped = seeded();
+        for _ in 0..50 {
+            assert_eq!(stepped.step().len(), 30);
+        }
+        assert!(stepped.is_finished());
+        assert_eq!(stepped.tour(), seeded().run());
+    }
+
+    #[test]
+    fn test_inver_over_beats_random_tours() {
+        use crate::utils::generate_normalized_points;
+
+        let points = generate_normalized_points(40, 20, 8);
+        let graph = create_distance_matrix(&points);
+        let tour = optimize_inver_over(&graph, points.len(), 30, 200, 0.02).unwrap();
+
+        assert_eq!(tour.len(), points.len());
+        let mut seen = std::collections::HashSet::new();
+        for &id in &tour {
+            assert!(seen.insert(id));
+        }
+
+        let random = crate::random_tour::generate_tour(points.len(), 1);
+        assert!(calculate_tour_length(&tour, &graph) < 0.5 * calculate_tour_length(&random, &graph));
     }
 }
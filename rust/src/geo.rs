@@ -0,0 +1,327 @@
+//! GIS point formats: GeoJSON and GPX waypoints
+//!
+//! Points are read as x = longitude and y = latitude in degrees, the order
+//! both formats store them in, ready for `Metric::Haversine`. `project` maps
+//! them onto a plane in km instead, for the solvers that work on coordinates
+//! and for the faster Euclidean metric; over a city or a region the error is
+//! well under a percent.
+
+use crate::problem::EARTH_RADIUS_KM;
+use crate::utils::Point;
+
+/// Just enough JSON to walk a GeoJSON document
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// Deepest nesting of arrays and objects `Parser` accepts; GeoJSON stays under ten
+const MAX_DEPTH: usize = 64;
+
+/// Recursive descent parser over the bytes of a document
+struct Parser<'a> {
+    bytes: &'a [u8],
+    at: usize,
+    /// Values being parsed that enclose the current one
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.bytes.get(self.at).is_some_and(u8::is_ascii_whitespace) {
+            self.at += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.bytes.get(self.at) == Some(&byte) {
+            self.at += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at byte {}", byte as char, self.at))
+        }
+    }
+
+    /// A value, failing instead of overflowing the stack on deep nesting
+    fn value(&mut self) -> Result<Json, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("JSON nested deeper than {} levels at byte {}", MAX_DEPTH, self.at));
+        }
+        self.depth += 1;
+        let value = self.nested_value();
+        self.depth -= 1;
+        value
+    }
+
+    fn nested_value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.bytes.get(self.at) {
+            Some(b'{') => {
+                self.at += 1;
+                let mut members = Vec::new();
+                self.skip_whitespace();
+                if self.bytes.get(self.at) == Some(&b'}') {
+                    self.at += 1;
+                    return Ok(Json::Object(members));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    members.push((key, self.value()?));
+                    self.skip_whitespace();
+                    match self.bytes.get(self.at) {
+                        Some(b',') => self.at += 1,
+                        _ => break,
+                    }
+                }
+                self.expect(b'}')?;
+                Ok(Json::Object(members))
+            }
+            Some(b'[') => {
+                self.at += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.bytes.get(self.at) == Some(&b']') {
+                    self.at += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.bytes.get(self.at) {
+                        Some(b',') => self.at += 1,
+                        _ => break,
+                    }
+                }
+                self.expect(b']')?;
+                Ok(Json::Array(items))
+            }
+            Some(b'"') => self.string().map(Json::String),
+            Some(_) => {
+                let start = self.at;
+                while self.bytes.get(self.at).is_some_and(|&b| !b",]} \t\r\n".contains(&b)) {
+                    self.at += 1;
+                }
+                let token = String::from_utf8_lossy(&self.bytes[start..self.at]);
+                match token.as_ref() {
+                    "null" => Ok(Json::Null),
+                    "true" => Ok(Json::Bool(true)),
+                    "false" => Ok(Json::Bool(false)),
+                    number => number.parse().map(Json::Number).map_err(|_| format!("invalid JSON value: {}", number)),
+                }
+            }
+            None => Err("unexpected end of JSON".to_string()),
+        }
+    }
+
+    /// A string literal; escapes other than `\uXXXX` map to their character
+    fn string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut out = Vec::new();
+        loop {
+            match self.bytes.get(self.at) {
+                Some(b'"') => {
+                    self.at += 1;
+                    return String::from_utf8(out).map_err(|_| "invalid UTF-8 in JSON string".to_string());
+                }
+                Some(b'\\') => {
+                    let escaped = *self.bytes.get(self.at + 1).ok_or("unterminated JSON string")?;
+                    self.at += 2;
+                    match escaped {
+                        b'n' => out.push(b'\n'),
+                        b't' => out.push(b'\t'),
+                        b'u' => {
+                            let hex = self.bytes.get(self.at..self.at + 4).ok_or("truncated \\u escape")?;
+                            let code = u32::from_str_radix(&String::from_utf8_lossy(hex), 16)
+                                .map_err(|_| "invalid \\u escape".to_string())?;
+                            let c = char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+                            out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                            self.at += 4;
+                        }
+                        other => out.push(other),
+                    }
+                }
+                Some(&byte) => {
+                    out.push(byte);
+                    self.at += 1;
+                }
+                None => return Err("unterminated JSON string".to_string()),
+            }
+        }
+    }
+}
+
+/// Position `[longitude, latitude, ...]` as a point
+fn position(value: &Json, id: usize) -> Result<Point, String> {
+    match value {
+        Json::Array(items) => match items.as_slice() {
+            [Json::Number(x), Json::Number(y), ..] => Ok(Point { x: *x, y: *y, angle: 0.0, id }),
+            _ => Err("a position needs a longitude and a latitude".to_string()),
+        },
+        _ => Err("a position must be an array".to_string()),
+    }
+}
+
+/// Collect the points of a GeoJSON object into `points`
+fn collect(object: &Json, points: &mut Vec<Point>) -> Result<(), String> {
+    match object.get("type").and_then(Json::as_str) {
+        Some("FeatureCollection") => match object.get("features") {
+            Some(Json::Array(features)) => features.iter().try_for_each(|feature| collect(feature, points)),
+            _ => Err("a FeatureCollection needs a features array".to_string()),
+        },
+        Some("Feature") => match object.get("geometry") {
+            Some(Json::Null) | None => Ok(()),
+            Some(geometry) => collect(geometry, points),
+        },
+        Some("GeometryCollection") => match object.get("geometries") {
+            Some(Json::Array(geometries)) => geometries.iter().try_for_each(|geometry| collect(geometry, points)),
+            _ => Err("a GeometryCollection needs a geometries array".to_string()),
+        },
+        Some("Point") => {
+            points.push(position(object.get("coordinates").unwrap_or(&Json::Null), points.len())?);
+            Ok(())
+        }
+        Some("MultiPoint") => match object.get("coordinates") {
+            Some(Json::Array(positions)) => positions.iter().try_for_each(|value| {
+                points.push(position(value, points.len())?);
+                Ok(())
+            }),
+            _ => Err("a MultiPoint needs an array of positions".to_string()),
+        },
+        // Lines and polygons are not waypoints
+        Some(_) => Ok(()),
+        None => Err("GeoJSON object without a type".to_string()),
+    }
+}
+
+/// Parse the Point and MultiPoint geometries of a GeoJSON document, in
+/// document order; other geometries are skipped
+/// Time complexity: O(length of the document)
+pub fn parse_geojson(content: &str) -> Result<Vec<Point>, String> {
+    let mut parser = Parser { bytes: content.as_bytes(), at: 0, depth: 0 };
+    let document = parser.value()?;
+    let mut points = Vec::new();
+    collect(&document, &mut points)?;
+    if points.is_empty() {
+        return Err("no Point or MultiPoint geometries".to_string());
+    }
+    Ok(points)
+}
+
+/// Value of the attribute `name` in the text of a tag
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(at) = rest.find(name) {
+        let before = rest[..at].chars().next_back();
+        let after = rest[at + name.len()..].trim_start();
+        rest = &rest[at + name.len()..];
+        if !before.is_some_and(char::is_whitespace) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else { continue };
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        return value[1..].split(quote).next();
+    }
+    None
+}
+
+/// Parse the `<wpt lat=".." lon="..">` waypoints of a GPX document, in
+/// document order
+/// Time complexity: O(length of the document)
+pub fn parse_gpx(content: &str) -> Result<Vec<Point>, String> {
+    let mut points = Vec::new();
+    for (at, _) in content.match_indices("<wpt") {
+        let tag = &content[at + 4..];
+        if !tag.starts_with(|c: char| c.is_whitespace()) {
+            continue;
+        }
+        let tag = &tag[..tag.find('>').ok_or("unterminated <wpt> tag")?];
+        let coordinate = |name: &str| {
+            let value = attribute(tag, name).ok_or(format!("waypoint {} has no {} attribute", points.len() + 1, name))?;
+            value.trim().parse::<f64>().map_err(|_| format!("invalid {} in waypoint: {}", name, value))
+        };
+        let (x, y) = (coordinate("lon")?, coordinate("lat")?);
+        points.push(Point { x, y, angle: 0.0, id: points.len() });
+    }
+    if points.is_empty() {
+        return Err("no <wpt> waypoints".to_string());
+    }
+    Ok(points)
+}
+
+/// Equirectangular projection of longitude/latitude points onto a plane in
+/// km, centred on their mean latitude
+/// Time complexity: O(n)
+pub fn project(points: &[Point]) -> Vec<Point> {
+    let mean_latitude = points.iter().map(|p| p.y).sum::<f64>() / points.len().max(1) as f64;
+    let scale = EARTH_RADIUS_KM.to_radians();
+    let x_scale = scale * mean_latitude.to_radians().cos();
+    points.iter().map(|p| Point { x: p.x * x_scale, y: p.y * scale, angle: 0.0, id: p.id }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::Metric;
+
+    #[test]
+    fn test_geojson_and_gpx_waypoints() {
+        let geojson = r#"{ "type": "FeatureCollection", "features": [
+            { "type": "Feature", "properties": { "name": "Café \"A\"" },
+              "geometry": { "type": "Point", "coordinates": [2.3522, 48.8566] } },
+            { "type": "Feature", "geometry": { "type": "LineString", "coordinates": [[0, 0], [1, 1]] } },
+            { "type": "Feature", "geometry": null },
+            { "type": "Feature", "geometry": { "type": "MultiPoint", "coordinates": [[-0.1276, 51.5072, 11.0]] } }
+        ] }"#;
+        let points = parse_geojson(geojson).unwrap();
+        assert_eq!(points.iter().map(|p| (p.x, p.y, p.id)).collect::<Vec<_>>(), [
+            (2.3522, 48.8566, 0),
+            (-0.1276, 51.5072, 1)
+        ]);
+        assert!(parse_geojson(r#"{ "type": "FeatureCollection", "features": [] }"#).is_err());
+        assert!(parse_geojson(r#"{ "type": "Point", "coordinates": [1] }"#).is_err());
+        let nested = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        assert!(parse_geojson(&nested).unwrap_err().contains("nested deeper"));
+
+        let gpx = r#"<?xml version="1.0"?>
+            <gpx version="1.1"><wpt lat="48.8566" lon="2.3522"><name>Paris</name></wpt>
+            <wpt lon='-0.1276' lat='51.5072'/><wptExtension lat="0" lon="0"/></gpx>"#;
+        let waypoints = parse_gpx(gpx).unwrap();
+        assert_eq!(waypoints.iter().map(|p| (p.x, p.y, p.id)).collect::<Vec<_>>(), [
+            (2.3522, 48.8566, 0),
+            (-0.1276, 51.5072, 1)
+        ]);
+        assert!(parse_gpx(r#"<gpx><wpt lat="1"></wpt></gpx>"#).is_err());
+
+        // Paris to London is about 344 km on the sphere and on the projection
+        let great_circle = Metric::Haversine.distance(&points[0], &points[1]);
+        assert!((great_circle - 343.9).abs() < 1.0, "{}", great_circle);
+        let projected = project(&points);
+        let planar = Metric::Euclidean.distance(&projected[0], &projected[1]);
+        assert!((planar - great_circle).abs() / great_circle < 0.01, "{}", planar);
+    }
+}
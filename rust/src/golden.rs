@@ -0,0 +1,176 @@
+//! Golden-tour snapshots - expected tours of deterministic algorithms on fixed instances
+//!
+//! `fixtures/golden.txt` holds one line per (algorithm, seed, n) triple:
+//! `<key> <seed> <n> <length> <tour...>`, with keys from the registry. A
+//! refactor that changes any of these tours shows up as a mismatch; when the
+//! change is intended, re-record the file with `benchmark golden --record`.
+
+use crate::problem::{Metric, ProblemInstance};
+use crate::registry;
+use crate::utils::generate_normalized_points;
+use std::fmt::{self, Write};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Registered algorithms whose tours depend only on the instance
+pub const KEYS: &[&str] = &[
+    "angular-sort",
+    "sonar-visit",
+    "nearest-neighbor",
+    "nearest-neighbor-grid",
+    "greedy-edge",
+    "karp-partition",
+    "quadtree",
+    "two-opt",
+    "two-h-opt",
+    "or-one",
+    "zigzag",
+    "held-karp",
+];
+
+/// Point seeds and sizes every key is recorded on, within its `max_n`
+pub const SEEDS: &[u64] = &[1, 12345];
+pub const SIZES: &[usize] = &[12, 50, 200];
+
+/// Grid resolution of the generated points
+const GRID_SIZE: usize = 40;
+
+/// Recorded lengths are printed with this many decimals and compared to match
+const LENGTH_DECIMALS: usize = 9;
+
+/// The fixture file in the source tree
+pub fn default_path() -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/golden.txt"))
+}
+
+/// A snapshot entry: a tour and its length
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub key: String,
+    pub seed: u64,
+    pub n: usize,
+    pub length: f64,
+    pub tour: Vec<usize>,
+}
+
+/// A recorded tour the current code no longer reproduces
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub key: String,
+    pub seed: u64,
+    pub n: usize,
+    pub detail: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} seed={} n={}: {}", self.key, self.seed, self.n, self.detail)
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Run `key` on `n` points from `seed` with the current code
+pub fn take(key: &str, seed: u64, n: usize) -> io::Result<Snapshot> {
+    let algorithm = registry::find(key).ok_or_else(|| invalid(format!("unknown algorithm {:?}", key)))?;
+    let points = generate_normalized_points(n, GRID_SIZE, seed);
+    let to_io = |e: crate::error::SonarError| invalid(format!("{}: {}", key, e));
+    let instance = ProblemInstance::new(points, Metric::Euclidean).map_err(to_io)?;
+    let tour = (algorithm.solve)(&instance).map_err(to_io)?;
+    Ok(Snapshot { key: key.to_string(), seed, n, length: instance.tour_length(&tour), tour })
+}
+
+/// Snapshots of every key on every seed and size it may run at
+pub fn take_all() -> io::Result<Vec<Snapshot>> {
+    let mut snapshots = Vec::new();
+    for &key in KEYS {
+        let max_n = registry::find(key).and_then(|a| a.max_n).unwrap_or(usize::MAX);
+        for &seed in SEEDS {
+            for &n in SIZES.iter().filter(|&&n| n <= max_n) {
+                snapshots.push(take(key, seed, n)?);
+            }
+        }
+    }
+    Ok(snapshots)
+}
+
+/// The fixture file's text for `snapshots`
+pub fn format(snapshots: &[Snapshot]) -> String {
+    let mut out = String::from("# Golden tours: <key> <seed> <n> <length> <tour...>\n");
+    out.push_str("# Regenerate with: cargo run --release -- golden --record\n");
+    for s in snapshots {
+        let tour: Vec<String> = s.tour.iter().map(usize::to_string).collect();
+        writeln!(out, "{} {} {} {:.*} {}", s.key, s.seed, s.n, LENGTH_DECIMALS, s.length, tour.join(" ")).unwrap();
+    }
+    out
+}
+
+/// Parse a fixture file, skipping `#` comments and blank lines
+pub fn parse(content: &str) -> io::Result<Vec<Snapshot>> {
+    let mut snapshots = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let bad = || invalid(format!("golden line {}: {:?}", number + 1, line));
+        let mut fields = line.split_whitespace();
+        let key = fields.next().ok_or_else(bad)?.to_string();
+        let seed = fields.next().and_then(|f| f.parse().ok()).ok_or_else(bad)?;
+        let n = fields.next().and_then(|f| f.parse().ok()).ok_or_else(bad)?;
+        let length = fields.next().and_then(|f| f.parse().ok()).ok_or_else(bad)?;
+        let tour = fields.map(|f| f.parse().map_err(|_| bad())).collect::<io::Result<_>>()?;
+        snapshots.push(Snapshot { key, seed, n, length, tour });
+    }
+    Ok(snapshots)
+}
+
+/// Write fresh snapshots of everything to `path`
+pub fn record(path: &Path) -> io::Result<usize> {
+    let snapshots = take_all()?;
+    std::fs::write(path, format(&snapshots))?;
+    Ok(snapshots.len())
+}
+
+/// Re-run every snapshot in `path` and list the ones that changed
+pub fn verify(path: &Path) -> io::Result<Vec<Mismatch>> {
+    let mut mismatches = Vec::new();
+    for expected in parse(&std::fs::read_to_string(path)?)? {
+        let actual = take(&expected.key, expected.seed, expected.n)?;
+        let rounded = |length: f64| format!("{:.*}", LENGTH_DECIMALS, length);
+        let detail = if actual.tour != expected.tour {
+            let at = actual.tour.iter().zip(&expected.tour).position(|(a, b)| a != b);
+            format!(
+                "tour differs from position {}, length {} was {}",
+                at.unwrap_or(actual.tour.len().min(expected.tour.len())),
+                rounded(actual.length),
+                rounded(expected.length)
+            )
+        } else if rounded(actual.length) != rounded(expected.length) {
+            format!("same tour, length {} was {}", rounded(actual.length), rounded(expected.length))
+        } else {
+            continue;
+        };
+        mismatches.push(Mismatch { key: expected.key, seed: expected.seed, n: expected.n, detail });
+    }
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tours_match_golden_fixtures() {
+        let mismatches = verify(&default_path()).unwrap();
+        assert!(mismatches.is_empty(), "re-record with `golden --record` if intended: {:?}", mismatches);
+        // Every recordable triple is in the file
+        let recorded = parse(&std::fs::read_to_string(default_path()).unwrap()).unwrap();
+        assert_eq!(recorded.len(), take_all().unwrap().len());
+
+        let snapshots = vec![take("angular-sort", 3, 8).unwrap()];
+        assert_eq!(parse(&format(&snapshots)).unwrap()[0].tour, snapshots[0].tour);
+    }
+}
@@ -0,0 +1,260 @@
+//! 2-opt with every move evaluated on the GPU - compute shader scoring, CPU application
+//!
+//! Each sweep uploads the tour's coordinates in tour order, and one shader
+//! invocation per tour position scans all of its 2-opt partners and reports the
+//! best one. The CPU re-checks those candidates in f64 and applies, best first,
+//! every move whose span of tour positions does not overlap an already chosen one.
+
+use crate::error::{validate_points, validate_tour, SonarError};
+use crate::utils::{distance, Point};
+use std::collections::BTreeMap;
+use wgpu::util::DeviceExt;
+
+/// Invocations per workgroup; must match `@workgroup_size` in the shader
+const WORKGROUP_SIZE: usize = 64;
+
+/// Most workgroups wgpu guarantees along one dispatch dimension
+const MAX_WORKGROUPS: usize = 65_535;
+
+/// For tour position i, the partner j with the most negative delta of
+/// replacing edges (i, i+1) and (j, j+1) by (i, j) and (i+1, j+1)
+const SHADER: &str = r#"
+struct Best {
+    delta: f32,
+    j: u32,
+}
+
+@group(0) @binding(0) var<storage, read> coords: array<vec2<f32>>;
+@group(0) @binding(1) var<storage, read_write> best: array<Best>;
+@group(0) @binding(2) var<uniform> params: vec4<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let n = params.x;
+    let i = id.x;
+    if (i >= n) {
+        return;
+    }
+    var best_delta = 0.0;
+    var best_j = 0u;
+    if (i + 2u < n) {
+        let a = coords[i];
+        let b = coords[i + 1u];
+        let ab = distance(a, b);
+        // From position 0 the last edge shares a city with the first
+        var last = n;
+        if (i == 0u) {
+            last = n - 1u;
+        }
+        for (var j = i + 2u; j < last; j = j + 1u) {
+            let c = coords[j];
+            let d = coords[(j + 1u) % n];
+            let delta = distance(a, c) + distance(b, d) - ab - distance(c, d);
+            if (delta < best_delta) {
+                best_delta = delta;
+                best_j = j;
+            }
+        }
+    }
+    best[i] = Best(best_delta, best_j);
+}
+"#;
+
+/// A GPU device with the 2-opt evaluation pipeline compiled
+/// Create it once and reuse it; adapter and shader setup dominate small runs.
+pub struct GpuTwoOpt {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuTwoOpt {
+    /// Pick the default adapter and compile the shader
+    /// Fails with `BackendUnavailable` on machines without a usable GPU.
+    pub fn new() -> Result<Self, SonarError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .ok_or(SonarError::BackendUnavailable { reason: "no GPU adapter found" })?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+            .map_err(|_| SonarError::BackendUnavailable { reason: "GPU device request failed" })?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("two_opt"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("two_opt"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        Ok(GpuTwoOpt { device, queue, pipeline })
+    }
+
+    /// Apply 2-opt to `tour` over `points` until a sweep finds no improving
+    /// move or after `max_iterations` sweeps
+    /// Distances are Euclidean on the points; no distance matrix is built.
+    /// Time complexity: O(n^2) per sweep on the GPU, O(n log n) on the CPU
+    pub fn improve(&self, points: &[Point], tour: &[usize], max_iterations: usize) -> Result<Vec<usize>, SonarError> {
+        validate_points(points)?;
+        validate_tour(tour, points.len())?;
+        let n = tour.len();
+        if n.div_ceil(WORKGROUP_SIZE) > MAX_WORKGROUPS {
+            return Err(SonarError::TooLarge { n, max: WORKGROUP_SIZE * MAX_WORKGROUPS });
+        }
+        let mut tour = tour.to_vec();
+        if n < 4 {
+            return Ok(tour);
+        }
+
+        // f32 coordinates relative to the bounding box corner keep their precision
+        let min_x = points.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
+        let min_y = points.iter().map(|p| p.y).fold(f64::INFINITY, f64::min);
+
+        let buffer = |label, size: usize, usage| {
+            self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: size as u64,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        let coords = buffer("coords", n * 8, wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST);
+        let best = buffer("best", n * 8, wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC);
+        let staging = buffer("staging", n * 8, wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST);
+        let params: Vec<u8> = [n as u32, 0, 0, 0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let params = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("params"),
+            contents: &params,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("two_opt"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: coords.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: best.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: params.as_entire_binding() },
+            ],
+        });
+
+        let mut candidates = Vec::new();
+        for _ in 0..max_iterations {
+            let bytes: Vec<u8> = tour
+                .iter()
+                .flat_map(|&city| {
+                    let (x, y) = ((points[city].x - min_x) as f32, (points[city].y - min_y) as f32);
+                    x.to_le_bytes().into_iter().chain(y.to_le_bytes())
+                })
+                .collect();
+            self.queue.write_buffer(&coords, 0, &bytes);
+
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+                pass.set_pipeline(&self.pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.dispatch_workgroups(n.div_ceil(WORKGROUP_SIZE) as u32, 1, 1);
+            }
+            encoder.copy_buffer_to_buffer(&best, 0, &staging, 0, (n * 8) as u64);
+            self.queue.submit(Some(encoder.finish()));
+
+            let slice = staging.slice(..);
+            let (sender, receiver) = std::sync::mpsc::channel();
+            slice.map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+            self.device.poll(wgpu::Maintain::Wait);
+            receiver
+                .recv()
+                .ok()
+                .and_then(|result| result.ok())
+                .ok_or(SonarError::BackendUnavailable { reason: "reading GPU results failed" })?;
+
+            // Re-score the reported moves in f64 so f32 rounding never makes a tour longer
+            candidates.clear();
+            let edge = |a: usize, b: usize| distance(&points[tour[a]], &points[tour[b % n]]);
+            for (i, entry) in slice.get_mapped_range().chunks_exact(8).enumerate() {
+                let j = u32::from_le_bytes(entry[4..8].try_into().unwrap()) as usize;
+                if j < i + 2 {
+                    continue;
+                }
+                let delta = edge(i, j) + edge(i + 1, j + 1) - edge(i, i + 1) - edge(j, j + 1);
+                if delta < -1e-12 {
+                    candidates.push((delta, i, j));
+                }
+            }
+            staging.unmap();
+
+            let moves = select_moves(&mut candidates, n);
+            if moves.is_empty() {
+                break;
+            }
+            for (i, j) in moves {
+                tour[i + 1..=j].reverse();
+            }
+        }
+        Ok(tour)
+    }
+}
+
+/// Apply GPU-evaluated 2-opt, see `GpuTwoOpt::improve`
+/// Sets up the GPU on every call; keep a `GpuTwoOpt` around to run it repeatedly.
+pub fn improve(points: &[Point], tour: &[usize], max_iterations: usize) -> Result<Vec<usize>, SonarError> {
+    GpuTwoOpt::new()?.improve(points, tour, max_iterations)
+}
+
+/// Pick improving moves `(delta, i, j)` best first, skipping any whose span of
+/// tour positions i..=j+1 overlaps an already picked move
+/// Non-overlapping moves change disjoint edges, so their deltas still hold
+/// when all are applied together. A span ending at position n wraps to 0.
+/// Time complexity: O(m log m) for m candidates
+fn select_moves(candidates: &mut [(f64, usize, usize)], n: usize) -> Vec<(usize, usize)> {
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+    // Picked spans by start position, mapped to their last position
+    let mut spans: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut moves = Vec::new();
+    for &(_, i, j) in candidates.iter() {
+        let (start, end) = (i, j + 1);
+        let overlaps = spans.range(..=end).next_back().is_some_and(|(_, &last)| last >= start)
+            || (end == n && spans.contains_key(&0))
+            || (start == 0 && spans.values().next_back() == Some(&n));
+        if !overlaps {
+            spans.insert(start, end);
+            moves.push((i, j));
+        }
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{calculate_tour_length, create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_selected_moves_do_not_overlap() {
+        let mut candidates = vec![(-3.0, 2, 6), (-5.0, 5, 9), (-1.0, 0, 3), (-2.0, 10, 19), (-4.0, 0, 4)];
+        assert_eq!(select_moves(&mut candidates, 20), vec![(5, 9), (0, 3)]);
+        // A span reaching the end of the tour wraps onto position 0
+        let mut candidates = vec![(-2.0, 12, 19), (-1.0, 0, 5)];
+        assert_eq!(select_moves(&mut candidates, 20), vec![(12, 19)]);
+    }
+
+    #[test]
+    fn test_gpu_two_opt_shortens_tour() {
+        // Machines without an adapter have nothing to check
+        let Ok(gpu) = GpuTwoOpt::new() else { return };
+        let points = generate_normalized_points(300, 40, 3);
+        let graph = create_distance_matrix(&points);
+        let initial: Vec<usize> = (0..points.len()).collect();
+        let tour = gpu.improve(&points, &initial, 1_000).unwrap();
+        validate_tour(&tour, points.len()).unwrap();
+        assert!(calculate_tour_length(&tour, &graph) < 0.5 * calculate_tour_length(&initial, &graph));
+    }
+}
@@ -1,27 +1,59 @@
 //! Greedy Edge (Nearest Edge) algorithm for TSP
 
+use alloc::{vec, vec::Vec};
+use crate::error::{validate_graph, validate_points, validate_size, SonarError};
+use crate::scratch::{reset, SolverScratch};
+use crate::spatial_grid::SpatialGrid;
+use crate::utils::{distance, Point};
+
 /// Generate a tour using the Greedy Edge heuristic
 /// Builds tour by repeatedly adding the shortest edge that doesn't:
 /// - Create a cycle (unless it completes the tour)
 /// - Give any vertex degree > 2
+///
+/// Infinite (forbidden) edges are never added; if the finite ones do not
+/// close a tour this way it fails with `SonarError::Unreachable`.
 /// Time complexity: O(n^2 log n)
-pub fn generate_tour(n: usize, graph: &[Vec<f64>]) -> Vec<usize> {
-    // Create list of all edges
-    let mut edges: Vec<(usize, usize, f64)> = Vec::new();
+pub fn generate_tour(n: usize, graph: &[Vec<f64>]) -> Result<Vec<usize>, SonarError> {
+    let mut tour = Vec::with_capacity(n);
+    generate_tour_with(n, graph, &mut SolverScratch::new(), &mut tour)?;
+    Ok(tour)
+}
+
+/// `generate_tour` into `tour`, with the candidate edges and bookkeeping kept in `scratch`
+/// Allocates nothing once both have held a tour of n cities.
+pub fn generate_tour_with(
+    n: usize,
+    graph: &[Vec<f64>],
+    scratch: &mut SolverScratch,
+    tour: &mut Vec<usize>,
+) -> Result<(), SonarError> {
+    validate_graph(graph)?;
+    validate_size(n, graph)?;
+
+    // Create list of all edges that may be used
+    let edges = &mut scratch.candidates;
+    edges.clear();
     for i in 0..n {
         for j in i + 1..n {
-            edges.push((i, j, graph[i][j]));
+            if graph[i][j].is_finite() && graph[j][i].is_finite() {
+                edges.push((i, j, graph[i][j]));
+            }
         }
     }
 
     // Sort edges by distance
-    edges.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
+    // Equal lengths are ordered by endpoints so the result is deterministic
+    edges.sort_unstable_by(|a, b| a.2.total_cmp(&b.2).then((a.0, a.1).cmp(&(b.0, b.1))));
 
     // Track degree of each node
-    let mut degree = vec![0; n];
+    let degree = &mut scratch.degree;
+    reset(degree, n, 0);
 
     // Union-Find for cycle detection
-    let mut parent: Vec<usize> = (0..n).collect();
+    let parent = &mut scratch.parent;
+    parent.clear();
+    parent.extend(0..n);
 
     fn find(parent: &mut [usize], x: usize) -> usize {
         if parent[x] != x {
@@ -36,11 +68,12 @@ pub fn generate_tour(n: usize, graph: &[Vec<f64>]) -> Vec<usize> {
         parent[px] = py;
     }
 
-    // Track adjacency for tour reconstruction
-    let mut adj: Vec<Vec<usize>> = vec![Vec::new(); n];
+    // Track adjacency for tour reconstruction; a node's links are its first `degree` entries
+    let links = &mut scratch.links;
+    reset(links, n, [usize::MAX; 2]);
     let mut edge_count = 0;
 
-    for (from, to, _dist) in edges {
+    for &(from, to, _dist) in edges.iter() {
         if edge_count >= n {
             break;
         }
@@ -51,22 +84,23 @@ pub fn generate_tour(n: usize, graph: &[Vec<f64>]) -> Vec<usize> {
         }
 
         // Check if it would create a premature cycle
-        if edge_count < n - 1 && find(&mut parent, from) == find(&mut parent, to) {
+        if edge_count < n - 1 && find(parent, from) == find(parent, to) {
             continue;
         }
 
         // Add edge
-        adj[from].push(to);
-        adj[to].push(from);
+        links[from][degree[from]] = to;
+        links[to][degree[to]] = from;
         degree[from] += 1;
         degree[to] += 1;
-        union(&mut parent, from, to);
+        union(parent, from, to);
         edge_count += 1;
     }
 
     // Reconstruct tour from adjacency list
-    let mut tour = Vec::with_capacity(n);
-    let mut visited = vec![false; n];
+    tour.clear();
+    let visited = &mut scratch.visited;
+    reset(visited, n, false);
     let mut current = 0;
 
     while tour.len() < n {
@@ -74,7 +108,7 @@ pub fn generate_tour(n: usize, graph: &[Vec<f64>]) -> Vec<usize> {
         visited[current] = true;
 
         let mut next = None;
-        for &neighbor in &adj[current] {
+        for &neighbor in &links[current][..degree[current]] {
             if !visited[neighbor] {
                 next = Some(neighbor);
                 break;
@@ -87,7 +121,97 @@ pub fn generate_tour(n: usize, graph: &[Vec<f64>]) -> Vec<usize> {
         }
     }
 
-    tour
+    // Without enough finite edges the fragments are left unjoined
+    if tour.len() < n || (n > 2 && edge_count < n) {
+        return Err(SonarError::Unreachable { from: current });
+    }
+    Ok(())
+}
+
+/// No link in a tour under construction
+const NONE: usize = usize::MAX;
+
+/// Generate a Greedy Edge tour choosing only among candidate edges, from each
+/// city to its `k` nearest neighbors found with a bucket grid, so that no
+/// distance matrix is built and O(n * k) edges are sorted instead of O(n^2)
+/// The candidates run out before the fragments they form are joined into a
+/// tour. The cities left with fewer than two edges then take their 2k
+/// nearest among each other as the next candidates, then 4k, and so on until
+/// the tour closes. Edges chosen in earlier rounds are kept, so the tour can
+/// differ from that of `generate_tour`, which sees every edge at once.
+/// Time complexity: ~O(n * k log(n * k)) on uniformly distributed points
+pub fn generate_tour_candidates(points: &[Point], k: usize) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
+    if k == 0 {
+        return Err(SonarError::InvalidParameter { name: "k", reason: "must be positive" });
+    }
+    let n = points.len();
+    if n < 3 {
+        return Ok((0..n).collect());
+    }
+
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+    let mut parent: Vec<usize> = (0..n).collect();
+    let mut links = vec![[NONE; 2]; n];
+    let mut degree = vec![0; n];
+    let mut edge_count = 0;
+
+    // Cities with fewer than two edges
+    let mut ends: Vec<usize> = (0..n).collect();
+    let mut k = k;
+    let mut edges: Vec<(f64, usize, usize)> = Vec::new();
+    while edge_count < n {
+        // A grid of the ends alone, as they grow sparse among the other cities
+        let end_points: Vec<Point> = ends.iter().map(|&city| points[city].clone()).collect();
+        let grid = SpatialGrid::new(&end_points, 2);
+        edges.clear();
+        for (a, &i) in ends.iter().enumerate() {
+            for b in grid.k_nearest(&end_points, points[i].x, points[i].y, k, Some(a)) {
+                let j = ends[b];
+                edges.push((distance(&points[i], &points[j]), i.min(j), i.max(j)));
+            }
+        }
+        // Equal lengths are ordered by endpoints so the result is deterministic;
+        // an edge between mutual candidates is listed from both ends
+        edges.sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+        edges.dedup_by_key(|&mut (_, i, j)| (i, j));
+
+        for &(_, i, j) in &edges {
+            if degree[i] == 2 || degree[j] == 2 {
+                continue;
+            }
+            // Only the last edge may close a cycle
+            if edge_count < n - 1 && find(&mut parent, i) == find(&mut parent, j) {
+                continue;
+            }
+            links[i][degree[i]] = j;
+            links[j][degree[j]] = i;
+            degree[i] += 1;
+            degree[j] += 1;
+            let root_i = find(&mut parent, i);
+            parent[root_i] = find(&mut parent, j);
+            edge_count += 1;
+        }
+
+        ends.retain(|&city| degree[city] < 2);
+        k = k.saturating_mul(2);
+    }
+
+    // Follow the links around the cycle
+    let mut tour = Vec::with_capacity(n);
+    let (mut previous, mut current) = (links[0][1], 0);
+    while tour.len() < n {
+        tour.push(current);
+        let next = if links[current][0] == previous { links[current][1] } else { links[current][0] };
+        (previous, current) = (current, next);
+    }
+    Ok(tour)
 }
 
 #[cfg(test)]
@@ -106,7 +230,7 @@ mod tests {
         ];
         let graph = create_distance_matrix(&points);
 
-        let tour = generate_tour(points.len(), &graph);
+        let tour = generate_tour(points.len(), &graph).unwrap();
 
         assert_eq!(tour.len(), points.len());
 
@@ -116,4 +240,22 @@ mod tests {
             assert!(seen.insert(id));
         }
     }
+
+    #[test]
+    fn test_candidate_edges_escalate_to_a_tour() {
+        use crate::error::validate_tour;
+        use crate::utils::{calculate_tour_length, generate_normalized_points};
+
+        let points = generate_normalized_points(500, 60, 4);
+        let graph = create_distance_matrix(&points);
+        let dense = calculate_tour_length(&generate_tour(points.len(), &graph).unwrap(), &graph);
+        // Two candidates per city leave many fragments to join in later rounds
+        for k in [2, 8] {
+            let tour = generate_tour_candidates(&points, k).unwrap();
+            validate_tour(&tour, points.len()).unwrap();
+            assert!(calculate_tour_length(&tour, &graph) < 1.1 * dense, "k = {}", k);
+        }
+        assert_eq!(generate_tour_candidates(&points[..2], 8), Ok(vec![0, 1]));
+        assert!(generate_tour_candidates(&points, 0).is_err());
+    }
 }
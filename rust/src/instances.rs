@@ -0,0 +1,103 @@
+//! Embedded canonical instances for tests and examples
+//!
+//! The TSPLIB files live in `rust/instances/` and are compiled into the crate,
+//! so no network access or working directory assumptions are needed.
+
+use crate::tsplib::{parse_tour, parse_tsp};
+use crate::utils::Point;
+
+const BERLIN52: &str = include_str!("../instances/berlin52.tsp");
+const BERLIN52_OPT_TOUR: &str = include_str!("../instances/berlin52.opt.tour");
+const EIL101: &str = include_str!("../instances/eil101.tsp");
+const GRID100: &str = include_str!("../instances/grid100.tsp");
+const CIRCLE64: &str = include_str!("../instances/circle64.tsp");
+
+/// An embedded instance with its known-optimal tour when one is shipped
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub name: &'static str,
+    pub points: Vec<Point>,
+    pub optimal_tour: Option<Vec<usize>>,
+}
+
+/// Names of all embedded instances
+pub fn names() -> &'static [&'static str] {
+    &["berlin52", "eil101", "grid100", "circle64"]
+}
+
+/// Load an embedded instance by name
+pub fn load(name: &str) -> Option<Instance> {
+    let (name, tsp, tour) = match name {
+        "berlin52" => ("berlin52", BERLIN52, Some(BERLIN52_OPT_TOUR)),
+        "eil101" => ("eil101", EIL101, None),
+        "grid100" => ("grid100", GRID100, None),
+        "circle64" => ("circle64", CIRCLE64, None),
+        _ => return None,
+    };
+
+    Some(Instance {
+        name,
+        points: parse_tsp(tsp).expect("embedded instance is valid TSPLIB"),
+        optimal_tour: tour.map(|t| parse_tour(t).expect("embedded tour is valid TSPLIB")),
+    })
+}
+
+/// berlin52: 52 locations in Berlin, optimal length 7542 (TSPLIB rounded metric)
+pub fn berlin52() -> Instance {
+    load("berlin52").unwrap()
+}
+
+/// eil101: 101 cities (Christofides/Eilon), optimal length 629 (TSPLIB rounded metric)
+pub fn eil101() -> Instance {
+    load("eil101").unwrap()
+}
+
+/// 10 x 10 lattice with spacing 10; every optimal tour has length 1000
+pub fn grid100() -> Instance {
+    load("grid100").unwrap()
+}
+
+/// 64 points on a circle of radius 500; the optimal tour is the polygon in input order
+pub fn circle64() -> Instance {
+    load("circle64").unwrap()
+}
+
+#[cfg(all(test, feature = "constructive", feature = "local-search"))]
+mod tests {
+    use super::*;
+    use crate::utils::{calculate_tour_length, create_distance_matrix};
+    use crate::{nearest_neighbor, two_opt};
+
+    #[test]
+    fn test_embedded_instances_load_with_known_optima() {
+        for name in names() {
+            let instance = load(name).unwrap();
+            assert_eq!(&instance.name, name);
+        }
+        assert!(load("unknown").is_none());
+
+        // berlin52's optimal tour scores 7542 with TSPLIB nearest-integer distances
+        let berlin = berlin52();
+        let tour = berlin.optimal_tour.unwrap();
+        let rounded: f64 = (0..tour.len())
+            .map(|i| {
+                let (a, b) = (&berlin.points[tour[i]], &berlin.points[tour[(i + 1) % tour.len()]]);
+                ((a.x - b.x).hypot(a.y - b.y) + 0.5).floor()
+            })
+            .sum();
+        assert_eq!(rounded, 7542.0);
+
+        let grid = grid100();
+        let graph = create_distance_matrix(&grid.points);
+        let initial = nearest_neighbor::generate_tour(100, &graph, 0).unwrap();
+        let tour = two_opt::improve(&initial, &graph, 1_000).unwrap();
+        assert!(calculate_tour_length(&tour, &graph) >= 1000.0 - 1e-9);
+
+        let circle = circle64();
+        let graph = create_distance_matrix(&circle.points);
+        let polygon: Vec<usize> = (0..64).collect();
+        let expected = 64.0 * 1000.0 * (std::f64::consts::PI / 64.0).sin();
+        assert!((calculate_tour_length(&polygon, &graph) - expected).abs() < 1e-3);
+        assert_eq!(eil101().points.len(), 101);
+    }
+}
@@ -0,0 +1,186 @@
+//! Invariant predicates every solver result should satisfy
+
+use alloc::{vec, vec::Vec};
+use crate::utils::calculate_tour_length;
+
+/// Relative tolerance used when comparing tour lengths
+const LENGTH_TOLERANCE: f64 = 1e-9;
+
+/// Whether `tour` visits each of the cities 0..n exactly once
+/// Time complexity: O(n)
+pub fn is_permutation(tour: &[usize], n: usize) -> bool {
+    if tour.len() != n {
+        return false;
+    }
+    let mut seen = vec![false; n];
+    for &city in tour {
+        if city >= n || seen[city] {
+            return false;
+        }
+        seen[city] = true;
+    }
+    true
+}
+
+/// Whether an improvement heuristic kept `after` no longer than `before`
+/// (up to floating-point rounding) and still visits the same cities
+/// Time complexity: O(n)
+pub fn length_nonincreasing_after_improve(before: &[usize], after: &[usize], graph: &[Vec<f64>]) -> bool {
+    if !is_permutation(after, before.len()) {
+        return false;
+    }
+    let before_length = calculate_tour_length(before, graph);
+    let after_length = calculate_tour_length(after, graph);
+    after_length <= before_length + LENGTH_TOLERANCE * before_length.max(1.0)
+}
+
+/// Whether the matrix is square with a zero diagonal and symmetric entries
+/// Time complexity: O(n^2)
+pub fn is_symmetric(graph: &[Vec<f64>]) -> bool {
+    let n = graph.len();
+    graph.iter().enumerate().all(|(i, row)| {
+        row.len() == n && row[i] == 0.0 && (0..n).all(|j| row[j] == graph[j][i])
+    })
+}
+
+/// Whether d(i, k) <= d(i, j) + d(j, k) holds for every triple of cities
+/// Time complexity: O(n^3)
+pub fn satisfies_triangle_inequality(graph: &[Vec<f64>]) -> bool {
+    let n = graph.len();
+    for i in 0..n {
+        for j in 0..n {
+            for k in 0..n {
+                let direct = graph[i][k];
+                let detour = graph[i][j] + graph[j][k];
+                if direct > detour + LENGTH_TOLERANCE * direct.max(1.0) {
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
+
+#[cfg(all(test, feature = "constructive", feature = "local-search", feature = "exact", feature = "metaheuristics"))]
+mod tests {
+    use super::*;
+    use crate::utils::{create_distance_matrix, normalize_points, Point};
+    use crate::{
+        angular_sort, branch_and_bound, brute_force, genetic, greedy_edge, karp_partition, kopt, nearest_neighbor,
+        or_one, quadtree, random_tour, simulated_annealing, sonar_visit, two_h_opt, two_opt, zigzag,
+    };
+    use crate::simulated_annealing::MoveKind;
+    use crate::tour::{Tour, TwoLevelList};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_predicates() {
+        assert!(is_permutation(&[2, 0, 1], 3));
+        assert!(!is_permutation(&[0, 0, 1], 3));
+        assert!(!is_permutation(&[0, 1], 3));
+        assert!(!is_permutation(&[0, 1, 3], 3));
+
+        let graph = vec![vec![0.0, 1.0, 5.0], vec![1.0, 0.0, 1.0], vec![5.0, 1.0, 0.0]];
+        assert!(is_symmetric(&graph));
+        assert!(!satisfies_triangle_inequality(&graph));
+        assert!(length_nonincreasing_after_improve(&[0, 1, 2], &[0, 2, 1], &graph));
+        assert!(!length_nonincreasing_after_improve(&[0, 1, 2], &[0, 2], &graph));
+    }
+
+    /// Instances of varied size and shape: tiny, uniform, clustered, collinear, and with coincident points
+    fn instances() -> impl Strategy<Value = Vec<Point>> {
+        let coordinate = || 0.0..1.0f64;
+        let tiny = prop::collection::vec((coordinate(), coordinate()), 1..4).boxed();
+        let uniform = prop::collection::vec((coordinate(), coordinate()), 1..40).boxed();
+        let clustered = prop::collection::vec((0..3usize, 0.0..0.02f64, 0.0..0.02f64), 1..40)
+            .prop_map(|v| {
+                v.into_iter()
+                    .map(|(c, dx, dy)| (c as f64 * 0.4 + dx, c as f64 * 0.3 + dy))
+                    .collect()
+            })
+            .boxed();
+        let collinear = prop::collection::vec(coordinate(), 1..40)
+            .prop_map(|v| v.into_iter().map(|x| (x, 0.5)).collect())
+            .boxed();
+        let duplicates = prop::collection::vec((0..4usize, 0..4usize), 1..40)
+            .prop_map(|v| v.into_iter().map(|(x, y)| (x as f64, y as f64)).collect())
+            .boxed();
+
+        prop_oneof![tiny, uniform, clustered, collinear, duplicates].prop_map(|coords: Vec<(f64, f64)>| {
+            let raw: Vec<Point> = coords
+                .into_iter()
+                .enumerate()
+                .map(|(id, (x, y))| Point { x, y, angle: 0.0, id })
+                .collect();
+            normalize_points(&raw)
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn prop_constructors_return_permutations(points in instances()) {
+            let n = points.len();
+            let graph = create_distance_matrix(&points);
+            prop_assert!(is_symmetric(&graph));
+            prop_assert!(satisfies_triangle_inequality(&graph));
+
+            prop_assert!(is_permutation(&angular_sort::generate_tour(&points).unwrap(), n));
+            prop_assert!(is_permutation(&sonar_visit::generate_tour(&points, 40).unwrap(), n));
+            prop_assert!(is_permutation(&nearest_neighbor::generate_tour(n, &graph, 0).unwrap(), n));
+            prop_assert!(is_permutation(&nearest_neighbor::generate_tour_grid(&points, 0).unwrap(), n));
+            prop_assert!(is_permutation(&greedy_edge::generate_tour(n, &graph).unwrap(), n));
+            prop_assert!(is_permutation(&karp_partition::generate_tour(&points, 6).unwrap(), n));
+            prop_assert!(is_permutation(&quadtree::generate_tour(&points, 4).unwrap(), n));
+            prop_assert!(is_permutation(&random_tour::generate_tour(n, 7), n));
+            prop_assert!(is_permutation(&genetic::optimize(&graph, n, 8, 5, 0.1).unwrap(), n));
+            prop_assert!(is_permutation(&genetic::optimize_inver_over(&graph, n, 8, 5, 0.02).unwrap(), n));
+            // The exact solvers are exponential, so only small instances; all three find the same optimum
+            if n <= 9 {
+                let (tour, optimum) = brute_force::held_karp(&graph, n).unwrap();
+                prop_assert!(is_permutation(&tour, n));
+                let (tour, length, proven, _) = branch_and_bound::solve(&graph, None).unwrap();
+                prop_assert!(is_permutation(&tour, n));
+                prop_assert!(proven && (length - optimum).abs() <= LENGTH_TOLERANCE * optimum.max(1.0));
+                if n <= 7 {
+                    let (tour, length) = brute_force::brute_force_exact(&graph, n).unwrap();
+                    prop_assert!(is_permutation(&tour, n));
+                    prop_assert!((length - optimum).abs() <= LENGTH_TOLERANCE * optimum.max(1.0));
+                }
+            }
+        }
+
+        #[test]
+        fn prop_improvers_never_lengthen(points in instances()) {
+            let n = points.len();
+            let graph = create_distance_matrix(&points);
+            let start = angular_sort::generate_tour(&points).unwrap();
+
+            let improved = [
+                two_opt::improve(&start, &graph, 100).unwrap(),
+                kopt::improve(&start, &graph, 100).unwrap(),
+                two_h_opt::improve(&start, &graph, 5, 100).unwrap(),
+                or_one::improve(&start, &graph, 5, 100).unwrap(),
+                zigzag::optimize(&start, &points, &graph).unwrap(),
+                {
+                    let mut list = TwoLevelList::new(&start);
+                    two_opt::improve_tour(&mut list, &graph, 100).unwrap();
+                    list.to_vec()
+                },
+            ];
+            for tour in &improved {
+                prop_assert!(length_nonincreasing_after_improve(&start, tour, &graph));
+            }
+
+            // Annealing may wander uphill but must still return a valid tour
+            let annealed = simulated_annealing::optimize(&graph, &start, 200, 10.0, 0.99).unwrap();
+            prop_assert!(is_permutation(&annealed, n));
+            let moves = [(MoveKind::Reversal, 1.0), (MoveKind::Insertion, 1.0), (MoveKind::Swap, 1.0)];
+            let mixed = simulated_annealing::optimize_with_moves(&graph, &start, 200, 10.0, 0.99, &moves).unwrap();
+            prop_assert!(is_permutation(&mixed, n));
+            let tempered = simulated_annealing::parallel_tempering(&graph, &start, 3, 5, 40, 0.01, 1.0).unwrap();
+            prop_assert!(is_permutation(&tempered, n));
+        }
+    }
+}
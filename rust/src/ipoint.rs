@@ -0,0 +1,169 @@
+//! Integer coordinate instances with exact comparisons
+//!
+//! Constructors that only need to order points, by angle or by distance, can
+//! do so exactly on integer coordinates: angles are compared with cross
+//! products and distances as squared integers, so no `atan2` or `sqrt` is
+//! evaluated and ties on grid instances are real ties, broken by id, instead of
+//! whichever way floating-point rounding happened to fall.
+
+use alloc::vec::Vec;
+#[cfg(feature = "constructive")]
+use alloc::vec;
+use crate::error::SonarError;
+use crate::math;
+use crate::utils::{compensated_sum, Point};
+use core::cmp::Ordering;
+use core::f64::consts::PI;
+
+/// Largest coordinate magnitude; squared distances then fit in an `i128`
+pub const MAX_COORDINATE: i64 = 1 << 62;
+
+/// A point with integer coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IPoint {
+    pub x: i64,
+    pub y: i64,
+    pub id: usize,
+}
+
+/// Exact squared distance between two points
+pub fn squared_distance(a: &IPoint, b: &IPoint) -> i128 {
+    let dx = a.x as i128 - b.x as i128;
+    let dy = a.y as i128 - b.y as i128;
+    dx * dx + dy * dy
+}
+
+/// Check that points are non-empty and within `MAX_COORDINATE`
+pub fn validate_ipoints(points: &[IPoint]) -> Result<(), SonarError> {
+    if points.is_empty() {
+        return Err(SonarError::EmptyInput);
+    }
+    let limit = MAX_COORDINATE.unsigned_abs();
+    let out_of_range = |p: &IPoint| p.x.unsigned_abs() > limit || p.y.unsigned_abs() > limit;
+    match points.iter().position(out_of_range) {
+        Some(index) => Err(SonarError::CoordinateOutOfRange { index }),
+        None => Ok(()),
+    }
+}
+
+/// Round `points` scaled by `scale` to integers, keeping their ids
+/// Points from `generate_normalized_points(n, grid_size, seed)` sit at cell
+/// centres, `(g + 0.5) / grid_size`, so `scale = 2 * grid_size` maps them
+/// exactly to odd integers.
+pub fn quantize(points: &[Point], scale: f64) -> Vec<IPoint> {
+    points
+        .iter()
+        .map(|p| IPoint { x: math::round(p.x * scale) as i64, y: math::round(p.y * scale) as i64, id: p.id })
+        .collect()
+}
+
+/// `points` as floating-point points, with angles around `center`, for the
+/// solvers that need them
+pub fn to_points(points: &[IPoint], center: (i64, i64)) -> Vec<Point> {
+    points
+        .iter()
+        .map(|p| {
+            let (dx, dy) = ((p.x - center.0) as f64, (p.y - center.1) as f64);
+            let mut angle = math::atan2(dy, dx);
+            if angle < 0.0 {
+                angle += 2.0 * PI;
+            }
+            Point { x: p.x as f64, y: p.y as f64, angle, id: p.id }
+        })
+        .collect()
+}
+
+/// Order of two offsets from the centre by angle in [0, 2pi), counterclockwise
+/// from the positive x axis, the order `atan2` normalized to [0, 2pi) gives
+fn angle_order(a: (i128, i128), b: (i128, i128)) -> Ordering {
+    // Upper half-plane, including the positive x axis and the centre itself, first
+    let lower = |(x, y): (i128, i128)| y < 0 || (y == 0 && x < 0);
+    lower(a).cmp(&lower(b)).then_with(|| {
+        let cross = a.0 * b.1 - a.1 * b.0;
+        0.cmp(&cross)
+    })
+}
+
+/// Angular sort with exact angle comparisons around `center`
+/// Points in the same direction from the centre are ordered by id, as the
+/// floating-point `angular_sort` does for equal angles.
+/// Time complexity: O(n log n)
+pub fn angular_sort(points: &[IPoint], center: (i64, i64)) -> Result<Vec<usize>, SonarError> {
+    validate_ipoints(points)?;
+    let offset = |p: &IPoint| (p.x as i128 - center.0 as i128, p.y as i128 - center.1 as i128);
+    let mut sorted: Vec<&IPoint> = points.iter().collect();
+    sorted.sort_by(|a, b| angle_order(offset(a), offset(b)).then(a.id.cmp(&b.id)));
+    Ok(sorted.iter().map(|p| p.id).collect())
+}
+
+/// Nearest neighbor tour with exact distance comparisons
+/// Equally near points are taken in index order.
+/// Time complexity: O(n^2)
+#[cfg(feature = "constructive")]
+pub fn nearest_neighbor(points: &[IPoint], start_city: usize) -> Result<Vec<usize>, SonarError> {
+    validate_ipoints(points)?;
+    let n = points.len();
+    if start_city >= n {
+        return Err(SonarError::InvalidParameter { name: "start_city", reason: "must be a city index" });
+    }
+    let mut visited = vec![false; n];
+    let mut tour = Vec::with_capacity(n);
+    let mut current = start_city;
+    visited[current] = true;
+    tour.push(current);
+    while tour.len() < n {
+        let next = (0..n)
+            .filter(|&i| !visited[i])
+            .min_by_key(|&i| squared_distance(&points[current], &points[i]))
+            .unwrap();
+        visited[next] = true;
+        tour.push(next);
+        current = next;
+    }
+    Ok(tour)
+}
+
+/// Length of a closed tour, each edge rounded once from its exact square
+/// Time complexity: O(n)
+pub fn tour_length(tour: &[usize], points: &[IPoint]) -> f64 {
+    let n = tour.len();
+    let edge = |a: usize, b: usize| math::sqrt(squared_distance(&points[a], &points[b]) as f64);
+    compensated_sum((0..n).map(|i| edge(tour[i], tour[(i + 1) % n])))
+}
+
+#[cfg(all(test, feature = "constructive"))]
+mod tests {
+    use super::*;
+    use crate::error::validate_tour;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_exact_orderings_on_a_grid() {
+        let grid_size = 20;
+        let points = generate_normalized_points(150, grid_size, 3);
+        let integer = quantize(&points, 2.0 * grid_size as f64);
+        assert!(integer.iter().all(|p| p.x % 2 == 1 && p.y % 2 == 1));
+        let center = (grid_size as i64, grid_size as i64);
+
+        // The exact order is the floating-point one up to rounding of equal angles
+        let tour = angular_sort(&integer, center).unwrap();
+        assert!(tour.windows(2).all(|w| points[w[0]].angle <= points[w[1]].angle + 1e-12));
+        let nearest = nearest_neighbor(&integer, 0).unwrap();
+        validate_tour(&nearest, 150).unwrap();
+        assert!(tour_length(&nearest, &integer) < tour_length(&tour, &integer));
+
+        // Collinear with the centre: same angle, so id order; equal distances: index order
+        let ray = [IPoint { x: 4, y: 4, id: 0 }, IPoint { x: 2, y: 2, id: 1 }, IPoint { x: -1, y: 0, id: 2 }];
+        assert_eq!(angular_sort(&ray, (0, 0)).unwrap(), [0, 1, 2]);
+        let cross = [
+            IPoint { x: 0, y: 0, id: 0 },
+            IPoint { x: 0, y: 1, id: 1 },
+            IPoint { x: 1, y: 0, id: 2 },
+            IPoint { x: -1, y: 0, id: 3 },
+        ];
+        assert_eq!(nearest_neighbor(&cross, 0).unwrap(), [0, 1, 2, 3]);
+        assert_eq!(squared_distance(&cross[2], &cross[3]), 4);
+        let far = [IPoint { x: i64::MAX, y: 0, id: 0 }];
+        assert_eq!(validate_ipoints(&far), Err(SonarError::CoordinateOutOfRange { index: 0 }));
+    }
+}
@@ -0,0 +1,142 @@
+//! Karp's partitioning heuristic - exact solves on small cells, stitched together
+
+use crate::brute_force;
+use crate::error::{validate_points, SonarError};
+use crate::utils::{create_distance_matrix, distance, Point};
+
+/// Number of points nearest to a split line whose edges are tried when stitching
+const STITCH_CANDIDATES: usize = 8;
+
+/// Generate a tour using Karp's partitioning heuristic
+/// The plane is recursively split at the median of the longer side until every
+/// cell holds at most `max_cell_size` points. Each cell is solved exactly with
+/// `brute_force::find_optimal` and neighbouring sub-tours are stitched by the
+/// cheapest exchange of one edge from each side. The relative error vanishes as
+/// n grows for uniformly distributed points.
+/// Time complexity: O(n log^2 n) plus n/m exact solves of size m
+pub fn generate_tour(points: &[Point], max_cell_size: usize) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
+    let max_cell_size = max_cell_size.clamp(1, brute_force::get_max_feasible_n());
+    let mut indices: Vec<usize> = (0..points.len()).collect();
+    solve_cell(points, &mut indices, max_cell_size)
+}
+
+/// Recursively partition and solve the cell holding `indices`
+fn solve_cell(points: &[Point], indices: &mut [usize], max_cell_size: usize) -> Result<Vec<usize>, SonarError> {
+    if indices.len() <= max_cell_size {
+        return solve_exact(points, indices);
+    }
+
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for &i in indices.iter() {
+        min_x = min_x.min(points[i].x);
+        min_y = min_y.min(points[i].y);
+        max_x = max_x.max(points[i].x);
+        max_y = max_y.max(points[i].y);
+    }
+    let split_on_x = max_x - min_x >= max_y - min_y;
+    let coord = |i: usize| if split_on_x { points[i].x } else { points[i].y };
+
+    let mid = indices.len() / 2;
+    indices.select_nth_unstable_by(mid, |&a, &b| coord(a).total_cmp(&coord(b)));
+    let split_value = coord(indices[mid]);
+
+    let (left, right) = indices.split_at_mut(mid);
+    let left_tour = solve_cell(points, left, max_cell_size)?;
+    let right_tour = solve_cell(points, right, max_cell_size)?;
+
+    Ok(stitch(points, &left_tour, &right_tour, |i| (coord(i) - split_value).abs()))
+}
+
+/// Solve a small cell exactly on its local distance matrix
+fn solve_exact(points: &[Point], indices: &[usize]) -> Result<Vec<usize>, SonarError> {
+    if indices.len() <= 3 {
+        return Ok(indices.to_vec());
+    }
+    let local: Vec<Point> = indices.iter().map(|&i| points[i].clone()).collect();
+    let graph = create_distance_matrix(&local);
+    let (tour, _) = brute_force::find_optimal(&graph, local.len())?;
+    Ok(tour.into_iter().map(|i| indices[i]).collect())
+}
+
+/// Merge two sub-tours by removing one edge from each and reconnecting them
+/// Only edges touching the points closest to the split line are considered.
+fn stitch<F>(points: &[Point], a: &[usize], b: &[usize], line_distance: F) -> Vec<usize>
+where
+    F: Fn(usize) -> f64,
+{
+    if a.is_empty() {
+        return b.to_vec();
+    }
+    if b.is_empty() {
+        return a.to_vec();
+    }
+
+    let closest = |tour: &[usize]| -> Vec<usize> {
+        let mut positions: Vec<usize> = (0..tour.len()).collect();
+        positions.sort_by(|&x, &y| {
+            line_distance(tour[x]).total_cmp(&line_distance(tour[y]))
+        });
+        positions.truncate(STITCH_CANDIDATES);
+        positions
+    };
+    let d = |i: usize, j: usize| distance(&points[i], &points[j]);
+
+    let (na, nb) = (a.len(), b.len());
+    let mut best = (f64::INFINITY, 0, 0, false);
+    for &i in &closest(a) {
+        let (a1, a2) = (a[i], a[(i + 1) % na]);
+        for &j in &closest(b) {
+            let (b1, b2) = (b[j], b[(j + 1) % nb]);
+            let removed = d(a1, a2) + d(b1, b2);
+            // Enter b at b2 and walk forward to b1
+            let forward = d(a1, b2) + d(b1, a2) - removed;
+            // Enter b at b1 and walk backward to b2
+            let backward = d(a1, b1) + d(b2, a2) - removed;
+            if forward < best.0 {
+                best = (forward, i, j, false);
+            }
+            if backward < best.0 {
+                best = (backward, i, j, true);
+            }
+        }
+    }
+
+    let (_, i, j, reversed) = best;
+    let mut tour = Vec::with_capacity(na + nb);
+    tour.extend_from_slice(&a[..=i]);
+    for k in 0..nb {
+        if reversed {
+            tour.push(b[(j + nb - k) % nb]);
+        } else {
+            tour.push(b[(j + 1 + k) % nb]);
+        }
+    }
+    tour.extend_from_slice(&a[i + 1..]);
+    tour
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{calculate_tour_length, generate_normalized_points};
+
+    #[test]
+    fn test_karp_partition_visits_all_and_is_exact_on_one_cell() {
+        let points = generate_normalized_points(500, 40, 3);
+        let tour = generate_tour(&points, 8).unwrap();
+
+        assert_eq!(tour.len(), points.len());
+        let mut seen = std::collections::HashSet::new();
+        for &id in &tour {
+            assert!(seen.insert(id));
+        }
+
+        let small = generate_normalized_points(8, 20, 5);
+        let graph = create_distance_matrix(&small);
+        let (_, optimal) = brute_force::brute_force_exact(&graph, small.len()).unwrap();
+        let length = calculate_tour_length(&generate_tour(&small, 8).unwrap(), &graph);
+        assert!((length - optimal).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,236 @@
+//! Generic k-opt move framework
+//!
+//! A move is described only by the tour edges it removes and the edges it adds.
+//! Gain and legality are computed the same way for every move type, so 2-opt,
+//! 3-opt, Or-opt and the double-bridge kick are just different constructors.
+
+use alloc::{vec, vec::Vec};
+use crate::error::{validate_graph, validate_tour, SonarError};
+
+/// An undirected edge between two cities
+pub type Edge = (usize, usize);
+
+/// A k-opt move: remove `removed` from the tour and add `added`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Move {
+    pub removed: Vec<Edge>,
+    pub added: Vec<Edge>,
+}
+
+impl Move {
+    /// Number of edges exchanged (the k in k-opt)
+    pub fn k(&self) -> usize {
+        self.removed.len()
+    }
+
+    /// Reduction in tour length if the move is applied (positive is better)
+    pub fn gain(&self, graph: &[Vec<f64>]) -> f64 {
+        let removed: f64 = self.removed.iter().map(|&(a, b)| graph[a][b]).sum();
+        let added: f64 = self.added.iter().map(|&(a, b)| graph[a][b]).sum();
+        removed - added
+    }
+
+    /// Whether applying the move to `tour` yields a single Hamiltonian cycle
+    pub fn is_legal(&self, tour: &[usize]) -> bool {
+        self.apply(tour).is_some()
+    }
+
+    /// Apply the move and return the new tour, or None if it is illegal
+    /// (a removed edge is not in the tour, or the result is not one cycle)
+    /// Time complexity: O(n)
+    pub fn apply(&self, tour: &[usize]) -> Option<Vec<usize>> {
+        let n = tour.len();
+        if n < 3 || self.removed.len() != self.added.len() {
+            return None;
+        }
+
+        // Two neighbour slots per city, usize::MAX marks a free slot
+        let mut adjacent = vec![[usize::MAX; 2]; n];
+        for i in 0..n {
+            let (a, b) = (tour[i], tour[(i + 1) % n]);
+            adjacent[a][1] = b;
+            adjacent[b][0] = a;
+        }
+
+        for &(a, b) in &self.removed {
+            let slot_a = adjacent[a].iter().position(|&x| x == b)?;
+            let slot_b = adjacent[b].iter().position(|&x| x == a)?;
+            adjacent[a][slot_a] = usize::MAX;
+            adjacent[b][slot_b] = usize::MAX;
+        }
+
+        for &(a, b) in &self.added {
+            if a == b {
+                return None;
+            }
+            let slot_a = adjacent[a].iter().position(|&x| x == usize::MAX)?;
+            adjacent[a][slot_a] = b;
+            let slot_b = adjacent[b].iter().position(|&x| x == usize::MAX)?;
+            adjacent[b][slot_b] = a;
+        }
+
+        // Walk the resulting 2-regular graph; it must be a single cycle
+        let mut result = Vec::with_capacity(n);
+        let mut previous = usize::MAX;
+        let mut current = tour[0];
+        loop {
+            result.push(current);
+            let next = if adjacent[current][0] != previous {
+                adjacent[current][0]
+            } else {
+                adjacent[current][1]
+            };
+            previous = current;
+            current = next;
+            if current == tour[0] || result.len() > n {
+                break;
+            }
+        }
+
+        if result.len() == n {
+            Some(result)
+        } else {
+            None
+        }
+    }
+}
+
+/// 2-opt: remove edges after positions `i` and `j` (i < j) and reconnect crosswise
+pub fn two_opt(tour: &[usize], i: usize, j: usize) -> Move {
+    let n = tour.len();
+    let (a, b) = (tour[i], tour[(i + 1) % n]);
+    let (c, d) = (tour[j], tour[(j + 1) % n]);
+    Move {
+        removed: vec![(a, b), (c, d)],
+        added: vec![(a, c), (b, d)],
+    }
+}
+
+/// Sequential 3-opt: cut after positions i < j < k, reverse both inner segments
+/// A B C -> A B' C' (edges a-c, b-e, d-f)
+pub fn three_opt(tour: &[usize], i: usize, j: usize, k: usize) -> Move {
+    let n = tour.len();
+    let (a, b) = (tour[i], tour[(i + 1) % n]);
+    let (c, d) = (tour[j], tour[(j + 1) % n]);
+    let (e, f) = (tour[k], tour[(k + 1) % n]);
+    Move {
+        removed: vec![(a, b), (c, d), (e, f)],
+        added: vec![(a, c), (b, e), (d, f)],
+    }
+}
+
+/// Or-opt: move the segment of `length` cities starting at position `start`
+/// so that it sits between positions `after` and `after + 1`, optionally reversed
+pub fn or_opt(tour: &[usize], start: usize, length: usize, after: usize, reversed: bool) -> Move {
+    let n = tour.len();
+    let prev = tour[(start + n - 1) % n];
+    let first = tour[start];
+    let last = tour[(start + length - 1) % n];
+    let next = tour[(start + length) % n];
+    let (p, q) = (tour[after], tour[(after + 1) % n]);
+    let (enter, exit) = if reversed { (last, first) } else { (first, last) };
+    Move {
+        removed: vec![(prev, first), (last, next), (p, q)],
+        added: vec![(prev, next), (p, enter), (exit, q)],
+    }
+}
+
+/// Double bridge: cut after positions i < j < k and reorder A B C D as A C B D
+/// Cannot be undone by a single 2-opt or Or-opt move, which makes it the
+/// standard perturbation for iterated local search
+pub fn double_bridge(tour: &[usize], i: usize, j: usize, k: usize) -> Move {
+    let n = tour.len();
+    let (a, b) = (tour[i], tour[(i + 1) % n]);
+    let (c, d) = (tour[j], tour[(j + 1) % n]);
+    let (e, f) = (tour[k], tour[(k + 1) % n]);
+    Move {
+        removed: vec![(a, b), (c, d), (e, f)],
+        added: vec![(a, d), (e, b), (c, f)],
+    }
+}
+
+/// First-improvement local search over 2-opt and Or-opt (segments up to 3) moves
+/// All moves are built and applied through the generic `Move` machinery
+/// Time complexity: O(n^2) evaluations per iteration
+pub fn improve(tour: &[usize], graph: &[Vec<f64>], max_iterations: usize) -> Result<Vec<usize>, SonarError> {
+    validate_graph(graph)?;
+    validate_tour(tour, graph.len())?;
+
+    let n = tour.len();
+    let mut current = tour.to_vec();
+    if n < 5 {
+        return Ok(current);
+    }
+
+    for _ in 0..max_iterations {
+        let mut found = None;
+
+        'search: for i in 0..n {
+            for j in i + 2..n {
+                if i == 0 && j == n - 1 {
+                    continue;
+                }
+                let mv = two_opt(&current, i, j);
+                if mv.gain(graph) > 1e-10 {
+                    found = Some(mv);
+                    break 'search;
+                }
+            }
+            for length in 1..=3 {
+                for offset in length..n - 1 {
+                    let after = (i + offset) % n;
+                    for reversed in [false, true] {
+                        let mv = or_opt(&current, i, length, after, reversed);
+                        if mv.gain(graph) > 1e-10 {
+                            found = Some(mv);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+
+        match found.and_then(|mv| mv.apply(&current)) {
+            Some(next) => current = next,
+            None => break,
+        }
+    }
+
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_tour;
+    use crate::utils::{calculate_tour_length, create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_moves_change_length_by_their_gain() {
+        let points = generate_normalized_points(30, 20, 4);
+        let graph = create_distance_matrix(&points);
+        let tour = random_tour::generate_tour(points.len(), 8);
+        let length = calculate_tour_length(&tour, &graph);
+
+        let moves = vec![
+            two_opt(&tour, 3, 17),
+            three_opt(&tour, 2, 9, 21),
+            or_opt(&tour, 5, 3, 20, false),
+            or_opt(&tour, 5, 2, 14, true),
+            double_bridge(&tour, 4, 12, 25),
+        ];
+        for mv in moves {
+            let applied = mv.apply(&tour).expect("move should be legal");
+            assert_eq!(applied.len(), tour.len());
+            let new_length = calculate_tour_length(&applied, &graph);
+            assert!((length - new_length - mv.gain(&graph)).abs() < 1e-9);
+        }
+
+        // Removing an edge that is not in the tour is illegal
+        let bogus = Move { removed: vec![(tour[0], tour[2])], added: vec![(tour[0], tour[1])] };
+        assert!(!bogus.is_legal(&tour));
+
+        let improved = improve(&tour, &graph, 1_000).unwrap();
+        assert!(calculate_tour_length(&improved, &graph) < length);
+    }
+}
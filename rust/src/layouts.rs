@@ -0,0 +1,204 @@
+//! Adversarial point layouts - degenerate and known-hard instances
+//!
+//! Uniform disc sampling never produces the layouts where heuristics break
+//! down: collinear or concyclic points give angular sort nothing but ties or
+//! one single ring, two distant clusters make nearest neighbor pay for the
+//! jump between them twice, and a spiral's arms interleave in angle. Points
+//! lie in the unit square with angles around (0.5, 0.5), like
+//! `generate_normalized_points`, and are listed in a seeded random order so no
+//! algorithm gets the answer from the indices.
+//!
+//! On the circle, convex and line layouts the optimal tour is known exactly for
+//! any n (`known_optimum`), so gaps can be checked far beyond exact solvers.
+
+use alloc::vec::Vec;
+use crate::math;
+use crate::rng::{Lcg, SonarRng};
+use crate::utils::{compensated_sum, distance, generate_normalized_points, Point};
+use core::f64::consts::PI;
+
+/// Radius the layouts keep within around the centre, as the disc sampling does
+const RADIUS: f64 = 0.45;
+
+/// An instance layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    /// Grid positions sampled uniformly from a disc, `generate_normalized_points`
+    #[default]
+    Disc,
+    /// Evenly spaced on a circle
+    Circle,
+    /// Evenly spaced on a horizontal line through the centre
+    Line,
+    /// Two dense discs at opposite sides of the square
+    TwoClusters,
+    /// An Archimedean spiral of several turns
+    Spiral,
+    /// Convex position: a random ellipse at stratified random angles
+    Convex,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 6] =
+        [Layout::Disc, Layout::Circle, Layout::Line, Layout::TwoClusters, Layout::Spiral, Layout::Convex];
+
+    /// Name for command lines
+    pub fn name(self) -> &'static str {
+        match self {
+            Layout::Disc => "disc",
+            Layout::Circle => "circle",
+            Layout::Line => "line",
+            Layout::TwoClusters => "two-clusters",
+            Layout::Spiral => "spiral",
+            Layout::Convex => "convex",
+        }
+    }
+
+    /// Whether `known_optimum` gives this layout's optimal tour length
+    pub fn has_known_optimum(self) -> bool {
+        matches!(self, Layout::Circle | Layout::Convex | Layout::Line)
+    }
+
+    pub fn from_name(name: &str) -> Option<Layout> {
+        Layout::ALL.into_iter().find(|layout| layout.name() == name)
+    }
+}
+
+/// `n` points of `layout` from `seed`
+/// The disc uses the benchmark's grid of `max(40, 2 * sqrt(n))` cells a side.
+/// Time complexity: O(n), or that of `generate_normalized_points` for the disc
+pub fn generate(layout: Layout, n: usize, seed: u64) -> Vec<Point> {
+    let mut rng = Lcg::new(seed);
+    let positions: Vec<(f64, f64)> = match layout {
+        Layout::Disc => return generate_normalized_points(n, 40.max(math::sqrt(n as f64) as usize * 2), seed),
+        Layout::Circle => {
+            let offset = rng.next_f64() * 2.0 * PI;
+            (0..n)
+                .map(|i| polar(RADIUS, offset + 2.0 * PI * i as f64 / n as f64))
+                .collect()
+        }
+        Layout::Line => (0..n)
+            .map(|i| (0.5 - RADIUS + 2.0 * RADIUS * (i as f64 + 0.5) / n as f64, 0.5))
+            .collect(),
+        Layout::TwoClusters => (0..n)
+            .map(|i| {
+                // Uniform in a small disc, left half then right half
+                let (r, angle) = (0.03 * math::sqrt(rng.next_f64()), rng.next_f64() * 2.0 * PI);
+                let (x, y) = polar(r, angle);
+                let shift = if i < n / 2 { -0.4 } else { 0.4 };
+                (x + shift, y)
+            })
+            .collect(),
+        Layout::Spiral => {
+            let turns = 5.0;
+            (0..n)
+                .map(|i| {
+                    let t = (i as f64 + 1.0) / n as f64;
+                    polar(RADIUS * t, turns * 2.0 * PI * t)
+                })
+                .collect()
+        }
+        Layout::Convex => {
+            // Semi-axes, then a rotation; the angles stay distinct and increasing
+            let (a, b) = (RADIUS, RADIUS * (0.4 + 0.6 * rng.next_f64()));
+            let rotation = rng.next_f64() * PI;
+            let (sin, cos) = (math::sin(rotation), math::cos(rotation));
+            (0..n)
+                .map(|i| {
+                    let t = 2.0 * PI * (i as f64 + 0.9 * rng.next_f64()) / n as f64;
+                    let (x, y) = (a * math::cos(t), b * math::sin(t));
+                    (0.5 + x * cos - y * sin, 0.5 + x * sin + y * cos)
+                })
+                .collect()
+        }
+    };
+    shuffled(positions, &mut rng)
+}
+
+/// Length of an optimal tour of `points` from `generate(layout, ..)`, where
+/// the layout makes it known
+/// Points in convex position, as on the circle and the ellipse, are toured
+/// optimally in hull order, which is their order by angle around the centre;
+/// points on a line need twice their span.
+/// Time complexity: O(n log n)
+pub fn known_optimum(layout: Layout, points: &[Point]) -> Option<f64> {
+    match layout {
+        Layout::Circle | Layout::Convex => {
+            let mut order: Vec<&Point> = points.iter().collect();
+            order.sort_by(|a, b| a.angle.total_cmp(&b.angle));
+            let n = order.len();
+            Some(compensated_sum((0..n).map(|i| distance(order[i], order[(i + 1) % n]))))
+        }
+        Layout::Line => {
+            let (low, high) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), p| {
+                (low.min(p.x), high.max(p.x))
+            });
+            Some(if points.len() < 2 { 0.0 } else { 2.0 * (high - low) })
+        }
+        Layout::Disc | Layout::TwoClusters | Layout::Spiral => None,
+    }
+}
+
+/// Offset `(r, angle)` from the centre as a position
+fn polar(r: f64, angle: f64) -> (f64, f64) {
+    (0.5 + r * math::cos(angle), 0.5 + r * math::sin(angle))
+}
+
+/// Points at `positions` in a seeded random order, with angles around the centre
+fn shuffled(mut positions: Vec<(f64, f64)>, rng: &mut Lcg) -> Vec<Point> {
+    for i in (1..positions.len()).rev() {
+        positions.swap(i, rng.below(i + 1));
+    }
+    positions
+        .into_iter()
+        .enumerate()
+        .map(|(id, (x, y))| {
+            let mut angle = math::atan2(y - 0.5, x - 0.5);
+            if angle < 0.0 {
+                angle += 2.0 * PI;
+            }
+            Point { x, y, angle, id }
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "exact", feature = "constructive", feature = "local-search"))]
+mod tests {
+    use super::*;
+    use crate::angular_sort;
+    use crate::utils::{calculate_tour_length, create_distance_matrix};
+
+    #[test]
+    fn test_layouts_are_seeded_and_in_the_unit_square() {
+        for layout in Layout::ALL {
+            assert_eq!(Layout::from_name(layout.name()), Some(layout));
+            let points = generate(layout, 200, 3);
+            assert_eq!(points.len(), 200, "{:?}", layout);
+            assert!(points.iter().all(|p| (0.0..=1.0).contains(&p.x) && (0.0..=1.0).contains(&p.y)));
+            let again = generate(layout, 200, 3);
+            assert!(points.iter().zip(&again).all(|(a, b)| (a.x, a.y) == (b.x, b.y)));
+        }
+
+        // On a circle angular sort is optimal: the perimeter of the 200-gon
+        let circle = generate(Layout::Circle, 200, 1);
+        let tour = angular_sort::generate_tour(&circle).unwrap();
+        let perimeter = 200.0 * 2.0 * RADIUS * math::sin(PI / 200.0);
+        assert!((calculate_tour_length(&tour, &create_distance_matrix(&circle)) - perimeter).abs() < 1e-9);
+        assert!((known_optimum(Layout::Circle, &circle).unwrap() - perimeter).abs() < 1e-9);
+
+        // Exact solvers and local search agree with the known optima
+        for layout in [Layout::Convex, Layout::Line] {
+            let points = generate(layout, 10, 4);
+            let (_, optimal) = crate::brute_force::held_karp(&create_distance_matrix(&points), 10).unwrap();
+            assert!((known_optimum(layout, &points).unwrap() - optimal).abs() < 1e-9, "{:?}", layout);
+        }
+        let convex = generate(Layout::Convex, 300, 2);
+        let graph = create_distance_matrix(&convex);
+        let initial = crate::nearest_neighbor::generate_tour(300, &graph, 0).unwrap();
+        let improved = calculate_tour_length(&crate::two_opt::improve(&initial, &graph, 100).unwrap(), &graph);
+        assert!(improved >= known_optimum(Layout::Convex, &convex).unwrap() - 1e-9);
+        assert_eq!(known_optimum(Layout::Spiral, &convex), None);
+        // On a line every point shares one of two angles
+        assert!(generate(Layout::Line, 50, 1).iter().all(|p| p.angle == 0.0 || (p.angle - PI).abs() < 1e-12));
+    }
+}
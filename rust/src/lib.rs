@@ -0,0 +1,159 @@
+//! TSP Algorithms
+//!
+//! Construction heuristics, local search improvers, metaheuristics and exact
+//! solvers for the Traveling Salesman Problem. The `benchmark` binary in
+//! `main.rs` drives all of them.
+//!
+//! With `default-features = false, features = ["libm"]` the crate is `no_std`
+//! (it still needs `alloc`): the construction heuristics and local search
+//! improvers remain, while modules needing timing, threads, OS randomness or
+//! the file system are gated behind the `std` feature.
+//!
+//! Beyond angular sort and sonar visit, each algorithm family has a feature of
+//! its own, all enabled by default: `constructive`, `local-search`, `exact`,
+//! `metaheuristics` (the only user of `rand`) and `render`. Modules combining
+//! families, such as the registry entries or the space partitions, need every
+//! family they use.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// Index-based loops over the distance matrix are the idiom used throughout.
+#![allow(clippy::needless_range_loop)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("enable the `std` feature, or `libm` for no_std float math");
+
+extern crate alloc;
+
+mod math;
+
+pub mod scalar;
+pub mod rng;
+pub mod fixed;
+pub mod utils;
+pub mod layouts;
+#[cfg(feature = "local-search")]
+pub mod two_opt;
+#[cfg(all(feature = "std", feature = "local-search"))]
+pub mod zigzag;
+pub mod sonar_visit;
+pub mod angular_sort;
+#[cfg(feature = "constructive")]
+pub mod nearest_neighbor;
+#[cfg(feature = "constructive")]
+pub mod greedy_edge;
+#[cfg(feature = "metaheuristics")]
+pub mod simulated_annealing;
+#[cfg(feature = "metaheuristics")]
+pub mod genetic;
+#[cfg(feature = "exact")]
+pub mod brute_force;
+#[cfg(all(feature = "exact", feature = "constructive", feature = "local-search"))]
+pub mod branch_and_bound;
+pub mod bounds;
+pub mod precedence;
+pub mod spatial_grid;
+#[cfg(all(feature = "exact", feature = "constructive"))]
+pub mod karp_partition;
+#[cfg(all(feature = "std", feature = "constructive", feature = "local-search"))]
+pub mod sector_partition;
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+pub mod sparse;
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+pub mod polylines;
+pub mod drill;
+#[cfg(all(feature = "metaheuristics", feature = "constructive", feature = "local-search"))]
+pub mod cluster_route;
+#[cfg(all(feature = "std", feature = "constructive"))]
+pub mod multilevel;
+#[cfg(feature = "gpu")]
+pub mod gpu_two_opt;
+#[cfg(feature = "web")]
+pub mod web;
+#[cfg(feature = "constructive")]
+pub mod quadtree;
+#[cfg(feature = "constructive")]
+pub mod space_filling;
+pub mod random_tour;
+pub mod tour;
+pub mod ipoint;
+#[cfg(feature = "local-search")]
+pub mod kopt;
+#[cfg(feature = "local-search")]
+pub mod neighbors;
+#[cfg(feature = "local-search")]
+pub mod two_h_opt;
+#[cfg(feature = "local-search")]
+pub mod or_one;
+#[cfg(feature = "local-search")]
+pub mod smooth;
+#[cfg(feature = "constructive")]
+pub mod dynamic_tour;
+#[cfg(feature = "std")]
+pub mod tsplib;
+#[cfg(feature = "std")]
+pub mod geo;
+#[cfg(feature = "std")]
+pub mod concorde;
+#[cfg(feature = "std")]
+pub mod instances;
+#[cfg(feature = "std")]
+pub mod determinism;
+#[cfg(feature = "std")]
+pub mod clock;
+#[cfg(feature = "std")]
+pub mod manifest;
+pub mod invariants;
+pub mod metrics;
+pub mod error;
+pub mod sanitize;
+pub mod stepwise;
+#[cfg(any(feature = "constructive", feature = "metaheuristics"))]
+pub mod scratch;
+#[cfg(feature = "std")]
+pub mod problem;
+#[cfg(feature = "std")]
+pub mod matrix_cache;
+#[cfg(feature = "std")]
+pub mod result_cache;
+#[cfg(feature = "std")]
+pub mod provider;
+#[cfg(feature = "render")]
+pub mod pareto;
+#[cfg(feature = "render")]
+pub mod render;
+#[cfg(feature = "tui")]
+pub mod dashboard;
+#[cfg(feature = "std")]
+pub mod complexity;
+#[cfg(feature = "std")]
+pub mod boundary;
+#[cfg(feature = "std")]
+pub mod registry;
+#[cfg(all(feature = "constructive", feature = "local-search", feature = "exact", feature = "metaheuristics"))]
+pub mod site_data;
+#[cfg(all(feature = "constructive", feature = "local-search", feature = "exact"))]
+pub mod parity;
+#[cfg(all(feature = "constructive", feature = "local-search", feature = "exact", feature = "metaheuristics"))]
+pub mod golden;
+#[cfg(feature = "std")]
+pub mod events;
+#[cfg(feature = "metaheuristics")]
+pub mod replay;
+#[cfg(all(feature = "std", feature = "constructive", feature = "local-search"))]
+pub mod portfolio;
+#[cfg(all(feature = "metaheuristics", feature = "constructive"))]
+pub mod tune;
+#[cfg(feature = "std")]
+pub mod sweep;
+#[cfg(all(feature = "std", feature = "constructive", feature = "local-search"))]
+pub mod pipeline;
+#[cfg(feature = "scripting")]
+pub mod script;
+#[cfg(feature = "std")]
+pub mod throughput;
+#[cfg(all(feature = "std", feature = "constructive", feature = "local-search"))]
+pub mod representations;
+#[cfg(feature = "std")]
+pub mod plotter;
+#[cfg(all(feature = "metaheuristics", feature = "constructive"))]
+pub mod edge_limit;
@@ -5,301 +5,2736 @@
 //!
 //! Run with: cargo run --release -- [timeout_seconds]
 //! Default timeout: 30 seconds
+//!
+//! Compare heuristics on a TSPLIB instance: cargo run --release -- --instance file.tsp
+//! Solve your own points, from a TSPLIB, GeoJSON or GPX waypoint file, and print
+//! the tour; GIS coordinates are projected to km, or with `--geo` measured along
+//! the Earth's surface: cargo run --release -- solve --input file [--algo <key>] [--geo]
+//! Add `--matrix <file>` to optimize precomputed costs between the points instead,
+//! such as road travel times: a text matrix, one row per line, or a matrix cache file.
+//! Add `--max-edge <L>` to rework the tour so that no step is longer than L.
+//! In a build with `--features scripting`, add `--script <file.rhai>` to solve
+//! or improve for a custom objective: a Rhai `edge_cost(a, b, distance)` gives
+//! the costs instead, and a `tour_penalty(tour)` is charged by a final 2-opt.
+//! Add `--gcode <file>` or `--hpgl <file>` to write the tour for a pen plotter or
+//! engraver, drawn as one line or with `--dots` as a dot per point, scaled by
+//! `--plot-scale <mm per unit>` (default 200) at `--travel-feed` and `--draw-feed` mm/min.
+//! Polish a tour from another tool with improvement algorithms only, reporting
+//! its length before and after: cargo run --release -- improve --input file.tsp
+//! --tour file.tour [--algo "two-opt > or-opt"]
+//! Draw stipple points, e.g. from an image stippler, as TSP art: one closed line
+//! through all of them, built by `--algo` and then each `--improve` step in turn:
+//! cargo run --release -- tsp-art --input points.txt --output art.svg [--improve "two-opt > smooth"]
+//! [--stroke-width <px>] [--curved]
+//! List the registered algorithms: cargo run --release -- list-algorithms [--json]
+//! Compute the cards of optimization-comparison.html in Rust, one instance sample per
+//! `--seeds` entry: cargo run --release -- export-site-data [file.json]
+//! Check the JS implementations produce the same tours, failing on any
+//! divergence: cargo run --release -- parity [n ...]
+//! Check deterministic algorithms still produce their recorded golden tours, or
+//! re-record them after an intended change: cargo run --release -- golden [--record]
+//! Tune simulated annealing or the genetic algorithm on one instance per
+//! `--seeds` entry and print the best parameters as TOML:
+//! cargo run --release -- tune <simulated-annealing|genetic> [seconds] [--halving]
+//! Benchmark every combination of parameter values at a fixed size, as a table
+//! or with `--csv` as CSV on stdout:
+//! cargo run --release -- sweep --algo sa --param cooling=0.995,0.9995 --param iters=1e4,1e5 [--n 1000] [--csv]
+//! Measure complete solves per second of every algorithm at small sizes, for
+//! embedding in interactive tools (default 50, 200 and 1000 points, a second each):
+//! cargo run --release -- throughput [n ...] [--seconds <s>]
+//! Time 2-opt on each tour representation (array, array tour, two-level list,
+//! treap) and report which is fastest at which size (default 100, 500 and 2000 points):
+//! cargo run --release -- representations [n ...] [--iterations <k>]
+//!
+//! Add `--format json` to end with a single JSON document (manifest, results and
+//! every timed run as a probe, or for `--instance` the comparison and its Pareto
+//! frontier), or `--format markdown` to end with a GitHub table of the summary. `--quiet` sends all
+//! human-readable output to stderr so stdout holds only that document.
+//! If `file.opt.tour` exists next to it, each heuristic's gap to the optimum is reported.
+//! The Pareto frontier of runtime versus tour length is printed as JSON; add
+//! `--pareto-svg <file>` to also draw it as an SVG chart.
+//!
+//! Add `--deterministic` to seed every RNG from a fixed seed and pin thread counts,
+//! so repeated runs produce identical tours. `--rng <lcg|pcg64|xoshiro256>` picks
+//! the generator every RNG is created as.
+//!
+//! Add `--result-cache <dir>` to store every benchmark, `sweep` and `solve` result
+//! in `dir`, keyed by algorithm, parameters, seed and n, and read it back instead
+//! of solving again when the same configuration comes up; entries from other
+//! commits are ignored.
+//!
+//! Add `--time-source cpu` to time runs by the CPU time of all threads of the
+//! process instead of by wall time, so multi-threaded algorithms are charged
+//! for all the work they do; the benchmark, `--instance` and `solve` honour it.
+//! As that time would include an abandoned run still going, nothing is run
+//! until it ends: those sizes count as failed.
+//!
+//! Add `--seed <n>` to generate the benchmark points from another seed, or
+//! `--seeds 1,2,3` to run every size on one instance per seed and average the
+//! times and tour lengths, so no single point set decides the ranking.
+//! `--points-version 2` samples the points without listing every grid position,
+//! much faster at large N, but gives other point sets than the default version 1.
+//! `--layout <circle|line|two-clusters|spiral|convex>` benchmarks on one of the
+//! adversarial layouts instead of the default uniform disc. On the circle, line
+//! and convex layouts the optimum is known for every n, so gaps are reported
+//! against it.
+//!
+//! Add `--score <quality_weight>` to rank the summary by a weighted mix of tour
+//! quality and runtime on a shared probe instance instead of by max N; the
+//! weight is between 0 (speed only) and 1 (quality only).
+//!
+//! Add `--preview` with `--instance` to draw every heuristic's tour in the
+//! terminal in braille characters.
+//!
+//! Add `--matrix-cache <file>` with `--instance` to store the instance's distance
+//! matrix on disk and read it back on later runs instead of recomputing it.
+//!
+//! The summary also estimates each algorithm's empirical complexity, fitting
+//! time ~ n^k over all of its timed runs, and splits the time at max N into
+//! distance-matrix construction, construction heuristic and improvement.
+//!
+//! Add `--bounds <algorithm>=<min_n>..<max_n>` to search another range of sizes
+//! than the built-in one, e.g. `--bounds AngularSort=..2000000` to lift its cap
+//! on a bigger machine. Either end may be left out; the algorithm is its full
+//! name, the name before the parentheses for every variant, or `*` for all of
+//! them. Repeat it for more algorithms; later entries win.
+//!
+//! Add `--pipeline <spec>` to benchmark a hybrid given as a pipeline spec, such
+//! as `--pipeline "nearest-neighbor > or-opt > 2opt(max_iters=50)"`, after the
+//! built-in ones; repeat it for more. `--instance` runs them too, and `solve`,
+//! `improve` and `tsp-art` take specs wherever they take an algorithm key.
+//!
+//! Every valid tour at n <= 20 is also compared with the optimum from Held-Karp,
+//! and its gap reported, as a spot check of quality and of the exact solvers.
+//!
+//! Every run goes on a worker thread; one still going at twice the timeout is
+//! abandoned and reported as timed out at that n instead of being waited for.
+//! Until it ends, its algorithm is not run again, and other runs alongside it
+//! get no time: they count as failed at their n.
+//!
+//! Add `--max-memory <size>` (bytes, or with a K/M/G suffix) to skip sizes whose
+//! distance matrix or DP tables would need more memory than that, estimated
+//! before the run instead of being OOM-killed partway through it.
+//!
+//! Build with `--features gpu` to also benchmark 2-opt with moves scored on the GPU.
+//! Build with `--features progress` to show a progress bar with an ETA on stderr
+//! for every run expected to take longer than a second.
+//! Build with `--features tui` and add `--tui` to follow a benchmark run on a live
+//! dashboard instead of scrolling progress lines.
 
-mod utils;
-mod two_opt;
-mod zigzag;
-mod sonar_visit;
-mod angular_sort;
-mod nearest_neighbor;
-mod greedy_edge;
-mod simulated_annealing;
-mod genetic;
-mod brute_force;
-
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
 use std::env;
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::thread;
 use std::time::{Duration, Instant};
-use utils::{Point, create_distance_matrix, generate_normalized_points};
+use tsp_algorithms::boundary::{BoundarySearch, TimeModel};
+use tsp_algorithms::clock::{self, TimeSource};
+use tsp_algorithms::complexity::{self, ExponentFit};
+use tsp_algorithms::error::{validate_tour, validate_tour_edges, SonarError};
+#[cfg(feature = "progress")]
+use indicatif::{ProgressBar, ProgressState, ProgressStyle};
+#[cfg(feature = "tui")]
+use tsp_algorithms::dashboard::{Dashboard, Event};
+use tsp_algorithms::genetic::GeneticSolver;
+#[cfg(feature = "gpu")]
+use tsp_algorithms::gpu_two_opt::GpuTwoOpt;
+use tsp_algorithms::layouts::{self, Layout};
+use tsp_algorithms::manifest::Manifest;
+use tsp_algorithms::pareto::{self, Measurement};
+use tsp_algorithms::pipeline::{self, Pipeline, Start};
+use tsp_algorithms::plotter::{self, Plotter, Stroke};
+use tsp_algorithms::portfolio::Cancellation;
+use tsp_algorithms::problem::{calculate_tour_length_points, Metric, ProblemInstance};
+use tsp_algorithms::registry;
+use tsp_algorithms::representations::{self, Representation};
+use tsp_algorithms::result_cache::{CacheKey, CachedRun, ResultCache};
+#[cfg(feature = "scripting")]
+use tsp_algorithms::script::Script;
+use tsp_algorithms::rng::RngKind;
+use tsp_algorithms::simulated_annealing::MoveKind;
+use tsp_algorithms::stepwise::SteppableSolver;
+use tsp_algorithms::utils::{
+    create_distance_matrix, generate_normalized_points, generate_normalized_points_version, json_string,
+    normalize_points, Point, PointsVersion,
+};
+use tsp_algorithms::{
+    angular_sort, branch_and_bound, brute_force, cluster_route, determinism, edge_limit, genetic, geo, golden,
+    greedy_edge,
+    karp_partition, matrix_cache, multilevel, parity, nearest_neighbor, or_one, provider, quadtree, random_tour,
+    render, sector_partition, simulated_annealing, site_data, sonar_visit, space_filling, sparse, sweep, throughput,
+    tour, tsplib, tune, two_h_opt, two_opt, zigzag,
+};
+
+/// Seed of the generated benchmark point sets unless `--seed` or `--seeds` is given
+const POINT_SEED: u64 = 12345;
+
+/// Point seeds from `--seed` or `--seeds`, if given
+static POINT_SEEDS: OnceLock<Vec<u64>> = OnceLock::new();
+
+/// Seeds of the point sets every size is benchmarked on
+fn point_seeds() -> &'static [u64] {
+    POINT_SEEDS.get().map_or(&[POINT_SEED], Vec::as_slice)
+}
+
+/// Sampling of the benchmark point sets, set by `--points-version`
+static POINTS_VERSION: OnceLock<PointsVersion> = OnceLock::new();
+
+/// Layout of the benchmark point sets, set by `--layout`
+static LAYOUT: OnceLock<Layout> = OnceLock::new();
+
+/// Size of the shared instance every algorithm is probed on for `--score`
+const PROBE_N: usize = 200;
+
+/// Set by `--quiet`: human-readable output goes to stderr, leaving stdout to results
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Set by `--preview`: each `--instance` tour is also drawn in the terminal
+static PREVIEW: AtomicBool = AtomicBool::new(false);
+
+/// Size of a `--preview` drawing in character cells
+const PREVIEW_COLUMNS: usize = 60;
+const PREVIEW_ROWS: usize = 20;
+
+/// Result document printed at the end of a run, from `--format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human-readable tables with the JSON results appended
+    Text,
+    Json,
+    /// A GitHub-flavoured table
+    Markdown,
+}
+
+/// Escape text for a Markdown table cell
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// Dashboard started by `--tui`, until the last algorithm is timed
+#[cfg(feature = "tui")]
+static DASHBOARD: Mutex<Option<Dashboard>> = Mutex::new(None);
+
+/// Pass an event to the `--tui` dashboard, if one is running
+#[cfg(feature = "tui")]
+fn report(event: Event) {
+    if let Some(dashboard) = DASHBOARD.lock().unwrap().as_ref() {
+        dashboard.report(event);
+    }
+}
+
+/// Send a line of output to the `--tui` dashboard's log instead of the
+/// terminal, returning whether a dashboard took it
+fn dashboard_log(_args: fmt::Arguments) -> bool {
+    #[cfg(feature = "tui")]
+    if let Some(dashboard) = DASHBOARD.lock().unwrap().as_ref() {
+        for line in _args.to_string().lines() {
+            dashboard.report(Event::Log(line.to_string()));
+        }
+        return true;
+    }
+    false
+}
+
+/// Print human-readable progress and tables, to stderr under `--quiet`
+macro_rules! log {
+    ($($arg:tt)*) => {
+        if dashboard_log(format_args!($($arg)*)) {
+        } else if QUIET.load(Ordering::Relaxed) {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+/// Report a usage error or unusable input on stderr and exit with status 2
+/// These are for the user to fix, so they get a message rather than a panic.
+macro_rules! fail {
+    ($($arg:tt)*) => {{
+        eprintln!($($arg)*);
+        std::process::exit(2)
+    }};
+}
+
+/// `name=value` text of a benchmark entry's parameters, each named after the
+/// constant its run reads, so the text cannot drift from what is run
+macro_rules! params {
+    ($($name:ident),* $(,)?) => {
+        [$(format!("{}={:?}", stringify!($name).to_lowercase(), $name)),*].join(", ")
+    };
+}
+
+/// `kind:weight` pairs of a simulated annealing neighbourhood, joined with `/`
+fn moves_text(moves: &[(MoveKind, f64)]) -> String {
+    let pairs: Vec<String> = moves.iter().map(|(kind, weight)| format!("{:?}:{}", kind, weight)).collect();
+    pairs.join("/").to_lowercase()
+}
+
+/// Stands in for `script::Script` in builds without `scripting`, which
+/// cannot load one
+#[cfg(not(feature = "scripting"))]
+enum Script {}
+
+#[cfg(not(feature = "scripting"))]
+impl Script {
+    fn compile(_source: &str) -> Result<Self, String> {
+        Err("--script needs a build with `--features scripting`".to_string())
+    }
+
+    fn has_edge_cost(&self) -> bool {
+        match *self {}
+    }
+
+    fn has_tour_penalty(&self) -> bool {
+        match *self {}
+    }
+
+    fn costs(&self, _points: &[Point], _metric: Metric) -> Result<Vec<Vec<f64>>, String> {
+        match *self {}
+    }
+
+    fn penalty(&self, _tour: &[usize]) -> Result<f64, String> {
+        match *self {}
+    }
+
+    fn improve(&self, _tour: &[usize], _graph: &[Vec<f64>], _iterations: usize) -> Result<Vec<usize>, String> {
+        match *self {}
+    }
+}
+
+/// Memory bound in bytes from `--max-memory`, if given
+static MAX_MEMORY: OnceLock<usize> = OnceLock::new();
+
+/// Result cache directory from `--result-cache`, if given
+static RESULT_CACHE: OnceLock<ResultCache> = OnceLock::new();
+
+/// The result cache's `params` for a run with `params`: also everything set
+/// for the whole process that changes tours or times
+fn cache_params(params: &str) -> String {
+    format!(
+        "{}; layout {:?}; points {:?}; time {}; rng {}{}",
+        params,
+        LAYOUT.get().copied().unwrap_or_default(),
+        POINTS_VERSION.get().copied().unwrap_or_default(),
+        clock::time_source().name(),
+        determinism::rng_kind().name(),
+        if determinism::is_deterministic() { format!(" seeded {}", determinism::seed()) } else { String::new() }
+    )
+}
+
+/// Parse a byte count such as `1048576`, `512M` or `4G`
+fn parse_size(text: &str) -> Option<usize> {
+    let (digits, unit) = match text.char_indices().last()? {
+        (i, 'K' | 'k') => (&text[..i], 1 << 10),
+        (i, 'M' | 'm') => (&text[..i], 1 << 20),
+        (i, 'G' | 'g') => (&text[..i], 1 << 30),
+        _ => (text, 1),
+    };
+    digits.parse::<usize>().ok()?.checked_mul(unit)
+}
+
+/// Search bounds from a `--bounds` entry, overriding an algorithm's built-in ones
+struct Bounds {
+    /// Full algorithm name, the name before its parenthesised details to cover
+    /// every variant, or `*` for every algorithm
+    algorithm: String,
+    min_n: Option<usize>,
+    max_n: Option<usize>,
+}
 
-/// Measures the execution time of a function
+impl Bounds {
+    /// Parse `<algorithm>=<min>..<max>`, where either end may be left out
+    fn parse(text: &str) -> Option<Bounds> {
+        let (algorithm, range) = text.rsplit_once('=')?;
+        let (min, max) = range.split_once("..")?;
+        let end = |end: &str| if end.is_empty() { Ok(None) } else { end.parse().map(Some) };
+        Some(Bounds { algorithm: algorithm.trim().to_string(), min_n: end(min).ok()?, max_n: end(max).ok()? })
+    }
+
+    fn covers(&self, name: &str) -> bool {
+        let family = name.split(" (").next().unwrap_or(name);
+        let algorithm = self.algorithm.as_str();
+        algorithm == "*" || algorithm.eq_ignore_ascii_case(name) || algorithm.eq_ignore_ascii_case(family)
+    }
+}
+
+/// Every `--bounds` entry, in the order given
+static BOUNDS: OnceLock<Vec<Bounds>> = OnceLock::new();
+
+/// `(min_n, max_n)` of `name` after the `--bounds` entries covering it, later
+/// entries overriding earlier ones
+fn search_bounds(name: &str, min_n: usize, max_n: usize) -> (usize, usize) {
+    let entries = BOUNDS.get().map_or(&[][..], Vec::as_slice);
+    entries.iter().filter(|bounds| bounds.covers(name)).fold((min_n, max_n), |(min_n, max_n), bounds| {
+        (bounds.min_n.unwrap_or(min_n), bounds.max_n.unwrap_or(max_n))
+    })
+}
+
+/// Measures the execution time of a function, by the `--time-source` clock
 fn measure_time<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
-    let start = Instant::now();
+    let clock = clock::time_source();
+    let start = clock.now();
     let result = f();
-    (result, start.elapsed())
+    (result, clock.now().saturating_sub(start))
+}
+
+thread_local! {
+    /// Clock reading when the running pipeline finished its initial tour, set by `construction_done`
+    static CONSTRUCTION_DONE: Cell<Option<Duration>> = const { Cell::new(None) };
+}
+
+/// Mark the end of a pipeline's construction heuristic; the rest of its run is
+/// timed as improvement. Pipelines that never call it count as construction only.
+fn construction_done() {
+    CONSTRUCTION_DONE.with(|done| done.set(Some(clock::time_source().now())));
+}
+
+/// Runs expected to take longer than this get a progress bar
+#[cfg(feature = "progress")]
+const PROGRESS_AFTER: Duration = Duration::from_secs(1);
+
+/// Expected time of one run at `n` points, from the time model the max N
+/// search fits to the timed `runs` (n, ms)
+fn expected_time(runs: &[(usize, f64)], n: usize) -> Option<Duration> {
+    let ms = TimeModel::fit(runs)?.time_ms(n);
+    ms.is_finite().then(|| Duration::from_secs_f64(ms / 1000.0))
+}
+
+/// A progress bar on stderr, cleared when dropped
+/// Solvers report no progress of their own, so the bar fills with elapsed
+/// time against the expected time, and the ETA is what remains of it.
+#[cfg(feature = "progress")]
+struct ProbeBar(ProgressBar);
+
+#[cfg(feature = "progress")]
+impl ProbeBar {
+    /// A bar for `name` at `n` points, if the run is expected to be long and
+    /// the `--tui` dashboard is not drawing on stderr
+    fn start(name: &str, n: usize, expected: Option<Duration>) -> Option<ProbeBar> {
+        let expected = expected.filter(|&expected| expected > PROGRESS_AFTER)?;
+        #[cfg(feature = "tui")]
+        if DASHBOARD.lock().unwrap().is_some() {
+            return None;
+        }
+        let fraction = move |state: &ProgressState| (state.elapsed().as_secs_f64() / expected.as_secs_f64()).min(1.0);
+        let style = ProgressStyle::with_template("{prefix} [{time_bar}] {elapsed_precise} / ~{expected} ({time_eta})")
+            .unwrap()
+            .with_key("time_bar", move |state: &ProgressState, out: &mut dyn fmt::Write| {
+                let filled = (fraction(state) * 30.0) as usize;
+                let _ = write!(out, "{}{}", "=".repeat(filled), " ".repeat(30 - filled));
+            })
+            .with_key("expected", move |_: &ProgressState, out: &mut dyn fmt::Write| {
+                let _ = write!(out, "{:.1}s", expected.as_secs_f64());
+            })
+            .with_key("time_eta", move |state: &ProgressState, out: &mut dyn fmt::Write| {
+                let _ = match expected.checked_sub(state.elapsed()) {
+                    Some(left) => write!(out, "ETA {:.1}s", left.as_secs_f64()),
+                    None => write!(out, "overdue"),
+                };
+            });
+        let bar = ProgressBar::new_spinner().with_style(style).with_prefix(format!("  {} n={}", name, n));
+        bar.enable_steady_tick(Duration::from_millis(100));
+        Some(ProbeBar(bar))
+    }
+}
+
+#[cfg(feature = "progress")]
+impl Drop for ProbeBar {
+    fn drop(&mut self) {
+        self.0.finish_and_clear();
+    }
+}
+
+/// Time spent in each phase of a run
+#[derive(Debug, Clone, Copy, Default)]
+struct Phases {
+    /// Building the O(n^2) distance matrix, zero for solvers that do not read it
+    matrix: Duration,
+    construction: Duration,
+    improvement: Duration,
+}
+
+impl Phases {
+    /// Time of the solver itself, which decides max N; the matrix is excluded
+    fn solve(&self) -> Duration {
+        self.construction + self.improvement
+    }
+
+    fn mean(total: Phases, runs: u32) -> Phases {
+        Phases {
+            matrix: total.matrix / runs,
+            construction: total.construction / runs,
+            improvement: total.improvement / runs,
+        }
+    }
+
+    /// (matrix, construction, improvement) in ms
+    fn as_ms(&self) -> (f64, f64, f64) {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        (ms(self.matrix), ms(self.construction), ms(self.improvement))
+    }
 }
 
 /// Result of finding maximum N for an algorithm
 #[derive(Debug)]
-#[allow(dead_code)]
 struct BenchmarkResult {
     name: String,
+    params: String,
     max_n: usize,
     time_ms: f64,
+    /// Phase breakdown of the run at max N
+    phases: Phases,
     error: Option<String>,
+    /// Tour length and time in ms on the shared `PROBE_N`-point instance,
+    /// if the algorithm reaches that size
+    probe: Option<(f64, f64)>,
+    /// Smallest n at which the algorithm returned a tour that is not a
+    /// permutation of the points, and why; such sizes earn no max N credit
+    invalid: Option<(usize, String)>,
+    /// Smallest n at which a run was still going at the hard deadline and was abandoned
+    timed_out: Option<usize>,
+    /// Fit of time ~ n^k over every valid timed run
+    complexity: Option<ExponentFit>,
+    /// Gap in % of the tour at max N to the optimum, where `--layout` makes it known
+    optimum_gap: Option<f64>,
+    /// Every timed run, in the order they ran
+    probes: Vec<Probe>,
+}
+
+/// One timed run of a search, for the JSON output
+#[derive(Debug, Clone, Copy)]
+struct Probe {
+    n: usize,
+    /// Mean solver time in ms, `None` for a run abandoned at the hard deadline
+    time_ms: Option<f64>,
+    /// Whether every tour was a permutation of the points
+    valid: bool,
+    /// Mean tour length, of valid runs only
+    tour_length: Option<f64>,
+    /// Mean gap in % to the optimum, of valid runs at n <= `OPTIMUM_CHECK_N`
+    optimum_gap: Option<f64>,
+}
+
+/// Largest n at which every valid tour is also compared with the optimum
+/// from Held-Karp, about a second per instance at this size
+const OPTIMUM_CHECK_N: usize = 20;
+
+/// Optimal tour length of each generated instance, by (n, point seed), so
+/// every algorithm probing an instance shares one Held-Karp run
+static OPTIMA: Mutex<BTreeMap<(usize, u64), f64>> = Mutex::new(BTreeMap::new());
+
+/// Optimal tour length of `instance`, the benchmark instance of n points from `seed`
+fn optimum_length(instance: &ProblemInstance, seed: u64) -> Option<f64> {
+    let key = (instance.len(), seed);
+    if let Some(&length) = OPTIMA.lock().unwrap().get(&key) {
+        return Some(length);
+    }
+    let (tour, _) = brute_force::held_karp(instance.matrix(), instance.len()).ok()?;
+    let length = calculate_tour_length_points(&tour, instance.points(), &instance.metric());
+    OPTIMA.lock().unwrap().insert(key, length);
+    Some(length)
+}
+
+/// Outcome of one timed run that did not fail outright
+enum Run {
+    /// A full permutation, with its phase times, length and gap in % to the
+    /// optimum at n <= `OPTIMUM_CHECK_N`
+    Valid(Phases, f64, Option<f64>),
+    /// A tour that drops, repeats or invents cities
+    Invalid(SonarError),
+    /// Still running at the hard deadline, `HARD_DEADLINE` timeouts in
+    TimedOut,
+    /// Not started, or timed alongside an abandoned run still going, so
+    /// without a time that can be compared with the others
+    Untimed(Untimed),
+}
+
+/// A run still going after this many timeouts is abandoned
+const HARD_DEADLINE: u32 = 2;
+
+thread_local! {
+    /// Cancellation of the run on this worker thread, set by `run_with_deadline`
+    static PROBE_CANCELLATION: RefCell<Option<Arc<Cancellation>>> = const { RefCell::new(None) };
+}
+
+/// Whether the run on this thread passed its hard deadline; pipelines that
+/// step a solver check it between steps to end an abandoned run early
+fn probe_cancelled() -> bool {
+    PROBE_CANCELLATION.with(|cancellation| cancellation.borrow().as_deref().is_some_and(Cancellation::is_cancelled))
+}
+
+/// (result, start, end of construction, end) of a run
+type Timed = (Result<Vec<usize>, SonarError>, Duration, Duration, Duration);
+
+/// Why a run has no time to report
+#[derive(Debug, Clone, Copy)]
+enum Untimed {
+    /// Not started: an abandoned run of the same algorithm is still going
+    Refused,
+    /// An abandoned run was still going alongside it, competing for the CPU
+    Disturbed,
+    /// Not started: times are process CPU time, which would count the
+    /// abandoned run still going
+    Uncounted,
+}
+
+/// Workers of abandoned runs that may still be going: the algorithm, the
+/// worker, and when to stop waiting for it
+static ABANDONED: Mutex<Vec<(String, thread::JoinHandle<()>, Instant)>> = Mutex::new(Vec::new());
+
+/// Run `run_fn`, the algorithm `name`, on `instance` on a worker thread and
+/// wait for it up to `limit`
+/// A thread cannot be stopped from outside: a run still going at the deadline
+/// is cancelled, which only the exact solvers and stepping pipelines notice,
+/// and otherwise left to end on its own with its result discarded. Runs wait
+/// for it to end, but no longer than `limit` past its abandonment, as one that
+/// never checks for cancellation could run for hours. While it is still going,
+/// `name` is not run again, `Err(Some(Untimed::Refused))`, and the runs of
+/// other algorithms are `Untimed::Disturbed`: their times would include the
+/// competition for the CPU. Under `TimeSource::Cpu` they would include its work
+/// as well, so nothing is run, `Err(Some(Untimed::Uncounted))`. `Err(None)` if
+/// the deadline passed.
+fn run_with_deadline<F>(
+    name: &str,
+    run_fn: &Arc<F>,
+    instance: &Arc<ProblemInstance>,
+    limit: Duration,
+) -> Result<(Timed, Option<Untimed>), Option<Untimed>>
+where
+    F: Fn(&ProblemInstance) -> Result<Vec<usize>, SonarError> + Send + Sync + 'static,
+{
+    let mut abandoned = ABANDONED.lock().unwrap();
+    loop {
+        abandoned.retain(|(_, worker, _)| !worker.is_finished());
+        let now = Instant::now();
+        let Some(until) = abandoned.iter().map(|&(_, _, until)| until).filter(|&until| now < until).max() else {
+            break;
+        };
+        thread::sleep(Duration::from_millis(10).min(until - now));
+    }
+    if abandoned.iter().any(|(algorithm, _, _)| algorithm == name) {
+        return Err(Some(Untimed::Refused));
+    }
+    if !abandoned.is_empty() && clock::time_source() == TimeSource::Cpu {
+        return Err(Some(Untimed::Uncounted));
+    }
+    let disturbed = (!abandoned.is_empty()).then_some(Untimed::Disturbed);
+
+    let deadline = Instant::now() + limit;
+    let cancellation = Arc::new(Cancellation::new(deadline));
+    let (run_fn, instance, token) = (Arc::clone(run_fn), Arc::clone(instance), Arc::clone(&cancellation));
+    let (sender, receiver) = mpsc::channel();
+    let worker = thread::spawn(move || {
+        PROBE_CANCELLATION.with(|current| *current.borrow_mut() = Some(token));
+        CONSTRUCTION_DONE.with(|done| done.set(None));
+        let clock = clock::time_source();
+        let start = clock.now();
+        let result = run_fn(&instance);
+        let end = clock.now();
+        let constructed = CONSTRUCTION_DONE.with(Cell::take).unwrap_or(end);
+        let _ = sender.send((result, start, constructed, end));
+    });
+    match receiver.recv_timeout(limit) {
+        Ok(timed) => Ok((timed, disturbed)),
+        Err(_) => {
+            cancellation.cancel();
+            abandoned.push((name.to_string(), worker, Instant::now() + limit));
+            Err(None)
+        }
+    }
 }
 
-/// Binary search to find maximum n that completes within timeout
+/// Find the maximum n that completes within timeout, by a `BoundarySearch`
 fn find_max_n<F>(
     name: &str,
+    params: &str,
     run_fn: F,
     min_n: usize,
     max_n: usize,
     timeout: Duration,
-    needs_graph: bool,
+    prebuild_matrix: bool,
 ) -> BenchmarkResult
 where
-    F: Fn(&[Point], &[Vec<f64>], usize) -> Vec<usize>,
+    F: Fn(&ProblemInstance) -> Result<Vec<usize>, SonarError> + Send + Sync + 'static,
 {
-    println!("\nTesting {}...", name);
+    find_max_n_with_memory(name, params, run_fn, min_n, max_n, timeout, prebuild_matrix, |_| 0)
+}
 
-    let mut best_n = min_n;
-    let mut _best_time = Duration::ZERO;
-    let mut n = min_n;
+/// `find_max_n` for algorithms that allocate `extra_bytes(n)` on top of the
+/// distance matrix; sizes whose estimate exceeds `--max-memory` are never run
+#[allow(clippy::too_many_arguments)]
+fn find_max_n_with_memory<F>(
+    name: &str,
+    params: &str,
+    run_fn: F,
+    min_n: usize,
+    max_n: usize,
+    timeout: Duration,
+    prebuild_matrix: bool,
+    extra_bytes: fn(usize) -> usize,
+) -> BenchmarkResult
+where
+    F: Fn(&ProblemInstance) -> Result<Vec<usize>, SonarError> + Send + Sync + 'static,
+{
+    #[cfg(feature = "tui")]
+    report(Event::Started(name.to_string()));
+    let result = search_max_n(name, params, run_fn, min_n, max_n, timeout, prebuild_matrix, extra_bytes);
+    #[cfg(feature = "tui")]
+    report(Event::Finished { max_n: result.max_n });
+    result
+}
 
-    // Find rough upper bound by doubling
-    while n <= max_n {
+/// The search of `find_max_n_with_memory`
+#[allow(clippy::too_many_arguments)]
+fn search_max_n<F>(
+    name: &str,
+    params: &str,
+    run_fn: F,
+    min_n: usize,
+    max_n: usize,
+    timeout: Duration,
+    prebuild_matrix: bool,
+    extra_bytes: fn(usize) -> usize,
+) -> BenchmarkResult
+where
+    F: Fn(&ProblemInstance) -> Result<Vec<usize>, SonarError> + Send + Sync + 'static,
+{
+    log!("\nTesting {}...", name);
+    let run_fn = Arc::new(run_fn);
+    let (min_n, max_n) = search_bounds(name, min_n, max_n);
+
+    let estimated_bytes = |n: usize| {
+        let matrix = if prebuild_matrix { n.saturating_mul(n).saturating_mul(8) } else { 0 };
+        matrix.saturating_add(extra_bytes(n))
+    };
+    let fits = |n: usize| MAX_MEMORY.get().is_none_or(|&limit| estimated_bytes(n) <= limit);
+
+    // Time one run per point seed on generated instances of n points and check
+    // every tour, returning the mean phase times and tour length; `expected`
+    // is the time one run should take, from `expected_time`
+    let probes = RefCell::new(Vec::new());
+    let cached_params = cache_params(params);
+    let run_at = |n: usize, expected: Option<Duration>| -> Result<Run, SonarError> {
         let grid_size = 40.max((n as f64).sqrt() as usize * 2);
-        let points = generate_normalized_points(n, grid_size, 12345);
-        let graph = if needs_graph {
-            create_distance_matrix(&points)
-        } else {
-            vec![]
-        };
+        let seeds = point_seeds();
+        #[cfg(feature = "progress")]
+        let _bar = ProbeBar::start(name, n, expected.map(|expected| expected * seeds.len() as u32));
+        #[cfg(not(feature = "progress"))]
+        let _ = expected;
+        let (mut total, mut total_length, mut total_gap) = (Phases::default(), 0.0, Some(0.0));
+        for &seed in seeds {
+            let points = match LAYOUT.get().copied().unwrap_or_default() {
+                Layout::Disc => {
+                    let version = POINTS_VERSION.get().copied().unwrap_or_default();
+                    generate_normalized_points_version(n, grid_size, seed, version)
+                }
+                layout => layouts::generate(layout, n, seed),
+            };
+            let instance = Arc::new(ProblemInstance::new(points, Metric::Euclidean)?);
+            let key = CacheKey { algorithm: name, params: &cached_params, seed, n };
+            let cached = RESULT_CACHE.get().and_then(|cache| cache.get(&key, instance.points(), instance.metric()));
+            let run = match cached {
+                Some(run) => run,
+                None => {
+                    // Matrix-based solvers are timed without the O(n^2) matrix construction,
+                    // which is reported as a phase of its own
+                    let matrix = if prebuild_matrix { measure_time(|| instance.matrix()).1 } else { Duration::ZERO };
+                    let limit = timeout * HARD_DEADLINE;
+                    let ((result, start, constructed, end), disturbed) =
+                        match run_with_deadline(name, &run_fn, &instance, limit) {
+                            Ok(timed) => timed,
+                            Err(untimed) => {
+                                let probe =
+                                    Probe { n, time_ms: None, valid: false, tour_length: None, optimum_gap: None };
+                                probes.borrow_mut().push(probe);
+                                return Ok(untimed.map_or(Run::TimedOut, Run::Untimed));
+                            }
+                        };
+                    let tour = result?;
+                    let time_ms = Some(end.saturating_sub(start).as_secs_f64() * 1000.0);
+                    if let Err(error) = validate_tour(&tour, n) {
+                        let probe = Probe { n, time_ms, valid: false, tour_length: None, optimum_gap: None };
+                        probes.borrow_mut().push(probe);
+                        return Ok(Run::Invalid(error));
+                    }
+                    if let Some(untimed) = disturbed {
+                        let probe = Probe { n, time_ms, valid: false, tour_length: None, optimum_gap: None };
+                        probes.borrow_mut().push(probe);
+                        return Ok(Run::Untimed(untimed));
+                    }
+                    // Measured from the points, so quality never depends on which solvers build a matrix
+                    let length = calculate_tour_length_points(&tour, instance.points(), &instance.metric());
+                    let construction = constructed.saturating_sub(start);
+                    let improvement = end.saturating_sub(constructed);
+                    let run = CachedRun { tour, length, matrix, construction, improvement };
+                    if let Some(cache) = RESULT_CACHE.get() {
+                        if let Err(e) = cache.put(&key, instance.points(), instance.metric(), &run) {
+                            log!("  cannot write to the result cache: {}", e);
+                        }
+                    }
+                    run
+                }
+            };
+            total.matrix += run.matrix;
+            total.construction += run.construction;
+            total.improvement += run.improvement;
+            let length = run.length;
+            total_length += length;
+            // Spot check of quality, and of correctness for the exact solvers
+            let optimum = if n <= OPTIMUM_CHECK_N { optimum_length(&instance, seed) } else { None };
+            total_gap = total_gap.zip(optimum).map(|(total, optimum)| total + (length - optimum) / optimum * 100.0);
+        }
+        let runs = seeds.len() as f64;
+        let (phases, length) = (Phases::mean(total, seeds.len() as u32), total_length / runs);
+        let optimum_gap = total_gap.map(|total| total / runs);
+        #[cfg(feature = "tui")]
+        report(Event::Probed { n, time: phases.solve() });
+        let time_ms = Some(phases.solve().as_secs_f64() * 1000.0);
+        probes.borrow_mut().push(Probe { n, time_ms, valid: true, tour_length: Some(length), optimum_gap });
+        Ok(Run::Valid(phases, length, optimum_gap))
+    };
+    let failed = |n: usize, error: String| {
+        log!("  n={}: error: {}", n, error);
+        BenchmarkResult {
+            name: name.to_string(),
+            params: params.to_string(),
+            max_n: 0,
+            time_ms: 0.0,
+            phases: Phases::default(),
+            error: Some(error),
+            probe: None,
+            invalid: None,
+            timed_out: None,
+            complexity: None,
+            optimum_gap: None,
+            probes: probes.take(),
+        }
+    };
+    let mut invalid: Option<(usize, String)> = None;
+    // Smallest n whose run was abandoned at the hard deadline
+    let mut timed_out: Option<usize> = None;
+    let mut record_timeout = |n: usize| {
+        log!("  n={}: timed out after {}s, abandoned", n, (timeout * HARD_DEADLINE).as_secs_f64());
+        timed_out = Some(timed_out.map_or(n, |m: usize| m.min(n)));
+    };
+    let record_untimed = |n: usize, untimed: Untimed| match untimed {
+        Untimed::Refused => log!("  n={}: not run, an abandoned run of {} is still going", n, name),
+        Untimed::Disturbed => log!("  n={}: time discarded, an abandoned run was still going", n),
+        Untimed::Uncounted => log!("  n={}: not run, its CPU time would count an abandoned run still going", n),
+    };
+    // (n, time in ms) of every valid run, for the complexity fit
+    let mut runs: Vec<(usize, f64)> = Vec::new();
+    let mut record_invalid = |n: usize, error: SonarError| {
+        log!("  n={}: INVALID TOUR: {}", n, error);
+        if invalid.as_ref().is_none_or(|&(m, _)| n < m) {
+            invalid = Some((n, error.to_string()));
+        }
+    };
+
+    // Probe where a time model fitted to the runs so far puts the boundary
+    let mut search = BoundarySearch::new(min_n, max_n, timeout);
+    while let Some(n) = search.next() {
+        if !fits(n) {
+            log!("  n={}: skipped, needs ~{} MB", n, estimated_bytes(n) >> 20);
+            if n == min_n {
+                let max = (0..min_n).rev().find(|&m| fits(m)).unwrap_or(0);
+                return failed(n, SonarError::TooLarge { n, max }.to_string());
+            }
+            search.failed(n, None);
+            continue;
+        }
+        match run_at(n, expected_time(&runs, n)) {
+            Ok(Run::Valid(phases, _, optimum_gap)) => {
+                let time = phases.solve();
+                let ms = time.as_secs_f64() * 1000.0;
+                runs.push((n, ms));
+                match optimum_gap {
+                    Some(gap) => log!("  n={}: {:.2}ms, {:.3}% over the optimum", n, ms, gap),
+                    None => log!("  n={}: {:.2}ms", n, ms),
+                }
+                if time <= timeout {
+                    search.passed(n, ms);
+                } else {
+                    search.failed(n, Some(ms));
+                }
+            }
+            Ok(Run::Invalid(error)) => {
+                record_invalid(n, error);
+                search.failed(n, None);
+            }
+            Ok(Run::TimedOut) => {
+                record_timeout(n);
+                search.failed(n, None);
+            }
+            Ok(Run::Untimed(untimed)) => {
+                record_untimed(n, untimed);
+                search.failed(n, None);
+            }
+            Err(error) => return failed(n, error.to_string()),
+        }
+    }
+    let best_n = search.best();
+
+    // Final verification; no size gets credit if even the smallest one is invalid
+    let (final_time, phases, final_length) = match run_at(best_n, expected_time(&runs, best_n)) {
+        Ok(Run::Valid(phases, length, _)) => {
+            runs.push((best_n, phases.solve().as_secs_f64() * 1000.0));
+            (phases.solve(), phases, length)
+        }
+        Ok(Run::Invalid(error)) => {
+            record_invalid(best_n, error.clone());
+            return BenchmarkResult { invalid, ..failed(best_n, error.to_string()) };
+        }
+        Ok(Run::TimedOut) => {
+            record_timeout(best_n);
+            return BenchmarkResult { timed_out, ..failed(best_n, format!("timed out at n={}", best_n)) };
+        }
+        Ok(Run::Untimed(untimed)) => {
+            record_untimed(best_n, untimed);
+            return BenchmarkResult { timed_out, ..failed(best_n, format!("no valid timing at n={}", best_n)) };
+        }
+        Err(error) => return failed(best_n, error.to_string()),
+    };
 
-        let (_, time) = measure_time(|| run_fn(&points, &graph, n));
+    // Quality probe on the instance shared by all algorithms
+    let probe = if best_n >= PROBE_N {
+        match run_at(PROBE_N, expected_time(&runs, PROBE_N)) {
+            Ok(Run::Valid(phases, length, _)) => {
+                let time_ms = phases.solve().as_secs_f64() * 1000.0;
+                runs.push((PROBE_N, time_ms));
+                Some((length, time_ms))
+            }
+            Ok(Run::Invalid(error)) => {
+                record_invalid(PROBE_N, error);
+                None
+            }
+            Ok(Run::TimedOut) => {
+                record_timeout(PROBE_N);
+                None
+            }
+            Ok(Run::Untimed(untimed)) => {
+                record_untimed(PROBE_N, untimed);
+                None
+            }
+            Err(error) => return failed(PROBE_N, error.to_string()),
+        }
+    } else {
+        None
+    };
 
-        println!("  n={}: {:.2}ms", n, time.as_secs_f64() * 1000.0);
+    log!("  RESULT: max n={} in {:.2}ms", best_n, final_time.as_secs_f64() * 1000.0);
+    let optimum_gap = known_optimum_at(best_n).map(|optimum| (final_length - optimum) / optimum * 100.0);
+    if let Some(gap) = optimum_gap {
+        log!("  Gap to the known optimum at max n: {:.3}%", gap);
+    }
 
-        if time > timeout {
-            break;
+    BenchmarkResult {
+        name: name.to_string(),
+        params: params.to_string(),
+        max_n: best_n,
+        time_ms: final_time.as_secs_f64() * 1000.0,
+        phases,
+        error: None,
+        probe,
+        invalid,
+        timed_out,
+        complexity: complexity::fit_exponent(&runs),
+        optimum_gap,
+        probes: probes.into_inner(),
+    }
+}
+
+/// Mean optimal tour length of the benchmark instances of `n` points, if the
+/// `--layout` makes it known
+fn known_optimum_at(n: usize) -> Option<f64> {
+    let layout = LAYOUT.get().copied().unwrap_or_default();
+    if !layout.has_known_optimum() {
+        return None;
+    }
+    let seeds = point_seeds();
+    let total: f64 = seeds
+        .iter()
+        .filter_map(|&seed| layouts::known_optimum(layout, &layouts::generate(layout, n, seed)))
+        .sum();
+    Some(total / seeds.len() as f64)
+}
+
+/// Length the probe tours' gaps are measured against: the known optimum of
+/// the probe instance where the layout has one, else the shortest probe tour
+fn probe_reference(results: &[BenchmarkResult]) -> f64 {
+    known_optimum_at(PROBE_N).unwrap_or_else(|| {
+        results.iter().filter_map(|r| r.probe).map(|(length, _)| length).fold(f64::INFINITY, f64::min)
+    })
+}
+
+/// Score every probed result as `(gap %, score)`, lower scores being better
+/// The gap is measured against `probe_reference`. Gaps and log-scaled
+/// probe times are each rescaled to [0, 1] across algorithms, then mixed as
+/// `quality_weight * gap + (1 - quality_weight) * time`.
+fn score_results(results: &[BenchmarkResult], quality_weight: f64) -> Vec<Option<(f64, f64)>> {
+    let probes: Vec<(f64, f64)> = results.iter().filter_map(|r| r.probe).collect();
+    let best_length = probe_reference(results);
+    // Clamp so sub-microsecond probes do not dominate the log scale
+    let log_time = |ms: f64| ms.max(1e-3).log10();
+    let max_gap = probes
+        .iter()
+        .map(|&(length, _)| (length - best_length) / best_length * 100.0)
+        .fold(0.0, f64::max);
+    let min_time = probes.iter().map(|&(_, ms)| log_time(ms)).fold(f64::INFINITY, f64::min);
+    let max_time = probes.iter().map(|&(_, ms)| log_time(ms)).fold(f64::NEG_INFINITY, f64::max);
+
+    results
+        .iter()
+        .map(|r| {
+            let (length, ms) = r.probe?;
+            let gap = (length - best_length) / best_length * 100.0;
+            let gap_term = if max_gap > 0.0 { gap / max_gap } else { 0.0 };
+            let time_term = if max_time > min_time { (log_time(ms) - min_time) / (max_time - min_time) } else { 0.0 };
+            Some((gap, quality_weight * gap_term + (1.0 - quality_weight) * time_term))
+        })
+        .collect()
+}
+
+/// Print every registered algorithm with its key, complexity, matrix use and
+/// parameters, as a table or as JSON
+fn list_algorithms(json: bool) {
+    if json {
+        println!("{}", registry::to_json(&registry::all()));
+        return;
+    }
+    let algorithms = registry::all();
+    let width = algorithms.iter().map(|a| a.complexity.len()).max().unwrap_or(0);
+    println!("{:<24} | {:<6} | {:<width$} | Parameters (defaults)", "Key", "Matrix", "Complexity");
+    println!("{}", "-".repeat(width + 60));
+    for a in algorithms {
+        let params: Vec<String> = a.params.iter().map(|p| format!("{}={}", p.name, p.default)).collect();
+        let params = if params.is_empty() { "-".to_string() } else { params.join(", ") };
+        let matrix = if a.needs_matrix { "yes" } else { "no" };
+        println!("{:<24} | {:<6} | {:<width$} | {}", a.key, matrix, a.complexity, params);
+    }
+}
+
+/// Compare the JS and Rust algorithms on every size and point seed
+/// Returns whether all results matched.
+fn check_parity(sizes: &[usize]) -> bool {
+    let mut matched = true;
+    for &seed in point_seeds() {
+        for &n in sizes {
+            let report = parity::check(n, 40, seed).unwrap_or_else(|e| fail!("parity driver: {}", e));
+            log!("n={} seed={}: {} results compared, {} diverged", n, seed, report.compared, report.divergences.len());
+            for divergence in &report.divergences {
+                log!("  DIVERGED {}", divergence);
+            }
+            matched &= report.divergences.is_empty();
         }
+    }
+    matched
+}
 
-        best_n = n;
-        _best_time = time;
+/// Size of the generated instances `tune` searches parameters on
+const TUNE_N: usize = 100;
 
-        // Adjust increment based on time
-        if time < timeout / 100 {
-            n = n.saturating_mul(2).min(max_n);
-        } else if time < timeout / 10 {
-            n = ((n as f64 * 1.5).ceil() as usize).min(max_n);
-        } else {
-            n += 1;
+/// Tune `space` on one generated instance per point seed, printing the result as TOML
+fn run_tuner<S: tune::SearchSpace>(space: &S, strategy: tune::Strategy, budget: Duration) {
+    let graphs: Vec<_> = point_seeds()
+        .iter()
+        .map(|&seed| create_distance_matrix(&generate_normalized_points(TUNE_N, 100, seed)))
+        .collect();
+    let tuned = tune::tune(space, &graphs, strategy, budget).unwrap_or_else(|e| fail!("tuning failed: {}", e));
+    log!(
+        "{} runs; tour length vs nearest neighbor {:.4} with the defaults, {:.4} tuned",
+        tuned.runs,
+        tuned.baseline,
+        tuned.score
+    );
+    print!("{}", tune::to_toml(space, &tuned.params));
+}
+
+/// Size of the generated instances `sweep` runs on unless `--n` is given
+const SWEEP_N: usize = 1000;
+
+/// Run the `sweep` subcommand on one generated instance per point seed
+fn run_sweep(args: &[String]) {
+    let usage = "usage: sweep --algo <key> --param <name=v1,v2,...> [--param ...] [--n <size>] [--csv]";
+    let value_of = |flag: &str| {
+        args.iter().position(|a| a == flag).map(|pos| args.get(pos + 1).unwrap_or_else(|| fail!("{}", usage)))
+    };
+    let key = value_of("--algo").unwrap_or_else(|| fail!("{}", usage));
+    let (algorithm, _) = sweep::find(key).unwrap_or_else(|| fail!("{} cannot be swept", key));
+    let axes: Vec<sweep::Axis> = args
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| *a == "--param")
+        .map(|(pos, _)| {
+            let spec = args.get(pos + 1).unwrap_or_else(|| fail!("{}", usage));
+            sweep::parse_axis(algorithm, spec).unwrap_or_else(|e| fail!("{}: {}", spec, e))
+        })
+        .collect();
+    let n = value_of("--n").map_or(SWEEP_N, |n| n.parse().unwrap_or_else(|_| fail!("{}", usage)));
+    let instances: Vec<ProblemInstance> = point_seeds()
+        .iter()
+        .map(|&seed| ProblemInstance::new(generate_normalized_points(n, 100, seed), Metric::Euclidean).unwrap())
+        .collect();
+    let cache = RESULT_CACHE.get().map(|cache| (cache, point_seeds()));
+    let rows = sweep::run(key, &axes, &instances, cache).unwrap_or_else(|e| fail!("sweep failed: {}", e));
+    if args.iter().any(|a| a == "--csv") {
+        print!("{}", sweep::to_csv(&axes, &rows));
+        return;
+    }
+
+    log!("{} at n={}, {} combinations", algorithm.name, n, rows.len());
+    let header: Vec<String> = axes.iter().map(|axis| format!("{:>20}", axis.name)).collect();
+    log!("{} {:>12} {:>12}", header.join(" "), "Length", "Time (ms)");
+    for row in &rows {
+        let values: Vec<String> = row.values.iter().map(|value| format!("{:>20}", value)).collect();
+        log!("{} {:>12.4} {:>12.3}", values.join(" "), row.length, row.time.as_secs_f64() * 1000.0);
+    }
+}
+
+/// Run the `throughput` subcommand on one generated instance per point seed
+/// and size, printing solves per second as a table or as JSON
+fn run_throughput(args: &[String], format: OutputFormat) {
+    let usage = "usage: throughput [n ...] [--seconds <s>]";
+    let mut args: Vec<&String> = args[2..].iter().collect();
+    let budget = match args.iter().position(|a| *a == "--seconds") {
+        Some(pos) => {
+            let seconds: f64 = args.get(pos + 1).and_then(|s| s.parse().ok()).unwrap_or_else(|| fail!("{}", usage));
+            args.drain(pos..pos + 2);
+            Duration::from_secs_f64(seconds)
         }
+        None => Duration::from_secs(1),
+    };
+    let sizes: Vec<usize> = args.iter().map(|n| n.parse().unwrap_or_else(|_| fail!("{}", usage))).collect();
+    let sizes = if sizes.is_empty() { throughput::SIZES } else { &sizes };
 
-        if n == best_n {
-            n += 1;
+    let header: Vec<String> = sizes.iter().map(|n| format!("{:>12}", format!("n={}", n))).collect();
+    log!("Complete solves per second, {:.1}s per algorithm and size", budget.as_secs_f64());
+    log!("\n{:<52} | {}", "Algorithm", header.join(" | "));
+    log!("{}", "-".repeat(55 + 15 * sizes.len()));
+    let mut rows = Vec::new();
+    for algorithm in registry::all() {
+        let mut cells = Vec::new();
+        for &n in sizes {
+            if algorithm.max_n.is_some_and(|max| n > max) {
+                cells.push(format!("{:>12}", "-"));
+                continue;
+            }
+            let instances: Vec<_> =
+                point_seeds().iter().map(|&seed| generate_normalized_points(n, 100, seed)).collect();
+            match throughput::measure(algorithm, &instances, budget) {
+                Ok(result) => {
+                    cells.push(format!("{:>12.1}", result.per_second()));
+                    rows.push(format!(
+                        "  {{ \"key\": {}, \"name\": {}, \"n\": {}, \"solves\": {}, \"seconds\": {:.4}, \
+                         \"solvesPerSecond\": {:.3} }}",
+                        json_string(algorithm.key),
+                        json_string(algorithm.name),
+                        n,
+                        result.solves,
+                        result.elapsed.as_secs_f64(),
+                        result.per_second()
+                    ));
+                }
+                Err(error) => cells.push(format!("{:>12}", format!("error: {}", error))),
+            }
         }
+        log!("{:<52} | {}", algorithm.name, cells.join(" | "));
     }
+    if format == OutputFormat::Json {
+        println!("[\n{}\n]", rows.join(",\n"));
+    }
+}
 
-    // Binary search for exact boundary
-    let mut low = best_n;
-    let mut high = n.min(max_n);
+/// Run the `representations` subcommand: time 2-opt on each tour
+/// representation at each size, summed over the point seeds, and report
+/// which is fastest where
+fn run_representations(args: &[String], format: OutputFormat) {
+    let usage = "usage: representations [n ...] [--iterations <k>]";
+    let mut args: Vec<&String> = args[2..].iter().collect();
+    let iterations = match args.iter().position(|a| *a == "--iterations") {
+        Some(pos) => {
+            let iterations: usize =
+                args.get(pos + 1).and_then(|s| s.parse().ok()).unwrap_or_else(|| fail!("{}", usage));
+            args.drain(pos..pos + 2);
+            iterations
+        }
+        None => 100,
+    };
+    let sizes: Vec<usize> = args.iter().map(|n| n.parse().unwrap_or_else(|_| fail!("{}", usage))).collect();
+    let sizes = if sizes.is_empty() { representations::SIZES } else { &sizes };
 
-    while low < high.saturating_sub(1) {
-        let mid = (low + high) / 2;
-        let grid_size = 40.max((mid as f64).sqrt() as usize * 2);
-        let points = generate_normalized_points(mid, grid_size, 12345);
-        let graph = if needs_graph {
-            create_distance_matrix(&points)
-        } else {
-            vec![]
-        };
+    let header: Vec<String> = Representation::ALL.iter().map(|r| format!("{:>16}", r.name())).collect();
+    log!("2-opt from nearest neighbor, at most {} sweeps; milliseconds over {} seeds", iterations, point_seeds().len());
+    log!("\n{:>8} | {} | Fastest", "n", header.join(" | "));
+    log!("{}", "-".repeat(30 + 19 * Representation::ALL.len()));
+    let mut rows = Vec::new();
+    let mut wins: Vec<Vec<usize>> = vec![Vec::new(); Representation::ALL.len()];
+    for &n in sizes {
+        let mut totals = vec![(Duration::ZERO, 0.0); Representation::ALL.len()];
+        for &seed in point_seeds() {
+            let timings = representations::measure(&generate_normalized_points(n, 100, seed), iterations)
+                .unwrap_or_else(|e| fail!("n={}: {}", n, e));
+            for (total, timing) in totals.iter_mut().zip(&timings) {
+                total.0 += timing.time;
+                total.1 += timing.length;
+            }
+        }
+        let timings: Vec<representations::Timing> = Representation::ALL
+            .iter()
+            .zip(&totals)
+            .map(|(&representation, &(time, length))| representations::Timing { representation, time, length })
+            .collect();
+        let fastest = representations::fastest(&timings).expect("every representation was timed");
+        wins[Representation::ALL.iter().position(|&r| r == fastest).unwrap()].push(n);
+        let cells: Vec<String> = timings.iter().map(|t| format!("{:>16.2}", t.time.as_secs_f64() * 1000.0)).collect();
+        log!("{:>8} | {} | {}", n, cells.join(" | "), fastest.name());
+        for timing in &timings {
+            rows.push(format!(
+                "  {{ \"representation\": {}, \"n\": {}, \"timeMs\": {:.4}, \"length\": {:.6}, \"fastest\": {} }}",
+                json_string(timing.representation.name()),
+                n,
+                timing.time.as_secs_f64() * 1000.0,
+                timing.length / point_seeds().len() as f64,
+                timing.representation == fastest
+            ));
+        }
+    }
+    log!("");
+    for (representation, sizes) in Representation::ALL.iter().zip(&wins).filter(|(_, sizes)| !sizes.is_empty()) {
+        let sizes: Vec<String> = sizes.iter().map(usize::to_string).collect();
+        log!("{} is fastest at n = {}", representation.name(), sizes.join(", "));
+    }
+    if format == OutputFormat::Json {
+        println!("[\n{}\n]", rows.join(",\n"));
+    }
+}
 
-        let (_, time) = measure_time(|| run_fn(&points, &graph, mid));
-        println!("  n={}: {:.2}ms", mid, time.as_secs_f64() * 1000.0);
+/// Algorithm `solve` runs unless `--algo` is given
+const SOLVE_ALGORITHM: &str = "two-opt";
 
-        if time <= timeout {
-            low = mid;
-            _best_time = time;
-        } else {
-            high = mid;
+/// Run the `solve` subcommand: read points from a TSPLIB, GeoJSON or GPX
+/// waypoint file, solve them and print the tour as 0-based point indices
+/// GeoJSON and GPX coordinates are longitude and latitude: `--geo` measures
+/// them with the haversine metric, otherwise they are projected to km.
+fn run_solve(args: &[String], format: OutputFormat) {
+    let usage = "usage: solve --input <file.tsp|file.geojson|file.gpx> [--algo <key|pipeline>] [--geo] \
+                 [--matrix <file>] [--script <file.rhai>] [--max-edge <L>] [--gcode <file>] [--hpgl <file>] [--dots] \
+                 [--plot-scale <mm>] [--travel-feed <mm/min>] [--draw-feed <mm/min>]";
+    let value_of = |flag: &str| {
+        args.iter().position(|a| a == flag).map(|pos| args.get(pos + 1).unwrap_or_else(|| fail!("{}", usage)))
+    };
+    let path = Path::new(value_of("--input").unwrap_or_else(|| fail!("{}", usage)));
+    let spec = value_of("--algo").map_or(SOLVE_ALGORITHM, String::as_str);
+    let pipeline = pipeline::parse(spec).unwrap_or_else(|e| fail!("{}: {}; see list-algorithms", spec, e));
+    let script = value_of("--script").map(|file| read_script(Path::new(file)));
+    let (points, metric, unit) = read_points(path, args.iter().any(|a| a == "--geo"));
+    if let Some(max) = pipeline.max_n().filter(|&max| points.len() > max) {
+        fail!("{} handles at most {} points, not {}", pipeline.name(), max, points.len());
+    }
+    let (instance, unit) = open_instance(path, (points, metric, unit), value_of("--matrix"), script.as_ref());
+    // Files carry no seed; their points' fingerprint identifies them instead
+    let seed = matrix_cache::fingerprint(instance.points(), instance.metric());
+    let params = cache_params(value_of("--matrix").map_or("", String::as_str));
+    let canonical = pipeline.to_string();
+    let key = CacheKey { algorithm: &canonical, params: &params, seed, n: instance.len() };
+    // Scripts can change between runs unnoticed, so their results are never cached
+    let cache = RESULT_CACHE.get().filter(|_| script.is_none());
+    let cached = cache.and_then(|cache| cache.get(&key, instance.points(), instance.metric()));
+    let (tour, time) = match cached {
+        Some(run) => {
+            log!("Reusing the cached result");
+            (run.tour.clone(), run.time())
+        }
+        None => {
+            let (tour, time) = measure_time(|| pipeline.solve(&instance));
+            let tour = tour.unwrap_or_else(|e| fail!("{} failed: {}", pipeline.name(), e));
+            if let Some(cache) = cache {
+                let length = instance.tour_length(&tour);
+                let (matrix, improvement) = (Duration::ZERO, Duration::ZERO);
+                let run = CachedRun { tour, length, matrix, construction: time, improvement };
+                if let Err(e) = cache.put(&key, instance.points(), instance.metric(), &run) {
+                    log!("Cannot write to the result cache: {}", e);
+                }
+                (run.tour, time)
+            } else {
+                (tour, time)
+            }
+        }
+    };
+    let (tour, scripted) = measure_time(|| apply_penalty(script.as_ref(), &instance, tour));
+    let time = time + scripted;
+    let (tour, time) = match value_of("--max-edge") {
+        Some(limit) => {
+            let max_edge = limit.parse().unwrap_or_else(|_| fail!("invalid --max-edge: {}", limit));
+            let (tour, rework) = measure_time(|| limit_edges(&instance, tour, max_edge));
+            let over = edge_limit::violations(&tour, instance.matrix(), max_edge);
+            log!("{} edges longer than {}{} remain", over, max_edge, unit);
+            (tour, time + rework)
+        }
+        None => (tour, time),
+    };
+    check_edges(&instance, &tour, &canonical);
+    let length = instance.tour_length(&tour);
+    let ms = time.as_secs_f64() * 1000.0;
+    log!("{} on {} points: length {:.3}{} in {:.2}ms", pipeline.name(), instance.len(), length, unit, ms);
+
+    let number = |flag: &str, default: f64| {
+        value_of(flag).map_or(default, |value| value.parse().unwrap_or_else(|_| fail!("invalid {}: {}", flag, value)))
+    };
+    let defaults = Plotter::default();
+    let plotter = Plotter {
+        scale: number("--plot-scale", defaults.scale),
+        travel_feed: number("--travel-feed", defaults.travel_feed),
+        draw_feed: number("--draw-feed", defaults.draw_feed),
+        ..defaults
+    };
+    let stroke = if args.iter().any(|a| a == "--dots") { Stroke::Dots } else { Stroke::Line };
+    for flag in ["--gcode", "--hpgl"] {
+        if let Some(file) = value_of(flag) {
+            let output = match flag {
+                "--gcode" => plotter::gcode(instance.points(), &tour, &plotter, stroke),
+                _ => plotter::hpgl(instance.points(), &tour, &plotter, stroke),
+            };
+            std::fs::write(file, output).unwrap_or_else(|e| fail!("cannot write {}: {}", file, e));
+            log!("Wrote plotter output to {}", file);
         }
     }
 
-    best_n = low;
+    let order: Vec<String> = tour.iter().map(usize::to_string).collect();
+    match format {
+        OutputFormat::Json => println!(
+            "{{ \"algorithm\": {}, \"length\": {:.6}, \"timeMs\": {:.3}, \"tour\": [{}] }}",
+            json_string(&canonical),
+            length,
+            ms,
+            order.join(", ")
+        ),
+        _ => println!("{}", order.join(" ")),
+    }
+}
+
+/// Exit with an error naming `what` produced `tour` if it takes an edge the
+/// instance's matrix forbids
+/// Only a matrix, from `--matrix` or a script, can forbid edges, so
+/// instances that never built one are not checked.
+fn check_edges(instance: &ProblemInstance, tour: &[usize], what: &str) {
+    if let Some(Err(e)) = instance.cached_matrix().map(|matrix| validate_tour_edges(tour, matrix)) {
+        eprintln!("{}: {}", what, e);
+        std::process::exit(1);
+    }
+}
+
+/// Points of a TSPLIB, GeoJSON or GPX waypoint file, their metric and the
+/// unit of lengths under it
+/// GeoJSON and GPX coordinates are longitude and latitude: with `geo` they are
+/// measured with the haversine metric, otherwise projected to km.
+fn read_points(path: &Path, geo: bool) -> (Vec<Point>, Metric, &'static str) {
+    let content = std::fs::read_to_string(path).unwrap_or_else(|e| fail!("cannot read {}: {}", path.display(), e));
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    match extension.as_str() {
+        "geojson" | "json" | "gpx" => {
+            let points = if extension == "gpx" { geo::parse_gpx(&content) } else { geo::parse_geojson(&content) };
+            let points = points.unwrap_or_else(|e| fail!("cannot parse {}: {}", path.display(), e));
+            if geo {
+                (points, Metric::Haversine, " km")
+            } else {
+                (geo::project(&points), Metric::Euclidean, " km")
+            }
+        }
+        _ => {
+            let points = tsplib::parse_tsp(&content);
+            let points = points.unwrap_or_else(|e| fail!("cannot parse {}: {}", path.display(), e));
+            (points, tsplib::parse_metric(&content), "")
+        }
+    }
+}
+
+/// Run the `improve` subcommand: read points and a tour over them, polish the
+/// tour with the `--algo` improvers in turn and print it, with its length before and after
+/// The tour is a TSPLIB `.tour`, a Concorde `.sol`, or 0-based indices as `solve` prints them.
+/// Improvers are given as pipeline stages, e.g. `or-opt > two-opt(max_iterations=50)`.
+fn run_improve(args: &[String], format: OutputFormat) {
+    let usage = "usage: improve --input <file.tsp|file.geojson|file.gpx> --tour <file.tour|file.sol|file> \
+                 [--algo <improver[(name=value,...)]> > ...] [--geo] [--matrix <file>] [--script <file.rhai>]";
+    let value_of = |flag: &str| {
+        args.iter().position(|a| a == flag).map(|pos| args.get(pos + 1).unwrap_or_else(|| fail!("{}", usage)))
+    };
+    let path = Path::new(value_of("--input").unwrap_or_else(|| fail!("{}", usage)));
+    let tour_path = Path::new(value_of("--tour").unwrap_or_else(|| fail!("{}", usage)));
+    let spec = value_of("--algo").map_or("two-opt", String::as_str);
+    let steps = pipeline::parse_improvers(spec).unwrap_or_else(|e| fail!("{}: {}; {}", spec, e, usage));
 
-    // Final verification
-    let grid_size = 40.max((best_n as f64).sqrt() as usize * 2);
-    let points = generate_normalized_points(best_n, grid_size, 12345);
-    let graph = if needs_graph {
-        create_distance_matrix(&points)
+    let script = value_of("--script").map(|file| read_script(Path::new(file)));
+    let points = read_points(path, args.iter().any(|a| a == "--geo"));
+    let (instance, unit) = open_instance(path, points, value_of("--matrix"), script.as_ref());
+    let content =
+        std::fs::read_to_string(tour_path).unwrap_or_else(|e| fail!("cannot read {}: {}", tour_path.display(), e));
+    let tour = if tour_path.extension().is_some_and(|e| e.eq_ignore_ascii_case("sol")) {
+        tsplib::parse_sol(&content)
+    } else if content.contains("TOUR_SECTION") {
+        tsplib::parse_tour(&content)
     } else {
-        vec![]
+        content.split_whitespace().map(|id| id.parse().map_err(|_| format!("invalid city index {}", id))).collect()
     };
-    let (_, final_time) = measure_time(|| run_fn(&points, &graph, best_n));
+    let tour = tour.unwrap_or_else(|e| fail!("cannot parse {}: {}", tour_path.display(), e));
+    validate_tour(&tour, instance.len())
+        .unwrap_or_else(|e| fail!("{} is not a tour of {}: {}", tour_path.display(), path.display(), e));
+    check_edges(&instance, &tour, &tour_path.display().to_string());
 
-    println!("  RESULT: max n={} in {:.2}ms", best_n, final_time.as_secs_f64() * 1000.0);
+    let before = instance.tour_length(&tour);
+    log!("Initial tour: length {:.3}{}", before, unit);
+    let mut tour = tour;
+    let mut total = Duration::ZERO;
+    for step in &steps {
+        let (improved, time) = measure_time(|| step.run(&instance, &tour));
+        tour = improved.unwrap_or_else(|e| fail!("{} failed: {}", step, e));
+        total += time;
+        let length = instance.tour_length(&tour);
+        log!("  {:<10} length {:.3}{} in {:.2}ms", step.to_string(), length, unit, time.as_secs_f64() * 1000.0);
+    }
+    if script.is_some() {
+        let (improved, time) = measure_time(|| apply_penalty(script.as_ref(), &instance, tour));
+        tour = improved;
+        total += time;
+    }
+    check_edges(&instance, &tour, spec);
+    let after = instance.tour_length(&tour);
+    let gain = if before > 0.0 { (before - after) / before * 100.0 } else { 0.0 };
+    log!("Improved from {:.3}{} to {:.3}{} ({:.2}% shorter)", before, unit, after, unit, gain);
 
-    BenchmarkResult {
-        name: name.to_string(),
-        max_n: best_n,
-        time_ms: final_time.as_secs_f64() * 1000.0,
-        error: None,
+    let order: Vec<String> = tour.iter().map(usize::to_string).collect();
+    match format {
+        OutputFormat::Json => println!(
+            "{{ \"improvers\": [{}], \"before\": {:.6}, \"after\": {:.6}, \"timeMs\": {:.3}, \"tour\": [{}] }}",
+            steps.iter().map(|step| json_string(&step.to_string())).collect::<Vec<_>>().join(", "),
+            before,
+            after,
+            total.as_secs_f64() * 1000.0,
+            order.join(", ")
+        ),
+        _ => println!("{}", order.join(" ")),
+    }
+}
+
+/// The instance `solve` and `improve` optimize, and the unit of its lengths:
+/// `points` from `path` under their metric, or with the costs of a `--matrix`
+/// file or of a `--script`'s `edge_cost`
+fn open_instance(
+    path: &Path,
+    (points, metric, unit): (Vec<Point>, Metric, &'static str),
+    matrix: Option<&String>,
+    script: Option<&Script>,
+) -> (ProblemInstance, &'static str) {
+    if let Some(script) = script.filter(|script| script.has_edge_cost()) {
+        if matrix.is_some() {
+            fail!("--matrix and the script's edge_cost both give the costs; drop one");
+        }
+        let costs = script.costs(&points, metric).unwrap_or_else(|e| fail!("edge_cost failed: {}", e));
+        let instance = ProblemInstance::from_provider(points, &costs);
+        return (instance.unwrap_or_else(|e| fail!("invalid scripted costs: {}", e)), "");
+    }
+    match matrix.map(Path::new) {
+        Some(costs) => {
+            let provider = provider::open(costs).unwrap_or_else(|e| fail!("cannot read {}: {}", costs.display(), e));
+            let instance = ProblemInstance::from_provider(points, &*provider);
+            (instance.unwrap_or_else(|e| fail!("invalid matrix {}: {}", costs.display(), e)), "")
+        }
+        None => {
+            let instance = ProblemInstance::new(points, metric);
+            (instance.unwrap_or_else(|e| fail!("invalid instance {}: {}", path.display(), e)), unit)
+        }
+    }
+}
+
+/// Compile the objective script at `path`
+fn read_script(path: &Path) -> Script {
+    let source = std::fs::read_to_string(path).unwrap_or_else(|e| fail!("cannot read {}: {}", path.display(), e));
+    Script::compile(&source).unwrap_or_else(|e| fail!("invalid script {}: {}", path.display(), e))
+}
+
+/// 2-opt sweeps `apply_penalty` runs at most
+const PENALTY_ITERATIONS: usize = 100;
+
+/// Polish `tour` by 2-opt on its length plus the script's `tour_penalty`, if
+/// there is a script and it defines one
+fn apply_penalty(script: Option<&Script>, instance: &ProblemInstance, tour: Vec<usize>) -> Vec<usize> {
+    let Some(script) = script.filter(|script| script.has_tour_penalty()) else {
+        return tour;
+    };
+    let tour = script.improve(&tour, instance.matrix(), PENALTY_ITERATIONS);
+    let tour = tour.unwrap_or_else(|e| fail!("tour_penalty failed: {}", e));
+    let penalty = script.penalty(&tour).unwrap_or_else(|e| fail!("tour_penalty failed: {}", e));
+    log!("Tour penalty {:.3}, objective {:.3}", penalty, instance.tour_length(&tour) + penalty);
+    tour
+}
+
+/// Rework `tour` so that no edge is longer than `max_edge`: a tour built
+/// within the limit and improved by 2-opt, which never adds a forbidden edge,
+/// or if none is found, simulated annealing on the penalized matrix from `tour`
+fn limit_edges(instance: &ProblemInstance, tour: Vec<usize>, max_edge: f64) -> Vec<usize> {
+    let graph = instance.matrix();
+    if edge_limit::violations(&tour, graph, max_edge) == 0 {
+        return tour;
+    }
+    let limited = edge_limit::limited_matrix(graph, max_edge).unwrap_or_else(|e| fail!("--max-edge: {}", e));
+    let reworked = match edge_limit::generate_tour(graph, max_edge) {
+        Ok(within) => two_opt::improve(&within, &limited, 1_000),
+        Err(_) => {
+            log!("No tour within the limit found; annealing with a penalty on longer edges");
+            // Temperatures on the scale of an average edge of the tour
+            let temperature = instance.tour_length(&tour) / tour.len() as f64;
+            let penalty = edge_limit::default_penalty(graph);
+            edge_limit::optimize_annealing(graph, max_edge, penalty, &tour, 200_000, temperature.max(1e-9), 0.99995)
+        }
+    };
+    reworked.unwrap_or_else(|e| fail!("--max-edge: {}", e))
+}
+
+/// Algorithm `tsp-art` builds the initial tour with unless `--algo` is given;
+/// it needs no distance matrix, so stipplings of tens of thousands of points fit
+const TSP_ART_ALGORITHM: &str = "multilevel";
+
+/// Run the `tsp-art` subcommand: read stipple points, tour them with a
+/// constructor followed by any improvers, and draw the tour as one stroke
+/// A `.tsp` file is read as TSPLIB with y up; any other file holds one `x y`
+/// or `x,y` pair per line in image coordinates, y down, as stipplers write them.
+fn run_tsp_art(args: &[String]) {
+    let usage = "usage: tsp-art --input <points> --output <file.svg> [--algo <key|pipeline>] \
+                 [--improve <improver[(name=value,...)]> > ...] [--width <px>] [--stroke-width <px>] \
+                 [--stroke <colour>] [--curved]";
+    let value_of = |flag: &str| {
+        args.iter().position(|a| a == flag).map(|pos| args.get(pos + 1).unwrap_or_else(|| fail!("{}", usage)))
+    };
+    let number = |flag: &str, default: f64| {
+        value_of(flag).map_or(default, |value| value.parse().unwrap_or_else(|_| fail!("invalid {}: {}", flag, value)))
+    };
+    let path = Path::new(value_of("--input").unwrap_or_else(|| fail!("{}", usage)));
+    let output = value_of("--output").unwrap_or_else(|| fail!("{}", usage));
+    let spec = value_of("--algo").map_or(TSP_ART_ALGORITHM, String::as_str);
+    let mut pipeline = pipeline::parse(spec).unwrap_or_else(|e| fail!("{}: {}; see list-algorithms", spec, e));
+    if let Some(spec) = value_of("--improve") {
+        let steps = pipeline::parse_improvers(spec).unwrap_or_else(|e| fail!("{}: {}; {}", spec, e, usage));
+        pipeline.steps.extend(steps);
+    }
+    let content = std::fs::read_to_string(path).unwrap_or_else(|e| fail!("cannot read {}: {}", path.display(), e));
+
+    let is_tsplib = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("tsp"));
+    let points = if is_tsplib { tsplib::parse_tsp(&content) } else { parse_xy(&content) };
+    let points = points.unwrap_or_else(|e| fail!("cannot parse {}: {}", path.display(), e));
+    if let Some(max) = pipeline.max_n().filter(|&max| points.len() > max) {
+        fail!("{} handles at most {} points, not {}", pipeline.name(), max, points.len());
+    }
+    let instance = ProblemInstance::new(points, Metric::Euclidean)
+        .unwrap_or_else(|e| fail!("invalid instance {}: {}", path.display(), e));
+
+    let (tour, time) = measure_time(|| pipeline.solve(&instance));
+    let tour = tour.unwrap_or_else(|e| fail!("{} failed: {}", pipeline, e));
+    let length = calculate_tour_length_points(&tour, instance.points(), &Metric::Euclidean);
+    let ms = time.as_secs_f64() * 1000.0;
+    log!("{} on {} points: length {:.3} in {:.2}ms", pipeline, instance.len(), length, ms);
+
+    let defaults = render::SvgStyle::default();
+    let style = render::SvgStyle {
+        width: number("--width", defaults.width),
+        stroke_width: number("--stroke-width", defaults.stroke_width),
+        stroke: value_of("--stroke").cloned().unwrap_or(defaults.stroke.clone()),
+        curved: args.iter().any(|a| a == "--curved"),
+        y_up: is_tsplib,
+        ..defaults
+    };
+    std::fs::write(output, render::svg(instance.points(), &tour, &style))
+        .unwrap_or_else(|e| fail!("cannot write {}: {}", output, e));
+    log!("Wrote single-line drawing to {}", output);
+}
+
+/// Points from one `x y` or `x,y` pair per line; blank lines and lines
+/// starting with `#` are skipped
+fn parse_xy(content: &str) -> Result<Vec<Point>, String> {
+    let mut points = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(|c: char| c == ',' || c.is_whitespace()).filter(|f| !f.is_empty()).collect();
+        let coordinate = |field: Option<&&str>| field.and_then(|f| f.parse::<f64>().ok());
+        match (coordinate(fields.first()), coordinate(fields.get(1))) {
+            (Some(x), Some(y)) => points.push(Point { x, y, angle: 0.0, id: points.len() }),
+            _ => return Err(format!("line {}: expected two coordinates, found {:?}", number + 1, line)),
+        }
+    }
+    Ok(points)
+}
+
+/// Run every heuristic once on a TSPLIB instance and report tour lengths,
+/// plus the gap to the known optimum when `<instance>.opt.tour` is present
+fn compare_instance(
+    path: &Path,
+    matrix_cache: Option<&Path>,
+    pareto_svg: Option<&Path>,
+    pipelines: &[Pipeline],
+    format: OutputFormat,
+) {
+    let content = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| fail!("cannot read {}: {}", path.display(), e));
+    let points = tsplib::parse_tsp(&content)
+        .unwrap_or_else(|e| fail!("cannot parse {}: {}", path.display(), e));
+    // EUC_2D instances are measured with TSPLIB's rounding so lengths are
+    // comparable with published optima
+    let metric = tsplib::parse_metric(&content);
+    let instance = match matrix_cache {
+        Some(cache) => {
+            let (instance, cached) = matrix_cache::load_or_build(points.clone(), metric, cache)
+                .unwrap_or_else(|e| fail!("cannot use matrix cache {}: {}", cache.display(), e));
+            if cached {
+                log!("Loaded distance matrix from {}", cache.display());
+            } else {
+                log!("Wrote distance matrix to {}", cache.display());
+            }
+            instance
+        }
+        None => ProblemInstance::new(points.clone(), metric)
+            .unwrap_or_else(|e| fail!("invalid instance {}: {}", path.display(), e)),
+    };
+    // Build the shared matrix up front so no heuristic's time includes it
+    instance.matrix();
+    // Sweep-based constructions expect points centred in the unit square, while
+    // the solvers compare, and lengths are reported in, the instance's metric
+    let instance = instance.with_points(normalize_points(&points)).expect("normalized points are finite");
+
+    let optimal = match tsplib::load_optimal_tour(path) {
+        Some(Ok(tour)) if tour.len() == points.len() => Some(instance.tour_length(&tour)),
+        Some(Ok(tour)) => {
+            log!("Ignoring optimal tour with {} cities (instance has {})", tour.len(), points.len());
+            None
+        }
+        Some(Err(e)) => {
+            log!("Ignoring unreadable optimal tour: {}", e);
+            None
+        }
+        None => None,
+    };
+
+    log!("Instance {} ({} cities)", path.display(), points.len());
+    if let Some(length) = optimal {
+        log!("Known optimal tour length: {:.2}", length);
+    }
+    log!("\n{:<52} | {:>12} | {:>8} | {:>10}", "Algorithm", "Length", "Gap %", "Time (ms)");
+    log!("{}", "-".repeat(90));
+
+    let mut measurements = Vec::new();
+    // Result rows as JSON objects and as Markdown table rows
+    let mut rows = Vec::new();
+    let mut markdown_rows = Vec::new();
+    // Every registry algorithm, then the `--pipeline`s; exact methods only run
+    // on instances small enough for them
+    let registered =
+        registry::all().into_iter().map(|algorithm| Pipeline { start: Start::Algorithm(algorithm), steps: Vec::new() });
+    let runs = registered.chain(pipelines.iter().cloned()).filter(|p| p.max_n().is_none_or(|max| points.len() <= max));
+    for pipeline in runs {
+        let name = &pipeline.name();
+        let (tour, time) = measure_time(|| pipeline.solve(&instance));
+        let tour = match tour {
+            Ok(tour) => tour,
+            Err(error) => {
+                log!("{:<52} | error: {}", name, error);
+                let error = json_string(&error.to_string());
+                rows.push(format!("{{ \"name\": {}, \"error\": {} }}", json_string(name), error));
+                markdown_rows.push(format!("| {} | error | | |", markdown_cell(name)));
+                continue;
+            }
+        };
+        let length = instance.tour_length(&tour);
+        let gap = optimal
+            .map(|opt| format!("{:.2}", (length - opt) / opt * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+        log!(
+            "{:<52} | {:>12.2} | {:>8} | {:>10.2}",
+            name,
+            length,
+            gap,
+            time.as_secs_f64() * 1000.0
+        );
+        if PREVIEW.load(Ordering::Relaxed) {
+            log!("{}", render::ascii(&points, &tour, PREVIEW_COLUMNS, PREVIEW_ROWS));
+        }
+        let gap_json = optimal.map_or("null".to_string(), |opt| format!("{:.4}", (length - opt) / opt * 100.0));
+        rows.push(format!(
+            "{{ \"name\": {}, \"length\": {:.4}, \"gapPercent\": {}, \"timeMs\": {:.4} }}",
+            json_string(name), length, gap_json, time.as_secs_f64() * 1000.0
+        ));
+        markdown_rows.push(format!(
+            "| {} | {:.2} | {} | {:.2} |",
+            markdown_cell(name), length, gap, time.as_secs_f64() * 1000.0
+        ));
+        measurements.push(Measurement { name: name.to_string(), time_ms: time.as_secs_f64() * 1000.0, length });
+    }
+
+    let front = pareto::frontier(&measurements);
+    if format == OutputFormat::Markdown {
+        println!("| Algorithm | Length | Gap % | Time (ms) |");
+        println!("| --- | ---: | ---: | ---: |");
+        for row in &markdown_rows {
+            println!("{}", row);
+        }
+    }
+    if format == OutputFormat::Json {
+        println!(
+            "{{ \"instance\": {}, \"cities\": {}, \"optimal\": {},\n\"results\": [\n  {}\n],\n\"frontier\": {} }}",
+            json_string(&path.display().to_string()),
+            points.len(),
+            optimal.map_or("null".to_string(), |length| format!("{:.4}", length)),
+            rows.join(",\n  "),
+            pareto::to_json(&measurements, &front)
+        );
+    } else {
+        log!("\nPareto frontier (time vs length):");
+        log!("{}", pareto::to_json(&measurements, &front));
+    }
+    if let Some(svg) = pareto_svg {
+        std::fs::write(svg, pareto::to_svg(&measurements, &front))
+            .unwrap_or_else(|e| fail!("cannot write {}: {}", svg.display(), e));
+        log!("Wrote Pareto chart to {}", svg.display());
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    if let Some(pos) = args.iter().position(|a| a == "--deterministic") {
+        args.remove(pos);
+        determinism::set_deterministic(true);
+    }
+    if let Some(pos) = args.iter().position(|a| a == "--time-source") {
+        args.remove(pos);
+        let source = args.get(pos).and_then(|name| TimeSource::from_name(name));
+        let source = source.unwrap_or_else(|| fail!("usage: --time-source <wall|cpu>"));
+        if !source.is_available() {
+            fail!("{} time is not available on this platform", source.name());
+        }
+        clock::set_time_source(source);
+        args.remove(pos);
+    }
+    if let Some(pos) = args.iter().position(|a| a == "--result-cache") {
+        args.remove(pos);
+        let dir = Path::new(args.get(pos).unwrap_or_else(|| fail!("usage: --result-cache <dir>")));
+        let cache = ResultCache::open(dir).unwrap_or_else(|e| fail!("cannot open {}: {}", dir.display(), e));
+        RESULT_CACHE.set(cache).unwrap();
+        args.remove(pos);
+    }
+    if let Some(pos) = args.iter().position(|a| a == "--rng") {
+        args.remove(pos);
+        let kind = args.get(pos).and_then(|name| RngKind::from_name(name));
+        determinism::set_rng_kind(kind.unwrap_or_else(|| fail!("usage: --rng <lcg|pcg64|xoshiro256>")));
+        args.remove(pos);
+    }
+    let matrix_cache = args.iter().position(|a| a == "--matrix-cache").map(|pos| {
+        args.remove(pos);
+        if pos >= args.len() {
+            fail!("usage: --matrix-cache <file>");
+        }
+        args.remove(pos)
+    });
+    let pareto_svg = args.iter().position(|a| a == "--pareto-svg").map(|pos| {
+        args.remove(pos);
+        if pos >= args.len() {
+            fail!("usage: --pareto-svg <file>");
+        }
+        args.remove(pos)
+    });
+    let quality_weight: Option<f64> = args.iter().position(|a| a == "--score").map(|pos| {
+        args.remove(pos);
+        let weight = args.get(pos).and_then(|s| s.parse().ok()).filter(|w| (0.0..=1.0).contains(w));
+        let weight = weight.unwrap_or_else(|| fail!("usage: --score <quality_weight between 0 and 1>"));
+        args.remove(pos);
+        weight
+    });
+    if let Some(pos) = args.iter().position(|a| a == "--quiet") {
+        args.remove(pos);
+        QUIET.store(true, Ordering::Relaxed);
+    }
+    if let Some(pos) = args.iter().position(|a| a == "--preview") {
+        args.remove(pos);
+        PREVIEW.store(true, Ordering::Relaxed);
+    }
+    let tui = args.iter().position(|a| a == "--tui").map(|pos| args.remove(pos)).is_some();
+    let format = match args.iter().position(|a| a == "--format") {
+        Some(pos) => {
+            args.remove(pos);
+            let format = if pos < args.len() { args.remove(pos) } else { String::new() };
+            match format.as_str() {
+                "text" => OutputFormat::Text,
+                "json" => OutputFormat::Json,
+                "markdown" => OutputFormat::Markdown,
+                _ => fail!("usage: --format <text|json|markdown>"),
+            }
+        }
+        None => OutputFormat::Text,
+    };
+    if args.get(1).map(String::as_str) == Some("list-algorithms") {
+        list_algorithms(format == OutputFormat::Json || args.iter().any(|a| a == "--json"));
+        return;
+    }
+    if let Some(pos) = args.iter().position(|a| a == "--seed" || a == "--seeds") {
+        args.remove(pos);
+        let seeds: Option<Vec<u64>> = args
+            .get(pos)
+            .and_then(|list| list.split(',').map(|seed| seed.trim().parse().ok()).collect());
+        let seeds = seeds.filter(|seeds| !seeds.is_empty());
+        POINT_SEEDS.set(seeds.unwrap_or_else(|| fail!("usage: --seed <n> or --seeds <n,n,...>"))).unwrap();
+        args.remove(pos);
+    }
+    if args.get(1).map(String::as_str) == Some("export-site-data") {
+        let data = site_data::export(point_seeds()).unwrap_or_else(|e| fail!("export failed: {}", e));
+        match args.get(2) {
+            Some(path) => {
+                std::fs::write(path, data).unwrap_or_else(|e| fail!("cannot write {}: {}", path, e));
+                log!("Wrote site data to {}", path);
+            }
+            None => print!("{}", data),
+        }
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("golden") {
+        let path = golden::default_path();
+        if args.iter().any(|a| a == "--record") {
+            let count = golden::record(&path).unwrap_or_else(|e| fail!("cannot record {}: {}", path.display(), e));
+            log!("Recorded {} golden tours to {}", count, path.display());
+            return;
+        }
+        let mismatches = golden::verify(&path).unwrap_or_else(|e| fail!("cannot verify {}: {}", path.display(), e));
+        for mismatch in &mismatches {
+            log!("CHANGED {}", mismatch);
+        }
+        if !mismatches.is_empty() {
+            std::process::exit(1);
+        }
+        log!("All golden tours reproduced");
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("parity") {
+        let parse = |n: &String| n.parse().unwrap_or_else(|_| fail!("usage: parity [n ...]"));
+        let sizes: Vec<usize> = args[2..].iter().map(parse).collect();
+        if !check_parity(if sizes.is_empty() { &[10, 50, 200] } else { &sizes }) {
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("tune") {
+        let strategy = match args.iter().position(|a| a == "--halving") {
+            Some(pos) => {
+                args.remove(pos);
+                tune::Strategy::SuccessiveHalving
+            }
+            None => tune::Strategy::Random,
+        };
+        let usage = "usage: tune <simulated-annealing|genetic> [seconds] [--halving]";
+        let seconds: u64 = args.get(3).map_or(10, |s| s.parse().unwrap_or_else(|_| fail!("{}", usage)));
+        let budget = Duration::from_secs(seconds);
+        match args.get(2).map(String::as_str) {
+            Some("simulated-annealing") => run_tuner(&tune::AnnealingSpace::default(), strategy, budget),
+            Some("genetic") => run_tuner(&tune::GeneticSpace::default(), strategy, budget),
+            _ => fail!("{}", usage),
+        }
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("sweep") {
+        run_sweep(&args);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("solve") {
+        run_solve(&args, format);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("improve") {
+        run_improve(&args, format);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("tsp-art") {
+        run_tsp_art(&args);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("representations") {
+        run_representations(&args, format);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("throughput") {
+        run_throughput(&args, format);
+        return;
+    }
+    if let Some(pos) = args.iter().position(|a| a == "--layout") {
+        args.remove(pos);
+        let layout = args.get(pos).and_then(|name| Layout::from_name(name));
+        let usage = "usage: --layout <disc|circle|line|two-clusters|spiral|convex>";
+        LAYOUT.set(layout.unwrap_or_else(|| fail!("{}", usage))).unwrap();
+        args.remove(pos);
+    }
+    if let Some(pos) = args.iter().position(|a| a == "--points-version") {
+        args.remove(pos);
+        let version = match args.get(pos).map(String::as_str) {
+            Some("1") => PointsVersion::V1,
+            Some("2") => PointsVersion::V2,
+            _ => fail!("usage: --points-version <1|2>"),
+        };
+        POINTS_VERSION.set(version).unwrap();
+        args.remove(pos);
+    }
+    if let Some(pos) = args.iter().position(|a| a == "--max-memory") {
+        args.remove(pos);
+        let limit = args.get(pos).and_then(|s| parse_size(s));
+        MAX_MEMORY.set(limit.unwrap_or_else(|| fail!("usage: --max-memory <bytes, or with a K/M/G suffix>"))).unwrap();
+        args.remove(pos);
+    }
+    let mut bounds = Vec::new();
+    while let Some(pos) = args.iter().position(|a| a == "--bounds") {
+        args.remove(pos);
+        let entry = args.get(pos).and_then(|s| Bounds::parse(s));
+        let usage = "usage: --bounds <algorithm>=<min_n>..<max_n>, either end optional";
+        bounds.push(entry.unwrap_or_else(|| fail!("{}", usage)));
+        args.remove(pos);
+    }
+    BOUNDS.set(bounds).ok();
+    let mut pipelines = Vec::new();
+    while let Some(pos) = args.iter().position(|a| a == "--pipeline") {
+        args.remove(pos);
+        let usage = "usage: --pipeline <construction > improver(name=value, ...) > ...>";
+        let spec = args.get(pos).unwrap_or_else(|| fail!("{}", usage));
+        pipelines.push(pipeline::parse(spec).unwrap_or_else(|e| fail!("{}: {}", spec, e)));
+        args.remove(pos);
+    }
+    if args.get(1).map(String::as_str) == Some("--instance") {
+        let path = args.get(2).unwrap_or_else(|| fail!("usage: benchmark --instance <file.tsp>"));
+        compare_instance(
+            Path::new(path),
+            matrix_cache.as_deref().map(Path::new),
+            pareto_svg.as_deref().map(Path::new),
+            &pipelines,
+            format,
+        );
+        return;
+    }
     let timeout_seconds: u64 = args.get(1)
         .and_then(|s| s.parse().ok())
         .unwrap_or(30);
 
     let timeout = Duration::from_secs(timeout_seconds);
 
-    println!("TSP Algorithms Benchmark (Rust)");
-    println!("Finding maximum points for each algorithm within {} seconds timeout\n", timeout_seconds);
-    println!("{}", "=".repeat(80));
+    log!("TSP Algorithms Benchmark (Rust)");
+    log!("Finding maximum points for each algorithm within {} seconds timeout", timeout_seconds);
+    if clock::time_source() == TimeSource::Cpu {
+        log!("Times are CPU time summed over all threads, so parallel runs reach the timeout sooner");
+    }
+    let seeds: Vec<String> = point_seeds().iter().map(u64::to_string).collect();
+    match seeds.len() {
+        1 => log!("Point seed: {}\n", seeds[0]),
+        _ => log!("Point seeds: {} (times and tour lengths are means over them)\n", seeds.join(", ")),
+    }
+    log!("{}", "=".repeat(80));
+    // Algorithms added with `registry::register` by a build that links plugins
+    let plugins = registry::registered();
+    // Every benchmarked algorithm, queued so the dashboard knows how many there are
+    let mut runs: Vec<Box<dyn FnOnce() -> BenchmarkResult + '_>> = Vec::new();
 
-    let mut results: Vec<BenchmarkResult> = Vec::new();
+    // BruteForce and HeldKarp - both exact searches fix the first city of every tour
+    {
+        const START_CITY: usize = brute_force::START_CITY;
 
-    // BruteForce (exact) - limit to 12 since it's O(n!) which grows extremely fast
-    results.push(find_max_n(
-        "BruteForce (bruteForceExact)",
-        |_points, graph, n| {
-            brute_force::brute_force_exact(graph, n).0
-        },
-        4,
-        12,
-        timeout,
-        true,
-    ));
-
-    // HeldKarp - limit to 20 since it grows exponentially O(2^n * n^2)
-    // n=21 takes ~15s, n=22 takes ~45s which is too long for most timeouts
-    results.push(find_max_n(
-        "BruteForce (heldKarp)",
-        |_points, graph, n| {
-            brute_force::held_karp(graph, n).0
-        },
-        4,
-        20,
-        timeout,
-        true,
-    ));
+        // BruteForce (exact) - limit to 12 since it's O(n!) which grows extremely fast
+        runs.push(Box::new(move || find_max_n(
+            "BruteForce (bruteForceExact)",
+            &params!(START_CITY),
+            |instance| {
+                let n = instance.len();
+                let (tour, _) = brute_force::brute_force_exact_cancellable(instance.matrix(), n, &probe_cancelled)?;
+                Ok(tour)
+            },
+            4,
+            12,
+            timeout,
+            true,
+        )));
+
+        // HeldKarp - limit to the largest size it accepts, since it grows exponentially O(2^n * n^2)
+        // Layers run in parallel on flat arrays; n=23 already needs ~0.8GB of DP tables
+        runs.push(Box::new(move || find_max_n_with_memory(
+            "BruteForce (heldKarp)",
+            &params!(START_CITY),
+            |instance| {
+                let n = instance.len();
+                let (tour, _) = brute_force::held_karp_cancellable(instance.matrix(), n, &probe_cancelled)?;
+                Ok(tour)
+            },
+            4,
+            brute_force::HELD_KARP_MAX_N,
+            timeout,
+            true,
+            brute_force::held_karp_memory,
+        )));
+    }
+
+    // RandomTour - seeded shuffle, the worst-case quality baseline
+    {
+        const SEED: u64 = 12345;
+        runs.push(Box::new(move || find_max_n(
+            "RandomTour (baseline)",
+            &params!(SEED),
+            |instance| {
+                Ok(random_tour::generate_tour(instance.len(), SEED))
+            },
+            50_000,
+            500_000,
+            timeout,
+            false,
+        )));
+    }
+
+    // BranchAndBound - exact with MST/1-tree bounds, grows exponentially in the worst case
+    {
+        const TIME_LIMIT: Option<Duration> = None;
+        runs.push(Box::new(move || find_max_n(
+            "BranchAndBound (1-tree bound)",
+            &params!(TIME_LIMIT),
+            |instance| {
+                branch_and_bound::solve(instance.matrix(), TIME_LIMIT).map(|(tour, ..)| tour)
+            },
+            4,
+            40,
+            timeout,
+            true,
+        )));
+    }
 
     // AngularSort - O(n log n), very fast, doesn't need distance matrix
-    results.push(find_max_n(
+    runs.push(Box::new(move || find_max_n(
         "AngularSort",
-        |points, _graph, _n| {
-            angular_sort::generate_tour(points)
+        "",
+        |instance| {
+            angular_sort::generate_tour(instance.points())
         },
         50_000,
         500_000,
         timeout,
         false,
-    ));
+    )));
 
-    // SonarVisit - O(n), very fast, doesn't need distance matrix
-    results.push(find_max_n(
-        "SonarVisit",
-        |points, _graph, _n| {
-            sonar_visit::generate_tour(points, 40)
-        },
+    // AngularSort around the centroid of each non-empty cell of an 8x8 grid
+    {
+        const GRID_SIZE: usize = 8;
+        runs.push(Box::new(move || find_max_n(
+            "AngularSort (multi-center, 8x8 cells)",
+            &params!(GRID_SIZE),
+            |instance| angular_sort::generate_tour_multi_center(instance.points(), GRID_SIZE),
+            50_000,
+            500_000,
+            timeout,
+            false,
+        )));
+    }
+
+    // MortonSort - O(n log n), points in Z-order curve order, no distance matrix
+    runs.push(Box::new(move || find_max_n(
+        "MortonSort",
+        "",
+        |instance| space_filling::morton_sort(instance.points()),
         50_000,
         500_000,
         timeout,
         false,
-    ));
+    )));
 
-    // NearestNeighbor
-    results.push(find_max_n(
-        "NearestNeighbor",
-        |points, graph, _n| {
-            nearest_neighbor::generate_tour(points.len(), graph, 0)
-        },
-        10,
-        10_000,
+    // HilbertSort - O(n log n), points in Hilbert curve order, no distance matrix
+    runs.push(Box::new(move || find_max_n(
+        "HilbertSort",
+        "",
+        |instance| space_filling::hilbert_sort(instance.points()),
+        50_000,
+        500_000,
         timeout,
-        true,
-    ));
+        false,
+    )));
+
+    // SonarVisit - O(n), very fast, doesn't need distance matrix
+    {
+        const GRID_SIZE: usize = 40;
+        runs.push(Box::new(move || find_max_n(
+            "SonarVisit",
+            &params!(GRID_SIZE),
+            |instance| {
+                sonar_visit::generate_tour(instance.points(), GRID_SIZE)
+            },
+            50_000,
+            500_000,
+            timeout,
+            false,
+        )));
+    }
+
+    // NearestNeighbor
+    {
+        const START_CITY: usize = 0;
+        runs.push(Box::new(move || find_max_n(
+            "NearestNeighbor",
+            &params!(START_CITY),
+            |instance| {
+                nearest_neighbor::generate_tour(instance.len(), instance.matrix(), START_CITY)
+            },
+            10,
+            10_000,
+            timeout,
+            true,
+        )));
+    }
+
+    // NearestNeighbor (grid) - no distance matrix, expanding ring search over cells
+    {
+        const START_CITY: usize = 0;
+        const POINTS_PER_CELL: usize = nearest_neighbor::POINTS_PER_CELL;
+        runs.push(Box::new(move || find_max_n(
+            "NearestNeighbor (grid)",
+            &params!(START_CITY, POINTS_PER_CELL),
+            |instance| {
+                nearest_neighbor::generate_tour_grid(instance.points(), START_CITY)
+            },
+            50_000,
+            500_000,
+            timeout,
+            false,
+        )));
+    }
+
+    // KarpPartition - exact solves on cells of 8 points, stitched together
+    {
+        const MAX_CELL_SIZE: usize = 8;
+        runs.push(Box::new(move || find_max_n(
+            "KarpPartition (cells of 8)",
+            &params!(MAX_CELL_SIZE),
+            |instance| {
+                karp_partition::generate_tour(instance.points(), MAX_CELL_SIZE)
+            },
+            1_000,
+            500_000,
+            timeout,
+            false,
+        )));
+    }
+
+    // MultiLevel - coarsen into super-nodes, tour the coarsest level, expand and refine
+    {
+        const NEIGHBORS: usize = 8;
+        runs.push(Box::new(move || find_max_n(
+            "MultiLevel (8 neighbors)",
+            &params!(NEIGHBORS),
+            |instance| {
+                multilevel::generate_tour(instance.points(), NEIGHBORS)
+            },
+            1_000,
+            2_000_000,
+            timeout,
+            false,
+        )));
+    }
+
+    // SectorPartition - angular sectors solved in parallel with NN + 2-opt, then stitched
+    {
+        const SECTORS: usize = 16;
+        const MAX_ITERATIONS: usize = 50;
+        runs.push(Box::new(move || find_max_n(
+            "SectorPartition (16 sectors, 2-opt)",
+            &params!(SECTORS, MAX_ITERATIONS),
+            |instance| {
+                sector_partition::generate_tour(instance.points(), SECTORS, MAX_ITERATIONS)
+            },
+            1_000,
+            500_000,
+            timeout,
+            false,
+        )));
+    }
+
+    // SparseKnn - greedy edge and 2-opt over the 8 nearest neighbors of each city, no matrix
+    {
+        const K: usize = 8;
+        const MAX_ITERATIONS: usize = 50;
+        runs.push(Box::new(move || find_max_n(
+            "SparseKnn (greedy edge, 2-opt, 8 neighbors)",
+            &params!(K, MAX_ITERATIONS),
+            |instance| {
+                sparse::generate_tour(instance.points(), K, MAX_ITERATIONS)
+            },
+            1_000,
+            500_000,
+            timeout,
+            false,
+        )));
+    }
+
+    // ClusterRoute - k-means clusters routed with NN + 2-opt, ordered by a centroid tour
+    {
+        const CLUSTERS: usize = 16;
+        const KMEANS_ITERATIONS: usize = 20;
+        const MAX_ITERATIONS: usize = 50;
+        runs.push(Box::new(move || find_max_n(
+            "ClusterRoute (k-means, 16 clusters, 2-opt)",
+            &params!(CLUSTERS, KMEANS_ITERATIONS, MAX_ITERATIONS),
+            |instance| {
+                cluster_route::generate_tour(instance.points(), CLUSTERS, KMEANS_ITERATIONS, |cluster| {
+                    cluster_route::nearest_neighbor_two_opt(cluster, MAX_ITERATIONS)
+                })
+            },
+            1_000,
+            500_000,
+            timeout,
+            false,
+        )));
+    }
+
+    // Quadtree - leaves in Hilbert order, nearest neighbor routing inside leaves
+    {
+        const LEAF_CAPACITY: usize = 8;
+        runs.push(Box::new(move || find_max_n(
+            "Quadtree (leaf capacity 8)",
+            &params!(LEAF_CAPACITY),
+            |instance| {
+                quadtree::generate_tour(instance.points(), LEAF_CAPACITY)
+            },
+            50_000,
+            500_000,
+            timeout,
+            false,
+        )));
+    }
 
     // GreedyEdge
-    results.push(find_max_n(
+    runs.push(Box::new(move || find_max_n(
         "GreedyEdge",
-        |points, graph, _n| {
-            greedy_edge::generate_tour(points.len(), graph)
+        "",
+        |instance| {
+            greedy_edge::generate_tour(instance.len(), instance.matrix())
         },
         10,
         5_000,
         timeout,
         true,
-    ));
+    )));
+
+    // GreedyEdge over each city's nearest neighbors, escalating k until the tour closes
+    {
+        const K: usize = 8;
+        runs.push(Box::new(move || find_max_n(
+            "GreedyEdge (8 nearest candidates)",
+            &params!(K),
+            |instance| greedy_edge::generate_tour_candidates(instance.points(), K),
+            10,
+            500_000,
+            timeout,
+            false,
+        )));
+    }
 
     // TwoOpt (with NearestNeighbor)
-    results.push(find_max_n(
-        "TwoOpt (with NearestNeighbor)",
-        |points, graph, _n| {
-            let initial = nearest_neighbor::generate_tour(points.len(), graph, 0);
-            two_opt::improve(&initial, graph, 100)
-        },
-        10,
-        3_000,
-        timeout,
-        true,
-    ));
+    {
+        const MAX_ITERATIONS: usize = 100;
+        runs.push(Box::new(move || find_max_n(
+            "TwoOpt (with NearestNeighbor)",
+            &params!(MAX_ITERATIONS),
+            |instance| {
+                let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+                construction_done();
+                two_opt::improve(&initial, instance.matrix(), MAX_ITERATIONS)
+            },
+            10,
+            3_000,
+            timeout,
+            true,
+        )));
+    }
+
+    // TwoOpt on a two-level doubly linked list (O(sqrt n) reversals)
+    {
+        const MAX_ITERATIONS: usize = 100;
+        runs.push(Box::new(move || find_max_n(
+            "TwoOpt (two-level list, with NearestNeighbor)",
+            &params!(MAX_ITERATIONS),
+            |instance| {
+                let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+                construction_done();
+                let mut list = tour::TwoLevelList::new(&initial);
+                two_opt::improve_tour(&mut list, instance.matrix(), MAX_ITERATIONS)?;
+                Ok(tour::Tour::to_vec(&list))
+            },
+            10,
+            3_000,
+            timeout,
+            true,
+        )));
+    }
+
+    // TwoOpt with all moves scored on the GPU, non-overlapping improving moves applied per sweep
+    #[cfg(feature = "gpu")]
+    {
+        const MAX_ITERATIONS: usize = 100;
+        let gpu = GpuTwoOpt::new();
+        runs.push(Box::new(move || find_max_n(
+            "TwoOpt (GPU, with NearestNeighbor)",
+            &params!(MAX_ITERATIONS),
+            move |instance| {
+                let gpu = gpu.as_ref().map_err(Clone::clone)?;
+                let initial = nearest_neighbor::generate_tour_grid(instance.points(), 0)?;
+                construction_done();
+                gpu.improve(instance.points(), &initial, MAX_ITERATIONS)
+            },
+            10,
+            100_000,
+            timeout,
+            false,
+        )));
+    }
+
+    // TwoHOpt (with NearestNeighbor) - 2-opt plus node insertion over 8 candidates
+    {
+        const CANDIDATES: usize = 8;
+        const MAX_ITERATIONS: usize = 100;
+        runs.push(Box::new(move || find_max_n(
+            "TwoHOpt (with NearestNeighbor, 8 candidates)",
+            &params!(CANDIDATES, MAX_ITERATIONS),
+            |instance| {
+                let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+                construction_done();
+                two_h_opt::improve(&initial, instance.matrix(), CANDIDATES, MAX_ITERATIONS)
+            },
+            10,
+            5_000,
+            timeout,
+            true,
+        )));
+    }
 
     // Zigzag (with AngularSort)
-    results.push(find_max_n(
+    runs.push(Box::new(move || find_max_n(
         "Zigzag (with AngularSort)",
-        |points, graph, _n| {
-            let initial = angular_sort::generate_tour(points);
-            zigzag::optimize(&initial, points, graph)
+        "",
+        |instance| {
+            let initial = angular_sort::generate_tour(instance.points())?;
+            construction_done();
+            zigzag::optimize(&initial, instance.points(), instance.matrix())
         },
         10,
         5_000,
         timeout,
         true,
-    ));
+    )));
+
+    // OrOne (with SonarVisit) - single-city reinsertion over 8 candidates
+    {
+        const GRID_SIZE: usize = 40;
+        const CANDIDATES: usize = 8;
+        const MAX_ITERATIONS: usize = 100;
+        runs.push(Box::new(move || find_max_n(
+            "OrOne (with SonarVisit, 8 candidates)",
+            &params!(GRID_SIZE, CANDIDATES, MAX_ITERATIONS),
+            |instance| {
+                let initial = sonar_visit::generate_tour(instance.points(), GRID_SIZE)?;
+                construction_done();
+                or_one::improve(&initial, instance.matrix(), CANDIDATES, MAX_ITERATIONS)
+            },
+            10,
+            5_000,
+            timeout,
+            true,
+        )));
+    }
 
     // SimulatedAnnealing
-    results.push(find_max_n(
-        "SimulatedAnnealing (with NearestNeighbor, 5000 iterations)",
-        |points, graph, _n| {
-            let initial = nearest_neighbor::generate_tour(points.len(), graph, 0);
-            simulated_annealing::optimize(graph, &initial, 5000, 1.0, 0.9995)
-        },
-        10,
-        5_000,
-        timeout,
-        true,
-    ));
+    {
+        const MAX_ITERATIONS: usize = 5000;
+        const INITIAL_TEMPERATURE: f64 = 1.0;
+        const COOLING_RATE: f64 = 0.9995;
+        runs.push(Box::new(move || find_max_n(
+            "SimulatedAnnealing (with NearestNeighbor, 5000 iterations)",
+            &params!(MAX_ITERATIONS, INITIAL_TEMPERATURE, COOLING_RATE),
+            |instance| {
+                let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+                construction_done();
+                let matrix = instance.matrix();
+                simulated_annealing::optimize(matrix, &initial, MAX_ITERATIONS, INITIAL_TEMPERATURE, COOLING_RATE)
+            },
+            10,
+            5_000,
+            timeout,
+            true,
+        )));
+    }
 
-    // GeneticAlgorithm
-    results.push(find_max_n(
-        "GeneticAlgorithm (pop=50, gen=100)",
-        |_points, graph, n| {
-            genetic::optimize(graph, n, 50, 100, 0.1)
-        },
-        10,
-        1_000,
-        timeout,
-        true,
-    ));
+    // SimulatedAnnealing with reversal, insertion and swap moves
+    {
+        const MAX_ITERATIONS: usize = 5000;
+        const INITIAL_TEMPERATURE: f64 = 1.0;
+        const COOLING_RATE: f64 = 0.9995;
+        const MOVES: [(MoveKind, f64); 3] =
+            [(MoveKind::Reversal, 0.6), (MoveKind::Insertion, 0.3), (MoveKind::Swap, 0.1)];
+        runs.push(Box::new(move || find_max_n(
+            "SimulatedAnnealing (mixed moves, 5000 iterations)",
+            &format!("{}, moves={}", params!(MAX_ITERATIONS, INITIAL_TEMPERATURE, COOLING_RATE), moves_text(&MOVES)),
+            |instance| {
+                let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+                construction_done();
+                let (matrix, iterations, temperature) = (instance.matrix(), MAX_ITERATIONS, INITIAL_TEMPERATURE);
+                let cooling = COOLING_RATE;
+                simulated_annealing::optimize_with_moves(matrix, &initial, iterations, temperature, cooling, &MOVES)
+            },
+            10,
+            5_000,
+            timeout,
+            true,
+        )));
+    }
+
+    // Parallel tempering - 4 SA chains on separate threads with replica exchange
+    {
+        const CHAINS: usize = 4;
+        const EXCHANGES: usize = 10;
+        const ITERATIONS_PER_EXCHANGE: usize = 500;
+        const MIN_TEMPERATURE: f64 = 0.001;
+        const MAX_TEMPERATURE: f64 = 1.0;
+        runs.push(Box::new(move || find_max_n(
+            "ParallelTempering (with NearestNeighbor, 4 chains)",
+            &params!(CHAINS, EXCHANGES, ITERATIONS_PER_EXCHANGE, MIN_TEMPERATURE, MAX_TEMPERATURE),
+            |instance| {
+                let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+                construction_done();
+                let (matrix, iterations) = (instance.matrix(), ITERATIONS_PER_EXCHANGE);
+                let (min, max) = (MIN_TEMPERATURE, MAX_TEMPERATURE);
+                simulated_annealing::parallel_tempering(matrix, &initial, CHAINS, EXCHANGES, iterations, min, max)
+            },
+            10,
+            5_000,
+            timeout,
+            true,
+        )));
+    }
+
+    // Cooperative SA - one chain per thread from different NN starts, sharing the best tour
+    {
+        const TIME_LIMIT_MS: u64 = 100;
+        const EXCHANGE_INTERVAL: usize = 1_000;
+        const TEMPERATURE: f64 = 1.0;
+        const COOLING_RATE: f64 = 0.9995;
+        let chains = std::thread::available_parallelism().map_or(4, |n| n.get());
+        runs.push(Box::new(move || find_max_n(
+            "CooperativeSA (with NearestNeighbor starts)",
+            &format!("chains={}, {}", chains, params!(TIME_LIMIT_MS, EXCHANGE_INTERVAL, TEMPERATURE, COOLING_RATE)),
+            move |instance| {
+                let n = instance.len();
+                let starts = (0..chains)
+                    .map(|k| nearest_neighbor::generate_tour(n, instance.matrix(), k * n / chains))
+                    .collect::<Result<Vec<_>, _>>()?;
+                construction_done();
+                let (matrix, time_limit) = (instance.matrix(), Duration::from_millis(TIME_LIMIT_MS));
+                let (interval, cooling) = (EXCHANGE_INTERVAL, COOLING_RATE);
+                simulated_annealing::cooperative(matrix, &starts, time_limit, interval, TEMPERATURE, cooling)
+            },
+            10,
+            5_000,
+            timeout,
+            true,
+        )));
+    }
+
+    // GeneticAlgorithm, stepped one generation at a time so an abandoned run ends early
+    {
+        const POPULATION_SIZE: usize = 50;
+        const GENERATIONS: usize = 100;
+        const MUTATION_RATE: f64 = 0.1;
+        runs.push(Box::new(move || find_max_n(
+            "GeneticAlgorithm (pop=50, gen=100)",
+            &params!(POPULATION_SIZE, GENERATIONS, MUTATION_RATE),
+            |instance| {
+                let (n, population) = (instance.len(), POPULATION_SIZE);
+                let mut solver = GeneticSolver::new(instance.matrix(), n, population, GENERATIONS, MUTATION_RATE)?;
+                while !solver.is_finished() && !probe_cancelled() {
+                    solver.step();
+                }
+                Ok(solver.tour().to_vec())
+            },
+            10,
+            1_000,
+            timeout,
+            true,
+        )));
+    }
+
+    // GeneticAlgorithm with the inver-over operator
+    {
+        const POPULATION_SIZE: usize = 50;
+        const GENERATIONS: usize = 100;
+        const RANDOM_INVERSION_RATE: f64 = 0.02;
+        runs.push(Box::new(move || find_max_n(
+            "GeneticAlgorithm (inver-over, pop=50, gen=100)",
+            &params!(POPULATION_SIZE, GENERATIONS, RANDOM_INVERSION_RATE),
+            |instance| {
+                let (n, rate) = (instance.len(), RANDOM_INVERSION_RATE);
+                genetic::optimize_inver_over(instance.matrix(), n, POPULATION_SIZE, GENERATIONS, rate)
+            },
+            10,
+            1_000,
+            timeout,
+            true,
+        )));
+    }
+
+    for algorithm in &plugins {
+        let params: Vec<String> = algorithm.params.iter().map(|p| format!("{}={}", p.name, p.default)).collect();
+        runs.push(Box::new(move || find_max_n(
+            algorithm.name,
+            &params.join(", "),
+            algorithm.solve,
+            10,
+            algorithm.max_n.unwrap_or(5_000),
+            timeout,
+            algorithm.needs_matrix,
+        )));
+    }
+
+    // Pipelines from `--pipeline`, timed like the hybrids above
+    for pipeline in pipelines.iter().cloned() {
+        runs.push(Box::new(move || find_max_n(
+            &pipeline.to_string(),
+            "",
+            move |instance| {
+                let initial = pipeline.construct(instance)?;
+                construction_done();
+                pipeline::improve(&pipeline.steps, instance, initial)
+            },
+            10,
+            5_000,
+            timeout,
+            true,
+        )));
+    }
+
+    if tui {
+        #[cfg(feature = "tui")]
+        {
+            let dashboard = Dashboard::start(runs.len());
+            let dashboard = dashboard.unwrap_or_else(|e| fail!("cannot draw the dashboard on this terminal: {}", e));
+            *DASHBOARD.lock().unwrap() = Some(dashboard);
+        }
+        #[cfg(not(feature = "tui"))]
+        fail!("--tui needs a build with `--features tui`");
+    }
+    let results: Vec<BenchmarkResult> = runs.into_iter().map(|run| run()).collect();
+    #[cfg(feature = "tui")]
+    if let Some(dashboard) = DASHBOARD.lock().unwrap().take() {
+        dashboard.finish().unwrap_or_else(|e| fail!("cannot restore the terminal: {}", e));
+    }
 
     // Summary
-    println!("\n{}", "=".repeat(80));
-    println!("\nSUMMARY (timeout: {}s)", timeout_seconds);
-    println!("{}", "=".repeat(80));
-    println!("\n{:<52} | {:>5} | {:>10}", "Algorithm", "Max N", "Time (ms)");
-    println!("{}", "-".repeat(80));
+    log!("\n{}", "=".repeat(80));
+    log!("\nSUMMARY (timeout: {}s)", timeout_seconds);
+    log!("{}", "=".repeat(80));
+    let scores = quality_weight.map(|weight| score_results(&results, weight));
+    match (&scores, quality_weight) {
+        (Some(scores), Some(weight)) => {
+            log!(
+                "\nRanked by score on {} points (quality weight {}, lower is better)",
+                PROBE_N, weight
+            );
+            log!(
+                "\n{:<52} | {:>5} | {:>8} | {:>10} | {:>6}",
+                "Algorithm", "Max N", "Gap %", "Probe (ms)", "Score"
+            );
+            log!("{}", "-".repeat(96));
+
+            let mut order: Vec<usize> = (0..results.len()).collect();
+            // Unscored algorithms (errors, or too slow for the probe) go last
+            order.sort_by(|&a, &b| {
+                let key = |i: usize| scores[i].map_or(f64::INFINITY, |(_, score)| score);
+                key(a).total_cmp(&key(b)).then(results[b].max_n.cmp(&results[a].max_n))
+            });
+            for i in order {
+                let r = &results[i];
+                match (&r.error, scores[i], r.probe) {
+                    (Some(error), ..) => log!("{:<52} | error: {}", r.name, error),
+                    (None, Some((gap, score)), Some((_, probe_ms))) => log!(
+                        "{:<52} | {:>5} | {:>8.2} | {:>10.2} | {:>6.3}",
+                        r.name, r.max_n, gap, probe_ms, score
+                    ),
+                    _ => log!("{:<52} | {:>5} | {:>8} | {:>10} | {:>6}", r.name, r.max_n, "-", "-", "-"),
+                }
+            }
+        }
+        _ => {
+            log!("\n{:<52} | {:>5} | {:>10}", "Algorithm", "Max N", "Time (ms)");
+            log!("{}", "-".repeat(80));
 
-    results.sort_by(|a, b| b.max_n.cmp(&a.max_n));
+            let mut order: Vec<usize> = (0..results.len()).collect();
+            order.sort_by_key(|&i| std::cmp::Reverse(results[i].max_n));
 
+            for i in order {
+                let r = &results[i];
+                match &r.error {
+                    Some(error) => log!("{:<52} | error: {}", r.name, error),
+                    None => log!("{:<52} | {:>5} | {:>10.2}", r.name, r.max_n, r.time_ms),
+                }
+            }
+        }
+    }
+
+    log!("\nEstimated complexity (time ~ n^k, 95% interval for k):");
     for r in &results {
-        println!("{:<52} | {:>5} | {:>10.2}", r.name, r.max_n, r.time_ms);
+        match r.complexity {
+            Some(fit) => log!(
+                "  {:<52} ≈ O(n^{:.2})  [{:.2}, {:.2}] from {} runs",
+                r.name, fit.exponent, fit.low, fit.high, fit.samples
+            ),
+            None => log!("  {:<52} too few timed runs", r.name),
+        }
     }
 
-    println!("\n{}", "=".repeat(80));
+    log!("\nTime per phase at max N (ms); only the solver phases count towards the timeout:");
+    log!("  {:<52} | {:>10} | {:>12} | {:>11}", "Algorithm", "Matrix", "Construction", "Improvement");
+    for r in results.iter().filter(|r| r.error.is_none()) {
+        let (matrix, construction, improvement) = r.phases.as_ms();
+        log!("  {:<52} | {:>10.2} | {:>12.2} | {:>11.2}", r.name, matrix, construction, improvement);
+    }
+
+    let invalid: Vec<&BenchmarkResult> = results.iter().filter(|r| r.invalid.is_some()).collect();
+    if !invalid.is_empty() {
+        log!("\nINVALID OUTPUT - sizes from these on earn no max N credit:");
+        for r in invalid {
+            let (n, reason) = r.invalid.as_ref().unwrap();
+            log!("  {} at n={}: {}", r.name, n, reason);
+        }
+    }
+    // Worst gap to the optimum over each algorithm's probes at small n
+    let worst_gaps: Vec<(&BenchmarkResult, usize, f64)> = results
+        .iter()
+        .filter_map(|r| {
+            let gaps = r.probes.iter().filter_map(|probe| probe.optimum_gap.map(|gap| (probe.n, gap)));
+            gaps.max_by(|a, b| a.1.total_cmp(&b.1)).map(|(n, gap)| (r, n, gap))
+        })
+        .collect();
+    if !worst_gaps.is_empty() {
+        log!("\nGap to the optimum from Held-Karp, worst over the probes at n <= {}:", OPTIMUM_CHECK_N);
+        for (r, n, gap) in worst_gaps {
+            log!("  {:<52} {:>8.3}% at n={}", r.name, gap, n);
+        }
+    }
+    let timed_out: Vec<&BenchmarkResult> = results.iter().filter(|r| r.timed_out.is_some()).collect();
+    if !timed_out.is_empty() {
+        log!("\nTIMED OUT - runs abandoned at {}x the timeout:", HARD_DEADLINE);
+        for r in timed_out {
+            log!("  {} timed out at n={}", r.name, r.timed_out.unwrap());
+        }
+    }
+
+    log!("\n{}", "=".repeat(80));
 
     // Output JSON for programmatic use
-    println!("\nJSON Results:");
-    println!("[");
+    let mut results_json = String::from("[\n");
     for (i, r) in results.iter().enumerate() {
         let comma = if i < results.len() - 1 { "," } else { "" };
-        println!(
-            "  {{ \"name\": \"{}\", \"maxN\": {}, \"timeMs\": {:.2} }}{}",
-            r.name, r.max_n, r.time_ms, comma
+        let mut extra = match scores.as_ref().and_then(|scores| scores[i]) {
+            Some((gap, score)) => format!(", \"gapPercent\": {:.4}, \"score\": {:.4}", gap, score),
+            None => String::new(),
+        };
+        if let Some(fit) = r.complexity {
+            extra.push_str(&format!(
+                ", \"exponent\": {:.3}, \"exponentLow\": {:.3}, \"exponentHigh\": {:.3}",
+                fit.exponent, fit.low, fit.high
+            ));
+        }
+        if let Some((n, _)) = &r.invalid {
+            extra.push_str(&format!(", \"invalidAtN\": {}", n));
+        }
+        if let Some(n) = r.timed_out {
+            extra.push_str(&format!(", \"timedOutAtN\": {}", n));
+        }
+        if let Some(gap) = r.optimum_gap {
+            extra.push_str(&format!(", \"optimumGapPercent\": {:.4}", gap));
+        }
+        let (matrix, construction, improvement) = r.phases.as_ms();
+        results_json.push_str(&format!(
+            "  {{ \"name\": {}, \"params\": {}, \"maxN\": {}, \"timeMs\": {:.2}, \"matrixMs\": {:.2}, \
+             \"constructionMs\": {:.2}, \"improvementMs\": {:.2}{} }}{}\n",
+            json_string(&r.name), json_string(&r.params), r.max_n, r.time_ms, matrix, construction, improvement,
+            extra, comma
+        ));
+    }
+    results_json.push(']');
+
+    let manifest = Manifest::collect(point_seeds()).to_json();
+    match format {
+        OutputFormat::Json => println!(
+            "{{ \"manifest\": {},\n\"results\": {},\n\"probes\": {} }}",
+            manifest,
+            results_json,
+            probes_json(&results)
+        ),
+        OutputFormat::Markdown => print_markdown_summary(&results),
+        OutputFormat::Text => {
+            log!("\nManifest:");
+            log!("{}", manifest);
+            log!("\nJSON Results:");
+            log!("{}", results_json);
+        }
+    }
+}
+
+/// Every timed run of every algorithm as a JSON array, one object per run
+/// Runs abandoned at the hard deadline have no time; only valid runs have a length.
+fn probes_json(results: &[BenchmarkResult]) -> String {
+    let number = |value: Option<f64>| value.map_or_else(|| "null".to_string(), |value| format!("{:.4}", value));
+    let probes: Vec<String> = results
+        .iter()
+        .flat_map(|r| r.probes.iter().map(move |probe| (r, probe)))
+        .map(|(r, probe)| {
+            format!(
+                "  {{ \"algorithm\": {}, \"n\": {}, \"timeMs\": {}, \"valid\": {}, \"tourLength\": {}, \
+                 \"optimumGapPercent\": {} }}",
+                json_string(&r.name),
+                probe.n,
+                number(probe.time_ms),
+                probe.valid,
+                number(probe.tour_length),
+                number(probe.optimum_gap)
+            )
+        })
+        .collect();
+    format!("[\n{}\n]", probes.join(",\n"))
+}
+
+/// Print the summary as a GitHub table, largest max N first
+/// Length and gap are measured on the shared `PROBE_N`-point instance, the gap
+/// against `probe_reference`. Phase times are at max N.
+fn print_markdown_summary(results: &[BenchmarkResult]) {
+    let best_length = probe_reference(results);
+    let mut order: Vec<&BenchmarkResult> = results.iter().collect();
+    order.sort_by_key(|r| std::cmp::Reverse(r.max_n));
+
+    println!(
+        "| Algorithm | Max N | Time (ms) | Matrix (ms) | Construction (ms) | Improvement (ms) | Length ({} points) \
+         | Gap % |",
+        PROBE_N
+    );
+    println!("| --- | ---: | ---: | ---: | ---: | ---: | ---: | ---: |");
+    for r in order {
+        let name = markdown_cell(&r.name);
+        let (matrix, construction, improvement) = r.phases.as_ms();
+        let timing = format!(
+            "{} | {:.2} | {:.2} | {:.2} | {:.2}",
+            r.max_n, r.time_ms, matrix, construction, improvement
         );
+        match (&r.error, r.probe) {
+            (Some(error), _) => println!("| {} | error: {} | | | | | | |", name, markdown_cell(error)),
+            (None, Some((length, _))) => println!(
+                "| {} | {} | {:.2} | {:.2} |",
+                name, timing, length, (length - best_length) / best_length * 100.0
+            ),
+            (None, None) => println!("| {} | {} | - | - |", name, timing),
+        }
     }
-    println!("]");
 }
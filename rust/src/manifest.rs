@@ -0,0 +1,96 @@
+//! Benchmark manifest: environment and provenance of a benchmark run
+
+use crate::{clock, determinism};
+use crate::utils::json_string;
+
+/// Everything needed to reproduce and attribute a set of benchmark numbers
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub crate_version: String,
+    pub git_hash: String,
+    pub rustc_version: String,
+    pub cpu_model: String,
+    pub cpu_cores: usize,
+    pub os: String,
+    pub arch: String,
+    pub deterministic: bool,
+    /// Seed of the deterministic RNG streams (see `determinism`)
+    pub rng_seed: u64,
+    /// Generator the RNG streams are created as
+    pub rng_kind: String,
+    /// Whether times are wall or CPU time (see `clock`)
+    pub time_source: String,
+    /// Seeds used to generate the benchmark point sets, one instance per seed
+    pub point_seeds: Vec<u64>,
+}
+
+impl Manifest {
+    /// Collect the manifest for the current build and machine
+    pub fn collect(point_seeds: &[u64]) -> Self {
+        Manifest {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_hash: env!("SONAR_GIT_HASH").to_string(),
+            rustc_version: env!("SONAR_RUSTC_VERSION").to_string(),
+            cpu_model: cpu_model(),
+            cpu_cores: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            deterministic: determinism::is_deterministic(),
+            rng_seed: determinism::seed(),
+            rng_kind: determinism::rng_kind().name().to_string(),
+            time_source: clock::time_source().name().to_string(),
+            point_seeds: point_seeds.to_vec(),
+        }
+    }
+
+    /// Render the manifest as a JSON object
+    /// `pointSeed` repeats the first seed for readers of older manifests.
+    pub fn to_json(&self) -> String {
+        let seeds: Vec<String> = self.point_seeds.iter().map(u64::to_string).collect();
+        format!(
+            "{{ \"crateVersion\": {}, \"gitHash\": {}, \"rustcVersion\": {}, \"cpuModel\": {}, \
+             \"cpuCores\": {}, \"os\": {}, \"arch\": {}, \"deterministic\": {}, \"rngSeed\": {}, \
+             \"rngKind\": {}, \"timeSource\": {}, \"pointSeed\": {}, \"pointSeeds\": [{}] }}",
+            json_string(&self.crate_version),
+            json_string(&self.git_hash),
+            json_string(&self.rustc_version),
+            json_string(&self.cpu_model),
+            self.cpu_cores,
+            json_string(&self.os),
+            json_string(&self.arch),
+            self.deterministic,
+            self.rng_seed,
+            json_string(&self.rng_kind),
+            json_string(&self.time_source),
+            seeds.first().map_or("null", String::as_str),
+            seeds.join(", ")
+        )
+    }
+}
+
+/// CPU model name from /proc/cpuinfo, or "unknown" where that is unavailable
+fn cpu_model() -> String {
+    std::fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|info| {
+            info.lines()
+                .find(|line| line.starts_with("model name"))
+                .and_then(|line| line.split_once(':'))
+                .map(|(_, model)| model.trim().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_json_has_provenance() {
+        let json = Manifest::collect(&[12345, 7]).to_json();
+        assert!(json.contains(&format!("\"crateVersion\": \"{}\"", env!("CARGO_PKG_VERSION"))));
+        assert!(json.contains("\"pointSeed\": 12345, \"pointSeeds\": [12345, 7]"));
+        assert!(json.contains("\"rustcVersion\": \"rustc"));
+        assert!(json.contains("\"rngKind\": \"xoshiro256\", \"timeSource\": \"wall\""));
+    }
+}
@@ -0,0 +1,81 @@
+//! Float functions that live in `std`, with `libm` fallbacks for `no_std` builds
+
+#[cfg(feature = "std")]
+pub(crate) fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+pub(crate) fn sqrt(x: f64) -> f64 {
+    libm::sqrt(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn atan2(y: f64, x: f64) -> f64 {
+    y.atan2(x)
+}
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+pub(crate) fn atan2(y: f64, x: f64) -> f64 {
+    libm::atan2(y, x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn ceil(x: f64) -> f64 {
+    x.ceil()
+}
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+pub(crate) fn ceil(x: f64) -> f64 {
+    libm::ceil(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn round(x: f64) -> f64 {
+    x.round()
+}
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+pub(crate) fn round(x: f64) -> f64 {
+    libm::round(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn floor(x: f64) -> f64 {
+    x.floor()
+}
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+pub(crate) fn floor(x: f64) -> f64 {
+    libm::floor(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn sin(x: f64) -> f64 {
+    x.sin()
+}
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+pub(crate) fn sin(x: f64) -> f64 {
+    libm::sin(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn cos(x: f64) -> f64 {
+    x.cos()
+}
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+pub(crate) fn cos(x: f64) -> f64 {
+    libm::cos(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn sqrtf(x: f32) -> f32 {
+    x.sqrt()
+}
+
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+pub(crate) fn sqrtf(x: f32) -> f32 {
+    libm::sqrtf(x)
+}
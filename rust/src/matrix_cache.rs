@@ -0,0 +1,190 @@
+//! Distance matrix disk cache, skipping the O(n^2) distance computations on reuse
+//!
+//! File layout (little endian): the magic `SONARDM1`, the city count n as u64,
+//! a fingerprint of the points and metric as u64, then the n * n distances as
+//! f64 in row-major order.
+//!
+//! The solvers take an in-memory matrix, so `load_or_build` reads the file
+//! back into one, a row at a time: reuse saves time, not memory.
+//! `MappedMatrix` maps a file for lookups of single entries, as a
+//! `DistanceProvider` does.
+
+use crate::problem::{Metric, ProblemInstance};
+use crate::utils::{DistanceMatrix, Point};
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"SONARDM1";
+const HEADER_LEN: usize = 24;
+
+/// FNV-1a hash of the metric and every coordinate, used to detect stale caches
+pub fn fingerprint(points: &[Point], metric: Metric) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut feed = |value: u64| {
+        for byte in value.to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    };
+    feed(metric as u64);
+    feed(points.len() as u64);
+    for p in points {
+        feed(p.x.to_bits());
+        feed(p.y.to_bits());
+    }
+    hash
+}
+
+/// Write a distance matrix to `path`
+/// The file is written next to its destination and renamed into place, so a
+/// crash never leaves a truncated cache behind.
+pub fn write_matrix(path: &Path, matrix: &[Vec<f64>], fingerprint: u64) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    {
+        let mut out = BufWriter::new(File::create(&tmp)?);
+        out.write_all(MAGIC)?;
+        out.write_all(&(matrix.len() as u64).to_le_bytes())?;
+        out.write_all(&fingerprint.to_le_bytes())?;
+        for row in matrix {
+            for d in row {
+                out.write_all(&d.to_le_bytes())?;
+            }
+        }
+        out.flush()?;
+    }
+    std::fs::rename(tmp, path)
+}
+
+/// A distance matrix file mapped into memory
+/// Entries are decoded straight from the mapping; nothing is read until used.
+pub struct MappedMatrix {
+    mmap: Mmap,
+    n: usize,
+    fingerprint: u64,
+}
+
+impl MappedMatrix {
+    /// Map a cache file written by `write_matrix`
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: the mapping is read-only; cache files are only ever replaced by
+        // rename, never modified in place, so the mapped bytes cannot change
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        if mmap.len() < HEADER_LEN || &mmap[..8] != MAGIC {
+            return Err(invalid("not a distance matrix cache file"));
+        }
+        let word = |offset: usize| u64::from_le_bytes(mmap[offset..offset + 8].try_into().unwrap());
+        let n = word(8) as usize;
+        let fingerprint = word(16);
+        let expected = n
+            .checked_mul(n)
+            .and_then(|cells| cells.checked_mul(8))
+            .and_then(|bytes| bytes.checked_add(HEADER_LEN));
+        if expected != Some(mmap.len()) {
+            return Err(invalid("distance matrix cache file has the wrong size"));
+        }
+
+        Ok(MappedMatrix { mmap, n, fingerprint })
+    }
+
+    /// Number of cities
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Fingerprint of the points the matrix was computed from
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    /// Distance from city i to city j
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        let offset = HEADER_LEN + (i * self.n + j) * 8;
+        f64::from_le_bytes(self.mmap[offset..offset + 8].try_into().unwrap())
+    }
+}
+
+/// Read the matrix cached at `path` if it was computed for `n` cities with
+/// `fingerprint`, or `None` if it belongs to other points
+/// Time complexity: O(n^2), holding one row of the file at a time
+fn read_matrix(path: &Path, n: usize, fingerprint: u64) -> io::Result<Option<DistanceMatrix>> {
+    let file = File::open(path)?;
+    let expected = (n as u64).checked_mul(n as u64).and_then(|cells| cells.checked_mul(8));
+    if expected.and_then(|bytes| bytes.checked_add(HEADER_LEN as u64)) != Some(file.metadata()?.len()) {
+        return Ok(None);
+    }
+    let mut input = BufReader::new(file);
+    let mut header = [0; HEADER_LEN];
+    input.read_exact(&mut header)?;
+    let word = |offset: usize| u64::from_le_bytes(header[offset..offset + 8].try_into().unwrap());
+    if &header[..8] != MAGIC || word(8) != n as u64 || word(16) != fingerprint {
+        return Ok(None);
+    }
+    let mut row = vec![0; n * 8];
+    let mut matrix = Vec::with_capacity(n);
+    for _ in 0..n {
+        input.read_exact(&mut row)?;
+        matrix.push(row.chunks_exact(8).map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap())).collect());
+    }
+    Ok(Some(matrix))
+}
+
+/// Build a problem instance, reusing the distance matrix cached at `path`
+/// If the cache is missing or was computed from other points, the matrix is
+/// built and written to `path` for the next run.
+/// Returns the instance and whether the cache was used.
+pub fn load_or_build(points: Vec<Point>, metric: Metric, path: &Path) -> io::Result<(ProblemInstance, bool)> {
+    let to_io = |e: crate::error::SonarError| io::Error::new(io::ErrorKind::InvalidInput, e.to_string());
+    let expected = fingerprint(&points, metric);
+
+    if let Ok(Some(matrix)) = read_matrix(path, points.len(), expected) {
+        let instance = ProblemInstance::with_matrix(points, metric, matrix).map_err(to_io)?;
+        return Ok((instance, true));
+    }
+
+    let instance = ProblemInstance::new(points, metric).map_err(to_io)?;
+    write_matrix(path, instance.matrix(), expected)?;
+    Ok((instance, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_cache_round_trips_and_detects_stale_points() {
+        let dir = std::env::temp_dir().join(format!("sonar-matrix-cache-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("matrix.bin");
+
+        let points = generate_normalized_points(40, 20, 9);
+        let (built, cached) = load_or_build(points.clone(), Metric::Euclidean, &path).unwrap();
+        assert!(!cached);
+        let (loaded, cached) = load_or_build(points, Metric::Euclidean, &path).unwrap();
+        assert!(cached);
+        assert_eq!(loaded.cached_matrix().unwrap(), built.matrix());
+
+        let mapped = MappedMatrix::open(&path).unwrap();
+        assert_eq!(mapped.get(3, 7), built.matrix()[3][7]);
+
+        // Different points must not reuse the old matrix
+        let other = generate_normalized_points(40, 20, 10);
+        let (_, cached) = load_or_build(other.clone(), Metric::Euclidean, &path).unwrap();
+        assert!(!cached);
+
+        std::fs::write(&path, b"garbage").unwrap();
+        assert!(MappedMatrix::open(&path).is_err());
+        let (_, cached) = load_or_build(other, Metric::Euclidean, &path).unwrap();
+        assert!(!cached);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+//! Tour similarity - how different two solutions are, beyond their lengths
+
+use alloc::{vec, vec::Vec};
+use crate::error::{validate_tour, SonarError};
+
+/// Number of undirected edges two tours of the same cities share
+/// Rotations and reversals of a tour share all n edges with it.
+/// Time complexity: O(n)
+pub fn edge_overlap(a: &[usize], b: &[usize]) -> Result<usize, SonarError> {
+    let n = a.len();
+    validate_tour(a, n)?;
+    validate_tour(b, n)?;
+    // Both tour neighbors of every city in `a`
+    let mut neighbors = vec![(0, 0); n];
+    for i in 0..n {
+        neighbors[a[i]] = (a[(i + n - 1) % n], a[(i + 1) % n]);
+    }
+    let shared = (0..n)
+        .filter(|&i| {
+            let (from, to) = (b[i], b[(i + 1) % n]);
+            neighbors[from].0 == to || neighbors[from].1 == to
+        })
+        .count();
+    Ok(shared)
+}
+
+/// Bond distance: the number of edges of `a` missing from `b`, from 0 for the
+/// same tour up to n
+/// A 2-opt move changes exactly two edges, so at least half this many 2-opt
+/// moves lead from one tour to the other.
+/// Time complexity: O(n)
+pub fn bond_distance(a: &[usize], b: &[usize]) -> Result<usize, SonarError> {
+    Ok(a.len() - edge_overlap(a, b)?)
+}
+
+/// Bond distance of every pair of `tours`, as a symmetric matrix
+/// Time complexity: O(k^2 * n) for k tours
+pub fn bond_distance_matrix(tours: &[Vec<usize>]) -> Result<Vec<Vec<usize>>, SonarError> {
+    let k = tours.len();
+    let mut distances = vec![vec![0; k]; k];
+    for i in 0..k {
+        for j in i + 1..k {
+            let d = bond_distance(&tours[i], &tours[j])?;
+            distances[i][j] = d;
+            distances[j][i] = d;
+        }
+    }
+    Ok(distances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_edges() {
+        let tour = [0, 1, 2, 3, 4, 5];
+        assert_eq!(edge_overlap(&tour, &[3, 4, 5, 0, 1, 2]).unwrap(), 6);
+        assert_eq!(edge_overlap(&tour, &[5, 4, 3, 2, 1, 0]).unwrap(), 6);
+        // One 2-opt move: reverse 1..=3
+        assert_eq!(bond_distance(&tour, &[0, 3, 2, 1, 4, 5]).unwrap(), 2);
+        assert_eq!(bond_distance(&tour, &[0, 2, 4, 1, 3, 5]).unwrap(), 5);
+
+        let distances = bond_distance_matrix(&[tour.to_vec(), vec![0, 3, 2, 1, 4, 5], vec![1, 2, 3, 4, 5, 0]]).unwrap();
+        assert_eq!(distances, [[0, 2, 0], [2, 0, 2], [0, 2, 0]]);
+        assert_eq!(edge_overlap(&tour, &[0, 1, 2]), Err(SonarError::SizeMismatch { expected: 6, found: 3 }));
+        assert_eq!(edge_overlap(&tour, &[0, 0, 1, 2, 3, 4]), Err(SonarError::InvalidTour));
+    }
+}
@@ -0,0 +1,235 @@
+//! Hierarchical multi-level solver for million-point instances
+//!
+//! Points are repeatedly merged into super-nodes on a coarsening grid, the
+//! coarsest level is toured directly, and the tour is expanded level by level,
+//! each time refined with neighbor-list 2-opt.
+
+use crate::error::{validate_points, SonarError};
+use crate::nearest_neighbor;
+use crate::spatial_grid::SpatialGrid;
+use crate::utils::{distance, Point};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Coarsening stops once a level has at most this many nodes
+const COARSEST_N: usize = 256;
+
+/// Nodes merged into one super-node per coarsening grid cell, on average
+const NODES_PER_CELL: f64 = 4.0;
+
+/// Longest segment a single 2-opt move may reverse; longer moves are skipped
+/// so one move never costs more than this on million-point tours
+const MAX_REVERSAL: usize = 50_000;
+
+/// One coarsening level: super-node positions and the finer nodes each one merges
+struct Level {
+    nodes: Vec<Point>,
+    children: Vec<Vec<usize>>,
+}
+
+/// Generate a tour by coarsening, solving the coarse instance and refining back up
+/// Each level is refined with 2-opt restricted to each node's `neighbors`
+/// nearest nodes, using don't-look bits so only nodes near changes are revisited.
+/// Time complexity: ~O(n log n * neighbors) on uniformly distributed points
+pub fn generate_tour(points: &[Point], neighbors: usize) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
+    if neighbors == 0 {
+        return Err(SonarError::InvalidParameter { name: "neighbors", reason: "must be positive" });
+    }
+
+    let mut levels: Vec<Level> = Vec::new();
+    let mut current: Vec<Point> = points.iter().enumerate().map(|(id, p)| Point { id, ..p.clone() }).collect();
+    while current.len() > COARSEST_N {
+        let level = coarsen(&current);
+        // Stop when the grid no longer merges anything (e.g. heavily clustered data)
+        if level.nodes.len() as f64 > 0.9 * current.len() as f64 {
+            break;
+        }
+        current = level.nodes.clone();
+        levels.push(level);
+    }
+
+    let mut tour = nearest_neighbor::generate_tour_grid(&current, 0)?;
+    refine(&current, &mut tour, neighbors);
+
+    // Expand super-nodes back into the nodes they merged, finest level last
+    for (depth, level) in levels.iter().enumerate().rev() {
+        let finer: Vec<Point> = if depth == 0 {
+            points.to_vec()
+        } else {
+            levels[depth - 1].nodes.clone()
+        };
+        tour = expand(&level.nodes, &level.children, &finer, &tour);
+        refine(&finer, &mut tour, neighbors);
+    }
+    Ok(tour)
+}
+
+/// Merge the nodes falling in each cell of a grid sized for `NODES_PER_CELL` nodes per cell
+fn coarsen(nodes: &[Point]) -> Level {
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for p in nodes {
+        min_x = min_x.min(p.x);
+        min_y = min_y.min(p.y);
+        max_x = max_x.max(p.x);
+        max_y = max_y.max(p.y);
+    }
+    let area = ((max_x - min_x) * (max_y - min_y)).max(f64::EPSILON);
+    let cell = (area * NODES_PER_CELL / nodes.len() as f64).sqrt().max(f64::EPSILON);
+
+    let mut cells: HashMap<(i64, i64), usize> = HashMap::new();
+    let mut children: Vec<Vec<usize>> = Vec::new();
+    for (i, p) in nodes.iter().enumerate() {
+        let key = (((p.x - min_x) / cell) as i64, ((p.y - min_y) / cell) as i64);
+        let slot = *cells.entry(key).or_insert_with(|| {
+            children.push(Vec::new());
+            children.len() - 1
+        });
+        children[slot].push(i);
+    }
+
+    let super_nodes = children
+        .iter()
+        .enumerate()
+        .map(|(id, members)| {
+            let count = members.len() as f64;
+            let x = members.iter().map(|&i| nodes[i].x).sum::<f64>() / count;
+            let y = members.iter().map(|&i| nodes[i].y).sum::<f64>() / count;
+            Point { x, y, angle: 0.0, id }
+        })
+        .collect();
+    Level { nodes: super_nodes, children }
+}
+
+/// Replace every super-node of a coarse tour by the nodes it merged
+/// Children are ordered along the direction from the previous to the next
+/// super-node, so the expanded tour flows through each cell.
+fn expand(coarse: &[Point], children: &[Vec<usize>], finer: &[Point], coarse_tour: &[usize]) -> Vec<usize> {
+    let m = coarse_tour.len();
+    let mut tour = Vec::with_capacity(finer.len());
+    for k in 0..m {
+        let prev = &coarse[coarse_tour[(k + m - 1) % m]];
+        let next = &coarse[coarse_tour[(k + 1) % m]];
+        let (dx, dy) = (next.x - prev.x, next.y - prev.y);
+        let mut members = children[coarse_tour[k]].clone();
+        members.sort_by(|&a, &b| {
+            let along = |i: usize| finer[i].x * dx + finer[i].y * dy;
+            along(a).total_cmp(&along(b)).then(a.cmp(&b))
+        });
+        tour.extend(members);
+    }
+    tour
+}
+
+/// 2-opt over candidate neighbors with don't-look bits, in place
+fn refine(nodes: &[Point], tour: &mut [usize], neighbors: usize) {
+    let n = tour.len();
+    if n < 5 {
+        return;
+    }
+    let grid = SpatialGrid::new(nodes, 2);
+    let candidates: Vec<Vec<usize>> = (0..n)
+        .into_par_iter()
+        .map(|i| grid.k_nearest(nodes, nodes[i].x, nodes[i].y, neighbors, Some(i)))
+        .collect();
+
+    let mut position = vec![0; n];
+    for (p, &city) in tour.iter().enumerate() {
+        position[city] = p;
+    }
+    let d = |a: usize, b: usize| distance(&nodes[a], &nodes[b]);
+
+    // Don't-look bits: only cities in the queue are examined
+    let mut queued = vec![true; n];
+    let mut queue: std::collections::VecDeque<usize> = tour.iter().copied().collect();
+
+    while let Some(a) = queue.pop_front() {
+        queued[a] = false;
+        let mut improved = false;
+
+        for forward in [true, false] {
+            let pa = position[a];
+            let b = if forward { tour[(pa + 1) % n] } else { tour[(pa + n - 1) % n] };
+            let d_ab = d(a, b);
+            for &c in &candidates[a] {
+                let d_ac = d(a, c);
+                if d_ac >= d_ab {
+                    break;
+                }
+                let pc = position[c];
+                let e = if forward { tour[(pc + 1) % n] } else { tour[(pc + n - 1) % n] };
+                if c == b || e == a {
+                    continue;
+                }
+                let delta = d_ac + d(b, e) - d_ab - d(c, e);
+                if delta >= -1e-12 {
+                    continue;
+                }
+
+                // forward: a b .. c e -> a c .. b e; backward: b a .. e c -> b e .. a c
+                let (from, to) = if forward { (position[b], pc) } else { (pa, position[e]) };
+                if !reverse_cyclic(tour, &mut position, from, to) {
+                    continue;
+                }
+                for city in [a, b, c, e] {
+                    if !queued[city] {
+                        queued[city] = true;
+                        queue.push_back(city);
+                    }
+                }
+                improved = true;
+                break;
+            }
+            if improved {
+                break;
+            }
+        }
+    }
+}
+
+/// Reverse the cyclic range of positions `from..=to`, or its complement when shorter
+/// Both give the same cycle. Returns false without changing anything when
+/// even the shorter side exceeds `MAX_REVERSAL`.
+fn reverse_cyclic(tour: &mut [usize], position: &mut [usize], from: usize, to: usize) -> bool {
+    let n = tour.len();
+    let inside = (to + n - from) % n + 1;
+    let (mut i, mut j, length) = if inside * 2 <= n {
+        (from, to, inside)
+    } else {
+        ((to + 1) % n, (from + n - 1) % n, n - inside)
+    };
+    if length > MAX_REVERSAL {
+        return false;
+    }
+    for _ in 0..length / 2 {
+        tour.swap(i, j);
+        position[tour[i]] = i;
+        position[tour[j]] = j;
+        i = (i + 1) % n;
+        j = (j + n - 1) % n;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{calculate_tour_length, create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_multilevel_tour_beats_nearest_neighbor() {
+        let points = generate_normalized_points(3_000, 120, 17);
+        let tour = generate_tour(&points, 8).unwrap();
+
+        assert_eq!(tour.len(), points.len());
+        let mut seen = std::collections::HashSet::new();
+        for &id in &tour {
+            assert!(seen.insert(id));
+        }
+
+        let graph = create_distance_matrix(&points);
+        let nn = nearest_neighbor::generate_tour_grid(&points, 0).unwrap();
+        assert!(calculate_tour_length(&tour, &graph) < 0.95 * calculate_tour_length(&nn, &graph));
+    }
+}
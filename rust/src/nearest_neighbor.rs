@@ -1,11 +1,38 @@
 //! Nearest Neighbor (Greedy) algorithm for TSP
 
+use alloc::vec::Vec;
+use crate::error::{validate_graph, validate_points, validate_size, SonarError};
+use crate::scratch::{reset, SolverScratch};
+use crate::spatial_grid::SpatialGrid;
+use crate::utils::Point;
+
 /// Generate a tour using the Nearest Neighbor heuristic
 /// Start from a point and always visit the closest unvisited point
+/// Fails with `SonarError::Unreachable` if only infinite edges lead onwards,
+/// or from the last city back to the start
 /// Time complexity: O(n^2)
-pub fn generate_tour(n: usize, graph: &[Vec<f64>], start_city: usize) -> Vec<usize> {
-    let mut visited = vec![false; n];
+pub fn generate_tour(n: usize, graph: &[Vec<f64>], start_city: usize) -> Result<Vec<usize>, SonarError> {
     let mut tour = Vec::with_capacity(n);
+    generate_tour_with(n, graph, start_city, &mut SolverScratch::new(), &mut tour)?;
+    Ok(tour)
+}
+
+/// `generate_tour` into `tour`, with its visited flags kept in `scratch`
+/// Allocates nothing once both have held a tour of n cities.
+pub fn generate_tour_with(
+    n: usize,
+    graph: &[Vec<f64>],
+    start_city: usize,
+    scratch: &mut SolverScratch,
+    tour: &mut Vec<usize>,
+) -> Result<(), SonarError> {
+    validate_graph(graph)?;
+    validate_size(n, graph)?;
+    validate_start(start_city, n)?;
+
+    let visited = &mut scratch.visited;
+    reset(visited, n, false);
+    tour.clear();
 
     let mut current = start_city;
     tour.push(current);
@@ -22,14 +49,50 @@ pub fn generate_tour(n: usize, graph: &[Vec<f64>], start_city: usize) -> Vec<usi
             }
         }
 
-        if let Some(next) = nearest {
-            tour.push(next);
-            visited[next] = true;
-            current = next;
-        }
+        let next = nearest.ok_or(SonarError::Unreachable { from: current })?;
+        tour.push(next);
+        visited[next] = true;
+        current = next;
+    }
+    if n > 1 && graph[current][start_city].is_infinite() {
+        return Err(SonarError::Unreachable { from: current });
+    }
+
+    Ok(())
+}
+
+/// Points per cell of the grid `generate_tour_grid` searches
+pub const POINTS_PER_CELL: usize = 2;
+
+/// Generate a Nearest Neighbor tour using a bucket grid instead of a distance matrix
+/// The closest unvisited point is found by an expanding ring search over grid cells,
+/// so no O(n^2) matrix is needed and large instances become practical.
+/// Time complexity: ~O(n) on uniformly distributed points, O(n^2) worst case
+pub fn generate_tour_grid(points: &[Point], start_city: usize) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
+    let n = points.len();
+    validate_start(start_city, n)?;
+    let mut tour = Vec::with_capacity(n);
+
+    let mut grid = SpatialGrid::new(points, POINTS_PER_CELL);
+    let mut current = start_city;
+    tour.push(current);
+    grid.remove(current);
+
+    while let Some(next) = grid.nearest(points, points[current].x, points[current].y) {
+        tour.push(next);
+        grid.remove(next);
+        current = next;
     }
 
-    tour
+    Ok(tour)
+}
+
+fn validate_start(start_city: usize, n: usize) -> Result<(), SonarError> {
+    if start_city >= n {
+        return Err(SonarError::InvalidParameter { name: "start_city", reason: "must be a city index" });
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -48,7 +111,7 @@ mod tests {
         ];
         let graph = create_distance_matrix(&points);
 
-        let tour = generate_tour(points.len(), &graph, 0);
+        let tour = generate_tour(points.len(), &graph, 0).unwrap();
 
         assert_eq!(tour.len(), points.len());
         assert_eq!(tour[0], 0); // Should start from city 0
@@ -59,4 +122,31 @@ mod tests {
             assert!(seen.insert(id));
         }
     }
+
+    #[test]
+    fn test_grid_matches_matrix_version() {
+        let points = crate::utils::generate_normalized_points(300, 40, 11);
+        let graph = create_distance_matrix(&points);
+
+        let matrix_tour = generate_tour(points.len(), &graph, 0).unwrap();
+        let grid_tour = generate_tour_grid(&points, 0).unwrap();
+
+        assert_eq!(grid_tour.len(), points.len());
+        let matrix_length = crate::utils::calculate_tour_length(&matrix_tour, &graph);
+        let grid_length = crate::utils::calculate_tour_length(&grid_tour, &graph);
+        assert!((matrix_length - grid_length).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        // City 2 is cut off from the others
+        let inf = f64::INFINITY;
+        let graph = vec![vec![0.0, 1.0, inf], vec![1.0, 0.0, inf], vec![inf, inf, 0.0]];
+        assert_eq!(generate_tour(3, &graph, 0), Err(SonarError::Unreachable { from: 1 }));
+        let one_way = vec![vec![0.0, 1.0, 2.0], vec![1.0, 0.0, 1.0], vec![inf, 1.0, 0.0]];
+        assert_eq!(generate_tour(3, &one_way, 0), Err(SonarError::Unreachable { from: 2 }));
+        assert!(matches!(generate_tour(3, &graph, 3), Err(SonarError::InvalidParameter { .. })));
+        assert!(matches!(generate_tour(2, &graph, 0), Err(SonarError::SizeMismatch { .. })));
+        assert_eq!(generate_tour_grid(&[], 0), Err(SonarError::EmptyInput));
+    }
 }
@@ -0,0 +1,49 @@
+//! Candidate (nearest neighbor) lists for local search
+
+use alloc::vec::Vec;
+
+/// Build the list of the `k` closest cities for every city
+/// Local search moves only consider edges to these candidates, which turns an
+/// O(n^2) neighbourhood scan into O(n * k)
+/// Time complexity: O(n^2) to build
+pub fn candidate_lists(graph: &[Vec<f64>], k: usize) -> Vec<Vec<usize>> {
+    let n = graph.len();
+    let k = k.min(n.saturating_sub(1));
+
+    (0..n)
+        .map(|i| {
+            let mut others: Vec<usize> = (0..n).filter(|&j| j != i).collect();
+            let by_distance = |a: &usize, b: &usize| graph[i][*a].total_cmp(&graph[i][*b]);
+            if k < others.len() {
+                others.select_nth_unstable_by(k, by_distance);
+                others.truncate(k);
+            }
+            others.sort_by(by_distance);
+            others
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_candidate_lists_are_sorted_nearest() {
+        let points = generate_normalized_points(50, 20, 2);
+        let graph = create_distance_matrix(&points);
+        let lists = candidate_lists(&graph, 5);
+
+        for (i, list) in lists.iter().enumerate() {
+            assert_eq!(list.len(), 5);
+            assert!(!list.contains(&i));
+            let farthest = graph[i][list[4]];
+            for j in 0..points.len() {
+                if j != i && !list.contains(&j) {
+                    assert!(graph[i][j] >= farthest);
+                }
+            }
+        }
+    }
+}
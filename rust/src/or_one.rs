@@ -0,0 +1,115 @@
+//! Or-1 local search: relocate single cities to their best insertion point
+
+use alloc::{vec, vec::Vec};
+use crate::error::{validate_graph, validate_tour, SonarError};
+use crate::neighbors::candidate_lists;
+
+/// Apply Or-1 improvement using candidate lists of size `k`
+/// Each city is removed from between its neighbours and reinserted next to one
+/// of its k nearest cities if that shortens the tour. Very cheap, and fixes the
+/// local detours left by sweep constructions such as angular sort or sonar visit.
+/// Time complexity: O(n * k) evaluations per iteration, plus O(d) per applied
+/// move for a city moved d positions
+pub fn improve(tour: &[usize], graph: &[Vec<f64>], k: usize, max_iterations: usize) -> Result<Vec<usize>, SonarError> {
+    validate_graph(graph)?;
+    validate_tour(tour, graph.len())?;
+
+    let n = tour.len();
+    let mut current_tour = tour.to_vec();
+    if n < 4 {
+        return Ok(current_tour);
+    }
+
+    let candidates = candidate_lists(graph, k);
+    let mut position = vec![0; n];
+    for (p, &city) in current_tour.iter().enumerate() {
+        position[city] = p;
+    }
+    let mut improved = true;
+    let mut iterations = 0;
+
+    while improved && iterations < max_iterations {
+        improved = false;
+        iterations += 1;
+
+        for b in 0..n {
+            let i = position[b];
+            let a = current_tour[(i + n - 1) % n];
+            let c = current_tour[(i + 1) % n];
+            let removal_gain = graph[a][b] + graph[b][c] - graph[a][c];
+
+            let mut best: Option<(usize, f64)> = None;
+            for &x in &candidates[b] {
+                let j = position[x];
+                // Try the edges on both sides of the candidate
+                for y in [current_tour[(j + 1) % n], current_tour[(j + n - 1) % n]] {
+                    if x == b || y == b {
+                        continue;
+                    }
+                    let gain = removal_gain - (graph[x][b] + graph[b][y] - graph[x][y]);
+                    if gain > 1e-10 && best.is_none_or(|(_, g)| gain > g) {
+                        // Insert after whichever of x, y comes first in the tour
+                        let after = if current_tour[(j + 1) % n] == y { x } else { y };
+                        best = Some((after, gain));
+                    }
+                }
+            }
+
+            if let Some((after, _)) = best {
+                let to = position[after];
+                relocate(&mut current_tour, &mut position, i, to);
+                improved = true;
+            }
+        }
+    }
+
+    Ok(current_tour)
+}
+
+/// Move the city at position `from` to just after position `after`, shifting
+/// the cities between them by one, and update `position` over that span
+/// Time complexity: O(|from - after|)
+pub(crate) fn relocate(tour: &mut [usize], position: &mut [usize], from: usize, after: usize) {
+    let span = if after > from {
+        tour[from..=after].rotate_left(1);
+        from..after + 1
+    } else {
+        tour[after + 1..=from].rotate_right(1);
+        after + 1..from + 1
+    };
+    for p in span {
+        position[tour[p]] = p;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::angular_sort;
+    use crate::utils::{calculate_tour_length, create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_or_one_improves_angular_sort() {
+        let points = generate_normalized_points(300, 40, 12);
+        let graph = create_distance_matrix(&points);
+        let initial = angular_sort::generate_tour(&points).unwrap();
+
+        let improved = improve(&initial, &graph, 8, 100).unwrap();
+
+        assert_eq!(improved.len(), points.len());
+        let mut seen = std::collections::HashSet::new();
+        for &id in &improved {
+            assert!(seen.insert(id));
+        }
+        assert!(calculate_tour_length(&improved, &graph) < calculate_tour_length(&initial, &graph));
+
+        // Relocation keeps the position table in step with the tour both ways
+        let mut tour = vec![0, 1, 2, 3, 4, 5];
+        let mut position = tour.clone();
+        relocate(&mut tour, &mut position, 1, 4);
+        assert_eq!(tour, [0, 2, 3, 4, 1, 5]);
+        relocate(&mut tour, &mut position, 5, 0);
+        assert_eq!(tour, [0, 5, 2, 3, 4, 1]);
+        assert!(tour.iter().enumerate().all(|(p, &city)| position[city] == p));
+    }
+}
@@ -0,0 +1,154 @@
+//! Pareto frontier of runtime versus tour length, as JSON and as an SVG chart
+
+use crate::utils::json_string;
+use std::fmt::Write;
+
+/// One algorithm or configuration run on a fixed instance
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub name: String,
+    pub time_ms: f64,
+    pub length: f64,
+}
+
+/// Indices of the measurements no other measurement beats on both runtime and
+/// length, ordered from fastest to shortest
+/// Of several identical measurements only the first is kept.
+/// Time complexity: O(m log m)
+pub fn frontier(measurements: &[Measurement]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..measurements.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&measurements[a], &measurements[b]);
+        a.time_ms.total_cmp(&b.time_ms).then(a.length.total_cmp(&b.length))
+    });
+
+    let mut best_length = f64::INFINITY;
+    let mut front = Vec::new();
+    for i in order {
+        if measurements[i].length < best_length {
+            best_length = measurements[i].length;
+            front.push(i);
+        }
+    }
+    front
+}
+
+/// JSON array of the frontier measurements, fastest first
+pub fn to_json(measurements: &[Measurement], front: &[usize]) -> String {
+    let mut out = String::from("[\n");
+    for (k, &i) in front.iter().enumerate() {
+        let m = &measurements[i];
+        let comma = if k + 1 < front.len() { "," } else { "" };
+        writeln!(
+            out,
+            "  {{ \"name\": {}, \"timeMs\": {:.4}, \"length\": {:.4} }}{}",
+            json_string(&m.name), m.time_ms, m.length, comma
+        )
+        .unwrap();
+    }
+    out.push(']');
+    out
+}
+
+/// Escape text for SVG element content
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Scatter chart of every measurement with the frontier highlighted
+/// Runtime is drawn on a log scale, since it spans orders of magnitude.
+pub fn to_svg(measurements: &[Measurement], front: &[usize]) -> String {
+    const WIDTH: f64 = 800.0;
+    const HEIGHT: f64 = 500.0;
+    const MARGIN: f64 = 70.0;
+
+    // Clamp so sub-microsecond runs stay on the log axis
+    let log_time = |ms: f64| ms.max(1e-3).log10();
+    let range = |values: &mut dyn Iterator<Item = f64>| {
+        let (lo, hi) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        if hi > lo { (lo, hi) } else { (lo - 0.5, lo + 0.5) }
+    };
+    let (t_min, t_max) = range(&mut measurements.iter().map(|m| log_time(m.time_ms)));
+    let (l_min, l_max) = range(&mut measurements.iter().map(|m| m.length).filter(|l| l.is_finite()));
+    let x = |m: &Measurement| MARGIN + (log_time(m.time_ms) - t_min) / (t_max - t_min) * (WIDTH - 2.0 * MARGIN);
+    let y = |m: &Measurement| HEIGHT - MARGIN - (m.length - l_min) / (l_max - l_min) * (HEIGHT - 2.0 * MARGIN);
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\" font-size=\"12\">",
+        WIDTH, HEIGHT
+    )
+    .unwrap();
+    writeln!(out, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>").unwrap();
+    writeln!(
+        out,
+        "<path d=\"M{0} {1} V{2} H{3}\" fill=\"none\" stroke=\"black\"/>",
+        MARGIN, MARGIN, HEIGHT - MARGIN, WIDTH - MARGIN
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">time (ms, log scale): {:.3} .. {:.3}</text>",
+        WIDTH / 2.0, HEIGHT - MARGIN / 3.0, 10f64.powf(t_min), 10f64.powf(t_max)
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<text x=\"{0}\" y=\"{1}\" text-anchor=\"middle\" transform=\"rotate(-90 {0} {1})\">tour length: {2:.2} .. {3:.2}</text>",
+        MARGIN / 3.0, HEIGHT / 2.0, l_min, l_max
+    )
+    .unwrap();
+
+    let line: Vec<String> = front
+        .iter()
+        .map(|&i| &measurements[i])
+        .filter(|m| m.length.is_finite())
+        .map(|m| format!("{:.1},{:.1}", x(m), y(m)))
+        .collect();
+    writeln!(out, "<polyline points=\"{}\" fill=\"none\" stroke=\"crimson\"/>", line.join(" ")).unwrap();
+
+    for (i, m) in measurements.iter().enumerate() {
+        if !m.length.is_finite() {
+            continue;
+        }
+        let on_front = front.contains(&i);
+        writeln!(
+            out,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"4\" fill=\"{}\"><title>{}</title></circle>",
+            x(m), y(m), if on_front { "crimson" } else { "gray" }, xml_escape(&m.name)
+        )
+        .unwrap();
+        if on_front {
+            writeln!(out, "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>", x(m) + 6.0, y(m) - 6.0, xml_escape(&m.name)).unwrap();
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frontier_drops_dominated_runs() {
+        let run = |name: &str, time_ms: f64, length: f64| Measurement { name: name.to_string(), time_ms, length };
+        let measurements = vec![
+            run("slow and long", 50.0, 120.0),
+            run("fast", 1.0, 150.0),
+            run("balanced", 10.0, 110.0),
+            run("best", 100.0, 100.0),
+            run("fast duplicate", 1.0, 150.0),
+        ];
+        let front = frontier(&measurements);
+        assert_eq!(front, vec![1, 2, 3]);
+
+        let json = to_json(&measurements, &front);
+        assert!(json.contains("\"name\": \"balanced\""));
+        assert!(!json.contains("slow and long"));
+        let svg = to_svg(&measurements, &front);
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<circle").count(), 5);
+    }
+}
@@ -0,0 +1,193 @@
+//! Cross-implementation parity - compare the JS algorithms with this crate
+//!
+//! The JS side runs through an external driver, `tests/parity-driver.js` under
+//! `node` unless `SONAR_PARITY_COMMAND` names another command line. It is given
+//! `<n> <grid_size> <seed>`, generates the same seeded points, and prints one
+//! `<key> <length> <tour...>` line per algorithm after a `points <x y angle ...>`
+//! line. Coordinates must match exactly. Angles come from each platform's
+//! `atan2` and may differ in the last bit, so the Rust side runs on the JS
+//! points; given those, every tour must match exactly.
+
+use crate::brute_force;
+use crate::error::SonarError;
+use crate::utils::{calculate_tour_length, create_distance_matrix, generate_normalized_points, Point};
+use crate::{angular_sort, greedy_edge, nearest_neighbor, sonar_visit, two_opt, zigzag};
+use std::fmt;
+use std::io;
+use std::process::Command;
+
+/// Lengths may differ by float summation order; anything more is a divergence
+const LENGTH_TOLERANCE: f64 = 1e-9;
+
+/// Angles within this are the same `atan2` up to last-bit rounding
+const ANGLE_TOLERANCE: f64 = 1e-12;
+
+/// Builds a tour the way the driver does for the same key
+type Build = fn(&[Point], &[Vec<f64>], usize) -> Result<Vec<usize>, SonarError>;
+
+/// Driver keys and their Rust counterparts
+const CASES: &[(&str, Build)] = &[
+    ("sonar-visit", |points, _, grid| sonar_visit::generate_tour(points, grid)),
+    ("sonar-visit-zigzag", |points, graph, grid| {
+        zigzag::optimize(&sonar_visit::generate_tour(points, grid)?, points, graph)
+    }),
+    ("angular-sort", |points, _, _| angular_sort::generate_tour(points)),
+    ("angular-sort-zigzag", |points, graph, _| {
+        zigzag::optimize(&angular_sort::generate_tour(points)?, points, graph)
+    }),
+    ("nearest-neighbor", |points, graph, _| nearest_neighbor::generate_tour(points.len(), graph, 0)),
+    ("greedy-edge", |points, graph, _| greedy_edge::generate_tour(points.len(), graph)),
+    ("two-opt", |points, graph, _| {
+        two_opt::improve(&nearest_neighbor::generate_tour(points.len(), graph, 0)?, graph, 100)
+    }),
+    ("brute-force", |points, graph, _| brute_force::find_optimal(graph, points.len()).map(|(tour, _)| tour)),
+];
+
+/// A result on which the two implementations disagree
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Driver key such as `sonar-visit`, or `points` for the generated instance
+    pub key: String,
+    pub detail: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.detail)
+    }
+}
+
+/// Outcome of one instance: how many results were compared and which diverged
+#[derive(Debug, Clone, Default)]
+pub struct ParityReport {
+    pub compared: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+/// The driver command line, split on whitespace
+fn command() -> Vec<String> {
+    match std::env::var("SONAR_PARITY_COMMAND") {
+        Ok(line) => line.split_whitespace().map(String::from).collect(),
+        Err(_) => vec!["node".into(), concat!(env!("CARGO_MANIFEST_DIR"), "/../tests/parity-driver.js").into()],
+    }
+}
+
+/// Whether the driver's interpreter can be started
+pub fn is_available() -> bool {
+    let command = command();
+    command.first().is_some_and(|program| Command::new(program).arg("--version").output().is_ok())
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Run the driver on `n` points of a `grid_size` grid from `seed` and compare
+/// each of its results with the Rust implementation
+/// Fails when the driver cannot be run or prints something unparseable;
+/// divergences are reported, not errors.
+pub fn check(n: usize, grid_size: usize, seed: u64) -> io::Result<ParityReport> {
+    let command = command();
+    let (program, args) = command.split_first().ok_or_else(|| invalid("empty parity command".into()))?;
+    let output = Command::new(program)
+        .args(args)
+        .args([n.to_string(), grid_size.to_string(), seed.to_string()])
+        .output()?;
+    if !output.status.success() {
+        return Err(invalid(format!("parity driver failed: {}", String::from_utf8_lossy(&output.stderr).trim())));
+    }
+    let stdout = String::from_utf8(output.stdout).map_err(|e| invalid(e.to_string()))?;
+    compare(&stdout, n, grid_size, seed)
+}
+
+/// Compare driver output with the Rust results on the same instance
+fn compare(output: &str, n: usize, grid_size: usize, seed: u64) -> io::Result<ParityReport> {
+    let expected = generate_normalized_points(n, grid_size, seed);
+    let mut points = expected.clone();
+    let mut graph = create_distance_matrix(&points);
+    let mut report = ParityReport::default();
+    let mut diverge = |key: &str, detail: String| report.divergences.push(Divergence { key: key.into(), detail });
+
+    for line in output.lines().filter(|line| !line.trim().is_empty()) {
+        let mut fields = line.split_whitespace();
+        let key = fields.next().unwrap_or_default();
+        let values: Vec<f64> = fields
+            .map(|field| field.parse().map_err(|_| invalid(format!("bad number {:?} in {:?}", field, key))))
+            .collect::<io::Result<_>>()?;
+
+        if key == "points" {
+            let js: Vec<Point> = values
+                .chunks_exact(3)
+                .enumerate()
+                .map(|(id, v)| Point { x: v[0], y: v[1], angle: v[2], id })
+                .collect();
+            let same_points = js.len() == expected.len()
+                && js.iter().zip(&expected).all(|(a, b)| {
+                    a.x == b.x && a.y == b.y && (a.angle - b.angle).abs() <= ANGLE_TOLERANCE
+                });
+            if !same_points {
+                diverge(key, format!("{} JS points differ from the {} generated in Rust", js.len(), expected.len()));
+            }
+            graph = create_distance_matrix(&js);
+            points = js;
+            continue;
+        }
+        let Some(&(_, build)) = CASES.iter().find(|(case, _)| *case == key) else {
+            return Err(invalid(format!("unknown parity key {:?}", key)));
+        };
+        let (&js_length, js_tour) = values.split_first().ok_or_else(|| invalid(format!("{} has no length", key)))?;
+        let js_tour: Vec<usize> = js_tour.iter().map(|&city| city as usize).collect();
+        let tour = build(&points, &graph, grid_size).map_err(|e| invalid(format!("{}: {}", key, e)))?;
+        let length = calculate_tour_length(&tour, &graph);
+
+        if js_tour != tour {
+            let at = js_tour.iter().zip(&tour).position(|(a, b)| a != b).unwrap_or(tour.len().min(js_tour.len()));
+            let detail = format!("tours differ from position {} (lengths {:.9} JS, {:.9} Rust)", at, js_length, length);
+            diverge(key, detail);
+        } else if (js_length - length).abs() > LENGTH_TOLERANCE * length.max(1.0) {
+            diverge(key, format!("same tour but length {} JS, {} Rust", js_length, length));
+        }
+        report.compared += 1;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_flags_divergent_tours() {
+        let points = generate_normalized_points(6, 10, 1);
+        let graph = create_distance_matrix(&points);
+        let coords: Vec<String> =
+            points.iter().flat_map(|p| [p.x, p.y, p.angle]).map(|v| v.to_string()).collect();
+        let nearest = nearest_neighbor::generate_tour(6, &graph, 0).unwrap();
+        let length = calculate_tour_length(&nearest, &graph);
+        let tour = |t: &[usize]| t.iter().map(usize::to_string).collect::<Vec<_>>().join(" ");
+
+        let agreeing = format!("points {}\nnearest-neighbor {} {}\n", coords.join(" "), length, tour(&nearest));
+        let report = compare(&agreeing, 6, 10, 1).unwrap();
+        assert_eq!((report.compared, report.divergences.len()), (1, 0));
+
+        let mut reversed = nearest.clone();
+        reversed[1..].reverse();
+        let drifted = format!("nearest-neighbor {} {}\n", length, tour(&reversed));
+        let report = compare(&drifted, 6, 10, 1).unwrap();
+        assert_eq!(report.divergences[0].key, "nearest-neighbor");
+        assert!(compare("no-such-key 1 0 1", 6, 10, 1).is_err());
+    }
+
+    #[test]
+    fn test_js_implementations_match() {
+        // Machines without node have nothing to check
+        if !is_available() {
+            return;
+        }
+        for (n, seed) in [(9, 12345), (200, 12345), (60, 7)] {
+            let report = check(n, 40, seed).unwrap();
+            assert!(report.divergences.is_empty(), "{:?}", report.divergences);
+            assert!(report.compared >= 7);
+        }
+    }
+}
@@ -0,0 +1,405 @@
+//! Pipeline specifications: a construction followed by improvers, written as
+//! `nearest-neighbor > or-opt > 2opt(max_iters=50)`
+//!
+//! Stages are separated by `>` and run left to right, each on the tour of the
+//! one before. Parameters go in parentheses as `name=value` pairs separated by
+//! commas; names are those of the registry or the short aliases `sweep`
+//! accepts, and parameters not given run at their defaults. The first stage
+//! is one of `CONSTRUCTIONS` or, without parameters, any registry algorithm;
+//! every later stage is one of `IMPROVERS`. New hybrids are then benchmarked
+//! and solved from the command line instead of being written into `main.rs`.
+
+use crate::error::SonarError;
+use crate::problem::ProblemInstance;
+use crate::registry::{self, param, Algorithm, Param};
+use crate::registry::{CANDIDATES, GREEDY_CANDIDATES, IMPROVEMENT_ITERATIONS, LEAF_CAPACITY, MAX_INCREASE_PERCENT};
+use crate::registry::{MIN_ANGLE_DEGREES, MULTILEVEL_NEIGHBORS, MULTI_CENTER_GRID_SIZE, RANDOM_SEED, SONAR_GRID_SIZE};
+use crate::registry::{START_CITY, TWO_OPT_ITERATIONS};
+use crate::sweep::{param_name, Settings};
+use crate::{angular_sort, greedy_edge, kopt, multilevel, nearest_neighbor, or_one, quadtree, random_tour};
+use crate::{smooth, sonar_visit, space_filling, two_h_opt, two_opt, zigzag};
+#[cfg(feature = "metaheuristics")]
+use crate::registry::{ANNEALING_ITERATIONS, COOLING_RATE, INITIAL_TEMPERATURE};
+#[cfg(feature = "metaheuristics")]
+use crate::simulated_annealing;
+use std::fmt;
+
+/// Builds an initial tour with the given parameter values
+pub type Construct = fn(&ProblemInstance, &Settings) -> Result<Vec<usize>, SonarError>;
+
+/// Improves a tour with the given parameter values
+pub type Improve = fn(&ProblemInstance, &[usize], &Settings) -> Result<Vec<usize>, SonarError>;
+
+/// A construction heuristic that can start a pipeline
+#[derive(Debug, Clone, Copy)]
+pub struct Construction {
+    pub key: &'static str,
+    pub params: &'static [Param],
+    pub construct: Construct,
+}
+
+/// An improvement algorithm that can follow the first stage
+#[derive(Debug, Clone, Copy)]
+pub struct Improver {
+    pub key: &'static str,
+    pub params: &'static [Param],
+    pub improve: Improve,
+}
+
+/// Construction heuristics with parameters, under their registry keys
+pub const CONSTRUCTIONS: &[Construction] = &[
+    Construction {
+        key: "random",
+        params: &[param("seed", RANDOM_SEED.text, "shuffle seed")],
+        construct: |instance, settings| Ok(random_tour::generate_tour(instance.len(), settings.count("seed")? as u64)),
+    },
+    Construction {
+        key: "angular-sort",
+        params: &[],
+        construct: |instance, _| angular_sort::generate_tour(instance.points()),
+    },
+    Construction {
+        key: "angular-sort-multi-center",
+        params: &[param("grid_size", MULTI_CENTER_GRID_SIZE.text, "cells per side, each non-empty one a centre")],
+        construct: |instance, settings| {
+            angular_sort::generate_tour_multi_center(instance.points(), settings.count("grid_size")?)
+        },
+    },
+    Construction {
+        key: "morton-sort",
+        params: &[],
+        construct: |instance, _| space_filling::morton_sort(instance.points()),
+    },
+    Construction {
+        key: "hilbert-sort",
+        params: &[],
+        construct: |instance, _| space_filling::hilbert_sort(instance.points()),
+    },
+    Construction {
+        key: "sonar-visit",
+        params: &[param("grid_size", SONAR_GRID_SIZE.text, "angular resolution of the sweep")],
+        construct: |instance, settings| sonar_visit::generate_tour(instance.points(), settings.count("grid_size")?),
+    },
+    Construction {
+        key: "nearest-neighbor",
+        params: &[param("start_city", START_CITY.text, "city the tour starts from")],
+        construct: |instance, settings| {
+            nearest_neighbor::generate_tour(instance.len(), instance.matrix(), settings.count("start_city")?)
+        },
+    },
+    Construction {
+        key: "nearest-neighbor-grid",
+        params: &[param("start_city", START_CITY.text, "city the tour starts from")],
+        construct: |instance, settings| {
+            nearest_neighbor::generate_tour_grid(instance.points(), settings.count("start_city")?)
+        },
+    },
+    Construction {
+        key: "greedy-edge",
+        params: &[],
+        construct: |instance, _| greedy_edge::generate_tour(instance.len(), instance.matrix()),
+    },
+    Construction {
+        key: "greedy-edge-candidates",
+        params: &[param("k", GREEDY_CANDIDATES.text, "nearest neighbors each city's candidate edges go to at first")],
+        construct: |instance, settings| {
+            greedy_edge::generate_tour_candidates(instance.points(), settings.count("k")?)
+        },
+    },
+    Construction {
+        key: "quadtree",
+        params: &[param("leaf_capacity", LEAF_CAPACITY.text, "points per quadtree leaf")],
+        construct: |instance, settings| quadtree::generate_tour(instance.points(), settings.count("leaf_capacity")?),
+    },
+    Construction {
+        key: "multilevel",
+        params: &[param("neighbors", MULTILEVEL_NEIGHBORS.text, "candidate neighbors per city in refinement")],
+        construct: |instance, settings| multilevel::generate_tour(instance.points(), settings.count("neighbors")?),
+    },
+];
+
+/// Improvement algorithms, under the names of their moves
+pub const IMPROVERS: &[Improver] = &[
+    Improver {
+        key: "two-opt",
+        params: &[param("max_iterations", TWO_OPT_ITERATIONS.text, "full 2-opt sweeps")],
+        improve: |instance, tour, settings| {
+            two_opt::improve(tour, instance.matrix(), settings.count("max_iterations")?)
+        },
+    },
+    Improver {
+        key: "or-opt",
+        params: &[
+            param("candidates", CANDIDATES.text, "nearest neighbors tried per city"),
+            param("max_iterations", IMPROVEMENT_ITERATIONS.text, "improvement sweeps"),
+        ],
+        improve: |instance, tour, settings| {
+            or_one::improve(tour, instance.matrix(), settings.count("candidates")?, settings.count("max_iterations")?)
+        },
+    },
+    Improver {
+        key: "two-h-opt",
+        params: &[
+            param("candidates", CANDIDATES.text, "nearest neighbors tried per city"),
+            param("max_iterations", IMPROVEMENT_ITERATIONS.text, "improvement sweeps"),
+        ],
+        improve: |instance, tour, settings| {
+            let (candidates, iterations) = (settings.count("candidates")?, settings.count("max_iterations")?);
+            two_h_opt::improve(tour, instance.matrix(), candidates, iterations)
+        },
+    },
+    Improver {
+        key: "k-opt",
+        params: &[param("max_iterations", IMPROVEMENT_ITERATIONS.text, "improvement sweeps")],
+        improve: |instance, tour, settings| kopt::improve(tour, instance.matrix(), settings.count("max_iterations")?),
+    },
+    Improver {
+        key: "smooth",
+        params: &[
+            param("min_angle_degrees", MIN_ANGLE_DEGREES.text, "sharpest turn left in place"),
+            param("max_increase_percent", MAX_INCREASE_PERCENT.text, "length the smoothing may add"),
+        ],
+        improve: |instance, tour, settings| {
+            let (angle, increase) = (settings.get("min_angle_degrees")?, settings.get("max_increase_percent")?);
+            smooth::improve(tour, instance.points(), angle, increase)
+        },
+    },
+    Improver {
+        key: "zigzag",
+        params: &[],
+        improve: |instance, tour, _| zigzag::optimize(tour, instance.points(), instance.matrix()),
+    },
+    #[cfg(feature = "metaheuristics")]
+    Improver {
+        key: "simulated-annealing",
+        params: &[
+            param("max_iterations", ANNEALING_ITERATIONS.text, "proposed moves"),
+            param("initial_temperature", INITIAL_TEMPERATURE.text, "starting temperature"),
+            param("cooling_rate", COOLING_RATE.text, "temperature factor per move"),
+        ],
+        improve: |instance, tour, settings| {
+            simulated_annealing::optimize(
+                instance.matrix(),
+                tour,
+                settings.count("max_iterations")?,
+                settings.get("initial_temperature")?,
+                settings.get("cooling_rate")?,
+            )
+        },
+    },
+];
+
+/// Short names accepted for stages
+const STAGE_ALIASES: &[(&str, &str)] = &[
+    ("nn", "nearest-neighbor"),
+    ("greedy", "greedy-edge"),
+    ("2opt", "two-opt"),
+    ("oropt", "or-opt"),
+    ("2hopt", "two-h-opt"),
+    ("kopt", "k-opt"),
+    ("sa", "simulated-annealing"),
+];
+
+fn stage_key(key: &str) -> &str {
+    STAGE_ALIASES.iter().find(|(alias, _)| *alias == key).map_or(key, |&(_, full)| full)
+}
+
+/// First stage of a pipeline
+#[derive(Debug, Clone)]
+pub enum Start {
+    /// A construction heuristic and the parameters given to it
+    Construction(&'static Construction, Vec<(&'static str, f64)>),
+    /// A registry algorithm, run with its defaults
+    Algorithm(&'static Algorithm),
+}
+
+/// An improver and the parameters given to it
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub improver: &'static Improver,
+    pub values: Vec<(&'static str, f64)>,
+}
+
+impl Step {
+    /// Improve `tour` on `instance`
+    pub fn run(&self, instance: &ProblemInstance, tour: &[usize]) -> Result<Vec<usize>, SonarError> {
+        (self.improver.improve)(instance, tour, &Settings::new(self.improver.params, &self.values))
+    }
+}
+
+/// A parsed pipeline specification
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    pub start: Start,
+    pub steps: Vec<Step>,
+}
+
+impl Pipeline {
+    /// The initial tour of the first stage
+    pub fn construct(&self, instance: &ProblemInstance) -> Result<Vec<usize>, SonarError> {
+        match &self.start {
+            Start::Construction(construction, values) => {
+                (construction.construct)(instance, &Settings::new(construction.params, values))
+            }
+            Start::Algorithm(algorithm) => (algorithm.solve)(instance),
+        }
+    }
+
+    /// Run every stage on `instance`
+    pub fn solve(&self, instance: &ProblemInstance) -> Result<Vec<usize>, SonarError> {
+        let tour = self.construct(instance)?;
+        improve(&self.steps, instance, tour)
+    }
+
+    /// Display name: the registry's for a lone registry algorithm, otherwise the spec
+    pub fn name(&self) -> String {
+        match (&self.start, self.steps.is_empty()) {
+            (Start::Algorithm(algorithm), true) => algorithm.name.to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Largest instance the first stage is sensible on, for exact methods
+    pub fn max_n(&self) -> Option<usize> {
+        match self.start {
+            Start::Algorithm(algorithm) => algorithm.max_n,
+            Start::Construction(..) => None,
+        }
+    }
+}
+
+/// Run `steps` in order, starting from `tour`
+pub fn improve(steps: &[Step], instance: &ProblemInstance, mut tour: Vec<usize>) -> Result<Vec<usize>, SonarError> {
+    for step in steps {
+        tour = step.run(instance, &tour)?;
+    }
+    Ok(tour)
+}
+
+fn invalid(reason: &'static str) -> SonarError {
+    SonarError::InvalidParameter { name: "pipeline", reason }
+}
+
+/// A stage's key and parameter values, names not yet checked
+type Parsed<'a> = (&'a str, Vec<(&'a str, f64)>);
+
+/// Split `key(name=value, ...)` into the key and its parameters by name
+fn parse_stage(stage: &str) -> Result<Parsed<'_>, SonarError> {
+    let stage = stage.trim();
+    let (key, list) = match stage.split_once('(') {
+        Some((key, rest)) => (key.trim(), rest.strip_suffix(')').ok_or(invalid("a stage must end after its `)`"))?),
+        None => (stage, ""),
+    };
+    if key.is_empty() {
+        return Err(invalid("every stage needs a key"));
+    }
+    let values = list
+        .split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').ok_or(invalid("parameters must be name=value"))?;
+            let value = value.trim().parse::<f64>().map_err(|_| invalid("parameter values must be numbers"))?;
+            Ok((param_name(name.trim()), value))
+        })
+        .collect::<Result<Vec<_>, SonarError>>()?;
+    Ok((stage_key(key), values))
+}
+
+/// Match parameter names to those of `params`
+fn resolve(params: &'static [Param], values: Vec<(&str, f64)>) -> Result<Vec<(&'static str, f64)>, SonarError> {
+    values
+        .into_iter()
+        .map(|(name, value)| {
+            let param = params.iter().find(|p| p.name == name).ok_or(invalid("not a parameter of its stage"))?;
+            Ok((param.name, value))
+        })
+        .collect()
+}
+
+/// Parse improver stages separated by `>`
+pub fn parse_improvers(spec: &str) -> Result<Vec<Step>, SonarError> {
+    spec.split('>')
+        .map(|stage| {
+            let (key, values) = parse_stage(stage)?;
+            let improver = IMPROVERS.iter().find(|i| i.key == key).ok_or(invalid("unknown improver"))?;
+            Ok(Step { improver, values: resolve(improver.params, values)? })
+        })
+        .collect()
+}
+
+/// Parse a pipeline specification
+pub fn parse(spec: &str) -> Result<Pipeline, SonarError> {
+    let (first, rest) = spec.split_once('>').map_or((spec, None), |(first, rest)| (first, Some(rest)));
+    let (key, values) = parse_stage(first)?;
+    let start = match CONSTRUCTIONS.iter().find(|c| c.key == key) {
+        Some(construction) => Start::Construction(construction, resolve(construction.params, values)?),
+        None => {
+            let algorithm = registry::find(key).ok_or(invalid("unknown construction or algorithm"))?;
+            if !values.is_empty() {
+                return Err(invalid("only constructions take parameters in the first stage"));
+            }
+            Start::Algorithm(algorithm)
+        }
+    };
+    let steps = rest.map_or(Ok(Vec::new()), parse_improvers)?;
+    Ok(Pipeline { start, steps })
+}
+
+/// Writes `key(name=value, ...)`, or just the key without parameters
+fn write_stage(f: &mut fmt::Formatter<'_>, key: &str, values: &[(&str, f64)]) -> fmt::Result {
+    write!(f, "{}", key)?;
+    if !values.is_empty() {
+        let values: Vec<String> = values.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+        write!(f, "({})", values.join(", "))?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_stage(f, self.improver.key, &self.values)
+    }
+}
+
+/// The spec in canonical form: full keys and parameter names, single spaces
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.start {
+            Start::Construction(construction, values) => write_stage(f, construction.key, values)?,
+            Start::Algorithm(algorithm) => write!(f, "{}", algorithm.key)?,
+        }
+        for step in &self.steps {
+            write!(f, " > {}", step)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::Metric;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_spec_runs_as_the_hand_written_hybrid() {
+        let pipeline = parse(" nn > oropt >2opt(max_iters=50) ").unwrap();
+        assert_eq!(pipeline.to_string(), "nearest-neighbor > or-opt > two-opt(max_iterations=50)");
+        assert_eq!(parse(&pipeline.to_string()).unwrap().to_string(), pipeline.to_string());
+
+        let instance = ProblemInstance::new(generate_normalized_points(60, 40, 3), Metric::Euclidean).unwrap();
+        let initial = nearest_neighbor::generate_tour(60, instance.matrix(), 0).unwrap();
+        let by_hand = or_one::improve(&initial, instance.matrix(), 8, 100).unwrap();
+        let by_hand = two_opt::improve(&by_hand, instance.matrix(), 50).unwrap();
+        assert_eq!(pipeline.solve(&instance).unwrap(), by_hand);
+
+        let lone = parse("two-opt").unwrap();
+        assert_eq!(lone.name(), registry::find("two-opt").unwrap().name);
+        assert_eq!(lone.solve(&instance).unwrap(), (registry::find("two-opt").unwrap().solve)(&instance).unwrap());
+        assert!(parse("nearest-neighbor(start=1)").is_err());
+        assert!(parse("two-opt(max_iterations=5)").is_err());
+        assert!(parse("greedy-edge > nearest-neighbor").is_err());
+        assert!(parse("greedy-edge > two-opt(max_iterations=50").is_err());
+        assert!(parse("greedy-edge >").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! Plotter and engraver output: G-code and HPGL
+//!
+//! A tour is turned into machine moves: pen-up travel to each point and
+//! either a dot there, for stippling, drilling or engraving single spots, or
+//! one continuous pen-down line through the whole tour, for single-line
+//! drawings. Coordinates are mapped to millimetres by `Plotter::scale` and
+//! `Plotter::offset`, with y up as both formats expect.
+
+use std::fmt::Write;
+use crate::utils::Point;
+
+/// HPGL plotter units per millimetre (0.025 mm each)
+const HPGL_UNITS_PER_MM: f64 = 40.0;
+
+/// What the pen does along the tour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stroke {
+    /// Travel to each point with the pen up and touch down once there
+    Dots,
+    /// Lower the pen at the first point and draw the closed tour in one line
+    Line,
+}
+
+/// Machine settings shared by both formats
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plotter {
+    /// Millimetres per unit of the points' coordinates
+    pub scale: f64,
+    /// Millimetres added to every coordinate after scaling
+    pub offset: (f64, f64),
+    /// Feed rate of pen-up travel, in mm/min (G-code only)
+    pub travel_feed: f64,
+    /// Feed rate while the pen is down, in mm/min (G-code only)
+    pub draw_feed: f64,
+    /// Z height with the pen lifted, in mm (G-code only)
+    pub pen_up_z: f64,
+    /// Z height with the pen on the paper, in mm (G-code only)
+    pub pen_down_z: f64,
+}
+
+impl Default for Plotter {
+    /// Points in the unit square drawn 200 mm wide at common pen plotter speeds
+    fn default() -> Self {
+        Plotter {
+            scale: 200.0,
+            offset: (0.0, 0.0),
+            travel_feed: 3000.0,
+            draw_feed: 1000.0,
+            pen_up_z: 5.0,
+            pen_down_z: 0.0,
+        }
+    }
+}
+
+impl Plotter {
+    /// Machine position of a point in mm
+    fn position(&self, p: &Point) -> (f64, f64) {
+        (p.x * self.scale + self.offset.0, p.y * self.scale + self.offset.1)
+    }
+}
+
+/// G-code for the tour: millimetres, absolute positioning, `G0` pen-up travel
+/// and `G1` moves with the pen lowered to `pen_down_z`
+/// Firmware such as GRBL moves `G0` at its own rapid rate and ignores
+/// `travel_feed`; Marlin honours it.
+/// Time complexity: O(n)
+pub fn gcode(points: &[Point], tour: &[usize], plotter: &Plotter, stroke: Stroke) -> String {
+    let mut out = String::new();
+    writeln!(out, "G21 ; millimetres").unwrap();
+    writeln!(out, "G90 ; absolute positioning").unwrap();
+    writeln!(out, "G0 Z{:.3}", plotter.pen_up_z).unwrap();
+    let pen_down = |out: &mut String| writeln!(out, "G1 Z{:.3} F{:.0}", plotter.pen_down_z, plotter.draw_feed).unwrap();
+    let pen_up = |out: &mut String| writeln!(out, "G0 Z{:.3}", plotter.pen_up_z).unwrap();
+    match stroke {
+        Stroke::Dots => {
+            for &city in tour {
+                let (x, y) = plotter.position(&points[city]);
+                writeln!(out, "G0 X{:.3} Y{:.3} F{:.0}", x, y, plotter.travel_feed).unwrap();
+                pen_down(&mut out);
+                pen_up(&mut out);
+            }
+        }
+        Stroke::Line => {
+            if let Some(&first) = tour.first() {
+                let (x, y) = plotter.position(&points[first]);
+                writeln!(out, "G0 X{:.3} Y{:.3} F{:.0}", x, y, plotter.travel_feed).unwrap();
+                pen_down(&mut out);
+                for &city in tour[1..].iter().chain([&first]) {
+                    let (x, y) = plotter.position(&points[city]);
+                    writeln!(out, "G1 X{:.3} Y{:.3} F{:.0}", x, y, plotter.draw_feed).unwrap();
+                }
+                pen_up(&mut out);
+            }
+        }
+    }
+    writeln!(out, "G0 X{:.3} Y{:.3}", plotter.offset.0, plotter.offset.1).unwrap();
+    out
+}
+
+/// HPGL for the tour: `PU`/`PD` moves in plotter units of 0.025 mm
+/// Time complexity: O(n)
+pub fn hpgl(points: &[Point], tour: &[usize], plotter: &Plotter, stroke: Stroke) -> String {
+    let units = |p: &Point| {
+        let (x, y) = plotter.position(p);
+        ((x * HPGL_UNITS_PER_MM).round() as i64, (y * HPGL_UNITS_PER_MM).round() as i64)
+    };
+    let mut out = String::from("IN;SP1;");
+    match stroke {
+        Stroke::Dots => {
+            for &city in tour {
+                let (x, y) = units(&points[city]);
+                write!(out, "PU{},{};PD;PU;", x, y).unwrap();
+            }
+        }
+        Stroke::Line => {
+            if let Some(&first) = tour.first() {
+                let (x, y) = units(&points[first]);
+                write!(out, "PU{},{};PD", x, y).unwrap();
+                for (k, &city) in tour[1..].iter().chain([&first]).enumerate() {
+                    let (x, y) = units(&points[city]);
+                    write!(out, "{}{},{}", if k == 0 { "" } else { "," }, x, y).unwrap();
+                }
+                out.push_str(";PU;");
+            }
+        }
+    }
+    out.push_str("SP0;\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square_as_gcode_and_hpgl() {
+        let square: Vec<Point> = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+            .iter()
+            .enumerate()
+            .map(|(id, &(x, y))| Point { x, y, angle: 0.0, id })
+            .collect();
+        let tour = [0, 1, 2, 3];
+        let plotter = Plotter { scale: 10.0, offset: (5.0, 5.0), ..Plotter::default() };
+
+        let line = gcode(&square, &tour, &plotter, Stroke::Line);
+        let draws: Vec<&str> = line.lines().filter(|l| l.starts_with("G1 X")).collect();
+        assert_eq!(draws.len(), 4);
+        assert_eq!(draws[0], "G1 X15.000 Y5.000 F1000");
+        assert_eq!(draws[3], "G1 X5.000 Y5.000 F1000");
+        assert!(line.contains("G0 X5.000 Y5.000 F3000\nG1 Z0.000 F1000\n"));
+
+        let dots = gcode(&square, &tour, &plotter, Stroke::Dots);
+        assert_eq!(dots.lines().filter(|l| l.starts_with("G1 Z")).count(), 4);
+        assert!(!dots.contains("G1 X"));
+
+        assert_eq!(
+            hpgl(&square, &tour, &plotter, Stroke::Line),
+            "IN;SP1;PU200,200;PD600,200,600,600,200,600,200,200;PU;SP0;\n"
+        );
+        assert_eq!(hpgl(&square, &tour[..1], &plotter, Stroke::Dots), "IN;SP1;PU200,200;PD;PU;SP0;\n");
+    }
+}
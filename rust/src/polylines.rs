@@ -0,0 +1,256 @@
+//! Ordering open polylines to minimize pen-up travel
+//!
+//! Plotters, laser cutters and CNC mills draw strokes, not dots: each item is
+//! an open polyline drawn from one end to the other, in either direction. The
+//! drawing itself costs the same whatever the order, so only the pen-up travel
+//! between the end of one stroke and the start of the next is minimized. The
+//! route is an open path from the first stroke to the last. Nearest neighbor,
+//! greedy edge and 2-opt are adapted: each step may enter a stroke at either
+//! end, and reversing a run of strokes also flips each of them.
+
+use alloc::{vec, vec::Vec};
+use crate::error::{validate_points, SonarError};
+use crate::utils::{distance, Point};
+
+/// An open polyline, drawn in one stroke from either end
+#[derive(Debug, Clone)]
+pub struct Polyline {
+    pub points: Vec<Point>,
+}
+
+impl Polyline {
+    pub fn start(&self) -> &Point {
+        &self.points[0]
+    }
+
+    pub fn end(&self) -> &Point {
+        &self.points[self.points.len() - 1]
+    }
+}
+
+/// One stroke of the route: which polyline, and whether it is drawn end to start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visit {
+    pub polyline: usize,
+    pub reversed: bool,
+}
+
+impl Visit {
+    /// Where the pen comes down for this stroke
+    fn entry<'a>(&self, polylines: &'a [Polyline]) -> &'a Point {
+        let line = &polylines[self.polyline];
+        if self.reversed { line.end() } else { line.start() }
+    }
+
+    /// Where the pen lifts after this stroke
+    fn exit<'a>(&self, polylines: &'a [Polyline]) -> &'a Point {
+        let line = &polylines[self.polyline];
+        if self.reversed { line.start() } else { line.end() }
+    }
+}
+
+/// Check that there is at least one polyline, none is empty, and all
+/// coordinates are finite
+/// Time complexity: O(total points)
+pub fn validate_polylines(polylines: &[Polyline]) -> Result<(), SonarError> {
+    if polylines.is_empty() {
+        return Err(SonarError::EmptyInput);
+    }
+    for line in polylines {
+        validate_points(&line.points)?;
+    }
+    Ok(())
+}
+
+/// Check that `order` draws every polyline exactly once
+/// Time complexity: O(n)
+pub fn validate_order(order: &[Visit], n: usize) -> Result<(), SonarError> {
+    if order.len() != n {
+        return Err(SonarError::SizeMismatch { expected: n, found: order.len() });
+    }
+    let mut seen = vec![false; n];
+    for visit in order {
+        if visit.polyline >= n || core::mem::replace(&mut seen[visit.polyline], true) {
+            return Err(SonarError::InvalidTour);
+        }
+    }
+    Ok(())
+}
+
+/// Pen-up travel between consecutive strokes of `order`
+/// Time complexity: O(n)
+pub fn travel(polylines: &[Polyline], order: &[Visit]) -> f64 {
+    order.windows(2).map(|w| distance(w[0].exit(polylines), w[1].entry(polylines))).sum()
+}
+
+/// Nearest neighbor route starting with `first` drawn forwards
+/// From where the pen lifts, the next stroke is the one with the closest
+/// undrawn end, entered at that end.
+/// Time complexity: O(n^2)
+pub fn nearest_neighbor(polylines: &[Polyline], first: usize) -> Result<Vec<Visit>, SonarError> {
+    validate_polylines(polylines)?;
+    let n = polylines.len();
+    if first >= n {
+        return Err(SonarError::InvalidParameter { name: "first", reason: "must be a polyline index" });
+    }
+    let mut drawn = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    let mut current = Visit { polyline: first, reversed: false };
+    loop {
+        drawn[current.polyline] = true;
+        order.push(current);
+        let pen = current.exit(polylines);
+        let mut nearest: Option<(f64, Visit)> = None;
+        for (polyline, line) in polylines.iter().enumerate().filter(|&(i, _)| !drawn[i]) {
+            for (reversed, entry) in [(false, line.start()), (true, line.end())] {
+                let gap = distance(pen, entry);
+                if nearest.is_none_or(|(best, _)| gap < best) {
+                    nearest = Some((gap, Visit { polyline, reversed }));
+                }
+            }
+        }
+        match nearest {
+            Some((_, next)) => current = next,
+            None => return Ok(order),
+        }
+    }
+}
+
+/// Greedy edge route: pen-up links between the ends of different polylines
+/// are taken shortest first while each end gets at most one link and no
+/// cycle closes, which chains all strokes into one path
+/// Endpoint 2i is the start of polyline i and 2i + 1 its end.
+/// Time complexity: O(n^2 log n)
+pub fn greedy(polylines: &[Polyline]) -> Result<Vec<Visit>, SonarError> {
+    validate_polylines(polylines)?;
+    let n = polylines.len();
+    let endpoint = |e: usize| if e.is_multiple_of(2) { polylines[e / 2].start() } else { polylines[e / 2].end() };
+    let mut links: Vec<(f64, usize, usize)> = Vec::with_capacity(2 * n * n);
+    for a in 0..2 * n {
+        for b in (a / 2 + 1) * 2..2 * n {
+            links.push((distance(endpoint(a), endpoint(b)), a, b));
+        }
+    }
+    // Equal lengths are ordered by endpoints so the result is deterministic
+    links.sort_unstable_by(|x, y| x.0.total_cmp(&y.0).then((x.1, x.2).cmp(&(y.1, y.2))));
+
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+    let mut parent: Vec<usize> = (0..n).collect();
+    let mut linked = vec![usize::MAX; 2 * n];
+    let mut count = 0;
+    for (_, a, b) in links {
+        if count + 1 == n {
+            break;
+        }
+        if linked[a] != usize::MAX || linked[b] != usize::MAX {
+            continue;
+        }
+        let (root_a, root_b) = (find(&mut parent, a / 2), find(&mut parent, b / 2));
+        if root_a == root_b {
+            continue;
+        }
+        parent[root_a] = root_b;
+        linked[a] = b;
+        linked[b] = a;
+        count += 1;
+    }
+
+    // Walk the path from a polyline with an unlinked end, entering there
+    let start = (0..2 * n).find(|&e| linked[e] == usize::MAX).unwrap_or(0);
+    let mut order = Vec::with_capacity(n);
+    let mut entry = start;
+    loop {
+        order.push(Visit { polyline: entry / 2, reversed: entry % 2 == 1 });
+        let exit = entry ^ 1;
+        match linked[exit] {
+            usize::MAX => return Ok(order),
+            next => entry = next,
+        }
+    }
+}
+
+/// 2-opt over the route: reversing the strokes from position i to j also
+/// flips each of them, which replaces the links into i and out of j
+/// A reversal of a single stroke just flips it. The ends of the route are
+/// open, so a reversal touching them changes one link only.
+/// Time complexity: O(n^2) per iteration
+pub fn two_opt(polylines: &[Polyline], order: &[Visit], max_iterations: usize) -> Result<Vec<Visit>, SonarError> {
+    validate_polylines(polylines)?;
+    validate_order(order, polylines.len())?;
+    let n = order.len();
+    let mut order = order.to_vec();
+
+    for _ in 0..max_iterations {
+        let mut improved = false;
+        for i in 0..n {
+            for j in i..n {
+                let (b, c) = (order[i].entry(polylines), order[j].exit(polylines));
+                let mut delta = 0.0;
+                if i > 0 {
+                    let a = order[i - 1].exit(polylines);
+                    delta += distance(a, c) - distance(a, b);
+                }
+                if j + 1 < n {
+                    let d = order[j + 1].entry(polylines);
+                    delta += distance(b, d) - distance(c, d);
+                }
+                if delta < -1e-10 {
+                    order[i..=j].reverse();
+                    for visit in &mut order[i..=j] {
+                        visit.reversed = !visit.reversed;
+                    }
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    Ok(order)
+}
+
+/// Greedy route improved by 2-opt
+/// Time complexity: O(n^2 log n) + O(n^2) per 2-opt iteration
+pub fn generate_order(polylines: &[Polyline], max_iterations: usize) -> Result<Vec<Visit>, SonarError> {
+    let initial = greedy(polylines)?;
+    two_opt(polylines, &initial, max_iterations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_strokes_are_ordered_and_flipped() {
+        // Horizontal hatching, every line drawn left to right as given
+        let point = |x: f64, y: f64| Point { x, y, angle: 0.0, id: 0 };
+        let line = |y: f64| Polyline { points: vec![point(0.0, y), point(0.5, y), point(1.0, y)] };
+        let hatching: Vec<Polyline> = (0..6).map(|k| line(k as f64)).collect();
+        let given: Vec<Visit> = (0..6).map(|polyline| Visit { polyline, reversed: false }).collect();
+        assert!((travel(&hatching, &given) - 5.0 * 2f64.sqrt()).abs() < 1e-9);
+        // Boustrophedon: one unit up between lines, alternating direction
+        for order in [nearest_neighbor(&hatching, 0).unwrap(), generate_order(&hatching, 100).unwrap()] {
+            validate_order(&order, 6).unwrap();
+            assert!((travel(&hatching, &order) - 5.0).abs() < 1e-9, "{:?}", order);
+        }
+
+        let ends = generate_normalized_points(80, 40, 2);
+        let strokes: Vec<Polyline> = ends.chunks(2).map(|pair| Polyline { points: pair.to_vec() }).collect();
+        let given: Vec<Visit> = (0..40).map(|polyline| Visit { polyline, reversed: false }).collect();
+        let greedy_order = greedy(&strokes).unwrap();
+        let improved = two_opt(&strokes, &greedy_order, 100).unwrap();
+        validate_order(&improved, 40).unwrap();
+        assert!(travel(&strokes, &improved) <= travel(&strokes, &greedy_order));
+        assert!(travel(&strokes, &greedy_order) < travel(&strokes, &given) / 2.0);
+        assert_eq!(validate_order(&given[..39], 40), Err(SonarError::SizeMismatch { expected: 40, found: 39 }));
+        assert!(greedy(&[]).is_err());
+    }
+}
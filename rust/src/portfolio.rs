@@ -0,0 +1,237 @@
+//! Portfolio solver - race several algorithms against one deadline, keep the best tour
+//!
+//! Every member runs on its own thread. Stepwise improvers stop at the deadline
+//! with the best tour they have so far; one-shot constructions cannot be
+//! interrupted, so a member still running at the deadline is reported as
+//! unfinished and its thread left to end on its own, its result discarded.
+
+use crate::error::{validate_tour, SonarError};
+use crate::nearest_neighbor;
+use crate::problem::ProblemInstance;
+use crate::registry;
+use crate::sanitize::Limits;
+use crate::stepwise::SteppableSolver;
+use crate::two_opt::TwoOptSolver;
+#[cfg(feature = "metaheuristics")]
+use crate::simulated_annealing::{AnnealingSolver, MoveKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Shared stop signal: the deadline passed or the race was called off
+#[derive(Debug)]
+pub struct Cancellation {
+    deadline: Instant,
+    cancelled: AtomicBool,
+}
+
+impl Cancellation {
+    pub fn new(deadline: Instant) -> Self {
+        Cancellation { deadline, cancelled: AtomicBool::new(false) }
+    }
+
+    /// Stop every member at its next check
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || Instant::now() >= self.deadline
+    }
+}
+
+/// Runs a member; stepwise members check `Cancellation` between steps
+pub type Run = fn(&ProblemInstance, &Cancellation) -> Result<Vec<usize>, SonarError>;
+
+/// An algorithm in the portfolio
+#[derive(Debug, Clone, Copy)]
+pub struct Member {
+    /// Registry key of the algorithm, or of the one it steps through
+    pub key: &'static str,
+    pub needs_matrix: bool,
+    pub run: Run,
+}
+
+/// Run the registered algorithm `key` with its defaults
+fn registered(key: &str, instance: &ProblemInstance) -> Result<Vec<usize>, SonarError> {
+    (registry::find(key).expect("portfolio members are registered").solve)(instance)
+}
+
+/// Step `solver` until it finishes or is cancelled, returning its best tour
+fn step_until_cancelled(solver: &mut impl SteppableSolver, cancel: &Cancellation) -> Vec<usize> {
+    while !solver.is_finished() && !cancel.is_cancelled() {
+        solver.step();
+    }
+    solver.tour().to_vec()
+}
+
+/// The default portfolio: fast constructions for a tour early on, then
+/// improvers that use whatever time is left
+pub const MEMBERS: &[Member] = &[
+    Member { key: "sonar-visit", needs_matrix: false, run: |instance, _| registered("sonar-visit", instance) },
+    Member { key: "multilevel", needs_matrix: false, run: |instance, _| registered("multilevel", instance) },
+    Member { key: "greedy-edge", needs_matrix: true, run: |instance, _| registered("greedy-edge", instance) },
+    Member { key: "or-one", needs_matrix: true, run: |instance, _| registered("or-one", instance) },
+    Member {
+        key: "two-opt",
+        needs_matrix: true,
+        run: |instance, cancel| {
+            let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+            let mut solver = TwoOptSolver::new(&initial, instance.matrix(), usize::MAX)?;
+            Ok(step_until_cancelled(&mut solver, cancel))
+        },
+    },
+    #[cfg(feature = "metaheuristics")]
+    Member {
+        key: "simulated-annealing",
+        needs_matrix: true,
+        run: |instance, cancel| {
+            let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+            let moves = [(MoveKind::Reversal, 0.6), (MoveKind::Insertion, 0.3), (MoveKind::Swap, 0.1)];
+            let mut solver = AnnealingSolver::new(instance.matrix(), &initial, 1_000_000, 1.0, 0.99999, &moves, 5_000)?;
+            Ok(step_until_cancelled(&mut solver, cancel))
+        },
+    },
+];
+
+/// How one member of the race ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// A valid tour of this length, found in this time
+    Solved { length: f64, time: Duration },
+    Failed(SonarError),
+    /// Not started, for the given reason
+    Skipped(&'static str),
+    /// Still running at the deadline
+    Unfinished,
+}
+
+/// One member's part in the race
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub key: &'static str,
+    pub outcome: Outcome,
+}
+
+/// The best tour of a race and how every member did
+#[derive(Debug, Clone)]
+pub struct PortfolioResult {
+    pub tour: Vec<usize>,
+    pub length: f64,
+    /// Key of the member that found `tour`
+    pub winner: &'static str,
+    pub reports: Vec<Report>,
+}
+
+/// Race the `MEMBERS` on `instance` until `deadline` and return the shortest valid tour
+/// Members needing a distance matrix are skipped on instances too large for
+/// one (`Limits::max_matrix_cities`). If no member has a tour by the
+/// deadline, the first valid tour to arrive is returned; an error is returned
+/// only when every member failed.
+pub fn solve(instance: Arc<ProblemInstance>, deadline: Instant) -> Result<PortfolioResult, SonarError> {
+    solve_with(MEMBERS, instance, deadline)
+}
+
+/// `solve` with another set of members
+pub fn solve_with(
+    members: &[Member],
+    instance: Arc<ProblemInstance>,
+    deadline: Instant,
+) -> Result<PortfolioResult, SonarError> {
+    let n = instance.len();
+    let cancel = Arc::new(Cancellation::new(deadline));
+    let (sender, receiver) = mpsc::channel();
+    let mut outcomes: Vec<Option<Outcome>> = vec![None; members.len()];
+    let mut pending = 0;
+    for (index, member) in members.iter().enumerate() {
+        if member.needs_matrix && n > Limits::default().max_matrix_cities {
+            outcomes[index] = Some(Outcome::Skipped("too many cities for a distance matrix"));
+            continue;
+        }
+        let (instance, cancel, sender, run) = (Arc::clone(&instance), Arc::clone(&cancel), sender.clone(), member.run);
+        thread::spawn(move || {
+            let start = Instant::now();
+            let result = run(&instance, &cancel);
+            // The race may be over already; then nobody listens
+            let _ = sender.send((index, result, start.elapsed()));
+        });
+        pending += 1;
+    }
+    drop(sender);
+
+    let mut best: Option<(Vec<usize>, f64, usize)> = None;
+    while pending > 0 {
+        let message = match best {
+            None => receiver.recv().ok(),
+            Some(_) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())).ok(),
+        };
+        let Some((index, result, time)) = message else { break };
+        pending -= 1;
+        let outcome = match result.and_then(|tour| validate_tour(&tour, n).map(|_| tour)) {
+            Ok(tour) => {
+                let length = instance.tour_length(&tour);
+                if best.as_ref().is_none_or(|&(_, best_length, _)| length < best_length) {
+                    best = Some((tour, length, index));
+                }
+                Outcome::Solved { length, time }
+            }
+            Err(error) => Outcome::Failed(error),
+        };
+        outcomes[index] = Some(outcome);
+    }
+    cancel.cancel();
+
+    let reports = members
+        .iter()
+        .zip(outcomes)
+        .map(|(member, outcome)| Report { key: member.key, outcome: outcome.unwrap_or(Outcome::Unfinished) })
+        .collect::<Vec<_>>();
+    match best {
+        Some((tour, length, index)) => Ok(PortfolioResult { tour, length, winner: members[index].key, reports }),
+        None => Err(reports
+            .into_iter()
+            .find_map(|report| match report.outcome {
+                Outcome::Failed(error) => Some(error),
+                _ => None,
+            })
+            .unwrap_or(SonarError::EmptyInput)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::Metric;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_race_returns_the_best_member() {
+        let points = generate_normalized_points(150, 40, 5);
+        let instance = Arc::new(ProblemInstance::new(points, Metric::Euclidean).unwrap());
+        let result = solve(Arc::clone(&instance), Instant::now() + Duration::from_millis(300)).unwrap();
+        assert_eq!(result.reports.len(), MEMBERS.len());
+        validate_tour(&result.tour, 150).unwrap();
+        for report in &result.reports {
+            if let Outcome::Solved { length, .. } = report.outcome {
+                assert!(result.length <= length, "{} beat the winner", report.key);
+            }
+        }
+        assert!(result.reports.iter().any(|r| r.key == result.winner && matches!(r.outcome, Outcome::Solved { .. })));
+
+        // A member that never finishes in time does not hold up the result
+        let slow = Member {
+            key: "slow",
+            needs_matrix: false,
+            run: |_, _| {
+                thread::sleep(Duration::from_secs(2));
+                Err(SonarError::EmptyInput)
+            },
+        };
+        let members = [slow, MEMBERS[0]];
+        let started = Instant::now();
+        let raced = solve_with(&members, instance, Instant::now() + Duration::from_millis(50)).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!((raced.winner, &raced.reports[0].outcome), ("sonar-visit", &Outcome::Unfinished));
+    }
+}
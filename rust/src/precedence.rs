@@ -0,0 +1,271 @@
+//! Precedence constraints: pickup before delivery
+//!
+//! A pair (a, b) requires city a to be visited before city b. A tour is a
+//! cycle, so "before" is read from a depot: the route starts there, and a must
+//! come earlier than b on the way round from it. Cheapest insertion and Or-opt
+//! here only ever build or keep tours that honour every pair, and
+//! `simulated_annealing::AnnealingSolver::with_precedence` makes annealing do
+//! the same.
+
+use alloc::{vec, vec::Vec};
+use crate::error::{validate_graph, validate_size, validate_tour, SonarError};
+
+/// Longest run of consecutive cities Or-opt moves at once
+const MAX_SEGMENT: usize = 3;
+
+/// Precedence pairs over n cities, read from a depot
+#[derive(Debug, Clone, PartialEq)]
+pub struct Precedence {
+    depot: usize,
+    pairs: Vec<(usize, usize)>,
+    /// Cities that must be visited before each city
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl Precedence {
+    /// Pairs `(before, after)` over cities 0..n, with the route starting at `depot`
+    /// Fails if a city is out of range, the depot has to come after another
+    /// city, or the pairs form a cycle, so that no tour could honour them.
+    /// Time complexity: O(n + pairs)
+    pub fn new(n: usize, depot: usize, pairs: &[(usize, usize)]) -> Result<Self, SonarError> {
+        if depot >= n {
+            return Err(SonarError::InvalidParameter { name: "depot", reason: "must be a city index" });
+        }
+        let mut predecessors = vec![Vec::new(); n];
+        for &(before, after) in pairs {
+            if before >= n || after >= n {
+                return Err(SonarError::InvalidParameter { name: "precedence", reason: "pairs must be city indices" });
+            }
+            if after == depot || before == after {
+                return Err(SonarError::InvalidParameter {
+                    name: "precedence",
+                    reason: "nothing can come before the depot or before itself",
+                });
+            }
+            predecessors[after].push(before);
+        }
+        let precedence = Precedence { depot, pairs: pairs.to_vec(), predecessors };
+        if precedence.topological_order().len() < n {
+            return Err(SonarError::InvalidParameter { name: "precedence", reason: "pairs form a cycle" });
+        }
+        Ok(precedence)
+    }
+
+    pub fn depot(&self) -> usize {
+        self.depot
+    }
+
+    pub fn pairs(&self) -> &[(usize, usize)] {
+        &self.pairs
+    }
+
+    /// Number of cities
+    pub fn len(&self) -> usize {
+        self.predecessors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.predecessors.is_empty()
+    }
+
+    /// Cities in an order that honours every pair (Kahn's algorithm); shorter
+    /// than n if the pairs form a cycle
+    fn topological_order(&self) -> Vec<usize> {
+        let n = self.len();
+        let mut pending: Vec<usize> = self.predecessors.iter().map(Vec::len).collect();
+        let mut successors = vec![Vec::new(); n];
+        for &(before, after) in &self.pairs {
+            successors[before].push(after);
+        }
+        let mut order: Vec<usize> = (0..n).filter(|&city| pending[city] == 0).collect();
+        let mut next = 0;
+        while next < order.len() {
+            for &after in &successors[order[next]] {
+                pending[after] -= 1;
+                if pending[after] == 0 {
+                    order.push(after);
+                }
+            }
+            next += 1;
+        }
+        order
+    }
+
+    /// The first pair `tour` visits in the wrong order, read from the depot
+    /// Time complexity: O(n + pairs)
+    pub fn first_violation(&self, tour: &[usize]) -> Option<(usize, usize)> {
+        let n = tour.len();
+        let mut position = vec![0; n];
+        for (i, &city) in tour.iter().enumerate() {
+            position[city] = i;
+        }
+        let from_depot = |city: usize| (position[city] + n - position[self.depot]) % n;
+        self.pairs.iter().copied().find(|&(before, after)| from_depot(before) > from_depot(after))
+    }
+
+    /// Whether `tour` visits every pair in order, read from the depot
+    /// Time complexity: O(n + pairs)
+    pub fn is_satisfied(&self, tour: &[usize]) -> bool {
+        self.first_violation(tour).is_none()
+    }
+
+    /// Check that `tour` visits each city exactly once and honours every pair
+    /// Time complexity: O(n + pairs)
+    pub fn validate(&self, tour: &[usize]) -> Result<(), SonarError> {
+        validate_tour(tour, self.len())?;
+        match self.first_violation(tour) {
+            Some((before, after)) => Err(SonarError::PrecedenceViolated { before, after }),
+            None => Ok(()),
+        }
+    }
+}
+
+fn validate_instance(graph: &[Vec<f64>], precedence: &Precedence) -> Result<(), SonarError> {
+    validate_graph(graph)?;
+    validate_size(precedence.len(), graph)
+}
+
+/// Cheapest insertion honouring `precedence`, starting from the depot
+/// A city becomes available once all the cities it must follow are in the
+/// tour; of the available cities, the one with the cheapest insertion point
+/// after the last of those is inserted next. The tour returned starts at the
+/// depot.
+/// Time complexity: O(n^3)
+pub fn insertion(graph: &[Vec<f64>], precedence: &Precedence) -> Result<Vec<usize>, SonarError> {
+    validate_instance(graph, precedence)?;
+    let n = graph.len();
+    let mut tour = Vec::with_capacity(n);
+    tour.push(precedence.depot);
+    let mut position = vec![usize::MAX; n];
+    position[precedence.depot] = 0;
+
+    while tour.len() < n {
+        let available = (0..n).filter(|&city| {
+            position[city] == usize::MAX && precedence.predecessors[city].iter().all(|&p| position[p] != usize::MAX)
+        });
+        // (cost, city, index of the tour city it goes after)
+        let mut best: Option<(f64, usize, usize)> = None;
+        for city in available {
+            let earliest = precedence.predecessors[city].iter().map(|&p| position[p]).max().unwrap_or(0);
+            for after in earliest..tour.len() {
+                let (x, y) = (tour[after], tour[(after + 1) % tour.len()]);
+                let cost = graph[x][city] + graph[city][y] - graph[x][y];
+                if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, city, after));
+                }
+            }
+        }
+        // Every city is eventually available, as `Precedence::new` rejects cycles
+        let (_, city, after) = best.expect("pairs are acyclic");
+        tour.insert(after + 1, city);
+        for (i, &city) in tour.iter().enumerate().skip(after + 1) {
+            position[city] = i;
+        }
+    }
+    Ok(tour)
+}
+
+/// Or-opt honouring `precedence`: move runs of up to three consecutive cities,
+/// forwards or reversed, to a better place in the tour
+/// A move is only made if it shortens the tour and every pair stays in order;
+/// the depot is never moved. `tour` must already honour the pairs.
+/// Time complexity: O(n^2) evaluations per iteration, plus O(n + pairs) per improving move checked
+pub fn or_opt(
+    tour: &[usize],
+    graph: &[Vec<f64>],
+    precedence: &Precedence,
+    max_iterations: usize,
+) -> Result<Vec<usize>, SonarError> {
+    validate_instance(graph, precedence)?;
+    precedence.validate(tour)?;
+    let n = tour.len();
+    // Read from the depot, so a move never wraps around it
+    let start = tour.iter().position(|&city| city == precedence.depot).unwrap_or(0);
+    let mut tour: Vec<usize> = tour[start..].iter().chain(&tour[..start]).copied().collect();
+
+    for _ in 0..max_iterations {
+        let mut improved = false;
+        for length in 1..=MAX_SEGMENT.min(n.saturating_sub(2)) {
+            let mut from = 1;
+            while from + length <= n {
+                let (prev, next) = (tour[from - 1], tour[(from + length) % n]);
+                let (first, last) = (tour[from], tour[from + length - 1]);
+                let removal_gain = graph[prev][first] + graph[last][next] - graph[prev][next];
+                let mut moved = false;
+                // Insert between tour[to] and the city after it, outside the run
+                for to in (0..n).filter(|&to| to + 1 < from || to > from + length - 1) {
+                    let (x, y) = (tour[to], tour[(to + 1) % n]);
+                    for reversed in [false, true] {
+                        let (head, tail) = if reversed { (last, first) } else { (first, last) };
+                        let gain = removal_gain - (graph[x][head] + graph[tail][y] - graph[x][y]);
+                        if gain <= 1e-10 {
+                            continue;
+                        }
+                        let mut run: Vec<usize> = tour[from..from + length].to_vec();
+                        if reversed {
+                            run.reverse();
+                        }
+                        let rest = tour[..from].iter().chain(&tour[from + length..]);
+                        let mut candidate: Vec<usize> = rest.copied().collect();
+                        let at = if to < from { to + 1 } else { to + 1 - length };
+                        candidate.splice(at..at, run);
+                        if precedence.is_satisfied(&candidate) {
+                            tour = candidate;
+                            moved = true;
+                            break;
+                        }
+                    }
+                    if moved {
+                        break;
+                    }
+                }
+                improved |= moved;
+                from += 1;
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    Ok(tour)
+}
+
+#[cfg(all(test, feature = "metaheuristics"))]
+mod tests {
+    use super::*;
+    use crate::simulated_annealing::{AnnealingSolver, MoveKind};
+    use crate::stepwise::SteppableSolver;
+    use crate::utils::{calculate_tour_length, create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_pickups_come_before_deliveries() {
+        let points = generate_normalized_points(30, 40, 6);
+        let graph = create_distance_matrix(&points);
+        // Ten pickup/delivery pairs, each delivery far round the free tour from its pickup
+        let pairs: Vec<(usize, usize)> = (1..=10).map(|i| (i + 10 * (i % 2), i + 10 * (1 - i % 2))).collect();
+        let precedence = Precedence::new(30, 0, &pairs).unwrap();
+
+        let built = insertion(&graph, &precedence).unwrap();
+        assert_eq!(built[0], 0);
+        precedence.validate(&built).unwrap();
+        let improved = or_opt(&built, &graph, &precedence, 100).unwrap();
+        precedence.validate(&improved).unwrap();
+        assert!(calculate_tour_length(&improved, &graph) < calculate_tour_length(&built, &graph));
+
+        let moves = [(MoveKind::Reversal, 0.4), (MoveKind::Insertion, 0.4), (MoveKind::Swap, 0.2)];
+        let annealed = AnnealingSolver::new(&graph, &built, 20_000, 0.05, 0.9995, &moves, 20_000)
+            .unwrap()
+            .with_precedence(&precedence)
+            .unwrap()
+            .run();
+        precedence.validate(&annealed).unwrap();
+        assert!(calculate_tour_length(&annealed, &graph) < calculate_tour_length(&built, &graph));
+
+        let reversed: Vec<usize> = built.iter().rev().copied().collect();
+        assert!(matches!(precedence.validate(&reversed), Err(SonarError::PrecedenceViolated { .. })));
+        assert!(or_opt(&reversed, &graph, &precedence, 100).is_err());
+        assert!(Precedence::new(3, 0, &[(1, 2), (2, 1)]).is_err());
+        assert!(Precedence::new(3, 0, &[(1, 0)]).is_err());
+        assert!(Precedence::new(3, 0, &[(1, 3)]).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Problem instances: points, metric and a lazily built distance matrix
+
+use crate::error::{validate_graph, validate_points, validate_size, SonarError};
+use crate::fixed::{self, to_fixed_point};
+use crate::provider::DistanceProvider;
+use crate::utils::{compensated_sum, distance, symmetric_matrix, DistanceMatrix, Point};
+use std::sync::OnceLock;
+
+/// How the distance between two points is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Metric {
+    /// Straight-line distance
+    #[default]
+    Euclidean,
+    /// TSPLIB `EUC_2D`: straight-line distance rounded to the nearest integer
+    /// (`nint`), so tour lengths match published optimal values exactly
+    Euc2d,
+    /// Straight-line distance computed in `fixed::Fixed` arithmetic, so every
+    /// distance, and every tour chosen by comparing them, is bit-identical
+    /// across platforms; points must lie within `fixed::MAX_DISTANCE` of each
+    /// other, and instances outside it are rejected
+    Fixed,
+    /// Great-circle distance in km on a spherical Earth, for points with
+    /// x = longitude and y = latitude in degrees
+    Haversine,
+}
+
+/// Mean Earth radius in km, as used by `Metric::Haversine`
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+impl Metric {
+    /// Distance between two points under this metric
+    pub fn distance(&self, a: &Point, b: &Point) -> f64 {
+        match self {
+            Metric::Euclidean => distance(a, b),
+            Metric::Euc2d => (distance(a, b) + 0.5).floor(),
+            Metric::Fixed => distance(&to_fixed_point(a), &to_fixed_point(b)).to_f64(),
+            Metric::Haversine => {
+                let (lat_a, lat_b) = (a.y.to_radians(), b.y.to_radians());
+                let half_lat = (lat_b - lat_a) / 2.0;
+                let half_lon = (b.x - a.x).to_radians() / 2.0;
+                let h = half_lat.sin().powi(2) + lat_a.cos() * lat_b.cos() * half_lon.sin().powi(2);
+                2.0 * EARTH_RADIUS_KM * h.sqrt().min(1.0).asin()
+            }
+        }
+    }
+}
+
+/// Length of a closed tour measured from the points under `metric`, without a matrix
+/// For instances far too large for an O(n^2) matrix, such as the tours of
+/// angular sort, sonar visit or the grid nearest neighbor at millions of
+/// points. Summed with Kahan compensation, since these lengths decide comparisons.
+/// Time complexity: O(n)
+pub fn calculate_tour_length_points(tour: &[usize], points: &[Point], metric: &Metric) -> f64 {
+    let n = tour.len();
+    compensated_sum((0..n).map(|i| metric.distance(&points[tour[i]], &points[tour[(i + 1) % n]])))
+}
+
+/// `validate_points`, and for `Metric::Fixed` that the points lie within its range
+fn validate_metric_points(points: &[Point], metric: Metric) -> Result<(), SonarError> {
+    validate_points(points)?;
+    if metric == Metric::Fixed {
+        fixed::validate_range(points)?;
+    }
+    Ok(())
+}
+
+/// A TSP instance handed to every solver
+/// The distance matrix is built on first use and cached, so solvers run one
+/// after another on the same instance share it, and coordinate-based solvers
+/// never pay for it. The cache is thread-safe, so an instance can be shared
+/// across threads.
+#[derive(Debug)]
+pub struct ProblemInstance {
+    points: Vec<Point>,
+    metric: Metric,
+    matrix: OnceLock<DistanceMatrix>,
+}
+
+impl ProblemInstance {
+    /// Create an instance; the matrix is built lazily on the first `matrix()` call
+    pub fn new(points: Vec<Point>, metric: Metric) -> Result<Self, SonarError> {
+        validate_metric_points(&points, metric)?;
+        Ok(ProblemInstance { points, metric, matrix: OnceLock::new() })
+    }
+
+    /// Create an instance with a precomputed matrix, e.g. one loaded from disk
+    pub fn with_matrix(points: Vec<Point>, metric: Metric, matrix: DistanceMatrix) -> Result<Self, SonarError> {
+        validate_metric_points(&points, metric)?;
+        validate_graph(&matrix)?;
+        validate_size(points.len(), &matrix)?;
+        Ok(ProblemInstance { points, metric, matrix: OnceLock::from(matrix) })
+    }
+
+    /// Create an instance whose matrix holds `provider`'s costs instead of
+    /// distances under a metric, e.g. road-network travel times
+    /// The solvers assume symmetric costs, so the two directions between each
+    /// pair of cities are averaged. The points are still used by the solvers
+    /// that work on coordinates alone, measured as Euclidean.
+    /// Time complexity: O(n^2) provider calls
+    pub fn from_provider(points: Vec<Point>, provider: &dyn DistanceProvider) -> Result<Self, SonarError> {
+        validate_points(&points)?;
+        if provider.cities() != points.len() {
+            return Err(SonarError::SizeMismatch { expected: points.len(), found: provider.cities() });
+        }
+        let matrix = symmetric_matrix(points.len(), |i, j| (provider.distance(i, j) + provider.distance(j, i)) / 2.0);
+        ProblemInstance::with_matrix(points, Metric::Euclidean, matrix)
+    }
+
+    /// The same cities at `points`, keeping the metric and any built matrix
+    /// For solvers that expect coordinates in another frame, such as the unit
+    /// square, while distances stay in the instance's own units. The matrix
+    /// then no longer matches the points, so lengths must come from it: build
+    /// it before the call.
+    pub fn with_points(self, points: Vec<Point>) -> Result<Self, SonarError> {
+        validate_metric_points(&points, self.metric)?;
+        if points.len() != self.points.len() {
+            return Err(SonarError::SizeMismatch { expected: self.points.len(), found: points.len() });
+        }
+        Ok(ProblemInstance { points, ..self })
+    }
+
+    /// Number of cities
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Always false: instances are validated to hold at least one point
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    /// The full distance matrix, built on first call (in parallel for large n) and cached
+    /// Time complexity: O(n^2) on the first call, O(1) afterwards
+    pub fn matrix(&self) -> &DistanceMatrix {
+        self.matrix.get_or_init(|| {
+            let points = &self.points;
+            symmetric_matrix(points.len(), |i, j| self.metric.distance(&points[i], &points[j]))
+        })
+    }
+
+    /// The distance matrix if it has already been built
+    pub fn cached_matrix(&self) -> Option<&DistanceMatrix> {
+        self.matrix.get()
+    }
+
+    /// Distance between cities i and j, from the cached matrix when available
+    pub fn distance(&self, i: usize, j: usize) -> f64 {
+        match self.matrix.get() {
+            Some(matrix) => matrix[i][j],
+            None => self.metric.distance(&self.points[i], &self.points[j]),
+        }
+    }
+
+    /// Length of a closed tour, without building the matrix
+    /// Summed with Kahan compensation, since these lengths decide comparisons
+    /// Time complexity: O(n)
+    pub fn tour_length(&self, tour: &[usize]) -> f64 {
+        match self.matrix.get() {
+            Some(matrix) => {
+                let n = tour.len();
+                compensated_sum((0..n).map(|i| matrix[tour[i]][tour[(i + 1) % n]]))
+            }
+            None => calculate_tour_length_points(tour, &self.points, &self.metric),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{calculate_tour_length, create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_matrix_is_built_once_and_shared() {
+        let points = generate_normalized_points(30, 20, 4);
+        let expected = create_distance_matrix(&points);
+        let instance = ProblemInstance::new(points, Metric::Euclidean).unwrap();
+
+        let tour: Vec<usize> = (0..instance.len()).collect();
+        let lazy_length = instance.tour_length(&tour);
+        assert!(instance.cached_matrix().is_none());
+
+        let first = instance.matrix() as *const DistanceMatrix;
+        assert!(std::ptr::eq(first, instance.matrix()));
+        assert_eq!(instance.matrix(), &expected);
+        assert!((lazy_length - calculate_tour_length(&tour, &expected)).abs() < 1e-12);
+
+        let square = vec![
+            Point { x: 0.0, y: 0.0, angle: 0.0, id: 0 },
+            Point { x: 2.5, y: 0.0, angle: 0.0, id: 1 },
+            Point { x: 2.5, y: 1.4, angle: 0.0, id: 2 },
+            Point { x: 0.0, y: 1.4, angle: 0.0, id: 3 },
+        ];
+        let rounded = ProblemInstance::new(square, Metric::Euc2d).unwrap();
+        assert_eq!(rounded.distance(0, 1), 3.0);
+        assert_eq!(rounded.distance(1, 2), 1.0);
+        assert_eq!(rounded.distance(0, 2), 3.0);
+        assert_eq!(rounded.tour_length(&[0, 1, 2, 3]), 8.0);
+        assert_eq!(Metric::Fixed.distance(&rounded.points()[0], &rounded.points()[1]), 2.5);
+        let wide = vec![Point { x: 0.0, y: 0.0, angle: 0.0, id: 0 }, Point { x: 50_000.0, y: 0.0, angle: 0.0, id: 1 }];
+        assert!(ProblemInstance::new(wide.clone(), Metric::Euclidean).is_ok());
+        let err = ProblemInstance::new(wide, Metric::Fixed).unwrap_err();
+        assert_eq!(err, SonarError::CoordinateOutOfRange { index: 1 });
+        assert_eq!(calculate_tour_length_points(&[0, 1, 2, 3], rounded.points(), &Metric::Euclidean), 7.8);
+
+        assert_eq!(ProblemInstance::new(Vec::new(), Metric::Euclidean).unwrap_err(), SonarError::EmptyInput);
+        let short = ProblemInstance::with_matrix(generate_normalized_points(3, 20, 4), Metric::Euclidean, expected);
+        assert!(matches!(short, Err(SonarError::SizeMismatch { .. })));
+
+        // Moved points keep the rounded distances of the matrix built before
+        rounded.matrix();
+        let shrunk = rounded.points().iter().map(|p| Point { x: p.x / 10.0, y: p.y / 10.0, ..*p }).collect();
+        let shrunk = rounded.with_points(shrunk).unwrap();
+        assert_eq!(shrunk.tour_length(&[0, 1, 2, 3]), 8.0);
+        assert_eq!(shrunk.points()[1].x, 0.25);
+        let one = vec![Point { x: 0.0, y: 0.0, angle: 0.0, id: 0 }];
+        assert!(matches!(shrunk.with_points(one), Err(SonarError::SizeMismatch { .. })));
+    }
+}
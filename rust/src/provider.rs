@@ -0,0 +1,128 @@
+//! Distances from outside the points' geometry
+//!
+//! Road-network travel times, costs cached from a routing API or any other
+//! precomputed matrix can stand in for the metric: `ProblemInstance::from_provider`
+//! fills the instance's distance matrix from a `DistanceProvider`, and every
+//! matrix-based solver then optimizes those costs. Solvers that work on the
+//! coordinates alone still use the points.
+
+use crate::matrix_cache::MappedMatrix;
+use crate::utils::DistanceMatrix;
+use std::io;
+use std::path::Path;
+
+/// A source of the cost of travelling between any two cities
+pub trait DistanceProvider: Sync {
+    /// Number of cities
+    fn cities(&self) -> usize;
+
+    /// Cost of travelling from city i to city j; infinite if there is no way
+    fn distance(&self, i: usize, j: usize) -> f64;
+}
+
+impl DistanceProvider for DistanceMatrix {
+    fn cities(&self) -> usize {
+        self.len()
+    }
+
+    fn distance(&self, i: usize, j: usize) -> f64 {
+        self[i][j]
+    }
+}
+
+impl DistanceProvider for MappedMatrix {
+    fn cities(&self) -> usize {
+        self.len()
+    }
+
+    fn distance(&self, i: usize, j: usize) -> f64 {
+        self.get(i, j)
+    }
+}
+
+/// A square matrix read from a text file, one row per line with the entries
+/// separated by commas or whitespace, as most routing tools export them
+/// Blank lines and lines starting with `#` are skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMatrix {
+    rows: DistanceMatrix,
+}
+
+impl TextMatrix {
+    /// Parse the text of a matrix file
+    /// Time complexity: O(n^2)
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut rows = Vec::new();
+        for line in content.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let invalid = |entry: &str| format!("invalid distance in row {}: {}", rows.len() + 1, entry);
+            let row: Result<Vec<f64>, String> = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| entry.parse().map_err(|_| invalid(entry)))
+                .collect();
+            rows.push(row?);
+        }
+        if rows.is_empty() {
+            return Err("no rows".to_string());
+        }
+        if let Some((i, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != rows.len()) {
+            return Err(format!("row {} has {} entries, expected {}", i + 1, row.len(), rows.len()));
+        }
+        Ok(TextMatrix { rows })
+    }
+
+    /// Read and parse a matrix file
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        TextMatrix::parse(&content).map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))
+    }
+}
+
+impl DistanceProvider for TextMatrix {
+    fn cities(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn distance(&self, i: usize, j: usize) -> f64 {
+        self.rows[i][j]
+    }
+}
+
+/// Open the precomputed matrix at `path`: a `matrix_cache` file, memory-mapped,
+/// or else a `TextMatrix`
+pub fn open(path: &Path) -> io::Result<Box<dyn DistanceProvider>> {
+    match MappedMatrix::open(path) {
+        Ok(mapped) => Ok(Box::new(mapped)),
+        Err(error) if error.kind() == io::ErrorKind::InvalidData => Ok(Box::new(TextMatrix::load(path)?)),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SonarError;
+    use crate::problem::ProblemInstance;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_provided_distances_replace_the_metric() {
+        // One-way streets: 0 -> 1 is quick, 1 -> 0 a detour
+        let durations = TextMatrix::parse("# travel minutes\n0, 2, 9\n6 0 4\n\n9,4,0\n").unwrap();
+        assert_eq!((durations.cities(), durations.distance(1, 0)), (3, 6.0));
+        let points = generate_normalized_points(3, 10, 1);
+        let instance = ProblemInstance::from_provider(points.clone(), &durations).unwrap();
+        // Both directions averaged, as the solvers assume symmetric costs
+        assert_eq!(instance.matrix()[0][1], 4.0);
+        assert_eq!(instance.matrix()[1][0], 4.0);
+        assert_eq!(instance.tour_length(&[0, 1, 2]), 4.0 + 4.0 + 9.0);
+
+        assert!(TextMatrix::parse("0 1\n1 0 3\n").is_err());
+        assert!(TextMatrix::parse("0 x\n1 0\n").is_err());
+        let two: DistanceMatrix = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        assert_eq!(
+            ProblemInstance::from_provider(points, &two).err(),
+            Some(SonarError::SizeMismatch { expected: 3, found: 2 })
+        );
+    }
+}
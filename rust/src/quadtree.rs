@@ -0,0 +1,178 @@
+//! Quadtree decomposition solver for very large instances
+
+use alloc::vec::Vec;
+use crate::error::{validate_points, SonarError};
+use crate::utils::Point;
+
+/// Maximum subdivision depth, guards against many coincident points
+const MAX_DEPTH: usize = 32;
+
+/// Resolution of the Hilbert curve used to order leaves (2^16 x 2^16 grid)
+const HILBERT_ORDER: u32 = 16;
+
+/// A leaf of the quadtree: its centre and the points it holds
+struct Leaf {
+    center_x: f64,
+    center_y: f64,
+    indices: Vec<usize>,
+}
+
+/// Generate a tour with a simplified Arora-style quadtree decomposition
+/// The plane is split into quadrants until each leaf holds at most `leaf_capacity`
+/// points. Leaves are visited in Hilbert-curve order of their centres, and inside
+/// a leaf points are routed by nearest neighbor starting from the point closest
+/// to where the previous leaf was left (its "portal").
+/// Time complexity: O(n log n + n * leaf_capacity)
+pub fn generate_tour(points: &[Point], leaf_capacity: usize) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
+    let n = points.len();
+
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for p in points {
+        min_x = min_x.min(p.x);
+        min_y = min_y.min(p.y);
+        max_x = max_x.max(p.x);
+        max_y = max_y.max(p.y);
+    }
+    let size = (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+
+    let mut leaves = Vec::new();
+    subdivide(
+        points,
+        (0..n).collect(),
+        (min_x, min_y, size),
+        leaf_capacity.max(1),
+        0,
+        &mut leaves,
+    );
+
+    let side = (1u64 << HILBERT_ORDER) - 1;
+    let to_cell = |v: f64, min: f64| (((v - min) / size).clamp(0.0, 1.0) * side as f64) as u64;
+    leaves.sort_by_key(|leaf| {
+        hilbert_index(to_cell(leaf.center_x, min_x), to_cell(leaf.center_y, min_y))
+    });
+
+    let mut tour = Vec::with_capacity(n);
+    for leaf in leaves {
+        route_leaf(points, leaf.indices, &mut tour);
+    }
+    Ok(tour)
+}
+
+/// Recursively split a square region into quadrants
+fn subdivide(
+    points: &[Point],
+    indices: Vec<usize>,
+    (x, y, size): (f64, f64, f64),
+    leaf_capacity: usize,
+    depth: usize,
+    leaves: &mut Vec<Leaf>,
+) {
+    if indices.is_empty() {
+        return;
+    }
+
+    let half = size / 2.0;
+    if indices.len() <= leaf_capacity || depth >= MAX_DEPTH {
+        leaves.push(Leaf { center_x: x + half, center_y: y + half, indices });
+        return;
+    }
+
+    let mut quadrants: [Vec<usize>; 4] = Default::default();
+    for i in indices {
+        let right = points[i].x >= x + half;
+        let top = points[i].y >= y + half;
+        quadrants[(top as usize) * 2 + right as usize].push(i);
+    }
+
+    for (q, quadrant) in quadrants.into_iter().enumerate() {
+        let qx = if q % 2 == 1 { x + half } else { x };
+        let qy = if q >= 2 { y + half } else { y };
+        subdivide(points, quadrant, (qx, qy, half), leaf_capacity, depth + 1, leaves);
+    }
+}
+
+/// Append the points of a leaf to the tour using nearest neighbor routing
+/// The entry portal is the leaf point closest to the current end of the tour.
+fn route_leaf(points: &[Point], mut remaining: Vec<usize>, tour: &mut Vec<usize>) {
+    let sq = |a: usize, b: usize| {
+        let dx = points[a].x - points[b].x;
+        let dy = points[a].y - points[b].y;
+        dx * dx + dy * dy
+    };
+
+    let mut current = match tour.last() {
+        Some(&last) => last,
+        None => remaining.swap_remove(0),
+    };
+    if tour.is_empty() {
+        tour.push(current);
+    }
+
+    while !remaining.is_empty() {
+        let mut best = 0;
+        for k in 1..remaining.len() {
+            if sq(current, remaining[k]) < sq(current, remaining[best]) {
+                best = k;
+            }
+        }
+        current = remaining.swap_remove(best);
+        tour.push(current);
+    }
+}
+
+/// Map a cell (x, y) on the 2^HILBERT_ORDER grid to its position along the Hilbert curve
+pub fn hilbert_index(mut x: u64, mut y: u64) -> u64 {
+    let full = 1u64 << HILBERT_ORDER;
+    let mut d = 0;
+    let mut s = full / 2;
+    while s > 0 {
+        let rx = ((x & s) > 0) as u64;
+        let ry = ((y & s) > 0) as u64;
+        d += s * s * ((3 * rx) ^ ry);
+        // Rotate the quadrant so the curve stays continuous
+        if ry == 0 {
+            if rx == 1 {
+                x = full - 1 - x;
+                y = full - 1 - y;
+            }
+            core::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_quadtree_visits_all() {
+        let points = generate_normalized_points(2_000, 100, 9);
+        let tour = generate_tour(&points, 8).unwrap();
+
+        assert_eq!(tour.len(), points.len());
+
+        // Check no duplicates
+        let mut seen = std::collections::HashSet::new();
+        for &id in &tour {
+            assert!(seen.insert(id));
+        }
+    }
+
+    #[test]
+    fn test_hilbert_index_is_a_bijection_on_small_grid() {
+        // The top 2 bits of a coordinate select the first-level quadrant
+        let shift = HILBERT_ORDER - 2;
+        let mut seen = std::collections::HashSet::new();
+        for x in 0..4u64 {
+            for y in 0..4u64 {
+                assert!(seen.insert(hilbert_index(x << shift, y << shift) >> (2 * shift)));
+            }
+        }
+        assert_eq!(seen.len(), 16);
+    }
+}
@@ -0,0 +1,35 @@
+//! Random tour baseline - a seeded shuffle used as the worst-case reference
+
+use alloc::vec::Vec;
+use crate::rng::{Lcg, SonarRng};
+
+/// Generate a random tour using a seeded Fisher-Yates shuffle
+/// Serves as the quality baseline every heuristic should beat by a wide margin
+/// Time complexity: O(n)
+pub fn generate_tour(n: usize, seed: u64) -> Vec<usize> {
+    let mut rng = Lcg::new(seed);
+    let mut tour: Vec<usize> = (0..n).collect();
+    for i in (1..n).rev() {
+        tour.swap(i, rng.below(i + 1));
+    }
+    tour
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_tour_is_seeded_permutation() {
+        let tour = generate_tour(100, 42);
+
+        assert_eq!(tour.len(), 100);
+        assert_eq!(tour, generate_tour(100, 42));
+
+        // Check no duplicates
+        let mut seen = std::collections::HashSet::new();
+        for &id in &tour {
+            assert!(seen.insert(id));
+        }
+    }
+}
@@ -0,0 +1,574 @@
+//! Algorithm registry - every solver under a stable key, with its metadata and defaults
+//!
+//! The built-in solvers are the constant `ALGORITHMS`. Downstream crates add
+//! their own with `register`, before they look anything up: a registered
+//! algorithm is found by `find`, listed by `all` and so shows up wherever the
+//! registry is consulted, in `list-algorithms`, the benchmark and pipeline specs.
+
+use crate::error::SonarError;
+use crate::problem::ProblemInstance;
+use crate::utils::json_string;
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+use crate::utils::create_distance_matrix;
+use crate::{angular_sort, random_tour, sonar_visit};
+#[cfg(feature = "exact")]
+use crate::brute_force;
+#[cfg(all(feature = "exact", feature = "constructive", feature = "local-search"))]
+use crate::branch_and_bound;
+#[cfg(all(feature = "metaheuristics", feature = "constructive", feature = "local-search"))]
+use crate::cluster_route;
+#[cfg(feature = "metaheuristics")]
+use crate::genetic;
+#[cfg(all(feature = "metaheuristics", feature = "constructive"))]
+use crate::simulated_annealing;
+#[cfg(all(feature = "exact", feature = "constructive"))]
+use crate::karp_partition;
+#[cfg(feature = "constructive")]
+use crate::{greedy_edge, multilevel, nearest_neighbor, quadtree, space_filling};
+#[cfg(feature = "local-search")]
+use crate::{or_one, zigzag};
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+use crate::{drill, sector_partition, smooth, sparse, two_h_opt, two_opt};
+use std::fmt::Write;
+use std::sync::RwLock;
+#[cfg(all(feature = "exact", feature = "constructive", feature = "local-search"))]
+use std::time::Duration;
+
+/// A tunable parameter and the value the registry runs it with
+#[derive(Debug, Clone, Copy)]
+pub struct Param {
+    pub name: &'static str,
+    pub default: &'static str,
+    pub description: &'static str,
+}
+
+/// Runs an algorithm on an instance with its default parameters
+pub type Solve = fn(&ProblemInstance) -> Result<Vec<usize>, SonarError>;
+
+/// A registered algorithm
+#[derive(Debug, Clone, Copy)]
+pub struct Algorithm {
+    /// Stable identifier for scripts and command lines, e.g. `two-opt`
+    pub key: &'static str,
+    /// Display name, as used in benchmark output
+    pub name: &'static str,
+    pub complexity: &'static str,
+    /// Whether the algorithm reads the O(n^2) distance matrix
+    pub needs_matrix: bool,
+    /// Largest instance it is sensible to run on, for exact methods
+    pub max_n: Option<usize>,
+    pub params: &'static [Param],
+    pub solve: Solve,
+}
+
+pub(crate) const fn param(name: &'static str, default: &'static str, description: &'static str) -> Param {
+    Param { name, default, description }
+}
+
+/// A parameter default: the value `solve` runs with and its text for `Param::default`
+pub(crate) struct ParamDefault<T> {
+    pub(crate) value: T,
+    pub(crate) text: &'static str,
+}
+
+/// `ParamDefault` spelled from one literal, and an optional unit for the text,
+/// so the listed default cannot drift from the value the solve uses
+macro_rules! default {
+    ($value:literal $(, $unit:literal)?) => {
+        ParamDefault { value: $value, text: concat!(stringify!($value) $(, $unit)?) }
+    };
+}
+
+pub(crate) const RANDOM_SEED: ParamDefault<u64> = default!(12345);
+pub(crate) const MULTI_CENTER_GRID_SIZE: ParamDefault<usize> = default!(8);
+pub(crate) const SONAR_GRID_SIZE: ParamDefault<usize> = default!(40);
+#[cfg(feature = "constructive")]
+pub(crate) const START_CITY: ParamDefault<usize> = default!(0);
+#[cfg(feature = "constructive")]
+pub(crate) const GREEDY_CANDIDATES: ParamDefault<usize> = default!(8);
+#[cfg(all(feature = "exact", feature = "constructive"))]
+pub(crate) const KARP_CELL_SIZE: ParamDefault<usize> = default!(8);
+#[cfg(feature = "constructive")]
+pub(crate) const LEAF_CAPACITY: ParamDefault<usize> = default!(8);
+#[cfg(feature = "constructive")]
+pub(crate) const MULTILEVEL_NEIGHBORS: ParamDefault<usize> = default!(8);
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+pub(crate) const SECTORS: ParamDefault<usize> = default!(16);
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+pub(crate) const SECTOR_ITERATIONS: ParamDefault<usize> = default!(50);
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+pub(crate) const SPARSE_NEIGHBORS: ParamDefault<usize> = default!(8);
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+pub(crate) const SPARSE_ITERATIONS: ParamDefault<usize> = default!(50);
+#[cfg(all(feature = "metaheuristics", feature = "constructive", feature = "local-search"))]
+pub(crate) const CLUSTERS: ParamDefault<usize> = default!(16);
+#[cfg(all(feature = "metaheuristics", feature = "constructive", feature = "local-search"))]
+pub(crate) const KMEANS_ITERATIONS: ParamDefault<usize> = default!(20);
+#[cfg(all(feature = "metaheuristics", feature = "constructive", feature = "local-search"))]
+pub(crate) const CLUSTER_ITERATIONS: ParamDefault<usize> = default!(50);
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+pub(crate) const TWO_OPT_ITERATIONS: ParamDefault<usize> = default!(100);
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+pub(crate) const DRILL_EPSILON: ParamDefault<f64> = default!(1e-6);
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+pub(crate) const MIN_ANGLE_DEGREES: ParamDefault<f64> = default!(30.0);
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+pub(crate) const MAX_INCREASE_PERCENT: ParamDefault<f64> = default!(2.0);
+#[cfg(feature = "local-search")]
+pub(crate) const CANDIDATES: ParamDefault<usize> = default!(8);
+#[cfg(feature = "local-search")]
+pub(crate) const IMPROVEMENT_ITERATIONS: ParamDefault<usize> = default!(100);
+#[cfg(all(feature = "metaheuristics", feature = "constructive"))]
+pub(crate) const ANNEALING_ITERATIONS: ParamDefault<usize> = default!(5000);
+#[cfg(all(feature = "metaheuristics", feature = "constructive"))]
+pub(crate) const INITIAL_TEMPERATURE: ParamDefault<f64> = default!(1.0);
+#[cfg(all(feature = "metaheuristics", feature = "constructive"))]
+pub(crate) const COOLING_RATE: ParamDefault<f64> = default!(0.9995);
+#[cfg(feature = "metaheuristics")]
+pub(crate) const POPULATION_SIZE: ParamDefault<usize> = default!(50);
+#[cfg(feature = "metaheuristics")]
+pub(crate) const GENERATIONS: ParamDefault<usize> = default!(100);
+#[cfg(feature = "metaheuristics")]
+pub(crate) const MUTATION_RATE: ParamDefault<f64> = default!(0.1);
+#[cfg(all(feature = "exact", feature = "constructive", feature = "local-search"))]
+pub(crate) const TIME_LIMIT_SECONDS: ParamDefault<u64> = default!(10, "s");
+
+/// Every registered algorithm, construction heuristics first
+/// Entries are compiled in with the algorithm families they use.
+pub const ALGORITHMS: &[Algorithm] = &[
+    Algorithm {
+        key: "random",
+        name: "RandomTour (baseline)",
+        complexity: "O(n)",
+        needs_matrix: false,
+        max_n: None,
+        params: &[param("seed", RANDOM_SEED.text, "shuffle seed")],
+        solve: |instance| Ok(random_tour::generate_tour(instance.len(), RANDOM_SEED.value)),
+    },
+    Algorithm {
+        key: "angular-sort",
+        name: "AngularSort",
+        complexity: "O(n log n)",
+        needs_matrix: false,
+        max_n: None,
+        params: &[],
+        solve: |instance| angular_sort::generate_tour(instance.points()),
+    },
+    Algorithm {
+        key: "angular-sort-multi-center",
+        name: "AngularSort (multi-center, 8x8 cells)",
+        complexity: "O(n log n)",
+        needs_matrix: false,
+        max_n: None,
+        params: &[param("grid_size", MULTI_CENTER_GRID_SIZE.text, "cells per side, each non-empty one a centre")],
+        solve: |instance| angular_sort::generate_tour_multi_center(instance.points(), MULTI_CENTER_GRID_SIZE.value),
+    },
+    #[cfg(feature = "constructive")]
+    Algorithm {
+        key: "morton-sort",
+        name: "MortonSort",
+        complexity: "O(n log n)",
+        needs_matrix: false,
+        max_n: None,
+        params: &[],
+        solve: |instance| space_filling::morton_sort(instance.points()),
+    },
+    #[cfg(feature = "constructive")]
+    Algorithm {
+        key: "hilbert-sort",
+        name: "HilbertSort",
+        complexity: "O(n log n)",
+        needs_matrix: false,
+        max_n: None,
+        params: &[],
+        solve: |instance| space_filling::hilbert_sort(instance.points()),
+    },
+    Algorithm {
+        key: "sonar-visit",
+        name: "SonarVisit",
+        complexity: "O(n log n)",
+        needs_matrix: false,
+        max_n: None,
+        params: &[param("grid_size", SONAR_GRID_SIZE.text, "angular resolution of the sweep")],
+        solve: |instance| sonar_visit::generate_tour(instance.points(), SONAR_GRID_SIZE.value),
+    },
+    #[cfg(feature = "constructive")]
+    Algorithm {
+        key: "nearest-neighbor",
+        name: "NearestNeighbor",
+        complexity: "O(n^2)",
+        needs_matrix: true,
+        max_n: None,
+        params: &[param("start_city", START_CITY.text, "city the tour starts from")],
+        solve: |instance| nearest_neighbor::generate_tour(instance.len(), instance.matrix(), START_CITY.value),
+    },
+    #[cfg(feature = "constructive")]
+    Algorithm {
+        key: "nearest-neighbor-grid",
+        name: "NearestNeighbor (grid)",
+        complexity: "~O(n) on uniform points, O(n^2) worst case",
+        needs_matrix: false,
+        max_n: None,
+        params: &[param("start_city", START_CITY.text, "city the tour starts from")],
+        solve: |instance| nearest_neighbor::generate_tour_grid(instance.points(), START_CITY.value),
+    },
+    #[cfg(feature = "constructive")]
+    Algorithm {
+        key: "greedy-edge",
+        name: "GreedyEdge",
+        complexity: "O(n^2 log n)",
+        needs_matrix: true,
+        max_n: None,
+        params: &[],
+        solve: |instance| greedy_edge::generate_tour(instance.len(), instance.matrix()),
+    },
+    #[cfg(feature = "constructive")]
+    Algorithm {
+        key: "greedy-edge-candidates",
+        name: "GreedyEdge (8 nearest candidates)",
+        complexity: "~O(n * k log(n * k)) on uniform points",
+        needs_matrix: false,
+        max_n: None,
+        params: &[param("k", GREEDY_CANDIDATES.text, "nearest neighbors each city's candidate edges go to at first")],
+        solve: |instance| greedy_edge::generate_tour_candidates(instance.points(), GREEDY_CANDIDATES.value),
+    },
+    #[cfg(all(feature = "exact", feature = "constructive"))]
+    Algorithm {
+        key: "karp-partition",
+        name: "KarpPartition (cells of 8)",
+        complexity: "O(n log^2 n) plus n/m exact solves of size m",
+        needs_matrix: false,
+        max_n: None,
+        params: &[param("max_cell_size", KARP_CELL_SIZE.text, "points per exactly solved cell")],
+        solve: |instance| karp_partition::generate_tour(instance.points(), KARP_CELL_SIZE.value),
+    },
+    #[cfg(feature = "constructive")]
+    Algorithm {
+        key: "quadtree",
+        name: "Quadtree (leaf capacity 8)",
+        complexity: "O(n log n + n * leaf_capacity)",
+        needs_matrix: false,
+        max_n: None,
+        params: &[param("leaf_capacity", LEAF_CAPACITY.text, "points per quadtree leaf")],
+        solve: |instance| quadtree::generate_tour(instance.points(), LEAF_CAPACITY.value),
+    },
+    #[cfg(feature = "constructive")]
+    Algorithm {
+        key: "multilevel",
+        name: "MultiLevel (8 neighbors)",
+        complexity: "~O(n log n)",
+        needs_matrix: false,
+        max_n: None,
+        params: &[param("neighbors", MULTILEVEL_NEIGHBORS.text, "candidate neighbors per city in refinement")],
+        solve: |instance| multilevel::generate_tour(instance.points(), MULTILEVEL_NEIGHBORS.value),
+    },
+    #[cfg(all(feature = "constructive", feature = "local-search"))]
+    Algorithm {
+        key: "sector-partition",
+        name: "SectorPartition (16 sectors, 2-opt)",
+        complexity: "O(n log n) + O((n/k)^2 * max_iterations) per sector",
+        needs_matrix: false,
+        max_n: None,
+        params: &[
+            param("sectors", SECTORS.text, "angular sectors solved in parallel"),
+            param("max_iterations", SECTOR_ITERATIONS.text, "2-opt sweeps per sector"),
+        ],
+        solve: |instance| sector_partition::generate_tour(instance.points(), SECTORS.value, SECTOR_ITERATIONS.value),
+    },
+    #[cfg(all(feature = "constructive", feature = "local-search"))]
+    Algorithm {
+        key: "sparse-knn",
+        name: "SparseKnn (greedy edge, 2-opt, 8 neighbors)",
+        complexity: "O(n * k log(n * k)) + O(n * k) per 2-opt sweep",
+        needs_matrix: false,
+        max_n: None,
+        params: &[
+            param("k", SPARSE_NEIGHBORS.text, "nearest neighbors each city may be joined to"),
+            param("max_iterations", SPARSE_ITERATIONS.text, "2-opt sweeps"),
+        ],
+        solve: |instance| sparse::generate_tour(instance.points(), SPARSE_NEIGHBORS.value, SPARSE_ITERATIONS.value),
+    },
+    #[cfg(all(feature = "metaheuristics", feature = "constructive", feature = "local-search"))]
+    Algorithm {
+        key: "cluster-route",
+        name: "ClusterRoute (k-means, 16 clusters, 2-opt)",
+        complexity: "O(n * k * kmeans_iterations) + per-cluster NN and 2-opt",
+        needs_matrix: false,
+        max_n: None,
+        params: &[
+            param("clusters", CLUSTERS.text, "k-means clusters"),
+            param("kmeans_iterations", KMEANS_ITERATIONS.text, "Lloyd iterations"),
+            param("max_iterations", CLUSTER_ITERATIONS.text, "2-opt sweeps per cluster"),
+        ],
+        solve: |instance| {
+            cluster_route::generate_tour(instance.points(), CLUSTERS.value, KMEANS_ITERATIONS.value, |cluster| {
+                cluster_route::nearest_neighbor_two_opt(cluster, CLUSTER_ITERATIONS.value)
+            })
+        },
+    },
+    #[cfg(all(feature = "constructive", feature = "local-search"))]
+    Algorithm {
+        key: "two-opt",
+        name: "TwoOpt (with NearestNeighbor)",
+        complexity: "O(n^2) per iteration",
+        needs_matrix: true,
+        max_n: None,
+        params: &[param("max_iterations", TWO_OPT_ITERATIONS.text, "full 2-opt sweeps")],
+        solve: |instance| {
+            let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+            two_opt::improve(&initial, instance.matrix(), TWO_OPT_ITERATIONS.value)
+        },
+    },
+    #[cfg(all(feature = "constructive", feature = "local-search"))]
+    Algorithm {
+        key: "drill",
+        name: "Drill (coincident points grouped, NearestNeighbor + 2-opt)",
+        complexity: "O(m^2) per 2-opt iteration over m distinct locations",
+        needs_matrix: false,
+        max_n: None,
+        params: &[
+            param("epsilon", DRILL_EPSILON.text, "points this close are drilled back to back"),
+            param("max_iterations", TWO_OPT_ITERATIONS.text, "full 2-opt sweeps"),
+        ],
+        solve: |instance| {
+            drill::generate_tour(instance.points(), DRILL_EPSILON.value, |holes| {
+                let initial = nearest_neighbor::generate_tour_grid(holes, 0)?;
+                two_opt::improve(&initial, &create_distance_matrix(holes), TWO_OPT_ITERATIONS.value)
+            })
+        },
+    },
+    #[cfg(all(feature = "constructive", feature = "local-search"))]
+    Algorithm {
+        key: "two-opt-smooth",
+        name: "TwoOpt (with NearestNeighbor, smoothed below 30 degrees)",
+        complexity: "O(n^2) per 2-opt iteration + O(n) per smoothing sweep",
+        needs_matrix: true,
+        max_n: None,
+        params: &[
+            param("max_iterations", TWO_OPT_ITERATIONS.text, "full 2-opt sweeps"),
+            param("min_angle_degrees", MIN_ANGLE_DEGREES.text, "sharpest turn left in place"),
+            param("max_increase_percent", MAX_INCREASE_PERCENT.text, "length the smoothing may add"),
+        ],
+        solve: |instance| {
+            let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+            let tour = two_opt::improve(&initial, instance.matrix(), TWO_OPT_ITERATIONS.value)?;
+            smooth::improve(&tour, instance.points(), MIN_ANGLE_DEGREES.value, MAX_INCREASE_PERCENT.value)
+        },
+    },
+    #[cfg(all(feature = "constructive", feature = "local-search"))]
+    Algorithm {
+        key: "two-h-opt",
+        name: "TwoHOpt (with NearestNeighbor, 8 candidates)",
+        complexity: "O(n * k) evaluations per iteration",
+        needs_matrix: true,
+        max_n: None,
+        params: &[
+            param("candidates", CANDIDATES.text, "nearest neighbors tried per city"),
+            param("max_iterations", IMPROVEMENT_ITERATIONS.text, "improvement sweeps"),
+        ],
+        solve: |instance| {
+            let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+            two_h_opt::improve(&initial, instance.matrix(), CANDIDATES.value, IMPROVEMENT_ITERATIONS.value)
+        },
+    },
+    #[cfg(feature = "local-search")]
+    Algorithm {
+        key: "or-one",
+        name: "OrOne (with SonarVisit, 8 candidates)",
+        complexity: "O(n * k) evaluations per iteration",
+        needs_matrix: true,
+        max_n: None,
+        params: &[
+            param("grid_size", SONAR_GRID_SIZE.text, "angular resolution of the initial sweep"),
+            param("candidates", CANDIDATES.text, "nearest neighbors tried per city"),
+            param("max_iterations", IMPROVEMENT_ITERATIONS.text, "improvement sweeps"),
+        ],
+        solve: |instance| {
+            let initial = sonar_visit::generate_tour(instance.points(), SONAR_GRID_SIZE.value)?;
+            or_one::improve(&initial, instance.matrix(), CANDIDATES.value, IMPROVEMENT_ITERATIONS.value)
+        },
+    },
+    #[cfg(feature = "local-search")]
+    Algorithm {
+        key: "zigzag",
+        name: "Zigzag (with AngularSort)",
+        complexity: "O(n)",
+        needs_matrix: true,
+        max_n: None,
+        params: &[],
+        solve: |instance| {
+            let initial = angular_sort::generate_tour(instance.points())?;
+            zigzag::optimize(&initial, instance.points(), instance.matrix())
+        },
+    },
+    #[cfg(all(feature = "metaheuristics", feature = "constructive"))]
+    Algorithm {
+        key: "simulated-annealing",
+        name: "SimulatedAnnealing (with NearestNeighbor)",
+        complexity: "O(n * max_iterations)",
+        needs_matrix: true,
+        max_n: None,
+        params: &[
+            param("max_iterations", ANNEALING_ITERATIONS.text, "proposed moves"),
+            param("initial_temperature", INITIAL_TEMPERATURE.text, "starting temperature"),
+            param("cooling_rate", COOLING_RATE.text, "temperature factor per move"),
+        ],
+        solve: |instance| {
+            let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+            let (iterations, temperature) = (ANNEALING_ITERATIONS.value, INITIAL_TEMPERATURE.value);
+            simulated_annealing::optimize(instance.matrix(), &initial, iterations, temperature, COOLING_RATE.value)
+        },
+    },
+    #[cfg(feature = "metaheuristics")]
+    Algorithm {
+        key: "genetic",
+        name: "GeneticAlgorithm (pop=50, gen=100)",
+        complexity: "O(population_size * generations * n)",
+        needs_matrix: true,
+        max_n: None,
+        params: &[
+            param("population_size", POPULATION_SIZE.text, "tours per generation"),
+            param("generations", GENERATIONS.text, "generations evolved"),
+            param("mutation_rate", MUTATION_RATE.text, "chance of mutating a child"),
+        ],
+        solve: |instance| {
+            let (population, generations) = (POPULATION_SIZE.value, GENERATIONS.value);
+            genetic::optimize(instance.matrix(), instance.len(), population, generations, MUTATION_RATE.value)
+        },
+    },
+    #[cfg(feature = "exact")]
+    Algorithm {
+        key: "brute-force",
+        name: "BruteForce (bruteForceExact)",
+        complexity: "O(n!)",
+        needs_matrix: true,
+        max_n: Some(10),
+        params: &[],
+        solve: |instance| brute_force::brute_force_exact(instance.matrix(), instance.len()).map(|(tour, _)| tour),
+    },
+    #[cfg(feature = "exact")]
+    Algorithm {
+        key: "held-karp",
+        name: "BruteForce (heldKarp)",
+        complexity: "O(2^n * n^2), memory O(2^n * n)",
+        needs_matrix: true,
+        max_n: Some(brute_force::HELD_KARP_MAX_N),
+        params: &[],
+        solve: |instance| brute_force::held_karp(instance.matrix(), instance.len()).map(|(tour, _)| tour),
+    },
+    #[cfg(all(feature = "exact", feature = "constructive", feature = "local-search"))]
+    Algorithm {
+        key: "branch-and-bound",
+        name: "BranchAndBound (1-tree bound)",
+        complexity: "exponential in the worst case, practical for n <= ~30",
+        needs_matrix: true,
+        max_n: Some(40),
+        params: &[param("time_limit", TIME_LIMIT_SECONDS.text, "search budget; the best tour so far is returned")],
+        solve: |instance| {
+            let time_limit = Duration::from_secs(TIME_LIMIT_SECONDS.value);
+            branch_and_bound::solve(instance.matrix(), Some(time_limit)).map(|(tour, ..)| tour)
+        },
+    },
+];
+
+/// Algorithms added by `register`, in registration order
+/// Entries are leaked so they can be handed out as `&'static` like the built-in ones.
+static REGISTERED: RwLock<Vec<&'static Algorithm>> = RwLock::new(Vec::new());
+
+/// Add an algorithm from outside the crate, after the built-in ones
+/// Its key must not be taken by a built-in or earlier registered algorithm.
+pub fn register(algorithm: Algorithm) -> Result<(), SonarError> {
+    let mut registered = REGISTERED.write().unwrap();
+    let taken = ALGORITHMS.iter().chain(registered.iter().copied()).any(|other| other.key == algorithm.key);
+    if taken {
+        return Err(SonarError::InvalidParameter { name: "key", reason: "is already registered" });
+    }
+    registered.push(Box::leak(Box::new(algorithm)));
+    Ok(())
+}
+
+/// Algorithms added by `register` so far
+pub fn registered() -> Vec<&'static Algorithm> {
+    REGISTERED.read().unwrap().clone()
+}
+
+/// Every algorithm: the built-in ones, then those added by `register`
+pub fn all() -> Vec<&'static Algorithm> {
+    ALGORITHMS.iter().chain(registered()).collect()
+}
+
+/// The algorithm registered under `key`
+pub fn find(key: &str) -> Option<&'static Algorithm> {
+    ALGORITHMS.iter().find(|algorithm| algorithm.key == key).or_else(|| {
+        REGISTERED.read().unwrap().iter().copied().find(|algorithm| algorithm.key == key)
+    })
+}
+
+/// JSON array describing the algorithms, without their solve functions
+pub fn to_json(algorithms: &[&Algorithm]) -> String {
+    let mut out = String::from("[\n");
+    for (i, a) in algorithms.iter().enumerate() {
+        let params: Vec<String> = a
+            .params
+            .iter()
+            .map(|p| {
+                format!(
+                    "{{ \"name\": {}, \"default\": {}, \"description\": {} }}",
+                    json_string(p.name), json_string(p.default), json_string(p.description)
+                )
+            })
+            .collect();
+        let max_n = a.max_n.map_or("null".to_string(), |n| n.to_string());
+        let comma = if i + 1 < algorithms.len() { "," } else { "" };
+        writeln!(
+            out,
+            "  {{ \"key\": {}, \"name\": {}, \"complexity\": {}, \"needsMatrix\": {}, \"maxN\": {}, \
+             \"params\": [{}] }}{}",
+            json_string(a.key), json_string(a.name), json_string(a.complexity), a.needs_matrix, max_n,
+            params.join(", "), comma
+        )
+        .unwrap();
+    }
+    out.push(']');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::validate_tour;
+    use crate::problem::Metric;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_every_algorithm_solves_under_a_unique_key() {
+        let instance = ProblemInstance::new(generate_normalized_points(9, 10, 2), Metric::Euclidean).unwrap();
+        for (i, algorithm) in ALGORITHMS.iter().enumerate() {
+            assert!(ALGORITHMS[..i].iter().all(|other| other.key != algorithm.key));
+            assert_eq!(find(algorithm.key).unwrap().name, algorithm.name);
+            let tour = (algorithm.solve)(&instance).unwrap();
+            validate_tour(&tour, instance.len()).unwrap();
+        }
+        assert!(find("no-such-algorithm").is_none());
+
+        let reversed = Algorithm {
+            key: "test-reversed",
+            name: "Reversed (test plugin)",
+            complexity: "O(n)",
+            needs_matrix: false,
+            max_n: None,
+            params: &[],
+            solve: |instance| Ok((0..instance.len()).rev().collect()),
+        };
+        register(reversed).unwrap();
+        assert!(register(reversed).is_err());
+        assert!(register(Algorithm { key: "sonar-visit", ..reversed }).is_err());
+        assert_eq!((find("test-reversed").unwrap().solve)(&instance).unwrap(), [8, 7, 6, 5, 4, 3, 2, 1, 0]);
+        assert_eq!(all().len(), ALGORITHMS.len() + registered().len());
+
+        let json = to_json(&all());
+        assert!(json.contains("\"key\": \"sonar-visit\""));
+        assert!(json.contains("\"key\": \"test-reversed\""));
+        assert!(json.contains("{ \"name\": \"grid_size\", \"default\": \"40\""));
+    }
+}
@@ -0,0 +1,205 @@
+//! Terminal previews and SVG drawings of instances and tours
+//!
+//! Each character cell is a braille pattern of 2 x 4 dots, so a 60-column
+//! preview has 120 x 80 dots of resolution; edges are drawn as dot lines
+//! between the cities. Good enough to spot a crossing or a missed cluster
+//! without opening an SVG file.
+//!
+//! `svg` draws the tour as a single closed stroke and nothing else, the style
+//! of TSP art: stippled points joined into one continuous line.
+
+use crate::utils::Point;
+use std::fmt::Write;
+
+/// Braille dot bit of each (column, row) position in a cell
+const DOT_BITS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+/// Draw `points` and the closed `tour` through them in `columns` x `rows`
+/// braille cells, one line per row
+/// The drawing keeps the points' aspect ratio, taking a dot as square, which
+/// it is in most terminal fonts; an empty tour draws the points alone.
+/// Time complexity: O(columns * rows + n * (columns + rows))
+pub fn ascii(points: &[Point], tour: &[usize], columns: usize, rows: usize) -> String {
+    let (width, height) = (columns * 2, rows * 4);
+    let mut dots = vec![false; width * height];
+    if !points.is_empty() && width > 0 && height > 0 {
+        let (min_x, max_x) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+            (lo.min(p.x), hi.max(p.x))
+        });
+        let (min_y, max_y) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+            (lo.min(p.y), hi.max(p.y))
+        });
+        // Largest scale at which both ranges fit, then centred
+        let fit = |dots: usize, range: f64| if range > 0.0 { (dots - 1) as f64 / range } else { f64::INFINITY };
+        let scale = fit(width, max_x - min_x).min(fit(height, max_y - min_y));
+        let scale = if scale.is_finite() { scale } else { 0.0 };
+        let left = ((width - 1) as f64 - (max_x - min_x) * scale) / 2.0;
+        let bottom = ((height - 1) as f64 - (max_y - min_y) * scale) / 2.0;
+        // Dot position of a point, y up
+        let dot = |p: &Point| {
+            let x = (left + (p.x - min_x) * scale).round() as i64;
+            let y = (height - 1) as i64 - (bottom + (p.y - min_y) * scale).round() as i64;
+            (x, y)
+        };
+        let mut set = |(x, y): (i64, i64)| {
+            if (0..width as i64).contains(&x) && (0..height as i64).contains(&y) {
+                dots[y as usize * width + x as usize] = true;
+            }
+        };
+
+        for p in points {
+            set(dot(p));
+        }
+        for i in 0..tour.len() {
+            let (from, to) = (dot(&points[tour[i]]), dot(&points[tour[(i + 1) % tour.len()]]));
+            let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs());
+            for step in 1..steps {
+                let t = step as f64 / steps as f64;
+                let x = from.0 as f64 + (to.0 - from.0) as f64 * t;
+                let y = from.1 as f64 + (to.1 - from.1) as f64 * t;
+                set((x.round() as i64, y.round() as i64));
+            }
+        }
+    }
+
+    let mut out = String::with_capacity(rows * (columns * 3 + 1));
+    for row in 0..rows {
+        for column in 0..columns {
+            let mut bits = 0;
+            for (dx, column_bits) in DOT_BITS.iter().enumerate() {
+                for (dy, &bit) in column_bits.iter().enumerate() {
+                    if dots[(row * 4 + dy) * width + column * 2 + dx] {
+                        bits |= bit;
+                    }
+                }
+            }
+            out.push(char::from_u32(0x2800 + bits).unwrap());
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// How `svg` draws a tour
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgStyle {
+    /// Width of the drawing in px; the height follows the points' aspect ratio
+    pub width: f64,
+    /// Blank border around the tour, in px
+    pub margin: f64,
+    pub stroke_width: f64,
+    /// Any SVG colour
+    pub stroke: String,
+    /// Colour filling the drawing behind the tour; transparent if `None`
+    pub background: Option<String>,
+    /// Draw Catmull-Rom curves through the points instead of straight segments
+    pub curved: bool,
+    /// Whether y grows upwards, as in TSPLIB files; image coordinates, such as
+    /// a stippler's, grow downwards like SVG's own
+    pub y_up: bool,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        SvgStyle {
+            width: 800.0,
+            margin: 10.0,
+            stroke_width: 1.0,
+            stroke: "black".to_string(),
+            background: Some("white".to_string()),
+            curved: false,
+            y_up: true,
+        }
+    }
+}
+
+/// Draw the closed `tour` through `points` as one SVG path
+/// Points are scaled to fit `style.width` less the margins, keeping their
+/// aspect ratio. Curves pass through every point, with tangents parallel to
+/// the line between its neighbors.
+/// Time complexity: O(n)
+pub fn svg(points: &[Point], tour: &[usize], style: &SvgStyle) -> String {
+    let (min_x, max_x) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+        (lo.min(p.x), hi.max(p.x))
+    });
+    let (min_y, max_y) = points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+        (lo.min(p.y), hi.max(p.y))
+    });
+    let inner = (style.width - 2.0 * style.margin).max(0.0);
+    let span = (max_x - min_x).max(max_y - min_y);
+    let scale = if span > 0.0 { inner / span } else { 0.0 };
+    let height = if points.is_empty() { style.width } else { (max_y - min_y) * scale + 2.0 * style.margin };
+    let at = |city: usize| {
+        let p = &points[city];
+        let y = if style.y_up { max_y - p.y } else { p.y - min_y };
+        (style.margin + (p.x - min_x) * scale, style.margin + y * scale)
+    };
+
+    let mut path = String::new();
+    let n = tour.len();
+    if let Some(&first) = tour.first() {
+        let (x, y) = at(first);
+        write!(path, "M{:.2} {:.2}", x, y).unwrap();
+        for i in 0..n {
+            let (x1, y1) = at(tour[(i + 1) % n]);
+            if style.curved {
+                let ((x0, y0), (x2, y2)) = (at(tour[(i + n - 1) % n]), at(tour[(i + 2) % n]));
+                let (from_x, from_y) = at(tour[i]);
+                let (c1x, c1y) = (from_x + (x1 - x0) / 6.0, from_y + (y1 - y0) / 6.0);
+                let (c2x, c2y) = (x1 - (x2 - from_x) / 6.0, y1 - (y2 - from_y) / 6.0);
+                write!(path, " C{:.2} {:.2} {:.2} {:.2} {:.2} {:.2}", c1x, c1y, c2x, c2y, x1, y1).unwrap();
+            } else if i + 1 < n {
+                write!(path, " L{:.2} {:.2}", x1, y1).unwrap();
+            }
+        }
+        path.push_str(" Z");
+    }
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0:.0}\" height=\"{1:.0}\" viewBox=\"0 0 {0:.2} {1:.2}\">",
+        style.width, height
+    )
+    .unwrap();
+    if let Some(background) = &style.background {
+        writeln!(out, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", background).unwrap();
+    }
+    writeln!(
+        out,
+        "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\" \
+         stroke-linecap=\"round\"/>",
+        path, style.stroke, style.stroke_width
+    )
+    .unwrap();
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square_tour_outline() {
+        let square: Vec<Point> = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+            .iter()
+            .enumerate()
+            .map(|(id, &(x, y))| Point { x, y, angle: 0.0, id })
+            .collect();
+        // 4 x 2 cells are 8 x 8 dots: the tour is the outline of the square
+        let outline = ascii(&square, &[0, 1, 2, 3], 4, 2);
+        assert_eq!(outline, "⡏⠉⠉⢹\n⣇⣀⣀⣸\n");
+        let corners = ascii(&square, &[], 4, 2);
+        assert_eq!(corners, "⠁⠀⠀⠈\n⡀⠀⠀⢀\n");
+        assert_eq!(ascii(&[], &[], 2, 1), "⠀⠀\n");
+
+        let style = SvgStyle { width: 120.0, ..SvgStyle::default() };
+        let line = svg(&square, &[0, 1, 2, 3], &style);
+        assert!(line.starts_with("<svg") && line.ends_with("</svg>\n"));
+        assert!(line.contains("d=\"M10.00 110.00 L110.00 110.00 L110.00 10.00 L10.00 10.00 Z\""), "{}", line);
+        let curved = svg(&square, &[0, 1, 2, 3], &SvgStyle { curved: true, background: None, ..style });
+        assert_eq!(curved.matches(" C").count(), 4);
+        assert!(!curved.contains("<rect"));
+    }
+}
@@ -0,0 +1,319 @@
+//! Record and replay the random decisions of simulated annealing and the genetic algorithm
+//!
+//! A recorded run keeps every raw random draw (and, for simulated annealing,
+//! every accepted move). Feeding the draws back reproduces the run exactly,
+//! so a rare bad result can be saved once and then debugged step by step.
+//!
+//! Log file layout (little endian): the magic `SONARRL1`, the draw count as
+//! u64 and the draws as u64, then the move count as u64 and each move as
+//! iteration u64, kind u8, i u64, j u64.
+
+use crate::determinism;
+use crate::error::SonarError;
+use crate::genetic::GeneticSolver;
+use crate::simulated_annealing::{AcceptedMove, AnnealingSolver, MoveKind};
+use crate::stepwise::SteppableSolver;
+use rand::RngCore;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"SONARRL1";
+
+/// Wraps an RNG and keeps every value it hands out
+pub struct RecordingRng<R> {
+    inner: R,
+    draws: Vec<u64>,
+}
+
+impl<R: RngCore> RecordingRng<R> {
+    pub fn new(inner: R) -> Self {
+        RecordingRng { inner, draws: Vec::new() }
+    }
+
+    /// Every draw so far, in order
+    pub fn draws(&self) -> &[u64] {
+        &self.draws
+    }
+}
+
+impl<R: RngCore> RngCore for RecordingRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        let value = self.inner.next_u32();
+        self.draws.push(value as u64);
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let value = self.inner.next_u64();
+        self.draws.push(value);
+        value
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        // Go through next_u64 so byte fills are recorded and replayed the same way
+        fill_via_next_u64(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+fn fill_via_next_u64(rng: &mut impl RngCore, dest: &mut [u8]) {
+    for chunk in dest.chunks_mut(8) {
+        let bytes = rng.next_u64().to_le_bytes();
+        chunk.copy_from_slice(&bytes[..chunk.len()]);
+    }
+}
+
+/// Hands out previously recorded draws in order
+/// Once the draws run out it yields zeros, so a diverging replay still ends
+/// and the divergence shows up when its log is compared with the original.
+pub struct ReplayRng<'a> {
+    draws: &'a [u64],
+    position: usize,
+}
+
+impl<'a> ReplayRng<'a> {
+    pub fn new(draws: &'a [u64]) -> Self {
+        ReplayRng { draws, position: 0 }
+    }
+}
+
+impl RngCore for ReplayRng<'_> {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let value = self.draws.get(self.position).copied().unwrap_or(0);
+        self.position += 1;
+        value
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_via_next_u64(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Random draws and accepted moves of one run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayLog {
+    pub draws: Vec<u64>,
+    /// Accepted moves; only simulated annealing records these
+    pub moves: Vec<AcceptedMove>,
+}
+
+impl ReplayLog {
+    /// Iteration of the first accepted move that differs between two runs
+    pub fn first_divergence(&self, other: &ReplayLog) -> Option<usize> {
+        let mismatch = self.moves.iter().zip(&other.moves).find(|(a, b)| a != b);
+        match mismatch {
+            Some((a, b)) => Some(a.iteration.min(b.iteration)),
+            None if self.moves.len() != other.moves.len() => {
+                let shorter = self.moves.len().min(other.moves.len());
+                let longer = if self.moves.len() > shorter { &self.moves } else { &other.moves };
+                Some(longer[shorter].iteration)
+            }
+            None => None,
+        }
+    }
+
+    pub fn write_to(&self, mut out: impl Write) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        out.write_all(&(self.draws.len() as u64).to_le_bytes())?;
+        for draw in &self.draws {
+            out.write_all(&draw.to_le_bytes())?;
+        }
+        out.write_all(&(self.moves.len() as u64).to_le_bytes())?;
+        for m in &self.moves {
+            let kind: u8 = match m.kind {
+                MoveKind::Reversal => 0,
+                MoveKind::Insertion => 1,
+                MoveKind::Swap => 2,
+            };
+            out.write_all(&(m.iteration as u64).to_le_bytes())?;
+            out.write_all(&[kind])?;
+            out.write_all(&(m.i as u64).to_le_bytes())?;
+            out.write_all(&(m.j as u64).to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from(mut input: impl Read) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a replay log"));
+        }
+        let draws = (0..read_u64(&mut input)?)
+            .map(|_| read_u64(&mut input))
+            .collect::<io::Result<Vec<u64>>>()?;
+
+        let mut moves = Vec::new();
+        for _ in 0..read_u64(&mut input)? {
+            let iteration = read_u64(&mut input)? as usize;
+            let mut kind = [0];
+            input.read_exact(&mut kind)?;
+            let kind = match kind[0] {
+                0 => MoveKind::Reversal,
+                1 => MoveKind::Insertion,
+                2 => MoveKind::Swap,
+                _ => return Err(invalid("unknown move kind in replay log")),
+            };
+            let (i, j) = (read_u64(&mut input)? as usize, read_u64(&mut input)? as usize);
+            moves.push(AcceptedMove { iteration, kind, i, j });
+        }
+        Ok(ReplayLog { draws, moves })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut out = io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_to(&mut out)?;
+        out.flush()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::read_from(io::BufReader::new(std::fs::File::open(path)?))
+    }
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Run `simulated_annealing::optimize_with_moves` and record its decisions
+pub fn record_annealing(
+    graph: &[Vec<f64>],
+    initial_tour: &[usize],
+    max_iterations: usize,
+    initial_temperature: f64,
+    cooling_rate: f64,
+    moves: &[(MoveKind, f64)],
+) -> Result<(Vec<usize>, ReplayLog), SonarError> {
+    let rng = RecordingRng::new(determinism::rng(0));
+    run_annealing(graph, initial_tour, max_iterations, initial_temperature, cooling_rate, moves, rng)
+}
+
+/// Re-run a recorded simulated annealing run from its log
+/// Returns the tour and the log of the replay itself; it equals `log` unless
+/// the code or inputs changed, and `first_divergence` tells where they part.
+pub fn replay_annealing(
+    graph: &[Vec<f64>],
+    initial_tour: &[usize],
+    max_iterations: usize,
+    initial_temperature: f64,
+    cooling_rate: f64,
+    moves: &[(MoveKind, f64)],
+    log: &ReplayLog,
+) -> Result<(Vec<usize>, ReplayLog), SonarError> {
+    let rng = RecordingRng::new(ReplayRng::new(&log.draws));
+    run_annealing(graph, initial_tour, max_iterations, initial_temperature, cooling_rate, moves, rng)
+}
+
+fn run_annealing<R: RngCore>(
+    graph: &[Vec<f64>],
+    initial_tour: &[usize],
+    max_iterations: usize,
+    initial_temperature: f64,
+    cooling_rate: f64,
+    moves: &[(MoveKind, f64)],
+    rng: RecordingRng<R>,
+) -> Result<(Vec<usize>, ReplayLog), SonarError> {
+    let mut solver = AnnealingSolver::new(
+        graph,
+        initial_tour,
+        max_iterations,
+        initial_temperature,
+        cooling_rate,
+        moves,
+        max_iterations.max(1),
+    )?
+    .with_rng(rng)
+    .record_moves();
+    let tour = solver.run();
+    let log = ReplayLog { draws: solver.rng().draws().to_vec(), moves: solver.accepted_moves().to_vec() };
+    Ok((tour, log))
+}
+
+/// Run `genetic::optimize` and record its random draws
+pub fn record_genetic(
+    graph: &[Vec<f64>],
+    n: usize,
+    population_size: usize,
+    generations: usize,
+    mutation_rate: f64,
+) -> Result<(Vec<usize>, ReplayLog), SonarError> {
+    let rng = RecordingRng::new(determinism::rng(0));
+    run_genetic(graph, n, population_size, generations, mutation_rate, rng)
+}
+
+/// Re-run a recorded genetic algorithm run from its log, see `replay_annealing`
+pub fn replay_genetic(
+    graph: &[Vec<f64>],
+    n: usize,
+    population_size: usize,
+    generations: usize,
+    mutation_rate: f64,
+    log: &ReplayLog,
+) -> Result<(Vec<usize>, ReplayLog), SonarError> {
+    let rng = RecordingRng::new(ReplayRng::new(&log.draws));
+    run_genetic(graph, n, population_size, generations, mutation_rate, rng)
+}
+
+fn run_genetic<R: RngCore>(
+    graph: &[Vec<f64>],
+    n: usize,
+    population_size: usize,
+    generations: usize,
+    mutation_rate: f64,
+    rng: RecordingRng<R>,
+) -> Result<(Vec<usize>, ReplayLog), SonarError> {
+    let mut solver = GeneticSolver::new(graph, n, population_size, generations, mutation_rate)?.with_rng(rng);
+    let tour = solver.run();
+    Ok((tour, ReplayLog { draws: solver.rng().draws().to_vec(), moves: Vec::new() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_replay_reproduces_recorded_runs() {
+        let points = generate_normalized_points(40, 20, 5);
+        let graph = create_distance_matrix(&points);
+        let initial: Vec<usize> = (0..points.len()).collect();
+        let moves = [(MoveKind::Reversal, 0.7), (MoveKind::Insertion, 0.2), (MoveKind::Swap, 0.1)];
+
+        let (tour, log) = record_annealing(&graph, &initial, 5_000, 0.1, 0.999, &moves).unwrap();
+        assert!(!log.moves.is_empty());
+
+        let mut bytes = Vec::new();
+        log.write_to(&mut bytes).unwrap();
+        let loaded = ReplayLog::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(loaded, log);
+
+        let (replayed, replay_log) = replay_annealing(&graph, &initial, 5_000, 0.1, 0.999, &moves, &loaded).unwrap();
+        assert_eq!(replayed, tour);
+        assert_eq!(replay_log.first_divergence(&log), None);
+
+        // A different cooling schedule accepts different moves
+        let (_, other) = replay_annealing(&graph, &initial, 5_000, 0.5, 0.999, &moves, &loaded).unwrap();
+        assert!(other.first_divergence(&log).is_some());
+
+        let (tour, log) = record_genetic(&graph, points.len(), 20, 30, 0.1).unwrap();
+        let (replayed, replay_log) = replay_genetic(&graph, points.len(), 20, 30, 0.1, &log).unwrap();
+        assert_eq!(replayed, tour);
+        assert_eq!(replay_log, log);
+    }
+}
@@ -0,0 +1,124 @@
+//! Tour representations compared: the same 2-opt on each, at several sizes
+//!
+//! 2-opt can apply its reversals to a plain array, to an `ArrayTour` that
+//! reverses the shorter side, to a `TwoLevelList` whose reversals are
+//! O(sqrt n) but whose `next` costs more, or to a `TreapTour` whose reversals
+//! and `next` are both O(log n). Which wins depends on n: `measure`
+//! times each on the same instance and start tour, so defaults can follow the
+//! numbers rather than the asymptotics. The lengths can differ a little: a
+//! representation may reverse the complementary path, which changes the
+//! order in which later moves are found.
+
+use crate::error::SonarError;
+use crate::nearest_neighbor;
+use crate::tour::{ArrayTour, Tour, TreapTour, TwoLevelList};
+use crate::two_opt;
+use crate::utils::{calculate_tour_length, create_distance_matrix, Point};
+use std::time::{Duration, Instant};
+
+/// Sizes measured unless others are given
+pub const SIZES: &[usize] = &[100, 500, 2000];
+
+/// A data structure 2-opt can run on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Representation {
+    /// `Vec` of cities, reversing the path in place (`two_opt::improve`)
+    Array,
+    /// `ArrayTour` through the `Tour` trait (`two_opt::improve_tour`)
+    ArrayTour,
+    /// `TwoLevelList` through the `Tour` trait (`two_opt::improve_tour`)
+    TwoLevelList,
+    /// `TreapTour` through the `Tour` trait (`two_opt::improve_tour`)
+    Treap,
+}
+
+impl Representation {
+    pub const ALL: [Representation; 4] =
+        [Representation::Array, Representation::ArrayTour, Representation::TwoLevelList, Representation::Treap];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Representation::Array => "array",
+            Representation::ArrayTour => "array-tour",
+            Representation::TwoLevelList => "two-level-list",
+            Representation::Treap => "treap",
+        }
+    }
+
+    /// Improve `tour` by 2-opt on this representation
+    pub fn improve(self, tour: &[usize], graph: &[Vec<f64>], max_iterations: usize) -> Result<Vec<usize>, SonarError> {
+        match self {
+            Representation::Array => two_opt::improve(tour, graph, max_iterations),
+            Representation::ArrayTour => {
+                let mut array = ArrayTour::new(tour);
+                two_opt::improve_tour(&mut array, graph, max_iterations)?;
+                Ok(array.to_vec())
+            }
+            Representation::TwoLevelList => {
+                let mut list = TwoLevelList::new(tour);
+                two_opt::improve_tour(&mut list, graph, max_iterations)?;
+                Ok(list.to_vec())
+            }
+            Representation::Treap => {
+                let mut treap = TreapTour::new(tour);
+                two_opt::improve_tour(&mut treap, graph, max_iterations)?;
+                Ok(treap.to_vec())
+            }
+        }
+    }
+}
+
+/// One representation's run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timing {
+    pub representation: Representation,
+    /// Time of the 2-opt alone, without building the representation's input
+    pub time: Duration,
+    pub length: f64,
+}
+
+/// Run 2-opt from the nearest neighbor tour of `points` on every representation
+/// The distance matrix and the start tour are built once, before timing.
+/// Time complexity: four 2-opt runs of up to `max_iterations` O(n^2) sweeps
+pub fn measure(points: &[Point], max_iterations: usize) -> Result<Vec<Timing>, SonarError> {
+    let graph = create_distance_matrix(points);
+    let initial = nearest_neighbor::generate_tour(points.len(), &graph, 0)?;
+    Representation::ALL
+        .iter()
+        .map(|&representation| {
+            let start = Instant::now();
+            let tour = representation.improve(&initial, &graph, max_iterations)?;
+            let time = start.elapsed();
+            Ok(Timing { representation, time, length: calculate_tour_length(&tour, &graph) })
+        })
+        .collect()
+}
+
+/// The representation with the shortest time
+pub fn fastest(timings: &[Timing]) -> Option<Representation> {
+    timings.iter().min_by_key(|timing| timing.time).map(|timing| timing.representation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::validate_tour;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_every_representation_improves_the_start_tour() {
+        let points = generate_normalized_points(120, 40, 6);
+        let graph = create_distance_matrix(&points);
+        let initial = nearest_neighbor::generate_tour(120, &graph, 0).unwrap();
+        let start = calculate_tour_length(&initial, &graph);
+        let timings = measure(&points, 100).unwrap();
+        assert_eq!(timings.iter().map(|t| t.representation).collect::<Vec<_>>(), Representation::ALL);
+        for timing in &timings {
+            assert!(timing.length < start, "{}", timing.representation.name());
+        }
+        for representation in Representation::ALL {
+            validate_tour(&representation.improve(&initial, &graph, 100).unwrap(), 120).unwrap();
+        }
+        assert!(fastest(&timings).is_some());
+    }
+}
@@ -0,0 +1,172 @@
+//! On-disk cache of solver results, keyed by (algorithm, params, seed, n)
+//!
+//! Reports and parameter sweeps rerun mostly the same configurations; with a
+//! cache directory each configuration is solved once and later requests read
+//! its tour and times back. Every entry is one small text file named after a
+//! hash of its key. An entry also records the git revision of the build and a
+//! fingerprint of the points, and is ignored unless both match, so results
+//! never outlive a commit or get applied to another instance. Changes not yet
+//! committed are not noticed: clear the directory after editing a solver.
+
+use crate::matrix_cache::fingerprint;
+use crate::problem::Metric;
+use crate::utils::Point;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Version of the entry format, on the first line of every entry
+const FORMAT: &str = "1";
+
+/// What a cached result was computed for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey<'a> {
+    /// Algorithm key or benchmark name
+    pub algorithm: &'a str,
+    /// Parameter values, and anything else besides the instance that changes the result
+    pub params: &'a str,
+    /// Seed the instance was generated from, or any other number identifying it
+    pub seed: u64,
+    pub n: usize,
+}
+
+impl CacheKey<'_> {
+    /// FNV-1a hash of every field, naming the entry's file
+    fn hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let (seed, n) = (self.seed.to_le_bytes(), (self.n as u64).to_le_bytes());
+        for field in [self.algorithm.as_bytes(), self.params.as_bytes(), &seed, &n] {
+            // The length separates fields, so ("ab", "c") and ("a", "bc") differ
+            for byte in field.len().to_le_bytes().iter().chain(field) {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        hash
+    }
+}
+
+/// A solver result as stored
+/// Runs not split into phases count as construction only.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedRun {
+    pub tour: Vec<usize>,
+    pub length: f64,
+    /// Building the distance matrix, zero if the run did not time it
+    pub matrix: Duration,
+    pub construction: Duration,
+    pub improvement: Duration,
+}
+
+impl CachedRun {
+    /// Time of the solver itself, without the matrix
+    pub fn time(&self) -> Duration {
+        self.construction + self.improvement
+    }
+}
+
+/// A directory of cached results
+#[derive(Debug, Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    /// Use `dir` as the cache, creating it if needed
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(ResultCache { dir: dir.to_path_buf() })
+    }
+
+    fn path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(format!("{:016x}.txt", key.hash()))
+    }
+
+    /// The result stored for `key` on `points` by this build, if any
+    /// Unreadable, stale and colliding entries are all misses.
+    pub fn get(&self, key: &CacheKey, points: &[Point], metric: Metric) -> Option<CachedRun> {
+        let content = fs::read_to_string(self.path(key)).ok()?;
+        let mut fields = content.lines().map(|line| line.split_once(' ').map_or((line, ""), |(k, v)| (k, v)));
+        let mut expect = |name: &str| fields.next().filter(|(field, _)| *field == name).map(|(_, value)| value);
+        let matches = expect("sonar-result")? == FORMAT
+            && expect("build")? == build()
+            && expect("algorithm")? == key.algorithm
+            && expect("params")? == key.params
+            && expect("seed")?.parse() == Ok(key.seed)
+            && expect("n")?.parse() == Ok(key.n)
+            && expect("instance")?.parse() == Ok(fingerprint(points, metric));
+        if !matches {
+            return None;
+        }
+        let mut duration = |name: &str| expect(name)?.parse().ok().map(Duration::from_nanos);
+        let matrix = duration("matrix_ns")?;
+        let construction = duration("construction_ns")?;
+        let improvement = duration("improvement_ns")?;
+        let length = expect("length")?.parse().ok()?;
+        let tour = expect("tour")?.split_whitespace().map(str::parse).collect::<Result<Vec<usize>, _>>().ok()?;
+        (tour.len() == key.n).then_some(CachedRun { tour, length, matrix, construction, improvement })
+    }
+
+    /// Store `run` as the result for `key` on `points`
+    /// The entry is written next to its destination and renamed into place,
+    /// so a concurrent reader never sees half of it.
+    pub fn put(&self, key: &CacheKey, points: &[Point], metric: Metric, run: &CachedRun) -> io::Result<()> {
+        let mut out = String::new();
+        writeln!(out, "sonar-result {}", FORMAT).unwrap();
+        writeln!(out, "build {}", build()).unwrap();
+        writeln!(out, "algorithm {}", key.algorithm).unwrap();
+        writeln!(out, "params {}", key.params).unwrap();
+        writeln!(out, "seed {}", key.seed).unwrap();
+        writeln!(out, "n {}", key.n).unwrap();
+        writeln!(out, "instance {}", fingerprint(points, metric)).unwrap();
+        writeln!(out, "matrix_ns {}", run.matrix.as_nanos()).unwrap();
+        writeln!(out, "construction_ns {}", run.construction.as_nanos()).unwrap();
+        writeln!(out, "improvement_ns {}", run.improvement.as_nanos()).unwrap();
+        writeln!(out, "length {:?}", run.length).unwrap();
+        let tour: Vec<String> = run.tour.iter().map(usize::to_string).collect();
+        writeln!(out, "tour {}", tour.join(" ")).unwrap();
+
+        let path = self.path(key);
+        let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&tmp, out)?;
+        fs::rename(tmp, path)
+    }
+}
+
+/// Crate version and git revision of this build
+fn build() -> String {
+    format!("{}-{}", env!("CARGO_PKG_VERSION"), env!("SONAR_GIT_HASH"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_results_are_reused_only_for_the_same_key_and_instance() {
+        let dir = std::env::temp_dir().join(format!("sonar-result-cache-{}", std::process::id()));
+        let cache = ResultCache::open(&dir).unwrap();
+        let points = generate_normalized_points(5, 10, 1);
+        let key = CacheKey { algorithm: "two-opt", params: "max_iterations=100", seed: 1, n: 5 };
+        let run = CachedRun {
+            tour: vec![0, 2, 4, 1, 3],
+            length: 1.25,
+            matrix: Duration::from_micros(3),
+            construction: Duration::from_micros(40),
+            improvement: Duration::from_nanos(123_456),
+        };
+        assert_eq!(cache.get(&key, &points, Metric::Euclidean), None);
+        cache.put(&key, &points, Metric::Euclidean, &run).unwrap();
+        assert_eq!(cache.get(&key, &points, Metric::Euclidean), Some(run.clone()));
+        assert_eq!(run.time(), Duration::from_nanos(163_456));
+
+        let other_params = CacheKey { params: "max_iterations=10", ..key.clone() };
+        assert_eq!(cache.get(&other_params, &points, Metric::Euclidean), None);
+        assert_eq!(cache.get(&key, &generate_normalized_points(5, 10, 2), Metric::Euclidean), None);
+        assert_eq!(cache.get(&key, &points, Metric::Euc2d), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
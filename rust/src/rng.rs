@@ -0,0 +1,278 @@
+//! Random number generators behind one trait object
+//!
+//! Every stochastic algorithm draws from a `SonarRng`, so a run's generator is
+//! chosen in one place (`determinism::set_rng_kind`) and a recorded seed
+//! reproduces the run whichever generator it used. `Lcg` is the generator the
+//! JS code and the benchmark point sets use; `Pcg64` and `Xoshiro256` are
+//! statistically sound ones for the metaheuristics. With the `metaheuristics`
+//! feature a boxed `SonarRng` is also a `rand::Rng`.
+
+use alloc::boxed::Box;
+
+/// A seeded source of random numbers
+pub trait SonarRng {
+    fn next_u64(&mut self) -> u64;
+
+    /// Uniform in [0, 1), from the top 53 bits of `next_u64`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in [0, bound), by scaling `next_f64`; `bound` must be positive
+    fn below(&mut self, bound: usize) -> usize {
+        ((self.next_f64() * bound as f64) as usize).min(bound - 1)
+    }
+}
+
+/// A generator chosen at run time
+pub type BoxedRng = Box<dyn SonarRng + Send>;
+
+/// The legacy 31-bit linear congruential generator shared with the JS code
+/// Its draws fill only the top 31 bits of `next_u64`, so `next_f64` and
+/// `below` reproduce the JS values exactly; too weak for long searches.
+#[derive(Debug, Clone)]
+pub struct Lcg {
+    seed: u64,
+}
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Lcg { seed }
+    }
+
+    /// Next draw in [0, 1), as the JS `Math.random` replacement computes it
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> f64 {
+        self.seed = (self.seed.wrapping_mul(1103515245).wrapping_add(12345)) % (1 << 31);
+        self.seed as f64 / (1u64 << 31) as f64
+    }
+}
+
+impl SonarRng for Lcg {
+    fn next_u64(&mut self) -> u64 {
+        self.next();
+        self.seed << 33
+    }
+}
+
+/// SplitMix64, used to expand a 64-bit seed into a generator's state
+fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// PCG with 128-bit state and 64-bit output (XSL RR)
+#[derive(Debug, Clone)]
+pub struct Pcg64 {
+    state: u128,
+    increment: u128,
+}
+
+impl Pcg64 {
+    const MULTIPLIER: u128 = 0x2360_ED05_1FC6_5DA4_4385_DF64_9FCC_F645;
+
+    pub fn new(seed: u64) -> Self {
+        let mut mix = seed;
+        let state = (split_mix(&mut mix) as u128) << 64 | split_mix(&mut mix) as u128;
+        let increment = ((split_mix(&mut mix) as u128) << 64 | split_mix(&mut mix) as u128) | 1;
+        let mut pcg = Pcg64 { state: state.wrapping_add(increment), increment };
+        pcg.step();
+        pcg
+    }
+
+    fn step(&mut self) {
+        self.state = self.state.wrapping_mul(Self::MULTIPLIER).wrapping_add(self.increment);
+    }
+}
+
+impl SonarRng for Pcg64 {
+    fn next_u64(&mut self) -> u64 {
+        self.step();
+        let rotation = (self.state >> 122) as u32;
+        (((self.state >> 64) as u64) ^ (self.state as u64)).rotate_right(rotation)
+    }
+}
+
+/// xoshiro256**, fast with 256 bits of state
+#[derive(Debug, Clone)]
+pub struct Xoshiro256 {
+    state: [u64; 4],
+}
+
+impl Xoshiro256 {
+    pub fn new(seed: u64) -> Self {
+        let mut mix = seed;
+        Xoshiro256 { state: [(); 4].map(|_| split_mix(&mut mix)) }
+    }
+}
+
+impl SonarRng for Xoshiro256 {
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+}
+
+/// The generators a run can choose from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RngKind {
+    Lcg,
+    Pcg64,
+    #[default]
+    Xoshiro256,
+}
+
+impl RngKind {
+    pub const ALL: [RngKind; 3] = [RngKind::Lcg, RngKind::Pcg64, RngKind::Xoshiro256];
+
+    /// Position of `RngKind::default()` in `ALL`
+    pub const DEFAULT_INDEX: usize = RngKind::Xoshiro256.index();
+
+    /// Position in `ALL`, which lists the kinds in declaration order
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Name for command lines and manifests
+    pub fn name(self) -> &'static str {
+        match self {
+            RngKind::Lcg => "lcg",
+            RngKind::Pcg64 => "pcg64",
+            RngKind::Xoshiro256 => "xoshiro256",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RngKind> {
+        RngKind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// A generator of this kind seeded with `seed`
+    pub fn seeded(self, seed: u64) -> BoxedRng {
+        match self {
+            RngKind::Lcg => Box::new(Lcg::new(seed)),
+            RngKind::Pcg64 => Box::new(Pcg64::new(seed)),
+            RngKind::Xoshiro256 => Box::new(Xoshiro256::new(seed)),
+        }
+    }
+
+    /// `seeded` held inline, for runs that must not allocate
+    pub fn unboxed(self, seed: u64) -> UnboxedRng {
+        match self {
+            RngKind::Lcg => UnboxedRng::Lcg(Lcg::new(seed)),
+            RngKind::Pcg64 => UnboxedRng::Pcg64(Pcg64::new(seed)),
+            RngKind::Xoshiro256 => UnboxedRng::Xoshiro256(Xoshiro256::new(seed)),
+        }
+    }
+}
+
+/// A generator chosen at run time, like `BoxedRng` but without the allocation
+#[derive(Debug, Clone)]
+pub enum UnboxedRng {
+    Lcg(Lcg),
+    Pcg64(Pcg64),
+    Xoshiro256(Xoshiro256),
+}
+
+impl SonarRng for UnboxedRng {
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            UnboxedRng::Lcg(rng) => rng.next_u64(),
+            UnboxedRng::Pcg64(rng) => rng.next_u64(),
+            UnboxedRng::Xoshiro256(rng) => rng.next_u64(),
+        }
+    }
+}
+
+/// Lets the `rand`-based solvers draw from a `BoxedRng`
+#[cfg(feature = "metaheuristics")]
+impl rand::RngCore for dyn SonarRng + Send {
+    fn next_u32(&mut self) -> u32 {
+        (SonarRng::next_u64(self) >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        SonarRng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = SonarRng::next_u64(self).to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Lets the `rand`-based solvers draw from an `UnboxedRng`
+#[cfg(feature = "metaheuristics")]
+impl rand::RngCore for UnboxedRng {
+    fn next_u32(&mut self) -> u32 {
+        (SonarRng::next_u64(self) >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        SonarRng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = SonarRng::next_u64(self).to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generators_are_seeded_and_uniform() {
+        // Through the trait the LCG gives the same draws as before
+        let (mut legacy, mut boxed) = (Lcg::new(42), RngKind::Lcg.seeded(42));
+        for _ in 0..100 {
+            assert_eq!(legacy.next(), boxed.next_f64());
+        }
+
+        assert_eq!(RngKind::ALL[RngKind::DEFAULT_INDEX], RngKind::default());
+        for kind in RngKind::ALL {
+            assert_eq!(RngKind::ALL[kind.index()], kind);
+            assert_eq!(RngKind::from_name(kind.name()), Some(kind));
+            let (mut a, mut b) = (kind.seeded(7), kind.seeded(7));
+            let draws: [u64; 8] = core::array::from_fn(|_| a.next_u64());
+            assert_eq!(draws, core::array::from_fn(|_| b.next_u64()));
+            let mut unboxed = kind.unboxed(7);
+            assert_eq!(draws, core::array::from_fn(|_| unboxed.next_u64()));
+            assert_ne!(draws[0], kind.seeded(8).next_u64());
+
+            // Every bucket of 10 gets close to a tenth of the draws
+            let mut rng = kind.seeded(1);
+            let mut buckets = [0usize; 10];
+            for _ in 0..20_000 {
+                buckets[rng.below(10)] += 1;
+            }
+            assert!(buckets.iter().all(|&count| (1800..2200).contains(&count)), "{:?} {:?}", kind, buckets);
+        }
+        assert_eq!(RngKind::from_name("mersenne"), None);
+    }
+}
@@ -0,0 +1,128 @@
+//! Input sanitization for untrusted callers - what to check before points or
+//! matrices from an HTTP request or the browser reach a solver
+//!
+//! The solvers already reject empty input, non-finite coordinates and invalid
+//! distances. On top of that this bounds instance sizes, so one request cannot
+//! ask for a multi-gigabyte distance matrix, and coordinate magnitudes, so
+//! squared distances cannot overflow to infinity. Iteration counts are clamped
+//! rather than rejected.
+
+use alloc::vec::Vec;
+use crate::error::{validate_graph, validate_points, SonarError};
+use crate::math;
+use crate::utils::Point;
+use core::f64::consts::PI;
+
+/// Bounds applied by the `sanitize_*` functions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// Most points a coordinate-based solver is given
+    pub max_points: usize,
+    /// Most cities of a distance matrix, which takes O(n^2) memory
+    pub max_matrix_cities: usize,
+    /// Largest absolute coordinate; squared distances stay finite below 1e150
+    pub max_coordinate: f64,
+    /// Most improvement iterations or generations a caller may request
+    pub max_iterations: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits { max_points: 1_000_000, max_matrix_cities: 10_000, max_coordinate: 1e150, max_iterations: 100_000 }
+    }
+}
+
+impl Limits {
+    /// `requested` iterations, at most `max_iterations`
+    pub fn clamp_iterations(&self, requested: usize) -> usize {
+        requested.min(self.max_iterations)
+    }
+}
+
+/// Check `points` against `limits` and return them ready for any solver
+/// Ids are renumbered to match positions, which the solvers' tours refer to,
+/// and missing (non-finite) angles are recomputed around (0.5, 0.5) as
+/// `generate_normalized_points` does.
+/// Time complexity: O(n)
+pub fn sanitize_points(points: &[Point], limits: &Limits) -> Result<Vec<Point>, SonarError> {
+    validate_points(points)?;
+    if points.len() > limits.max_points {
+        return Err(SonarError::TooLarge { n: points.len(), max: limits.max_points });
+    }
+    let out_of_range = |p: &Point| p.x.abs() > limits.max_coordinate || p.y.abs() > limits.max_coordinate;
+    if let Some(index) = points.iter().position(out_of_range) {
+        return Err(SonarError::CoordinateOutOfRange { index });
+    }
+    Ok(points
+        .iter()
+        .enumerate()
+        .map(|(id, p)| {
+            let mut angle = p.angle;
+            if !angle.is_finite() {
+                angle = math::atan2(p.y - 0.5, p.x - 0.5);
+                if angle < 0.0 {
+                    angle += 2.0 * PI;
+                }
+            }
+            Point { x: p.x, y: p.y, angle, id }
+        })
+        .collect())
+}
+
+/// Check a distance matrix against `limits` and for the shape and values every
+/// matrix-based solver expects
+/// Time complexity: O(n^2)
+pub fn sanitize_matrix(graph: &[Vec<f64>], limits: &Limits) -> Result<(), SonarError> {
+    if graph.len() > limits.max_matrix_cities {
+        return Err(SonarError::TooLarge { n: graph.len(), max: limits.max_matrix_cities });
+    }
+    validate_graph(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::validate_tour;
+    use crate::problem::{Metric, ProblemInstance};
+    use crate::registry;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_sanitize_rejects_and_repairs() {
+        let limits = Limits { max_points: 3, max_matrix_cities: 2, ..Limits::default() };
+        let point = |x: f64, angle: f64, id: usize| Point { x, y: 0.5, angle, id };
+
+        let repaired = sanitize_points(&[point(0.0, f64::NAN, 7), point(1.0, 0.0, 7)], &limits).unwrap();
+        assert_eq!(repaired.iter().map(|p| p.id).collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(repaired[0].angle, PI);
+
+        let four = [point(0.0, 0.0, 0), point(0.1, 0.0, 1), point(0.2, 0.0, 2), point(0.3, 0.0, 3)];
+        let error = |points: &[Point]| sanitize_points(points, &limits).unwrap_err();
+        assert_eq!(error(&four), SonarError::TooLarge { n: 4, max: 3 });
+        assert_eq!(error(&[point(0.0, 0.0, 0), point(1e200, 0.0, 1)]), SonarError::CoordinateOutOfRange { index: 1 });
+        assert_eq!(error(&[point(f64::INFINITY, 0.0, 0)]), SonarError::NonFiniteCoordinate { index: 0 });
+
+        let square = vec![vec![0.0; 3]; 3];
+        assert_eq!(sanitize_matrix(&square, &limits), Err(SonarError::TooLarge { n: 3, max: 2 }));
+        assert_eq!(limits.clamp_iterations(usize::MAX), limits.max_iterations);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(24))]
+
+        /// Anything that passes sanitization is solved by every registered algorithm
+        #[test]
+        fn prop_sanitized_points_are_solvable(
+            coords in prop::collection::vec((-1e150..1e150f64, -1e150..1e150f64), 1..12)
+        ) {
+            let raw: Vec<Point> = coords.iter().map(|&(x, y)| Point { x, y, angle: f64::NAN, id: 0 }).collect();
+            let points = sanitize_points(&raw, &Limits::default()).unwrap();
+            let n = points.len();
+            let instance = ProblemInstance::new(points, Metric::Euclidean).unwrap();
+            for algorithm in registry::ALGORITHMS.iter().filter(|a| a.max_n.is_none_or(|max| n <= max.min(9))) {
+                let tour = (algorithm.solve)(&instance).unwrap();
+                prop_assert!(validate_tour(&tour, n).is_ok(), "{}", algorithm.key);
+            }
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! Numeric type used for coordinates, distances and tour lengths
+
+use crate::math;
+use core::fmt::Debug;
+use num_traits::Num;
+
+/// A float-like scalar: arithmetic and ordering from `num_traits::Num`, plus a
+/// square root for Euclidean distances
+/// Implemented for `f32` and `f64`. A fixed-point type only has to provide
+/// these operations to be used with `Point`, `DistanceMatrix` and
+/// `calculate_tour_length`. It must be `Send + Sync` so matrices of it can be
+/// built in parallel.
+pub trait Scalar: Num + Copy + PartialOrd + Debug + Send + Sync {
+    /// Square root, used for Euclidean distances
+    fn sqrt(self) -> Self;
+}
+
+impl Scalar for f64 {
+    fn sqrt(self) -> Self {
+        math::sqrt(self)
+    }
+}
+
+impl Scalar for f32 {
+    fn sqrt(self) -> Self {
+        math::sqrtf(self)
+    }
+}
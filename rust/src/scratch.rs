@@ -0,0 +1,125 @@
+//! Working buffers reused across solves
+//!
+//! A caller solving many small instances, thousands a second as measured by
+//! the throughput mode, pays for the allocations of every solve as much as for
+//! the search. The `_with` variants of the solvers below take a
+//! `SolverScratch` and an output tour instead: they size the buffers on the
+//! first call and only clear them afterwards, so repeated solves at the same or
+//! a smaller n allocate nothing.
+//!
+//! - `nearest_neighbor::generate_tour_with`: visited flags
+//! - `greedy_edge::generate_tour_with`: candidate edges, degrees, union-find
+//!   parents, links and visited flags
+//! - `genetic::optimize_with`: the population, its offspring and fitnesses
+
+use alloc::vec::Vec;
+
+/// Buffers shared by the solvers taking one; each solver leaves them in an
+/// unspecified state, so one scratch serves any sequence of solvers
+#[derive(Debug, Clone, Default)]
+pub struct SolverScratch {
+    pub(crate) visited: Vec<bool>,
+    #[cfg(feature = "constructive")]
+    pub(crate) candidates: Vec<(usize, usize, f64)>,
+    #[cfg(feature = "constructive")]
+    pub(crate) degree: Vec<usize>,
+    #[cfg(feature = "constructive")]
+    pub(crate) parent: Vec<usize>,
+    #[cfg(feature = "constructive")]
+    pub(crate) links: Vec<[usize; 2]>,
+    #[cfg(feature = "metaheuristics")]
+    pub(crate) population: Vec<Vec<usize>>,
+    #[cfg(feature = "metaheuristics")]
+    pub(crate) offspring: Vec<Vec<usize>>,
+    #[cfg(feature = "metaheuristics")]
+    pub(crate) fitnesses: Vec<f64>,
+    #[cfg(feature = "metaheuristics")]
+    pub(crate) best: Vec<usize>,
+}
+
+impl SolverScratch {
+    pub fn new() -> Self {
+        SolverScratch::default()
+    }
+}
+
+/// Empty `buffer` and refill it with `n` copies of `value`, keeping its capacity
+pub(crate) fn reset<T: Clone>(buffer: &mut Vec<T>, n: usize, value: T) {
+    buffer.clear();
+    buffer.resize(n, value);
+}
+
+#[cfg(all(test, feature = "constructive", feature = "metaheuristics"))]
+mod tests {
+    use super::*;
+    use crate::error::validate_tour;
+    use crate::utils::{create_distance_matrix, generate_normalized_points};
+    use crate::{genetic, greedy_edge, nearest_neighbor};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    thread_local! {
+        /// Allocations made by this thread so far
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// The system allocator, counting each thread's allocations
+    struct Counting;
+
+    // SAFETY: every call is passed on to `System` unchanged
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.with(|count| count.set(count.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            ALLOCATIONS.with(|count| count.set(count.get() + 1));
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static COUNTING: Counting = Counting;
+
+    /// Allocations `f` makes on this thread
+    fn allocations(f: impl FnOnce()) -> usize {
+        let before = ALLOCATIONS.with(Cell::get);
+        f();
+        ALLOCATIONS.with(Cell::get) - before
+    }
+
+    #[test]
+    fn test_reused_scratch_matches_fresh_solves_without_reallocating() {
+        let mut scratch = SolverScratch::new();
+        let mut tour = Vec::new();
+        let mut addresses = None;
+        for seed in 1..4 {
+            let graph = create_distance_matrix(&generate_normalized_points(40, 20, seed));
+            let mut allocated = allocations(|| {
+                nearest_neighbor::generate_tour_with(40, &graph, 0, &mut scratch, &mut tour).unwrap()
+            });
+            assert_eq!(tour, nearest_neighbor::generate_tour(40, &graph, 0).unwrap());
+            allocated += allocations(|| greedy_edge::generate_tour_with(40, &graph, &mut scratch, &mut tour).unwrap());
+            assert_eq!(tour, greedy_edge::generate_tour(40, &graph).unwrap());
+            allocated += allocations(|| {
+                genetic::optimize_with(&graph, 40, 20, 10, 0.1, &mut scratch, &mut tour).unwrap()
+            });
+            assert!(seed == 1 || allocated == 0, "seed {} allocated {} times", seed, allocated);
+            validate_tour(&tour, 40).unwrap();
+
+            let now = (
+                tour.as_ptr(),
+                scratch.visited.as_ptr(),
+                scratch.candidates.as_ptr(),
+                scratch.population[5].as_ptr(),
+            );
+            assert!(addresses.is_none_or(|before| before == now), "seed {} reallocated", seed);
+            addresses = Some(now);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Custom objectives written in Rhai, tried without recompiling
+//!
+//! A script defines either or both of two functions:
+//!
+//! - `edge_cost(a, b, distance)`: the cost of travelling from `a` to `b`,
+//!   object maps with `id`, `x` and `y`, whose distance under the metric is
+//!   `distance`. `costs` tabulates it for every pair, and
+//!   `ProblemInstance::from_provider` turns the table into an instance that
+//!   every matrix-based solver and improver then optimizes.
+//! - `tour_penalty(tour)`: an extra cost of the whole tour, given as an array
+//!   of city indices, for objectives no edge cost can express. Only `improve`
+//!   charges it, being the one improver that evaluates whole tours.
+//!
+//! Scripts are interpreted, so both are slow next to the built-in metrics;
+//! they are meant for experiments on small instances.
+
+use crate::problem::Metric;
+use crate::utils::{DistanceMatrix, Point};
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+
+/// A compiled objective script
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    edge_cost: bool,
+    tour_penalty: bool,
+}
+
+impl Script {
+    /// Compile `source`, which must define `edge_cost` with three parameters,
+    /// `tour_penalty` with one, or both
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let engine = Engine::new();
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        let defines = |name: &str, arity| ast.iter_functions().any(|f| f.name == name && f.params.len() == arity);
+        let (edge_cost, tour_penalty) = (defines("edge_cost", 3), defines("tour_penalty", 1));
+        if !edge_cost && !tour_penalty {
+            return Err("the script defines neither edge_cost(a, b, distance) nor tour_penalty(tour)".to_string());
+        }
+        Ok(Script { engine, ast, edge_cost, tour_penalty })
+    }
+
+    pub fn has_edge_cost(&self) -> bool {
+        self.edge_cost
+    }
+
+    pub fn has_tour_penalty(&self) -> bool {
+        self.tour_penalty
+    }
+
+    /// Call `name` and read its result as a number
+    fn call(&self, name: &str, args: Vec<Dynamic>) -> Result<f64, String> {
+        let result: Dynamic = self.engine.call_fn(&mut Scope::new(), &self.ast, name, args).map_err(|e| e.to_string())?;
+        let value = result.as_float().or_else(|_| result.as_int().map(|i| i as f64));
+        value.map_err(|found| format!("{} returned {} instead of a number", name, found))
+    }
+
+    /// The cost of every ordered pair of `points` under `edge_cost`, zero on the diagonal
+    /// Time complexity: O(n^2) script calls
+    pub fn costs(&self, points: &[Point], metric: Metric) -> Result<DistanceMatrix, String> {
+        if !self.edge_cost {
+            return Err("the script defines no edge_cost(a, b, distance)".to_string());
+        }
+        let maps: Vec<Dynamic> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let mut map = Map::new();
+                map.insert("id".into(), Dynamic::from_int(i as i64));
+                map.insert("x".into(), Dynamic::from_float(p.x));
+                map.insert("y".into(), Dynamic::from_float(p.y));
+                Dynamic::from_map(map)
+            })
+            .collect();
+        let mut costs = vec![vec![0.0; points.len()]; points.len()];
+        for (i, row) in costs.iter_mut().enumerate() {
+            for (j, cost) in row.iter_mut().enumerate().filter(|&(j, _)| j != i) {
+                let distance = Dynamic::from_float(metric.distance(&points[i], &points[j]));
+                *cost = self.call("edge_cost", vec![maps[i].clone(), maps[j].clone(), distance])?;
+                if cost.is_nan() || *cost < 0.0 {
+                    return Err(format!("edge_cost from {} to {} is {}, not a cost", i, j, cost));
+                }
+            }
+        }
+        Ok(costs)
+    }
+
+    /// `tour_penalty` of `tour`, or zero if the script does not define it
+    pub fn penalty(&self, tour: &[usize]) -> Result<f64, String> {
+        if !self.tour_penalty {
+            return Ok(0.0);
+        }
+        let tour: Array = tour.iter().map(|&city| Dynamic::from_int(city as i64)).collect();
+        self.call("tour_penalty", vec![Dynamic::from_array(tour)])
+    }
+
+    /// Length of `tour` on `graph` plus its penalty
+    pub fn objective(&self, tour: &[usize], graph: &[Vec<f64>]) -> Result<f64, String> {
+        let length: f64 = (0..tour.len()).map(|k| graph[tour[k]][tour[(k + 1) % tour.len()]]).sum();
+        Ok(length + self.penalty(tour)?)
+    }
+
+    /// 2-opt on `objective`: every reversal is scored on the whole tour, with
+    /// its penalty, and kept if that lowers the objective
+    /// Time complexity: O(n^3) plus O(n^2) penalty calls per iteration
+    pub fn improve(&self, tour: &[usize], graph: &[Vec<f64>], max_iterations: usize) -> Result<Vec<usize>, String> {
+        let n = tour.len();
+        let mut tour = tour.to_vec();
+        let mut best = self.objective(&tour, graph)?;
+        for _ in 0..max_iterations {
+            let mut improved = false;
+            for i in 1..n {
+                for j in i + 1..n {
+                    tour[i..=j].reverse();
+                    let candidate = self.objective(&tour, graph)?;
+                    if candidate < best - 1e-10 {
+                        best = candidate;
+                        improved = true;
+                    } else {
+                        tour[i..=j].reverse();
+                    }
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+        Ok(tour)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::ProblemInstance;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_scripted_costs_and_penalty_steer_the_tour() {
+        // Moving right costs double, and city 3 must come right after city 0
+        let script = Script::compile(
+            "fn edge_cost(a, b, distance) { if b.x > a.x { 2.0 * distance } else { distance } }
+             fn tour_penalty(tour) { if tour[1] == 3 { 0 } else { 100.0 } }",
+        )
+        .unwrap();
+        let points = generate_normalized_points(8, 10, 5);
+        let costs = script.costs(&points, Metric::Euclidean).unwrap();
+        let (a, b) = if points[0].x < points[1].x { (0, 1) } else { (1, 0) };
+        assert!((costs[a][b] - 2.0 * costs[b][a]).abs() < 1e-9);
+
+        let instance = ProblemInstance::from_provider(points, &costs).unwrap();
+        let tour: Vec<usize> = (0..8).collect();
+        assert_eq!(script.penalty(&tour).unwrap(), 100.0);
+        let improved = script.improve(&tour, instance.matrix(), 100).unwrap();
+        assert_eq!(improved[1], 3);
+        assert!(script.objective(&improved, instance.matrix()).unwrap() < instance.tour_length(&tour));
+
+        assert!(Script::compile("fn unrelated() { 1 }").is_err());
+        assert!(Script::compile("fn edge_cost(a, b, distance) { -distance }")
+            .unwrap()
+            .costs(&instance.points()[..3], Metric::Euclidean)
+            .is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Sector-partitioned parallel solve - angular sectors solved independently, stitched together
+
+use crate::error::{validate_points, SonarError};
+use crate::nearest_neighbor;
+use crate::two_opt;
+use crate::utils::{create_distance_matrix, distance, Point};
+use rayon::prelude::*;
+
+/// Generate a tour by splitting the points into `sectors` angular sectors
+/// Points are ordered by angle around their centroid and cut into sectors of
+/// equal size. Each sector is solved in parallel (grid nearest neighbor, then
+/// 2-opt on the sector's own distance matrix) and the sector cycles are opened
+/// and joined in angular order, each cut where it best continues from the
+/// previous sector's exit point.
+/// Time complexity: O(n log n) + O((n/k)^2 * max_iterations) per sector, k sectors in parallel
+pub fn generate_tour(points: &[Point], sectors: usize, max_iterations: usize) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
+    if sectors == 0 {
+        return Err(SonarError::InvalidParameter { name: "sectors", reason: "must be positive" });
+    }
+
+    let n = points.len() as f64;
+    let center_x = points.iter().map(|p| p.x).sum::<f64>() / n;
+    let center_y = points.iter().map(|p| p.y).sum::<f64>() / n;
+    let angle = |p: &Point| (p.y - center_y).atan2(p.x - center_x);
+
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| angle(&points[a]).total_cmp(&angle(&points[b])).then(a.cmp(&b)));
+
+    let sector_size = points.len().div_ceil(sectors);
+    let cycles: Vec<Vec<usize>> = order
+        .par_chunks(sector_size)
+        .map(|sector| solve_sector(points, sector, max_iterations))
+        .collect::<Result<_, _>>()?;
+
+    let mut tour: Vec<usize> = Vec::with_capacity(points.len());
+    for cycle in &cycles {
+        tour.extend(open_cycle(points, cycle, tour.last().copied()));
+    }
+    Ok(tour)
+}
+
+/// Solve one sector's points as a closed tour of global point indices
+fn solve_sector(points: &[Point], sector: &[usize], max_iterations: usize) -> Result<Vec<usize>, SonarError> {
+    let local: Vec<Point> = sector.iter().map(|&i| points[i].clone()).collect();
+    let initial = nearest_neighbor::generate_tour_grid(&local, 0)?;
+    let graph = create_distance_matrix(&local);
+    let tour = two_opt::improve(&initial, &graph, max_iterations)?;
+    Ok(tour.into_iter().map(|i| sector[i]).collect())
+}
+
+/// Turn a cycle into a path by removing one of its edges
+/// With a previous exit point, the removed edge is the one whose endpoint is
+/// cheapest to enter from it; the first sector drops its longest edge.
+pub(crate) fn open_cycle(points: &[Point], cycle: &[usize], previous: Option<usize>) -> Vec<usize> {
+    let m = cycle.len();
+    if m <= 1 {
+        return cycle.to_vec();
+    }
+
+    // (cost, position of the edge's first city, whether the path runs backwards)
+    let mut best = (f64::INFINITY, 0, false);
+    for p in 0..m {
+        let (u, v) = (cycle[p], cycle[(p + 1) % m]);
+        let removed = distance(&points[u], &points[v]);
+        let options = match previous {
+            Some(e) => [
+                (distance(&points[e], &points[v]) - removed, false),
+                (distance(&points[e], &points[u]) - removed, true),
+            ],
+            None => [(-removed, false), (f64::INFINITY, true)],
+        };
+        for (cost, backwards) in options {
+            if cost < best.0 {
+                best = (cost, p, backwards);
+            }
+        }
+    }
+
+    // Path from v around the cycle to u, reversed to run from u when backwards
+    let (_, p, backwards) = best;
+    let mut path: Vec<usize> = cycle[p + 1..].iter().chain(&cycle[..=p]).copied().collect();
+    if backwards {
+        path.reverse();
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{calculate_tour_length, generate_normalized_points};
+
+    #[test]
+    fn test_sector_tour_visits_all_and_beats_angular_sort() {
+        let points = generate_normalized_points(400, 40, 11);
+        let tour = generate_tour(&points, 8, 50).unwrap();
+
+        assert_eq!(tour.len(), points.len());
+        let mut seen = std::collections::HashSet::new();
+        for &id in &tour {
+            assert!(seen.insert(id));
+        }
+
+        let graph = create_distance_matrix(&points);
+        let sweep = crate::angular_sort::generate_tour(&points).unwrap();
+        assert!(calculate_tour_length(&tour, &graph) < calculate_tour_length(&sweep, &graph));
+
+        // More sectors than points still yields a full tour
+        assert_eq!(generate_tour(&points[..5], 16, 10).unwrap().len(), 5);
+    }
+}
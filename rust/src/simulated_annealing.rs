@@ -1,7 +1,118 @@
 //! Simulated Annealing algorithm for TSP
 
+use crate::determinism;
+use crate::error::{validate_graph, validate_tour, SonarError};
+use crate::precedence::Precedence;
+use crate::stepwise::SteppableSolver;
 use crate::utils::calculate_tour_length;
+use crate::rng::BoxedRng;
 use rand::Rng;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Pick a random segment and compute the length change of reversing it (2-opt move)
+/// Returns the segment bounds and the delta
+fn propose_reversal(graph: &[Vec<f64>], tour: &[usize], rng: &mut impl Rng) -> (usize, usize, f64) {
+    let n = tour.len();
+    let i = rng.gen_range(0..n - 1);
+    let mut j = rng.gen_range(0..n - 1);
+    if j >= i {
+        j += 1;
+    }
+
+    let (min_idx, max_idx) = if i < j { (i, j) } else { (j, i) };
+    if min_idx == 0 && max_idx == n - 1 {
+        // Reversing the whole tour leaves its length unchanged
+        return (min_idx, max_idx, 0.0);
+    }
+
+    // Calculate delta
+    let prev = (min_idx + n - 1) % n;
+    let next_max = (max_idx + 1) % n;
+
+    let old_dist = graph[tour[prev]][tour[min_idx]] + graph[tour[max_idx]][tour[next_max]];
+    let new_dist = graph[tour[prev]][tour[max_idx]] + graph[tour[min_idx]][tour[next_max]];
+
+    (min_idx, max_idx, new_dist - old_dist)
+}
+
+/// Neighbourhood move proposed by simulated annealing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveKind {
+    /// Reverse a segment (2-opt move)
+    Reversal,
+    /// Remove one city and reinsert it elsewhere
+    Insertion,
+    /// Exchange the positions of two cities
+    Swap,
+}
+
+/// Pick a move kind from `(kind, weight)` pairs with probability proportional to weight
+fn choose_move(moves: &[(MoveKind, f64)], rng: &mut impl Rng) -> MoveKind {
+    let total: f64 = moves.iter().map(|&(_, w)| w.max(0.0)).sum();
+    let mut r = rng.gen::<f64>() * total;
+    for &(kind, weight) in moves {
+        r -= weight.max(0.0);
+        if r <= 0.0 {
+            return kind;
+        }
+    }
+    moves.last().map(|&(kind, _)| kind).unwrap_or(MoveKind::Reversal)
+}
+
+/// Pick a random city and target slot, and compute the length change of moving it
+/// `to` is the index of the city in the resulting tour
+fn propose_insertion(graph: &[Vec<f64>], tour: &[usize], rng: &mut impl Rng) -> (usize, usize, f64) {
+    let n = tour.len();
+    let from = rng.gen_range(0..n);
+    let to = rng.gen_range(0..n);
+
+    let city = tour[from];
+    let prev = tour[(from + n - 1) % n];
+    let next = tour[(from + 1) % n];
+    let removal = graph[prev][city] + graph[city][next] - graph[prev][next];
+
+    // Tour without `city`, indexed cyclically
+    let without = |k: usize| {
+        let k = k % (n - 1);
+        if k < from { tour[k] } else { tour[k + 1] }
+    };
+    let x = without(to + n - 2);
+    let y = without(to);
+    let insertion = graph[x][city] + graph[city][y] - graph[x][y];
+
+    (from, to, insertion - removal)
+}
+
+/// Pick two random positions and compute the length change of swapping their cities
+fn propose_swap(graph: &[Vec<f64>], tour: &mut [usize], rng: &mut impl Rng) -> (usize, usize, f64) {
+    let n = tour.len();
+    let i = rng.gen_range(0..n);
+    let mut j = rng.gen_range(0..n - 1);
+    if j >= i {
+        j += 1;
+    }
+
+    // Only the edges starting at these positions change
+    let mut starts = [(i + n - 1) % n, i, (j + n - 1) % n, j];
+    starts.sort_unstable();
+    let edges = |tour: &[usize]| -> f64 {
+        let mut total = 0.0;
+        for (k, &start) in starts.iter().enumerate() {
+            if k == 0 || starts[k - 1] != start {
+                total += graph[tour[start]][tour[(start + 1) % n]];
+            }
+        }
+        total
+    };
+
+    let before = edges(tour);
+    tour.swap(i, j);
+    let after = edges(tour);
+    tour.swap(i, j);
+
+    (i, j, after - before)
+}
 
 /// Optimize a tour using Simulated Annealing
 /// Metaheuristic that accepts worse solutions with decreasing probability
@@ -13,57 +124,410 @@ pub fn optimize(
     max_iterations: usize,
     initial_temperature: f64,
     cooling_rate: f64,
-) -> Vec<usize> {
-    let mut rng = rand::thread_rng();
-    let n = initial_tour.len();
+) -> Result<Vec<usize>, SonarError> {
+    optimize_with_moves(
+        graph,
+        initial_tour,
+        max_iterations,
+        initial_temperature,
+        cooling_rate,
+        &[(MoveKind::Reversal, 1.0)],
+    )
+}
+
+/// Optimize a tour using Simulated Annealing with a mixed neighbourhood
+/// Each iteration picks a move kind from `moves` with probability proportional
+/// to its weight; mixing insertion and swap moves with reversals helps on
+/// clustered instances where pure 2-opt moves get stuck
+/// Time complexity: O(n * iterations)
+pub fn optimize_with_moves(
+    graph: &[Vec<f64>],
+    initial_tour: &[usize],
+    max_iterations: usize,
+    initial_temperature: f64,
+    cooling_rate: f64,
+    moves: &[(MoveKind, f64)],
+) -> Result<Vec<usize>, SonarError> {
+    let mut solver = AnnealingSolver::new(
+        graph,
+        initial_tour,
+        max_iterations,
+        initial_temperature,
+        cooling_rate,
+        moves,
+        max_iterations.max(1),
+    )?;
+    Ok(solver.run())
+}
+
+/// A move the Metropolis criterion accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcceptedMove {
+    /// Iteration the move was accepted in, counting from 0
+    pub iteration: usize,
+    pub kind: MoveKind,
+    /// Move operands as returned by the proposal (segment bounds or positions)
+    pub i: usize,
+    pub j: usize,
+}
+
+/// Simulated annealing run `iterations_per_step` iterations per `step`
+/// Same search as `optimize_with_moves`, which runs it in one step; stepping
+/// two solvers with equally seeded RNGs (`with_rng`) gives the same tour.
+pub struct AnnealingSolver<'a, R: Rng = BoxedRng> {
+    graph: &'a [Vec<f64>],
+    moves: &'a [(MoveKind, f64)],
+    rng: R,
+    current_tour: Vec<usize>,
+    current_length: f64,
+    best_tour: Vec<usize>,
+    best_length: f64,
+    temperature: f64,
+    cooling_rate: f64,
+    remaining: usize,
+    iterations_per_step: usize,
+    iteration: usize,
+    accepted: Option<Vec<AcceptedMove>>,
+    precedence: Option<&'a Precedence>,
+}
+
+impl<'a> AnnealingSolver<'a> {
+    pub fn new(
+        graph: &'a [Vec<f64>],
+        initial_tour: &[usize],
+        max_iterations: usize,
+        initial_temperature: f64,
+        cooling_rate: f64,
+        moves: &'a [(MoveKind, f64)],
+        iterations_per_step: usize,
+    ) -> Result<Self, SonarError> {
+        validate_graph(graph)?;
+        validate_tour(initial_tour, graph.len())?;
+        validate_temperature("initial_temperature", initial_temperature)?;
+        if !(cooling_rate > 0.0 && cooling_rate <= 1.0) {
+            return Err(SonarError::InvalidParameter { name: "cooling_rate", reason: "must be in (0, 1]" });
+        }
+        if !moves.iter().any(|&(_, weight)| weight > 0.0) {
+            return Err(SonarError::InvalidParameter { name: "moves", reason: "need a positive weight" });
+        }
+        if iterations_per_step == 0 {
+            return Err(SonarError::InvalidParameter { name: "iterations_per_step", reason: "must be positive" });
+        }
+
+        let current_length = calculate_tour_length(initial_tour, graph);
+        Ok(AnnealingSolver {
+            graph,
+            moves,
+            rng: determinism::rng(0),
+            current_tour: initial_tour.to_vec(),
+            current_length,
+            best_tour: initial_tour.to_vec(),
+            best_length: current_length,
+            temperature: initial_temperature,
+            cooling_rate,
+            // Tours of fewer than 4 cities have no improving moves
+            remaining: if initial_tour.len() < 4 { 0 } else { max_iterations },
+            iterations_per_step,
+            iteration: 0,
+            accepted: None,
+            precedence: None,
+        })
+    }
+}
+
+impl<'a, R: Rng> AnnealingSolver<'a, R> {
+    /// Draw random numbers from `rng` instead of the default stream
+    pub fn with_rng<S: Rng>(self, rng: S) -> AnnealingSolver<'a, S> {
+        AnnealingSolver {
+            graph: self.graph,
+            moves: self.moves,
+            rng,
+            current_tour: self.current_tour,
+            current_length: self.current_length,
+            best_tour: self.best_tour,
+            best_length: self.best_length,
+            temperature: self.temperature,
+            cooling_rate: self.cooling_rate,
+            remaining: self.remaining,
+            iterations_per_step: self.iterations_per_step,
+            iteration: self.iteration,
+            accepted: self.accepted,
+            precedence: self.precedence,
+        }
+    }
+
+    /// Only keep tours that honour `precedence`: a move the Metropolis
+    /// criterion accepts is undone if it puts a pair out of order
+    /// Fails unless the initial tour already honours every pair.
+    pub fn with_precedence(mut self, precedence: &'a Precedence) -> Result<Self, SonarError> {
+        precedence.validate(&self.current_tour)?;
+        self.precedence = Some(precedence);
+        Ok(self)
+    }
+
+    /// Keep a list of every accepted move, see `accepted_moves`
+    pub fn record_moves(mut self) -> Self {
+        self.accepted = Some(Vec::new());
+        self
+    }
+
+    /// Moves accepted so far; empty unless `record_moves` was called
+    pub fn accepted_moves(&self) -> &[AcceptedMove] {
+        self.accepted.as_deref().unwrap_or(&[])
+    }
+
+    pub fn rng(&self) -> &R {
+        &self.rng
+    }
+}
 
-    let mut current_tour = initial_tour.to_vec();
-    let mut current_length = calculate_tour_length(&current_tour, graph);
-    let mut best_tour = current_tour.clone();
-    let mut best_length = current_length;
+impl<R: Rng> SteppableSolver for AnnealingSolver<'_, R> {
+    /// Time complexity: O(n * iterations_per_step)
+    fn step(&mut self) -> &[usize] {
+        let graph = self.graph;
+        let rng = &mut self.rng;
+        let current_tour = &mut self.current_tour;
+        let batch = self.iterations_per_step.min(self.remaining);
+        self.remaining -= batch;
 
-    let mut temperature = initial_temperature;
+        for _ in 0..batch {
+            let kind = choose_move(self.moves, rng);
+            let (i, j, delta) = match kind {
+                MoveKind::Reversal => propose_reversal(graph, current_tour, rng),
+                MoveKind::Insertion => propose_insertion(graph, current_tour, rng),
+                MoveKind::Swap => propose_swap(graph, current_tour, rng),
+            };
 
-    for _ in 0..max_iterations {
-        // Generate neighbor by 2-opt swap
-        let i = rng.gen_range(0..n - 1);
-        let mut j = rng.gen_range(0..n - 1);
-        if j >= i {
-            j += 1;
+            // Accept or reject based on Metropolis criterion
+            if delta < 0.0 || rng.gen::<f64>() < (-delta / self.temperature).exp() {
+                match kind {
+                    MoveKind::Reversal => current_tour[i..=j].reverse(),
+                    MoveKind::Insertion => {
+                        let city = current_tour.remove(i);
+                        current_tour.insert(j, city);
+                    }
+                    MoveKind::Swap => current_tour.swap(i, j),
+                }
+                if self.precedence.is_some_and(|precedence| !precedence.is_satisfied(current_tour)) {
+                    // Undo a move that puts a pair out of order
+                    match kind {
+                        MoveKind::Reversal => current_tour[i..=j].reverse(),
+                        MoveKind::Insertion => {
+                            let city = current_tour.remove(j);
+                            current_tour.insert(i, city);
+                        }
+                        MoveKind::Swap => current_tour.swap(i, j),
+                    }
+                } else {
+                    self.current_length += delta;
+                    if let Some(accepted) = &mut self.accepted {
+                        accepted.push(AcceptedMove { iteration: self.iteration, kind, i, j });
+                    }
+
+                    if self.current_length < self.best_length {
+                        self.best_tour = current_tour.clone();
+                        self.best_length = self.current_length;
+                    }
+                }
+            }
+
+            self.temperature *= self.cooling_rate;
+            self.iteration += 1;
         }
 
-        let (min_idx, max_idx) = if i < j { (i, j) } else { (j, i) };
+        &self.best_tour
+    }
+
+    fn tour(&self) -> &[usize] {
+        &self.best_tour
+    }
+
+    fn is_finished(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+/// Temperatures must be positive and finite for the Metropolis criterion
+fn validate_temperature(name: &'static str, temperature: f64) -> Result<(), SonarError> {
+    if !(temperature > 0.0 && temperature.is_finite()) {
+        return Err(SonarError::InvalidParameter { name, reason: "must be positive and finite" });
+    }
+    Ok(())
+}
 
-        // Calculate delta
-        let prev = (min_idx + n - 1) % n;
-        let next_max = (max_idx + 1) % n;
+/// Optimize a tour using parallel tempering (replica exchange) simulated annealing
+/// `chains` copies of the tour run at fixed temperatures spaced geometrically
+/// between `min_temperature` and `max_temperature`, each on its own thread.
+/// After every `iterations_per_exchange` steps, adjacent chains swap their tours
+/// with the replica-exchange probability min(1, exp((1/T_i - 1/T_j)(L_i - L_j))),
+/// so good tours drift to cold chains while hot chains keep exploring.
+/// Time complexity: O(chains * exchanges * iterations_per_exchange) on chains threads
+pub fn parallel_tempering(
+    graph: &[Vec<f64>],
+    initial_tour: &[usize],
+    chains: usize,
+    exchanges: usize,
+    iterations_per_exchange: usize,
+    min_temperature: f64,
+    max_temperature: f64,
+) -> Result<Vec<usize>, SonarError> {
+    validate_graph(graph)?;
+    validate_tour(initial_tour, graph.len())?;
+    validate_temperature("min_temperature", min_temperature)?;
+    validate_temperature("max_temperature", max_temperature)?;
 
-        let old_dist = graph[current_tour[prev]][current_tour[min_idx]]
-            + graph[current_tour[max_idx]][current_tour[next_max]];
-        let new_dist = graph[current_tour[prev]][current_tour[max_idx]]
-            + graph[current_tour[min_idx]][current_tour[next_max]];
+    let chains = chains.max(1);
+    let n = initial_tour.len();
+    let initial_length = calculate_tour_length(initial_tour, graph);
+    if n < 4 {
+        return Ok(initial_tour.to_vec());
+    }
 
-        let delta = new_dist - old_dist;
+    let ratio = if chains > 1 {
+        (max_temperature / min_temperature).powf(1.0 / (chains - 1) as f64)
+    } else {
+        1.0
+    };
+    let temperatures: Vec<f64> = (0..chains)
+        .map(|k| min_temperature * ratio.powi(k as i32))
+        .collect();
 
-        // Accept or reject based on Metropolis criterion
-        if delta < 0.0 || rng.gen::<f64>() < (-delta / temperature).exp() {
-            // Reverse the segment
-            current_tour[min_idx..=max_idx].reverse();
-            current_length = calculate_tour_length(&current_tour, graph);
+    // (tour, length) per chain, ordered from coldest to hottest
+    let mut states: Vec<(Vec<usize>, f64)> = vec![(initial_tour.to_vec(), initial_length); chains];
+    let mut best_tour = initial_tour.to_vec();
+    let mut best_length = initial_length;
+    let mut rng = determinism::rng(0);
 
-            if current_length < best_length {
-                best_tour = current_tour.clone();
-                best_length = current_length;
+    for exchange in 0..exchanges {
+        let results: Vec<(Vec<usize>, f64, Vec<usize>, f64)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = states
+                .drain(..)
+                .zip(&temperatures)
+                .enumerate()
+                .map(|(k, ((mut tour, mut length), &temperature))| {
+                    // One random stream per chain and exchange round
+                    let stream = 1 + (exchange * chains + k) as u64;
+                    scope.spawn(move || {
+                        let mut rng = determinism::rng(stream);
+                        let mut chain_best = tour.clone();
+                        let mut chain_best_length = length;
+                        for _ in 0..iterations_per_exchange {
+                            let (min_idx, max_idx, delta) = propose_reversal(graph, &tour, &mut rng);
+                            if delta < 0.0 || rng.gen::<f64>() < (-delta / temperature).exp() {
+                                tour[min_idx..=max_idx].reverse();
+                                length += delta;
+                                if length < chain_best_length {
+                                    chain_best = tour.clone();
+                                    chain_best_length = length;
+                                }
+                            }
+                        }
+                        (tour, length, chain_best, chain_best_length)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        for (tour, length, chain_best, chain_best_length) in results {
+            if chain_best_length < best_length {
+                best_length = chain_best_length;
+                best_tour = chain_best;
             }
+            states.push((tour, length));
         }
 
-        temperature *= cooling_rate;
+        // Replica exchange between neighbouring temperatures
+        for k in 0..chains - 1 {
+            let exponent = (1.0 / temperatures[k] - 1.0 / temperatures[k + 1])
+                * (states[k].1 - states[k + 1].1);
+            if exponent >= 0.0 || rng.gen::<f64>() < exponent.exp() {
+                states.swap(k, k + 1);
+            }
+        }
     }
 
-    best_tour
+    Ok(best_tour)
 }
 
-#[cfg(test)]
+/// Optimize with cooperating simulated annealing chains sharing an incumbent
+/// One chain per initial tour runs on its own thread with its own random
+/// stream. Every `exchange_interval` iterations a chain publishes its best tour
+/// to the shared incumbent if it beats it, or else restarts from the incumbent
+/// when that is shorter than its current tour. All chains stop at `time_limit`
+/// and the incumbent is returned.
+/// Time complexity: O(n) per iteration, chains threads until the deadline
+pub fn cooperative(
+    graph: &[Vec<f64>],
+    initial_tours: &[Vec<usize>],
+    time_limit: Duration,
+    exchange_interval: usize,
+    initial_temperature: f64,
+    cooling_rate: f64,
+) -> Result<Vec<usize>, SonarError> {
+    validate_graph(graph)?;
+    if initial_tours.is_empty() {
+        return Err(SonarError::InvalidParameter { name: "initial_tours", reason: "need at least one tour" });
+    }
+    for tour in initial_tours {
+        validate_tour(tour, graph.len())?;
+    }
+    validate_temperature("initial_temperature", initial_temperature)?;
+    if !(cooling_rate > 0.0 && cooling_rate <= 1.0) {
+        return Err(SonarError::InvalidParameter { name: "cooling_rate", reason: "must be in (0, 1]" });
+    }
+    if exchange_interval == 0 {
+        return Err(SonarError::InvalidParameter { name: "exchange_interval", reason: "must be positive" });
+    }
+
+    let incumbent = initial_tours
+        .iter()
+        .map(|tour| (tour.clone(), calculate_tour_length(tour, graph)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+    if graph.len() < 4 {
+        return Ok(incumbent.0);
+    }
+    let incumbent = Mutex::new(incumbent);
+    let deadline = Instant::now() + time_limit;
+
+    std::thread::scope(|scope| {
+        for (k, initial) in initial_tours.iter().enumerate() {
+            let incumbent = &incumbent;
+            scope.spawn(move || {
+                let mut rng = determinism::rng(1 + k as u64);
+                let mut tour = initial.clone();
+                let mut length = calculate_tour_length(&tour, graph);
+                let mut best = (tour.clone(), length);
+                let mut temperature = initial_temperature;
+                while Instant::now() < deadline {
+                    for _ in 0..exchange_interval {
+                        let (i, j, delta) = propose_reversal(graph, &tour, &mut rng);
+                        if delta < 0.0 || rng.gen::<f64>() < (-delta / temperature).exp() {
+                            tour[i..=j].reverse();
+                            length += delta;
+                            if length < best.1 {
+                                best = (tour.clone(), length);
+                            }
+                        }
+                        temperature *= cooling_rate;
+                    }
+
+                    let mut shared = incumbent.lock().unwrap();
+                    if best.1 < shared.1 {
+                        *shared = best.clone();
+                    } else if shared.1 < length {
+                        (tour, length) = shared.clone();
+                    }
+                }
+            });
+        }
+    });
+
+    Ok(incumbent.into_inner().unwrap().0)
+}
+
+#[cfg(all(test, feature = "constructive"))]
 mod tests {
     use super::*;
     use crate::utils::{create_distance_matrix, Point};
@@ -79,8 +543,8 @@ mod tests {
             Point { x: 0.0, y: 1.0, angle: PI / 2.0, id: 3 },
         ];
         let graph = create_distance_matrix(&points);
-        let initial = nearest_neighbor::generate_tour(points.len(), &graph, 0);
-        let tour = optimize(&graph, &initial, 5000, 1.0, 0.9995);
+        let initial = nearest_neighbor::generate_tour(points.len(), &graph, 0).unwrap();
+        let tour = optimize(&graph, &initial, 5000, 1.0, 0.9995).unwrap();
 
         assert_eq!(tour.len(), points.len());
 
@@ -90,4 +554,77 @@ mod tests {
             assert!(seen.insert(id));
         }
     }
+
+    #[test]
+    fn test_mixed_moves_track_length_and_improve() {
+        use crate::utils::generate_normalized_points;
+
+        let points = generate_normalized_points(50, 20, 4);
+        let graph = create_distance_matrix(&points);
+        let initial: Vec<usize> = (0..points.len()).collect();
+
+        // Each proposal's delta must match the actual length change
+        let mut rng = determinism::rng(0);
+        let mut tour = initial.clone();
+        for _ in 0..200 {
+            let length = calculate_tour_length(&tour, &graph);
+            let (i, j, delta) = propose_insertion(&graph, &tour, &mut rng);
+            let city = tour.remove(i);
+            tour.insert(j, city);
+            assert!((calculate_tour_length(&tour, &graph) - length - delta).abs() < 1e-9);
+
+            let length = calculate_tour_length(&tour, &graph);
+            let (i, j, delta) = propose_swap(&graph, &mut tour, &mut rng);
+            tour.swap(i, j);
+            assert!((calculate_tour_length(&tour, &graph) - length - delta).abs() < 1e-9);
+        }
+
+        let moves = [(MoveKind::Reversal, 0.6), (MoveKind::Insertion, 0.3), (MoveKind::Swap, 0.1)];
+        let optimized = optimize_with_moves(&graph, &initial, 20_000, 0.1, 0.9995, &moves).unwrap();
+        assert_eq!(optimized.len(), points.len());
+        assert!(calculate_tour_length(&optimized, &graph) < calculate_tour_length(&initial, &graph));
+
+        // Stepping in batches replays the same run
+        let seeded = |per_step| {
+            AnnealingSolver::new(&graph, &initial, 20_000, 0.1, 0.9995, &moves, per_step)
+                .unwrap()
+                .with_rng(determinism::seeded(7))
+        };
+        assert_eq!(seeded(300).run(), seeded(20_000).run());
+    }
+
+    #[test]
+    fn test_parallel_tempering_improves_tour() {
+        use crate::utils::generate_normalized_points;
+
+        let points = generate_normalized_points(60, 20, 3);
+        let graph = create_distance_matrix(&points);
+        let initial: Vec<usize> = (0..points.len()).collect();
+        let tour = parallel_tempering(&graph, &initial, 4, 20, 2_000, 0.001, 0.1).unwrap();
+
+        assert_eq!(tour.len(), points.len());
+        let mut seen = std::collections::HashSet::new();
+        for &id in &tour {
+            assert!(seen.insert(id));
+        }
+        assert!(calculate_tour_length(&tour, &graph) < calculate_tour_length(&initial, &graph));
+    }
+
+    #[test]
+    fn test_cooperative_chains_return_shared_best() {
+        use crate::utils::generate_normalized_points;
+
+        let points = generate_normalized_points(60, 20, 6);
+        let graph = create_distance_matrix(&points);
+        let starts: Vec<Vec<usize>> = [0, 20, 40]
+            .iter()
+            .map(|&start| nearest_neighbor::generate_tour(points.len(), &graph, start).unwrap())
+            .collect();
+        let tour = cooperative(&graph, &starts, Duration::from_millis(200), 500, 0.05, 0.9995).unwrap();
+
+        validate_tour(&tour, points.len()).unwrap();
+        let best_start = starts.iter().map(|t| calculate_tour_length(t, &graph)).fold(f64::INFINITY, f64::min);
+        assert!(calculate_tour_length(&tour, &graph) < best_start);
+        assert!(cooperative(&graph, &[], Duration::ZERO, 500, 0.05, 0.9995).is_err());
+    }
 }
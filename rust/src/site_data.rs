@@ -0,0 +1,272 @@
+//! Rust reference results for the methods of `optimization-comparison.html`
+//!
+//! The page runs fifteen method pipelines in JS on 18 points of a 40x40 grid,
+//! scaled to percent coordinates, and shows for each a name, path length,
+//! efficiency against the brute force optimum, runtime, complexity and what the
+//! method is best for. It embeds those constants itself and loads nothing, so
+//! `METHODS`, `NUM_POINTS` and `GRID_SIZE` copy them, checked against the page
+//! by the tests, and `export` computes the same cards in Rust for comparison
+//! with what the page shows.
+
+use crate::error::SonarError;
+use crate::utils::{
+    calculate_efficiency, calculate_tour_length, create_distance_matrix, generate_normalized_points, json_string, Point,
+};
+use crate::{
+    angular_sort, brute_force, genetic, greedy_edge, nearest_neighbor, simulated_annealing, sonar_visit, two_opt,
+    zigzag,
+};
+use std::fmt::Write;
+use std::time::Instant;
+
+/// Points per instance, `NUM_POINTS` on the page
+pub const NUM_POINTS: usize = 18;
+/// Grid resolution, `GRID_SIZE` on the page
+pub const GRID_SIZE: usize = 40;
+
+/// Builds a method's tour from percent-scaled points and their distance matrix
+type Run = fn(&[Point], &[Vec<f64>]) -> Result<Vec<usize>, SonarError>;
+
+/// One card of the comparison page
+pub struct Method {
+    pub name: &'static str,
+    pub complexity: &'static str,
+    pub best_for: &'static str,
+    run: Run,
+}
+
+fn sonar(points: &[Point]) -> Result<Vec<usize>, SonarError> {
+    sonar_visit::generate_tour(points, GRID_SIZE)
+}
+
+fn two_opt(tour: &[usize], graph: &[Vec<f64>]) -> Result<Vec<usize>, SonarError> {
+    two_opt::improve(tour, graph, 100)
+}
+
+/// The page's methods in card order; brute force, the optimal reference, comes last
+pub const METHODS: &[Method] = &[
+    Method {
+        name: "Sonar Visit",
+        complexity: "O(1)*",
+        best_for: "Constant-time approximation",
+        run: |points, _| sonar(points),
+    },
+    Method {
+        name: "Sonar Visit + Zigzag",
+        complexity: "O(n)",
+        best_for: "Fast local optimization",
+        run: |points, graph| zigzag::optimize(&sonar(points)?, points, graph),
+    },
+    Method {
+        name: "Sonar Visit + 2-Opt",
+        complexity: "O(n^2)",
+        best_for: "High-quality from O(1) base",
+        run: |points, graph| two_opt(&sonar(points)?, graph),
+    },
+    Method {
+        name: "Sonar Visit + Zigzag + 2-Opt",
+        complexity: "O(n^2)",
+        best_for: "Local then global optimization",
+        run: |points, graph| two_opt(&zigzag::optimize(&sonar(points)?, points, graph)?, graph),
+    },
+    Method {
+        name: "Sonar Visit + 2-Opt + Zigzag",
+        complexity: "O(n^2)",
+        best_for: "Global then local optimization",
+        run: |points, graph| zigzag::optimize(&two_opt(&sonar(points)?, graph)?, points, graph),
+    },
+    Method {
+        name: "Angular Sort",
+        complexity: "O(n log n)",
+        best_for: "Quick approximate solutions",
+        run: |points, _| angular_sort::generate_tour(points),
+    },
+    Method {
+        name: "Angular Sort + Zigzag",
+        complexity: "O(n log n)",
+        best_for: "Local optimization",
+        run: |points, graph| zigzag::optimize(&angular_sort::generate_tour(points)?, points, graph),
+    },
+    Method {
+        name: "Angular Sort + 2-Opt",
+        complexity: "O(n^2)",
+        best_for: "High-quality solutions",
+        run: |points, graph| two_opt(&angular_sort::generate_tour(points)?, graph),
+    },
+    Method {
+        name: "Angular Sort + Zigzag + 2-Opt",
+        complexity: "O(n^2)",
+        best_for: "Local then global optimization",
+        run: |points, graph| {
+            two_opt(&zigzag::optimize(&angular_sort::generate_tour(points)?, points, graph)?, graph)
+        },
+    },
+    Method {
+        name: "Angular Sort + 2-Opt + Zigzag",
+        complexity: "O(n^2)",
+        best_for: "Global then local optimization",
+        run: |points, graph| {
+            zigzag::optimize(&two_opt(&angular_sort::generate_tour(points)?, graph)?, points, graph)
+        },
+    },
+    Method {
+        name: "Nearest Neighbor",
+        complexity: "O(n^2)",
+        best_for: "Fast approximate solution",
+        run: |points, graph| nearest_neighbor::generate_tour(points.len(), graph, 0),
+    },
+    Method {
+        name: "Greedy (Nearest Edge)",
+        complexity: "O(n^2 log n)",
+        best_for: "Good average performance",
+        run: |points, graph| greedy_edge::generate_tour(points.len(), graph),
+    },
+    Method {
+        name: "Simulated Annealing",
+        complexity: "O(n * iter)",
+        best_for: "Near-optimal solutions",
+        run: |points, graph| {
+            let initial = nearest_neighbor::generate_tour(points.len(), graph, 0)?;
+            simulated_annealing::optimize(graph, &initial, 5000, 1.0, 0.9995)
+        },
+    },
+    Method {
+        name: "Genetic Algorithm",
+        complexity: "O(pop * gen * n)",
+        best_for: "Large-scale optimization",
+        run: |points, graph| genetic::optimize(graph, points.len(), 50, 100, 0.1),
+    },
+    Method {
+        name: "Brute Force (Optimal)",
+        complexity: "O(2^n * n^2)",
+        best_for: "Optimal reference (small n)",
+        run: |points, graph| brute_force::find_optimal(graph, points.len()).map(|(tour, _)| tour),
+    },
+];
+
+/// Sizes the methods are additionally timed at, brute force excepted
+pub const BENCHMARK_SIZES: &[usize] = &[100, 1000];
+
+/// A method's tour, its length and its runtime in ms
+fn run_method(method: &Method, points: &[Point], graph: &[Vec<f64>]) -> Result<(Vec<usize>, f64, f64), SonarError> {
+    let start = Instant::now();
+    let tour = (method.run)(points, graph)?;
+    let runtime = start.elapsed().as_secs_f64() * 1000.0;
+    let length = calculate_tour_length(&tour, graph);
+    Ok((tour, length, runtime))
+}
+
+/// Normalized points from `seed` scaled to the page's percent coordinates
+/// Angles and ids are kept, as `scalePointsToPercent` does.
+fn percent_points(n: usize, seed: u64) -> Vec<Point> {
+    generate_normalized_points(n, GRID_SIZE, seed)
+        .into_iter()
+        .map(|p| Point { x: p.x * 100.0, y: p.y * 100.0, ..p })
+        .collect()
+}
+
+/// JSON document with the page's algorithms, one instance sample per seed with
+/// every method's result on it, and the methods' runtimes at `BENCHMARK_SIZES`
+/// Efficiency is capped at 100% as on the page.
+pub fn export(seeds: &[u64]) -> Result<String, SonarError> {
+    let mut out = String::new();
+    writeln!(out, "{{\n  \"numPoints\": {},\n  \"gridSize\": {},", NUM_POINTS, GRID_SIZE).unwrap();
+
+    let algorithms: Vec<String> = METHODS
+        .iter()
+        .map(|m| {
+            format!(
+                "    {{ \"name\": {}, \"complexity\": {}, \"bestFor\": {} }}",
+                json_string(m.name), json_string(m.complexity), json_string(m.best_for)
+            )
+        })
+        .collect();
+    writeln!(out, "  \"algorithms\": [\n{}\n  ],", algorithms.join(",\n")).unwrap();
+
+    let mut samples = Vec::new();
+    for &seed in seeds {
+        let points = percent_points(NUM_POINTS, seed);
+        let graph = create_distance_matrix(&points);
+        let (_, optimal_length) = brute_force::find_optimal(&graph, points.len())?;
+        let mut results = Vec::new();
+        for method in METHODS {
+            let (tour, length, runtime) = run_method(method, &points, &graph)?;
+            let tour: Vec<String> = tour.iter().map(usize::to_string).collect();
+            results.push(format!(
+                "        {{ \"name\": {}, \"pathLength\": {:.4}, \"efficiency\": {:.2}, \"runtime\": {:.4}, \
+                 \"tour\": [{}] }}",
+                json_string(method.name),
+                length,
+                calculate_efficiency(length, optimal_length).min(100.0),
+                runtime,
+                tour.join(", ")
+            ));
+        }
+        let points: Vec<String> = points
+            .iter()
+            .enumerate()
+            .map(|(id, p)| format!("{{ \"id\": {}, \"percentX\": {:.4}, \"percentY\": {:.4} }}", id, p.x, p.y))
+            .collect();
+        samples.push(format!(
+            "    {{\n      \"seed\": {},\n      \"optimalLength\": {:.4},\n      \"points\": [{}],\n      \
+             \"results\": [\n{}\n      ]\n    }}",
+            seed,
+            optimal_length,
+            points.join(", "),
+            results.join(",\n")
+        ));
+    }
+    writeln!(out, "  \"instances\": [\n{}\n  ],", samples.join(",\n")).unwrap();
+
+    let mut benchmarks = Vec::new();
+    for &n in BENCHMARK_SIZES {
+        let points = percent_points(n, seeds.first().copied().unwrap_or(12345));
+        let graph = create_distance_matrix(&points);
+        for method in METHODS.iter().filter(|m| m.name != "Brute Force (Optimal)") {
+            let (_, length, runtime) = run_method(method, &points, &graph)?;
+            benchmarks.push(format!(
+                "    {{ \"name\": {}, \"n\": {}, \"pathLength\": {:.4}, \"runtime\": {:.4} }}",
+                json_string(method.name), points.len(), length, runtime
+            ));
+        }
+    }
+    writeln!(out, "  \"benchmarks\": [\n{}\n  ]\n}}", benchmarks.join(",\n")).unwrap();
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::validate_tour;
+
+    #[test]
+    fn test_every_method_matches_optimum_bound() {
+        let points = percent_points(NUM_POINTS, 7);
+        let graph = create_distance_matrix(&points);
+        let (_, optimal) = brute_force::find_optimal(&graph, points.len()).unwrap();
+        for method in METHODS {
+            let (tour, length, _) = run_method(method, &points, &graph).unwrap();
+            validate_tour(&tour, points.len()).unwrap();
+            assert!(length >= optimal - 1e-9, "{} beat the optimum", method.name);
+        }
+        assert_eq!(METHODS.len(), 15);
+    }
+
+    #[test]
+    fn test_constants_match_the_page() {
+        let page = include_str!("../../optimization-comparison.html");
+        assert!(page.contains(&format!("const NUM_POINTS = {};", NUM_POINTS)));
+        assert!(page.contains(&format!("const GRID_SIZE = {};", GRID_SIZE)));
+        // The page's result objects come in card order, complexity then what the method is best for
+        let mut rest = page;
+        for method in METHODS {
+            assert!(page.contains(&json_string(method.name)), "{} is not on the page", method.name);
+            let start = rest.find("complexity: ").unwrap_or_else(|| panic!("no card for {}", method.name));
+            let (complexity, after) = rest[start..].split_once("bestFor: ").unwrap();
+            assert!(complexity.contains(&json_string(method.complexity)), "{} complexity differs", method.name);
+            assert!(after.starts_with(&json_string(method.best_for)), "{} best for differs", method.name);
+            rest = after;
+        }
+        assert!(!rest.contains("complexity: "), "the page has more cards than METHODS");
+    }
+}
@@ -0,0 +1,157 @@
+//! Smoothing: remove sharp reversals from a finished tour
+//!
+//! A shortest tour often doubles back on itself, which a vehicle, a drone or a
+//! plotter head has to brake for. `improve` runs after any constructor or
+//! improver and trades a little length for fewer hairpins: wherever the tour
+//! turns by more than allowed, the cities around the turn are reordered by a
+//! short reversal or by moving the city elsewhere nearby, as long as the tour
+//! grows by no more than a set share of its length in total.
+
+use alloc::vec::Vec;
+use crate::error::{validate_points, validate_tour, SonarError};
+use crate::math;
+use crate::utils::{distance, Point};
+use core::f64::consts::PI;
+
+/// Cities on either side of a sharp turn that may be reordered
+const WINDOW: usize = 3;
+
+/// Angle at `b` between the edges to `a` and to `c`, in radians: pi on a
+/// straight line, near 0 where the tour turns back on itself
+/// A zero-length edge has no direction, so the angle counts as straight.
+fn angle(a: &Point, b: &Point, c: &Point) -> f64 {
+    let (ux, uy, vx, vy) = (a.x - b.x, a.y - b.y, c.x - b.x, c.y - b.y);
+    if (ux == 0.0 && uy == 0.0) || (vx == 0.0 && vy == 0.0) {
+        return PI;
+    }
+    math::atan2(ux * vy - uy * vx, ux * vx + uy * vy).abs()
+}
+
+/// Length of the path through `seq` and the number of its inner cities that
+/// turn sharper than `min_angle`
+fn score(seq: &[usize], points: &[Point], min_angle: f64) -> (f64, usize) {
+    let length = seq.windows(2).map(|w| distance(&points[w[0]], &points[w[1]])).sum();
+    let sharp = seq.windows(3).filter(|w| angle(&points[w[0]], &points[w[1]], &points[w[2]]) < min_angle).count();
+    (length, sharp)
+}
+
+/// Remove turns sharper than `min_angle_degrees` (the angle between the
+/// incoming and outgoing edge at a city) while the tour grows by at most
+/// `max_increase_percent` of its length
+/// Each sharp city is handled within the `WINDOW` cities on either side of it:
+/// every reversal inside the window and every new position for the city
+/// itself are tried, and of those leaving fewer sharp turns in the window the
+/// shortest is taken. Sweeps repeat until one changes nothing.
+/// Time complexity: O(n * WINDOW^3) per sweep
+pub fn improve(
+    tour: &[usize],
+    points: &[Point],
+    min_angle_degrees: f64,
+    max_increase_percent: f64,
+) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
+    validate_tour(tour, points.len())?;
+    if !(0.0..=180.0).contains(&min_angle_degrees) {
+        return Err(SonarError::InvalidParameter { name: "min_angle_degrees", reason: "must be in [0, 180]" });
+    }
+    if !(max_increase_percent >= 0.0 && max_increase_percent.is_finite()) {
+        return Err(SonarError::InvalidParameter { name: "max_increase_percent", reason: "must be non-negative" });
+    }
+    let n = tour.len();
+    let mut tour = tour.to_vec();
+    // The window plus two fixed cities on each side, whose angles also count
+    let window = WINDOW.min(n.saturating_sub(5) / 2);
+    if window == 0 {
+        return Ok(tour);
+    }
+    let min_angle = min_angle_degrees.to_radians();
+    let span = 2 * window + 5;
+    let (initial, _) = score(&tour.iter().chain(&tour[..1]).copied().collect::<Vec<_>>(), points, min_angle);
+    let mut budget = initial * max_increase_percent / 100.0;
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for center in 0..n {
+            let at = |k: usize| (center + n + k - window - 2) % n;
+            let (prev, city, next) = (tour[(center + n - 1) % n], tour[center], tour[(center + 1) % n]);
+            if angle(&points[prev], &points[city], &points[next]) >= min_angle {
+                continue;
+            }
+            let seq: Vec<usize> = (0..span).map(|k| tour[at(k)]).collect();
+            let (length, sharp) = score(&seq, points, min_angle);
+            // Cities 2..span - 2 may move; the two at each end stay put
+            let (first, last, middle) = (2, span - 3, window + 2);
+            let mut candidates: Vec<Vec<usize>> = Vec::new();
+            for from in first..last {
+                for to in from + 1..=last {
+                    let mut reversed = seq.clone();
+                    reversed[from..=to].reverse();
+                    candidates.push(reversed);
+                }
+            }
+            for to in (first..=last).filter(|&to| to != middle) {
+                let mut moved = seq.clone();
+                let city = moved.remove(middle);
+                moved.insert(to, city);
+                candidates.push(moved);
+            }
+
+            let mut best: Option<(usize, f64, Vec<usize>)> = None;
+            for candidate in candidates {
+                let (new_length, new_sharp) = score(&candidate, points, min_angle);
+                let growth = new_length - length;
+                if new_sharp >= sharp || growth > budget {
+                    continue;
+                }
+                if best.as_ref().is_none_or(|(s, g, _)| (new_sharp, growth) < (*s, *g)) {
+                    best = Some((new_sharp, growth, candidate));
+                }
+            }
+            if let Some((_, growth, seq)) = best {
+                budget -= growth.max(0.0);
+                for (k, city) in seq.into_iter().enumerate() {
+                    tour[at(k)] = city;
+                }
+                changed = true;
+            }
+        }
+    }
+    Ok(tour)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{calculate_tour_length, create_distance_matrix, generate_normalized_points};
+
+    fn sharp_turns(tour: &[usize], points: &[Point], min_angle_degrees: f64) -> usize {
+        let n = tour.len();
+        (0..n)
+            .filter(|&i| {
+                let (a, b, c) = (tour[(i + n - 1) % n], tour[i], tour[(i + 1) % n]);
+                angle(&points[a], &points[b], &points[c]) < min_angle_degrees.to_radians()
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_smoothing_removes_sharp_turns_within_budget() {
+        let points = generate_normalized_points(200, 40, 8);
+        let graph = create_distance_matrix(&points);
+        let tour = crate::two_opt::improve(&(0..200).collect::<Vec<_>>(), &graph, 1_000).unwrap();
+        let before = sharp_turns(&tour, &points, 45.0);
+        assert!(before > 0);
+
+        let smooth = improve(&tour, &points, 45.0, 5.0).unwrap();
+        validate_tour(&smooth, 200).unwrap();
+        assert!(sharp_turns(&smooth, &points, 45.0) < before);
+        let (length, smooth_length) = (calculate_tour_length(&tour, &graph), calculate_tour_length(&smooth, &graph));
+        assert!(smooth_length <= length * 1.05 + 1e-9);
+
+        // No budget: only changes that do not lengthen the tour
+        let free = improve(&tour, &points, 45.0, 0.0).unwrap();
+        assert!(calculate_tour_length(&free, &graph) <= length + 1e-9);
+        assert!(improve(&tour, &points, 200.0, 5.0).is_err());
+    }
+}
@@ -1,14 +1,40 @@
 //! Sonar Visit algorithm - O(1) angle sweep approach
 
-use std::collections::BTreeMap;
+use alloc::{collections::BTreeMap, vec::Vec};
+use crate::error::{validate_points, SonarError};
+use crate::math;
 use crate::utils::Point;
-use std::f64::consts::PI;
+use core::f64::consts::PI;
 
 /// Generate a tour using the Sonar Visit algorithm
 /// 360 degree rotation sweep in fixed angle steps
 /// Points on the same angle are connected by distance from center
 /// Time complexity: O(1) angle steps (determined by grid size, not point count)
-pub fn generate_tour(points: &[Point], grid_size: usize) -> Vec<usize> {
+pub fn generate_tour(points: &[Point], grid_size: usize) -> Result<Vec<usize>, SonarError> {
+    Ok(generate_tour_with_buckets(points, grid_size)?.tour)
+}
+
+/// A sonar sweep as computed, for frontends that draw the beam and buckets
+#[derive(Debug, Clone, PartialEq)]
+pub struct SonarSweep {
+    pub tour: Vec<usize>,
+    /// Angle covered by one sweep step, in radians
+    pub angle_step: f64,
+    /// Non-empty buckets in sweep order as (bucket index, point ids); bucket k
+    /// covers angles [k * angle_step, (k + 1) * angle_step) and its ids are
+    /// ordered by distance from the center, as they appear in the tour
+    pub buckets: Vec<(usize, Vec<usize>)>,
+}
+
+/// Generate a Sonar Visit tour along with its angle step and bucket assignment
+/// The tour is the concatenation of the buckets.
+/// Time complexity: same as `generate_tour`
+pub fn generate_tour_with_buckets(points: &[Point], grid_size: usize) -> Result<SonarSweep, SonarError> {
+    validate_points(points)?;
+    if grid_size == 0 {
+        return Err(SonarError::InvalidParameter { name: "grid_size", reason: "must be positive" });
+    }
+
     // Calculate angle step based on grid size
     let angle_steps = 4 * grid_size;
     let angle_step = (2.0 * PI) / angle_steps as f64;
@@ -22,7 +48,8 @@ pub fn generate_tour(points: &[Point], grid_size: usize) -> Vec<usize> {
             angle += 2.0 * PI;
         }
 
-        let bucket_index = (angle / angle_step) as usize;
+        // Angles of exactly 2*pi (or beyond) fold into the last bucket
+        let bucket_index = ((angle / angle_step) as usize).min(angle_steps - 1);
         angle_buckets.entry(bucket_index).or_default().push(point);
     }
 
@@ -32,23 +59,22 @@ pub fn generate_tour(points: &[Point], grid_size: usize) -> Vec<usize> {
 
     for bucket_points in angle_buckets.values_mut() {
         bucket_points.sort_by(|a, b| {
-            let dist_a = ((a.x - center_x).powi(2) + (a.y - center_y).powi(2)).sqrt();
-            let dist_b = ((b.x - center_x).powi(2) + (b.y - center_y).powi(2)).sqrt();
-            dist_a.partial_cmp(&dist_b).unwrap()
+            let dist_a = math::sqrt((a.x - center_x) * (a.x - center_x) + (a.y - center_y) * (a.y - center_y));
+            let dist_b = math::sqrt((b.x - center_x) * (b.x - center_x) + (b.y - center_y) * (b.y - center_y));
+            dist_a.total_cmp(&dist_b).then(a.id.cmp(&b.id))
         });
     }
 
-    // Build tour by sweeping through angle buckets in order
+    // Build tour by sweeping through angle buckets in order (the map iterates by bucket index)
     let mut tour = Vec::with_capacity(points.len());
-    for i in 0..angle_steps {
-        if let Some(bucket_points) = angle_buckets.get(&i) {
-            for p in bucket_points {
-                tour.push(p.id);
-            }
-        }
+    let mut buckets = Vec::with_capacity(angle_buckets.len());
+    for (index, bucket_points) in angle_buckets {
+        let ids: Vec<usize> = bucket_points.iter().map(|p| p.id).collect();
+        tour.extend_from_slice(&ids);
+        buckets.push((index, ids));
     }
 
-    tour
+    Ok(SonarSweep { tour, angle_step, buckets })
 }
 
 #[cfg(test)]
@@ -59,7 +85,7 @@ mod tests {
     #[test]
     fn test_sonar_visit_visits_all() {
         let points = generate_normalized_points(10, 20, 42);
-        let tour = generate_tour(&points, 20);
+        let tour = generate_tour(&points, 20).unwrap();
 
         assert_eq!(tour.len(), points.len());
 
@@ -69,4 +95,21 @@ mod tests {
             assert!(seen.insert(id));
         }
     }
+
+    #[test]
+    fn test_buckets_match_tour() {
+        let points = generate_normalized_points(60, 20, 7);
+        let sweep = generate_tour_with_buckets(&points, 10).unwrap();
+
+        assert!((sweep.angle_step - PI / 20.0).abs() < 1e-12);
+        let flattened: Vec<usize> = sweep.buckets.iter().flat_map(|(_, ids)| ids.iter().copied()).collect();
+        assert_eq!(flattened, sweep.tour);
+        assert_eq!(sweep.tour, generate_tour(&points, 10).unwrap());
+        for (index, ids) in &sweep.buckets {
+            for &id in ids {
+                let bucket = (points[id].angle / sweep.angle_step) as usize;
+                assert_eq!(bucket.min(39), *index);
+            }
+        }
+    }
 }
@@ -0,0 +1,90 @@
+//! Space-filling curve tours: points visited in Morton (Z-order) or Hilbert order
+//!
+//! Both map the bounding square onto a 2^16 x 2^16 grid and sort the points
+//! by their cell's position along the curve, so they cost one sort like
+//! angular sort but follow the points' spatial layout. The Morton index only
+//! interleaves the coordinate bits, which makes it cheaper, while its
+//! diagonal jumps between quadrants make the tour longer; the Hilbert curve
+//! never jumps, at the price of a rotation per level.
+
+use alloc::vec::Vec;
+use crate::error::{validate_points, SonarError};
+use crate::quadtree::hilbert_index;
+use crate::utils::Point;
+
+/// Bits per coordinate of the curve grid, the resolution of `hilbert_index`
+const ORDER: u32 = 16;
+
+/// Spread the low 32 bits of `v` over the even bits of the result
+fn spread(v: u64) -> u64 {
+    let mut v = v & 0xFFFF_FFFF;
+    v = (v | (v << 16)) & 0x0000_FFFF_0000_FFFF;
+    v = (v | (v << 8)) & 0x00FF_00FF_00FF_00FF;
+    v = (v | (v << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+    (v | (v << 1)) & 0x5555_5555_5555_5555
+}
+
+/// Map a cell (x, y) to its position along the Morton curve, x taking the lower bit of each pair
+pub fn morton_index(x: u64, y: u64) -> u64 {
+    spread(x) | (spread(y) << 1)
+}
+
+/// Order the points by the `index` of their cell on the curve grid, ties by position in `points`
+fn sort_by_curve(points: &[Point], index: fn(u64, u64) -> u64) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for p in points {
+        min_x = min_x.min(p.x);
+        min_y = min_y.min(p.y);
+        max_x = max_x.max(p.x);
+        max_y = max_y.max(p.y);
+    }
+    let size = (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+    let side = (1u64 << ORDER) - 1;
+    let to_cell = |v: f64, min: f64| (((v - min) / size).clamp(0.0, 1.0) * side as f64) as u64;
+
+    let mut keyed: Vec<(u64, usize)> =
+        points.iter().enumerate().map(|(i, p)| (index(to_cell(p.x, min_x), to_cell(p.y, min_y)), i)).collect();
+    keyed.sort_unstable();
+    Ok(keyed.into_iter().map(|(_, i)| i).collect())
+}
+
+/// Generate a tour visiting the points in Morton (Z-order) curve order
+/// Time complexity: O(n log n) dominated by the sort
+pub fn morton_sort(points: &[Point]) -> Result<Vec<usize>, SonarError> {
+    sort_by_curve(points, morton_index)
+}
+
+/// Generate a tour visiting the points in Hilbert curve order
+/// Time complexity: O(n log n) dominated by the sort
+pub fn hilbert_sort(points: &[Point]) -> Result<Vec<usize>, SonarError> {
+    sort_by_curve(points, hilbert_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::validate_tour;
+    use crate::angular_sort;
+    use crate::utils::{calculate_tour_length, create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_curve_tours_follow_the_layout() {
+        // Z-order over a 2 x 2 grid: bottom left, bottom right, top left, top right
+        assert_eq!([(0, 0), (1, 0), (0, 1), (1, 1)].map(|(x, y)| morton_index(x, y)), [0, 1, 2, 3]);
+        let side = (1 << ORDER) - 1;
+        assert_eq!(morton_index(side, side), (1 << (2 * ORDER)) - 1);
+
+        let points = generate_normalized_points(2_000, 100, 3);
+        let graph = create_distance_matrix(&points);
+        let angular = calculate_tour_length(&angular_sort::generate_tour(&points).unwrap(), &graph);
+        let morton = morton_sort(&points).unwrap();
+        let hilbert = hilbert_sort(&points).unwrap();
+        validate_tour(&morton, points.len()).unwrap();
+        validate_tour(&hilbert, points.len()).unwrap();
+        let (morton, hilbert) = (calculate_tour_length(&morton, &graph), calculate_tour_length(&hilbert, &graph));
+        assert!(hilbert < morton && morton < angular, "{} {} {}", hilbert, morton, angular);
+    }
+}
@@ -0,0 +1,283 @@
+//! Sparse k-nearest-neighbor graph solving
+//!
+//! The dense solvers read an n x n distance matrix, 8 GB at n = 32_000. Here
+//! only the edges from each city to its k nearest neighbors are feasible:
+//! the graph takes O(n * k) memory, is built with a bucket grid, and nearest
+//! neighbor, greedy edge and 2-opt only ever add edges from it. A sparse
+//! graph need not be connected, let alone hold a tour, so wherever the search
+//! runs out of graph edges a repair step links the pieces by the shortest
+//! edges outside it, found through the grid.
+
+use alloc::{vec, vec::Vec};
+use crate::error::{validate_points, validate_tour, SonarError};
+use crate::spatial_grid::SpatialGrid;
+use crate::utils::{distance, Point};
+
+/// No neighbor in a tour under construction
+const NONE: usize = usize::MAX;
+
+/// The k nearest neighbors of every city
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnnGraph {
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl KnnGraph {
+    /// Find the `k` nearest neighbors of every point, nearest first
+    /// Time complexity: ~O(n * k log k) on uniformly distributed points
+    pub fn new(points: &[Point], k: usize) -> Self {
+        let grid = SpatialGrid::new(points, 2);
+        let neighbors = points.iter().enumerate().map(|(i, p)| grid.k_nearest(points, p.x, p.y, k, Some(i))).collect();
+        KnnGraph { neighbors }
+    }
+
+    /// Number of cities
+    pub fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.neighbors.is_empty()
+    }
+
+    /// Neighbors of `city`, nearest first
+    pub fn neighbors(&self, city: usize) -> &[usize] {
+        &self.neighbors[city]
+    }
+
+    /// Every edge of the graph once, as (smaller, larger) city pairs
+    /// Time complexity: O(n * k log(n * k))
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges: Vec<(usize, usize)> = (0..self.len())
+            .flat_map(|i| self.neighbors[i].iter().map(move |&j| (i.min(j), i.max(j))))
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    }
+}
+
+fn validate_graph_size(points: &[Point], graph: &KnnGraph) -> Result<(), SonarError> {
+    validate_points(points)?;
+    if graph.len() != points.len() {
+        return Err(SonarError::SizeMismatch { expected: points.len(), found: graph.len() });
+    }
+    Ok(())
+}
+
+/// Nearest neighbor tour over the graph's edges from `start_city`
+/// From each city the nearest unvisited neighbor in the graph is taken; when
+/// all of them are visited, the repair step moves to the nearest unvisited
+/// city found through a bucket grid.
+/// Time complexity: O(n * k) plus the grid searches of the repairs
+pub fn nearest_neighbor(points: &[Point], graph: &KnnGraph, start_city: usize) -> Result<Vec<usize>, SonarError> {
+    validate_graph_size(points, graph)?;
+    if start_city >= points.len() {
+        return Err(SonarError::InvalidParameter { name: "start_city", reason: "must be a city index" });
+    }
+    let mut grid = SpatialGrid::new(points, 2);
+    let mut visited = vec![false; points.len()];
+    let mut tour = Vec::with_capacity(points.len());
+    let mut current = start_city;
+    loop {
+        tour.push(current);
+        visited[current] = true;
+        grid.remove(current);
+        let next = graph.neighbors(current).iter().copied().find(|&city| !visited[city]);
+        match next.or_else(|| grid.nearest(points, points[current].x, points[current].y)) {
+            Some(city) => current = city,
+            None => break,
+        }
+    }
+    Ok(tour)
+}
+
+/// The path of a greedy fragment from its end `start` to its other end,
+/// appended to `tour`; returns the other end
+fn walk(links: &[[usize; 2]], start: usize, tour: &mut Vec<usize>) -> usize {
+    let (mut previous, mut current) = (NONE, start);
+    loop {
+        tour.push(current);
+        let next = links[current].into_iter().find(|&city| city != NONE && city != previous);
+        match next {
+            Some(city) => (previous, current) = (current, city),
+            None => return current,
+        }
+    }
+}
+
+/// Greedy edge tour over the graph's edges
+/// The graph's edges are taken shortest first while no city gets a third
+/// edge and no cycle closes, which leaves paths. The repair step then chains
+/// them: from the end of the tour so far it jumps to the nearest end of
+/// another path, found through a grid of the path ends, and follows that path.
+/// Time complexity: O(n * k log(n * k)) plus the grid searches of the repairs
+pub fn greedy_edge(points: &[Point], graph: &KnnGraph) -> Result<Vec<usize>, SonarError> {
+    validate_graph_size(points, graph)?;
+    let n = points.len();
+    let mut edges: Vec<(f64, usize, usize)> =
+        graph.edges().into_iter().map(|(i, j)| (distance(&points[i], &points[j]), i, j)).collect();
+    // Equal lengths are ordered by endpoints so the result is deterministic
+    edges.sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+    let mut parent: Vec<usize> = (0..n).collect();
+    let mut links = vec![[NONE; 2]; n];
+    let degree = |links: &[[usize; 2]], city: usize| links[city].iter().filter(|&&other| other != NONE).count();
+    for (_, i, j) in edges {
+        if degree(&links, i) == 2 || degree(&links, j) == 2 {
+            continue;
+        }
+        let (root_i, root_j) = (find(&mut parent, i), find(&mut parent, j));
+        if root_i == root_j {
+            continue;
+        }
+        parent[root_i] = root_j;
+        let (slot_i, slot_j) = (degree(&links, i), degree(&links, j));
+        links[i][slot_i] = j;
+        links[j][slot_j] = i;
+    }
+
+    // Repair: chain the paths, nearest free end first
+    let mut ends = SpatialGrid::new(points, 2);
+    for city in (0..n).filter(|&city| degree(&links, city) == 2) {
+        ends.remove(city);
+    }
+    let mut tour = Vec::with_capacity(n);
+    let mut start = (0..n).find(|&city| degree(&links, city) < 2).unwrap_or(0);
+    loop {
+        ends.remove(start);
+        let end = walk(&links, start, &mut tour);
+        if end != start {
+            ends.remove(end);
+        }
+        match ends.nearest(points, points[end].x, points[end].y) {
+            Some(city) => start = city,
+            None => break,
+        }
+    }
+    Ok(tour)
+}
+
+/// Reverse the tour between positions `from` and `to` inclusive, wrapping
+/// around the end; the complementary segment is reversed instead when it is
+/// shorter, which gives the same cycle
+fn reverse(tour: &mut [usize], position: &mut [usize], mut from: usize, mut to: usize) {
+    let n = tour.len();
+    let mut len = (to + n - from) % n + 1;
+    if len * 2 > n {
+        (from, to) = ((to + 1) % n, (from + n - 1) % n);
+        len = n - len;
+    }
+    for step in 0..len / 2 {
+        let (a, b) = ((from + step) % n, (to + n - step) % n);
+        tour.swap(a, b);
+        position[tour[a]] = a;
+        position[tour[b]] = b;
+    }
+}
+
+/// 2-opt whose moves add an edge from each city to one of its graph neighbors
+/// For every tour edge (a, b), neighbors c of a nearer than b are tried,
+/// replacing (a, b) and (c, d) by (a, c) and (b, d). Distances come from the
+/// points, so no matrix is built.
+/// Time complexity: O(n * k) evaluations per sweep, plus O(n) per move made
+pub fn two_opt(
+    tour: &[usize],
+    points: &[Point],
+    graph: &KnnGraph,
+    max_iterations: usize,
+) -> Result<Vec<usize>, SonarError> {
+    validate_graph_size(points, graph)?;
+    validate_tour(tour, points.len())?;
+    let n = tour.len();
+    let mut tour = tour.to_vec();
+    if n < 4 {
+        return Ok(tour);
+    }
+    let mut position = vec![0; n];
+    for (i, &city) in tour.iter().enumerate() {
+        position[city] = i;
+    }
+    let d = |a: usize, b: usize| distance(&points[a], &points[b]);
+
+    for _ in 0..max_iterations {
+        let mut improved = false;
+        for i in 0..n {
+            let (a, b) = (tour[i], tour[(i + 1) % n]);
+            let ab = d(a, b);
+            for &c in graph.neighbors(a) {
+                let ac = d(a, c);
+                if ac >= ab {
+                    break;
+                }
+                let j = position[c];
+                let dd = tour[(j + 1) % n];
+                if c == b || dd == a {
+                    continue;
+                }
+                if ab + d(c, dd) - ac - d(b, dd) > 1e-10 {
+                    reverse(&mut tour, &mut position, (i + 1) % n, j);
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    Ok(tour)
+}
+
+/// Greedy edge then 2-opt, both over the `k` nearest neighbors of each city
+/// Time complexity: O(n * k log(n * k)) + O(n * k) per 2-opt sweep
+pub fn generate_tour(points: &[Point], k: usize, max_iterations: usize) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
+    if k == 0 {
+        return Err(SonarError::InvalidParameter { name: "k", reason: "must be positive" });
+    }
+    let graph = KnnGraph::new(points, k);
+    let initial = greedy_edge(points, &graph)?;
+    two_opt(&initial, points, &graph, max_iterations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{calculate_tour_length, create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_sparse_tours_are_complete_and_short() {
+        let points = generate_normalized_points(400, 40, 3);
+        let graph = KnnGraph::new(&points, 5);
+        assert!((0..points.len()).all(|i| graph.neighbors(i).len() == 5 && !graph.neighbors(i).contains(&i)));
+        let matrix = create_distance_matrix(&points);
+
+        let nearest = nearest_neighbor(&points, &graph, 0).unwrap();
+        let greedy = greedy_edge(&points, &graph).unwrap();
+        let improved = two_opt(&greedy, &points, &graph, 100).unwrap();
+        for tour in [&nearest, &greedy, &improved] {
+            validate_tour(tour, points.len()).unwrap();
+        }
+        let dense = crate::nearest_neighbor::generate_tour(points.len(), &matrix, 0).unwrap();
+        assert_eq!(nearest, dense);
+        assert!(calculate_tour_length(&improved, &matrix) < calculate_tour_length(&greedy, &matrix));
+
+        // Two far clusters: no graph edge joins them, the repair does
+        let mut clusters = generate_normalized_points(40, 20, 5);
+        for p in clusters.iter_mut().skip(20) {
+            p.x += 100.0;
+        }
+        let graph = KnnGraph::new(&clusters, 3);
+        validate_tour(&greedy_edge(&clusters, &graph).unwrap(), 40).unwrap();
+        validate_tour(&generate_tour(&clusters, 3, 50).unwrap(), 40).unwrap();
+        assert!(generate_tour(&clusters, 0, 50).is_err());
+    }
+}
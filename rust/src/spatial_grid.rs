@@ -0,0 +1,244 @@
+//! Uniform bucket grid for fast nearest-point queries
+
+use alloc::{vec, vec::Vec};
+use crate::math;
+use crate::utils::Point;
+
+/// A uniform grid over the bounding box of a point set
+/// Each cell holds the indices of the points that fall inside it.
+/// Points can be removed, which makes it suitable for "nearest unvisited" queries.
+pub struct SpatialGrid {
+    min_x: f64,
+    min_y: f64,
+    cell_size: f64,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    /// Cell index and slot within that cell for every point (usize::MAX when removed)
+    location: Vec<(usize, usize)>,
+    len: usize,
+}
+
+impl SpatialGrid {
+    /// Build a grid holding every point, sized for roughly `per_cell` points per cell
+    pub fn new(points: &[Point], per_cell: usize) -> Self {
+        let n = points.len();
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for p in points {
+            min_x = min_x.min(p.x);
+            min_y = min_y.min(p.y);
+            max_x = max_x.max(p.x);
+            max_y = max_y.max(p.y);
+        }
+        if n == 0 {
+            min_x = 0.0;
+            min_y = 0.0;
+            max_x = 0.0;
+            max_y = 0.0;
+        }
+
+        let width = (max_x - min_x).max(f64::EPSILON);
+        let height = (max_y - min_y).max(f64::EPSILON);
+        let target_cells = (n / per_cell.max(1)).max(1) as f64;
+        let cell_size = math::sqrt((width * height) / target_cells).max(width.max(height) / 4096.0);
+        let cols = ((width / cell_size) as usize + 1).max(1);
+        let rows = ((height / cell_size) as usize + 1).max(1);
+
+        let mut grid = SpatialGrid {
+            min_x,
+            min_y,
+            cell_size,
+            cols,
+            rows,
+            cells: vec![Vec::new(); cols * rows],
+            location: vec![(usize::MAX, usize::MAX); n],
+            len: n,
+        };
+
+        for (i, p) in points.iter().enumerate() {
+            let cell = grid.cell_of(p.x, p.y);
+            grid.location[i] = (cell, grid.cells[cell].len());
+            grid.cells[cell].push(i);
+        }
+
+        grid
+    }
+
+    /// Number of points still stored in the grid
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether every point has been removed
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn col_row(&self, x: f64, y: f64) -> (usize, usize) {
+        let col = (((x - self.min_x) / self.cell_size).max(0.0) as usize).min(self.cols - 1);
+        let row = (((y - self.min_y) / self.cell_size).max(0.0) as usize).min(self.rows - 1);
+        (col, row)
+    }
+
+    fn cell_of(&self, x: f64, y: f64) -> usize {
+        let (col, row) = self.col_row(x, y);
+        row * self.cols + col
+    }
+
+    /// Remove a point from the grid in O(1)
+    pub fn remove(&mut self, index: usize) {
+        let (cell, slot) = self.location[index];
+        if cell == usize::MAX {
+            return;
+        }
+        let bucket = &mut self.cells[cell];
+        bucket.swap_remove(slot);
+        if slot < bucket.len() {
+            let moved = bucket[slot];
+            self.location[moved] = (cell, slot);
+        }
+        self.location[index] = (usize::MAX, usize::MAX);
+        self.len -= 1;
+    }
+
+    /// Find the stored point closest to (x, y)
+    /// Searches rings of cells outwards from the query cell and stops as soon as
+    /// no unexplored ring can contain a closer point.
+    pub fn nearest(&self, points: &[Point], x: f64, y: f64) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let (col, row) = self.col_row(x, y);
+        let max_radius = self.cols.max(self.rows);
+        let mut best = None;
+        let mut best_dist_sq = f64::INFINITY;
+
+        for radius in 0..=max_radius {
+            let min_col = col.saturating_sub(radius);
+            let max_col = (col + radius).min(self.cols - 1);
+            let min_row = row.saturating_sub(radius);
+            let max_row = (row + radius).min(self.rows - 1);
+
+            for r in min_row..=max_row {
+                let on_edge_row = r + radius == row || r == row + radius;
+                for c in min_col..=max_col {
+                    // Only visit the cells on the ring itself
+                    if !on_edge_row && c + radius != col && c != col + radius {
+                        continue;
+                    }
+                    for &i in &self.cells[r * self.cols + c] {
+                        let dx = points[i].x - x;
+                        let dy = points[i].y - y;
+                        let d = dx * dx + dy * dy;
+                        if d < best_dist_sq || (d == best_dist_sq && Some(i) < best) {
+                            best_dist_sq = d;
+                            best = Some(i);
+                        }
+                    }
+                }
+            }
+
+            // Every cell beyond this ring is at least `radius * cell_size` away
+            let reach = radius as f64 * self.cell_size;
+            if best.is_some() && best_dist_sq <= reach * reach {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// Find the `k` stored points closest to (x, y), nearest first, skipping `exclude`
+    /// Same ring search as `nearest`, stopping once the k-th best is closer
+    /// than any unexplored ring.
+    pub fn k_nearest(&self, points: &[Point], x: f64, y: f64, k: usize, exclude: Option<usize>) -> Vec<usize> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let (col, row) = self.col_row(x, y);
+        let max_radius = self.cols.max(self.rows);
+        // (squared distance, index), kept sorted and at most k long
+        let mut best: Vec<(f64, usize)> = Vec::with_capacity(k + 1);
+
+        for radius in 0..=max_radius {
+            let min_col = col.saturating_sub(radius);
+            let max_col = (col + radius).min(self.cols - 1);
+            let min_row = row.saturating_sub(radius);
+            let max_row = (row + radius).min(self.rows - 1);
+
+            for r in min_row..=max_row {
+                let on_edge_row = r + radius == row || r == row + radius;
+                for c in min_col..=max_col {
+                    if !on_edge_row && c + radius != col && c != col + radius {
+                        continue;
+                    }
+                    for &i in &self.cells[r * self.cols + c] {
+                        if Some(i) == exclude {
+                            continue;
+                        }
+                        let dx = points[i].x - x;
+                        let dy = points[i].y - y;
+                        let candidate = (dx * dx + dy * dy, i);
+                        if best.len() == k && candidate >= best[k - 1] {
+                            continue;
+                        }
+                        let slot = best.partition_point(|&b| b < candidate);
+                        best.insert(slot, candidate);
+                        best.truncate(k);
+                    }
+                }
+            }
+
+            let reach = radius as f64 * self.cell_size;
+            if best.len() == k && best[k - 1].0 <= reach * reach {
+                break;
+            }
+        }
+
+        best.into_iter().map(|(_, i)| i).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{distance, generate_normalized_points};
+
+    #[test]
+    fn test_nearest_matches_linear_scan() {
+        let points = generate_normalized_points(200, 40, 7);
+        let mut grid = SpatialGrid::new(&points, 2);
+        for i in (0..points.len()).step_by(3) {
+            grid.remove(i);
+        }
+
+        for q in &points {
+            let expected = (0..points.len())
+                .filter(|i| i % 3 != 0)
+                .min_by(|&a, &b| {
+                    distance(q, &points[a]).partial_cmp(&distance(q, &points[b])).unwrap()
+                })
+                .unwrap();
+            let found = grid.nearest(&points, q.x, q.y).unwrap();
+            assert!((distance(q, &points[found]) - distance(q, &points[expected])).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_k_nearest_matches_sorting() {
+        let points = generate_normalized_points(150, 40, 9);
+        let grid = SpatialGrid::new(&points, 2);
+
+        for (q, p) in points.iter().enumerate() {
+            let mut expected: Vec<usize> = (0..points.len()).filter(|&i| i != q).collect();
+            expected.sort_by(|&a, &b| distance(p, &points[a]).total_cmp(&distance(p, &points[b])));
+            let found = grid.k_nearest(&points, p.x, p.y, 6, Some(q));
+            assert_eq!(found.len(), 6);
+            for (f, e) in found.iter().zip(&expected) {
+                assert!((distance(p, &points[*f]) - distance(p, &points[*e])).abs() < 1e-12);
+            }
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! Incremental execution: solvers that advance in bounded steps
+//!
+//! Each `step` call does a bounded amount of work (one 2-opt sweep, one GA
+//! generation, a batch of SA iterations), so a caller such as a UI render loop
+//! can show progress between steps without blocking.
+
+use alloc::vec::Vec;
+
+/// A solver driven one bounded step at a time
+pub trait SteppableSolver {
+    /// Do one bounded unit of work and return the best tour found so far
+    /// Calling `step` after the solver has finished leaves the tour unchanged.
+    fn step(&mut self) -> &[usize];
+
+    /// Best tour found so far
+    fn tour(&self) -> &[usize];
+
+    /// Whether further steps can no longer change the tour
+    fn is_finished(&self) -> bool;
+
+    /// Step until finished and return the final tour
+    fn run(&mut self) -> Vec<usize> {
+        while !self.is_finished() {
+            self.step();
+        }
+        self.tour().to_vec()
+    }
+}
@@ -0,0 +1,296 @@
+//! Parameter grid search: run an algorithm on every combination of parameter
+//! values and report tour length against time for each
+//!
+//! Parameters are named as in the registry, or by a short alias (`iters`,
+//! `cooling`, ...); any parameter not swept runs at its registry default.
+
+use crate::error::SonarError;
+use crate::problem::ProblemInstance;
+use crate::registry::{self, Algorithm, Param};
+use crate::result_cache::{CacheKey, CachedRun, ResultCache};
+#[cfg(all(feature = "constructive", any(feature = "local-search", feature = "metaheuristics")))]
+use crate::nearest_neighbor;
+#[cfg(feature = "metaheuristics")]
+use crate::genetic;
+#[cfg(all(feature = "metaheuristics", feature = "constructive"))]
+use crate::simulated_annealing;
+#[cfg(all(feature = "constructive", feature = "local-search"))]
+use crate::two_opt;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Short names accepted for algorithm keys
+const ALGORITHM_ALIASES: &[(&str, &str)] = &[("sa", "simulated-annealing"), ("ga", "genetic"), ("2opt", "two-opt")];
+
+/// Short names accepted for parameters
+const PARAM_ALIASES: &[(&str, &str)] = &[
+    ("iters", "max_iterations"),
+    ("max_iters", "max_iterations"),
+    ("temp", "initial_temperature"),
+    ("cooling", "cooling_rate"),
+    ("pop", "population_size"),
+    ("gens", "generations"),
+    ("mutation", "mutation_rate"),
+];
+
+/// The registry name of a parameter given by name or alias
+pub fn param_name(name: &str) -> &str {
+    PARAM_ALIASES.iter().find(|(alias, _)| *alias == name).map_or(name, |&(_, full)| full)
+}
+
+/// Parameter values of one run: swept values, then registry defaults
+pub struct Settings<'a> {
+    params: &'static [Param],
+    values: &'a [(&'static str, f64)],
+}
+
+impl<'a> Settings<'a> {
+    /// `values` of some of `params`, the others at their defaults
+    pub fn new(params: &'static [Param], values: &'a [(&'static str, f64)]) -> Self {
+        Settings { params, values }
+    }
+
+    /// Value of the registry parameter `name`
+    pub fn get(&self, name: &'static str) -> Result<f64, SonarError> {
+        if let Some(&(_, value)) = self.values.iter().find(|(swept, _)| *swept == name) {
+            return Ok(value);
+        }
+        self.params
+            .iter()
+            .find(|param| param.name == name)
+            .and_then(|param| param.default.parse().ok())
+            .ok_or(SonarError::InvalidParameter { name, reason: "has no numeric default" })
+    }
+
+    /// Value of `name`, which must be a whole number
+    pub fn count(&self, name: &'static str) -> Result<usize, SonarError> {
+        let value = self.get(name)?;
+        if value >= 0.0 && value.fract() == 0.0 {
+            Ok(value as usize)
+        } else {
+            Err(SonarError::InvalidParameter { name, reason: "must be a whole number" })
+        }
+    }
+}
+
+/// Runs an algorithm with the given parameter values
+pub type SolveWith = fn(&ProblemInstance, &Settings) -> Result<Vec<usize>, SonarError>;
+
+/// An algorithm whose registry parameters can be swept
+#[derive(Debug, Clone, Copy)]
+pub struct Sweepable {
+    pub key: &'static str,
+    pub solve: SolveWith,
+}
+
+/// Every algorithm `run` can sweep, with the same construction as its registry entry
+pub const SWEEPABLE: &[Sweepable] = &[
+    #[cfg(all(feature = "constructive", feature = "local-search"))]
+    Sweepable {
+        key: "two-opt",
+        solve: |instance, settings| {
+            let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+            two_opt::improve(&initial, instance.matrix(), settings.count("max_iterations")?)
+        },
+    },
+    #[cfg(all(feature = "metaheuristics", feature = "constructive"))]
+    Sweepable {
+        key: "simulated-annealing",
+        solve: |instance, settings| {
+            let initial = nearest_neighbor::generate_tour(instance.len(), instance.matrix(), 0)?;
+            simulated_annealing::optimize(
+                instance.matrix(),
+                &initial,
+                settings.count("max_iterations")?,
+                settings.get("initial_temperature")?,
+                settings.get("cooling_rate")?,
+            )
+        },
+    },
+    #[cfg(feature = "metaheuristics")]
+    Sweepable {
+        key: "genetic",
+        solve: |instance, settings| {
+            genetic::optimize(
+                instance.matrix(),
+                instance.len(),
+                settings.count("population_size")?,
+                settings.count("generations")?,
+                settings.get("mutation_rate")?,
+            )
+        },
+    },
+];
+
+/// The registry entry of a sweepable algorithm, by key or alias
+pub fn find(key: &str) -> Option<(&'static Algorithm, &'static Sweepable)> {
+    let key = ALGORITHM_ALIASES.iter().find(|(alias, _)| *alias == key).map_or(key, |&(_, full)| full);
+    let sweepable = SWEEPABLE.iter().find(|s| s.key == key)?;
+    Some((registry::find(key)?, sweepable))
+}
+
+/// One swept parameter and the values it takes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Axis {
+    /// Registry name of the parameter
+    pub name: &'static str,
+    pub values: Vec<f64>,
+}
+
+/// Parse `name=v1,v2,...` into an axis of `algorithm`'s parameters
+/// Values are numbers, such as `0.995` or `1e4`.
+pub fn parse_axis(algorithm: &Algorithm, spec: &str) -> Result<Axis, SonarError> {
+    let invalid = |reason| SonarError::InvalidParameter { name: "param", reason };
+    let (name, list) = spec.split_once('=').ok_or(invalid("expected name=value,value,..."))?;
+    let name = param_name(name);
+    let param = algorithm.params.iter().find(|p| p.name == name).ok_or(invalid("not a parameter of the algorithm"))?;
+    let values = list
+        .split(',')
+        .map(|value| value.trim().parse::<f64>().map_err(|_| invalid("values must be numbers")))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Axis { name: param.name, values })
+}
+
+/// Results of one parameter combination, averaged over the instances
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// Value of every axis, in axis order
+    pub values: Vec<f64>,
+    pub length: f64,
+    pub time: Duration,
+}
+
+/// Run the algorithm `key` on every instance for every combination of the
+/// axes' values, the last axis varying fastest
+/// Distance matrices are built before timing starts. With a `cache` and the
+/// seed of each instance, runs already in the cache are read from it, and
+/// the others stored there.
+/// Time complexity: O(product of axis lengths * instances) algorithm runs
+pub fn run(
+    key: &str,
+    axes: &[Axis],
+    instances: &[ProblemInstance],
+    cache: Option<(&ResultCache, &[u64])>,
+) -> Result<Vec<Row>, SonarError> {
+    let (algorithm, sweepable) =
+        find(key).ok_or(SonarError::InvalidParameter { name: "algorithm", reason: "cannot be swept" })?;
+    if instances.is_empty() || axes.iter().any(|axis| axis.values.is_empty()) {
+        return Err(SonarError::EmptyInput);
+    }
+    if let Some((_, seeds)) = cache {
+        if seeds.len() != instances.len() {
+            return Err(SonarError::SizeMismatch { expected: instances.len(), found: seeds.len() });
+        }
+    }
+    for instance in instances {
+        instance.matrix();
+    }
+
+    let combinations: usize = axes.iter().map(|axis| axis.values.len()).product();
+    let mut rows = Vec::with_capacity(combinations);
+    for combination in 0..combinations {
+        // Mixed-radix digits of `combination`, the last axis least significant
+        let mut rest = combination;
+        let mut values: Vec<(&'static str, f64)> = Vec::with_capacity(axes.len());
+        for axis in axes.iter().rev() {
+            values.push((axis.name, axis.values[rest % axis.values.len()]));
+            rest /= axis.values.len();
+        }
+        values.reverse();
+        let settings = Settings::new(algorithm.params, &values);
+        let params: Vec<String> = values.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+        let params = params.join(" ");
+
+        let (mut length, mut time) = (0.0, Duration::ZERO);
+        for (i, instance) in instances.iter().enumerate() {
+            let cached = cache.map(|(cache, seeds)| {
+                (cache, CacheKey { algorithm: sweepable.key, params: &params, seed: seeds[i], n: instance.len() })
+            });
+            let (points, metric) = (instance.points(), instance.metric());
+            if let Some(run) = cached.as_ref().and_then(|(cache, key)| cache.get(key, points, metric)) {
+                time += run.time();
+                length += run.length;
+                continue;
+            }
+            let start = Instant::now();
+            let tour = (sweepable.solve)(instance, &settings)?;
+            let elapsed = start.elapsed();
+            let tour_length = instance.tour_length(&tour);
+            if let Some((cache, key)) = &cached {
+                let run = CachedRun {
+                    tour,
+                    length: tour_length,
+                    matrix: Duration::ZERO,
+                    construction: elapsed,
+                    improvement: Duration::ZERO,
+                };
+                // A cache that cannot be written only costs the reuse
+                let _ = cache.put(key, points, metric, &run);
+            }
+            time += elapsed;
+            length += tour_length;
+        }
+        let runs = instances.len() as u32;
+        rows.push(Row {
+            values: values.iter().map(|&(_, value)| value).collect(),
+            length: length / runs as f64,
+            time: time / runs,
+        });
+    }
+    Ok(rows)
+}
+
+/// Rows as CSV, one column per axis then `length` and `time_ms`
+pub fn to_csv(axes: &[Axis], rows: &[Row]) -> String {
+    let mut out = String::new();
+    for axis in axes {
+        write!(out, "{},", axis.name).unwrap();
+    }
+    out.push_str("length,time_ms\n");
+    for row in rows {
+        for value in &row.values {
+            write!(out, "{},", value).unwrap();
+        }
+        writeln!(out, "{:.6},{:.3}", row.length, row.time.as_secs_f64() * 1000.0).unwrap();
+    }
+    out
+}
+
+#[cfg(all(test, feature = "metaheuristics", feature = "constructive"))]
+mod tests {
+    use super::*;
+    use crate::problem::Metric;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_sweep_covers_the_cartesian_product() {
+        let (algorithm, _) = find("sa").unwrap();
+        let axes = [
+            parse_axis(algorithm, "cooling=0.99,0.999").unwrap(),
+            parse_axis(algorithm, "iters=1e2,1e3,2e3").unwrap(),
+        ];
+        assert_eq!(axes[1], Axis { name: "max_iterations", values: vec![100.0, 1000.0, 2000.0] });
+        let seeds = [1, 2];
+        let instances: Vec<_> = seeds
+            .iter()
+            .map(|&seed| ProblemInstance::new(generate_normalized_points(30, 20, seed), Metric::Euclidean).unwrap())
+            .collect();
+        let dir = std::env::temp_dir().join(format!("sonar-sweep-cache-{}", std::process::id()));
+        let cache = ResultCache::open(&dir).unwrap();
+        let rows = run("simulated-annealing", &axes, &instances, Some((&cache, &seeds))).unwrap();
+        // Every run is read back from the cache, times included
+        assert_eq!(run("simulated-annealing", &axes, &instances, Some((&cache, &seeds))).unwrap(), rows);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let values: Vec<_> = rows.iter().map(|row| row.values.clone()).collect();
+        assert_eq!(values[..4], [vec![0.99, 100.0], vec![0.99, 1000.0], vec![0.99, 2000.0], vec![0.999, 100.0]]);
+        assert_eq!(rows.len(), 6);
+        let csv = to_csv(&axes, &rows);
+        assert!(csv.starts_with("cooling_rate,max_iterations,length,time_ms\n0.99,100,"));
+        assert_eq!(csv.lines().count(), 7);
+
+        assert!(parse_axis(algorithm, "population_size=10").is_err());
+        let fractional = [parse_axis(algorithm, "iters=10.5").unwrap()];
+        assert!(matches!(run("sa", &fractional, &instances, None), Err(SonarError::InvalidParameter { .. })));
+        assert!(run("brute-force", &[], &instances, None).is_err());
+    }
+}
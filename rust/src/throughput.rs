@@ -0,0 +1,70 @@
+//! Throughput: complete solves per second at a fixed small n
+//!
+//! Max N says how large an instance an algorithm handles in the time limit;
+//! an interactive tool or a game instead solves small instances over and over
+//! and needs to know how many it can afford per frame. Each solve starts from
+//! the points alone, so algorithms that read the distance matrix pay for
+//! building it every time, as they would when embedded.
+
+use crate::error::{validate_tour, SonarError};
+use crate::problem::{Metric, ProblemInstance};
+use crate::registry::Algorithm;
+use crate::utils::Point;
+use std::time::{Duration, Instant};
+
+/// Sizes measured unless others are given
+pub const SIZES: &[usize] = &[50, 200, 1000];
+
+/// Solves of one algorithm at one size
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throughput {
+    pub n: usize,
+    pub solves: usize,
+    /// Time spent in the solves themselves
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    pub fn per_second(&self) -> f64 {
+        self.solves as f64 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+/// Solve the point sets of `instances` in turn with `algorithm` until the
+/// solves add up to `budget`, at least once each
+/// Every tour is checked, outside the timed part.
+/// Time complexity: about `budget`, or one solve of each instance if longer
+pub fn measure(algorithm: &Algorithm, instances: &[Vec<Point>], budget: Duration) -> Result<Throughput, SonarError> {
+    let n = instances.first().ok_or(SonarError::EmptyInput)?.len();
+    let (mut solves, mut elapsed) = (0, Duration::ZERO);
+    while elapsed < budget || solves < instances.len() {
+        let points = &instances[solves % instances.len()];
+        let start = Instant::now();
+        let instance = ProblemInstance::new(points.clone(), Metric::Euclidean)?;
+        let tour = (algorithm.solve)(&instance)?;
+        elapsed += start.elapsed();
+        validate_tour(&tour, points.len())?;
+        solves += 1;
+    }
+    Ok(Throughput { n, solves, elapsed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_measure_solves_every_instance() {
+        let algorithm = registry::find("angular-sort").unwrap();
+        let instances: Vec<_> = [1, 2, 3].iter().map(|&seed| generate_normalized_points(50, 20, seed)).collect();
+        let throughput = measure(algorithm, &instances, Duration::ZERO).unwrap();
+        assert_eq!((throughput.n, throughput.solves), (50, 3));
+        assert!(throughput.per_second() > 0.0);
+
+        let budget = Duration::from_millis(20);
+        assert!(measure(algorithm, &instances, budget).unwrap().elapsed >= budget);
+        assert_eq!(measure(algorithm, &[], budget), Err(SonarError::EmptyInput));
+    }
+}
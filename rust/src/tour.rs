@@ -0,0 +1,623 @@
+//! Tour representations for local search
+//!
+//! Local search moves are expressed through the `Tour` trait so the same
+//! improver can run on a plain array (O(n) reversal), on a two-level doubly
+//! linked list (O(sqrt n) reversal) for large instances, or on an implicit
+//! treap (O(log n) reversal) for the largest.
+
+use alloc::{vec, vec::Vec};
+use crate::math;
+
+/// Orientation-aware cyclic tour supporting the primitives used by k-opt moves
+pub trait Tour {
+    /// Number of cities in the tour
+    fn len(&self) -> usize;
+
+    /// Whether the tour has no cities
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// City following `city` in the current orientation
+    fn next(&self, city: usize) -> usize;
+
+    /// City preceding `city` in the current orientation
+    fn prev(&self, city: usize) -> usize;
+
+    /// Whether `b` lies on the path from `a` forward to `c` (inclusive)
+    fn between(&self, a: usize, b: usize, c: usize) -> bool;
+
+    /// Reverse the path from `a` forward to `b`
+    /// Implementations may reverse the complementary path instead, which gives
+    /// the same cycle traversed in the opposite direction.
+    fn reverse(&mut self, a: usize, b: usize);
+
+    /// Cities in tour order, starting from the first city of the representation
+    fn to_vec(&self) -> Vec<usize>;
+}
+
+/// Whether `b` lies on the cyclic range from `a` to `c` given their positions
+fn cyclic_between<P: PartialOrd>(a: P, b: P, c: P) -> bool {
+    if a <= c {
+        a <= b && b <= c
+    } else {
+        b >= a || b <= c
+    }
+}
+
+/// Array tour with a position index
+/// Reversal is O(n), always applied to the shorter of the path and its complement
+#[derive(Debug, Clone)]
+pub struct ArrayTour {
+    order: Vec<usize>,
+    position: Vec<usize>,
+}
+
+impl ArrayTour {
+    pub fn new(tour: &[usize]) -> Self {
+        let mut position = vec![0; tour.len()];
+        for (i, &city) in tour.iter().enumerate() {
+            position[city] = i;
+        }
+        ArrayTour { order: tour.to_vec(), position }
+    }
+}
+
+impl Tour for ArrayTour {
+    fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    fn next(&self, city: usize) -> usize {
+        self.order[(self.position[city] + 1) % self.order.len()]
+    }
+
+    fn prev(&self, city: usize) -> usize {
+        let n = self.order.len();
+        self.order[(self.position[city] + n - 1) % n]
+    }
+
+    fn between(&self, a: usize, b: usize, c: usize) -> bool {
+        cyclic_between(self.position[a], self.position[b], self.position[c])
+    }
+
+    fn reverse(&mut self, a: usize, b: usize) {
+        let n = self.order.len();
+        let (mut i, mut j) = (self.position[a], self.position[b]);
+        let mut length = (j + n - i) % n + 1;
+        if 2 * length > n {
+            // Reverse the complement: next(b) .. prev(a)
+            i = (j + 1) % n;
+            j = (self.position[a] + n - 1) % n;
+            length = n - length;
+        }
+        for _ in 0..length / 2 {
+            self.order.swap(i, j);
+            self.position[self.order[i]] = i;
+            self.position[self.order[j]] = j;
+            i = (i + 1) % n;
+            j = (j + n - 1) % n;
+        }
+    }
+
+    fn to_vec(&self) -> Vec<usize> {
+        self.order.clone()
+    }
+}
+
+/// A block of consecutive cities in a two-level list
+#[derive(Debug, Clone)]
+struct Segment {
+    cities: Vec<usize>,
+    reversed: bool,
+}
+
+/// Two-level doubly linked list tour
+/// Cities are grouped into about sqrt(n) segments, each carrying a reversal bit.
+/// Reversing a path splits at most two segments and then only reorders and flips
+/// whole segments, so `next`/`prev`/`between` are O(1) and `reverse` is O(sqrt n)
+/// amortized (the list is rebalanced when splits make segments too small).
+#[derive(Debug, Clone)]
+pub struct TwoLevelList {
+    segments: Vec<Segment>,
+    /// Segment ids in tour order
+    order: Vec<usize>,
+    /// Position of each segment inside `order`
+    rank: Vec<usize>,
+    /// Segment holding each city and the city's slot in `Segment::cities`
+    segment_of: Vec<usize>,
+    slot_of: Vec<usize>,
+    target_size: usize,
+}
+
+impl TwoLevelList {
+    pub fn new(tour: &[usize]) -> Self {
+        let n = tour.len();
+        let mut list = TwoLevelList {
+            segments: Vec::new(),
+            order: Vec::new(),
+            rank: Vec::new(),
+            segment_of: vec![0; n],
+            slot_of: vec![0; n],
+            target_size: (math::ceil(math::sqrt(n as f64)) as usize).max(1),
+        };
+        list.rebuild(tour);
+        list
+    }
+
+    /// Regroup the cities into segments of `target_size`
+    fn rebuild(&mut self, tour: &[usize]) {
+        self.segments = tour
+            .chunks(self.target_size)
+            .map(|chunk| Segment { cities: chunk.to_vec(), reversed: false })
+            .collect();
+        self.order = (0..self.segments.len()).collect();
+        self.rank = self.order.clone();
+        for s in 0..self.segments.len() {
+            self.index_segment(s);
+        }
+    }
+
+    fn index_segment(&mut self, s: usize) {
+        for (slot, &city) in self.segments[s].cities.iter().enumerate() {
+            self.segment_of[city] = s;
+            self.slot_of[city] = slot;
+        }
+    }
+
+    /// Offset of a city from the logical start of its segment
+    fn offset(&self, city: usize) -> usize {
+        let segment = &self.segments[self.segment_of[city]];
+        if segment.reversed {
+            segment.cities.len() - 1 - self.slot_of[city]
+        } else {
+            self.slot_of[city]
+        }
+    }
+
+    fn first_of(&self, s: usize) -> usize {
+        let segment = &self.segments[s];
+        if segment.reversed {
+            *segment.cities.last().unwrap()
+        } else {
+            segment.cities[0]
+        }
+    }
+
+    fn last_of(&self, s: usize) -> usize {
+        let segment = &self.segments[s];
+        if segment.reversed {
+            segment.cities[0]
+        } else {
+            *segment.cities.last().unwrap()
+        }
+    }
+
+    /// Split the segment of `city` so that `city` starts a segment
+    fn split_before(&mut self, city: usize) {
+        let offset = self.offset(city);
+        if offset == 0 {
+            return;
+        }
+        let s = self.segment_of[city];
+        let slot = self.slot_of[city];
+        let reversed = self.segments[s].reversed;
+
+        // Physical layout: the logical tail is cities[slot..] when forward,
+        // cities[..=slot] when reversed
+        let tail = if reversed {
+            let rest = self.segments[s].cities.split_off(slot + 1);
+            core::mem::replace(&mut self.segments[s].cities, rest)
+        } else {
+            self.segments[s].cities.split_off(slot)
+        };
+
+        let t = self.segments.len();
+        self.segments.push(Segment { cities: tail, reversed });
+        self.index_segment(s);
+        self.index_segment(t);
+
+        let r = self.rank[s];
+        self.order.insert(r + 1, t);
+        self.rank.push(0);
+        for k in r + 1..self.order.len() {
+            self.rank[self.order[k]] = k;
+        }
+    }
+
+    /// Reverse the segments at ranks `from..=to` in `order` and flip their bits
+    fn reverse_segments(&mut self, from: usize, to: usize) {
+        self.order[from..=to].reverse();
+        for k in from..=to {
+            let s = self.order[k];
+            self.rank[s] = k;
+            self.segments[s].reversed = !self.segments[s].reversed;
+        }
+    }
+}
+
+impl Tour for TwoLevelList {
+    fn len(&self) -> usize {
+        self.segment_of.len()
+    }
+
+    fn next(&self, city: usize) -> usize {
+        let s = self.segment_of[city];
+        if city == self.last_of(s) {
+            let r = (self.rank[s] + 1) % self.order.len();
+            return self.first_of(self.order[r]);
+        }
+        let slot = self.slot_of[city];
+        let segment = &self.segments[s];
+        if segment.reversed {
+            segment.cities[slot - 1]
+        } else {
+            segment.cities[slot + 1]
+        }
+    }
+
+    fn prev(&self, city: usize) -> usize {
+        let s = self.segment_of[city];
+        if city == self.first_of(s) {
+            let count = self.order.len();
+            let r = (self.rank[s] + count - 1) % count;
+            return self.last_of(self.order[r]);
+        }
+        let slot = self.slot_of[city];
+        let segment = &self.segments[s];
+        if segment.reversed {
+            segment.cities[slot + 1]
+        } else {
+            segment.cities[slot - 1]
+        }
+    }
+
+    fn between(&self, a: usize, b: usize, c: usize) -> bool {
+        let key = |city: usize| (self.rank[self.segment_of[city]], self.offset(city));
+        cyclic_between(key(a), key(b), key(c))
+    }
+
+    fn reverse(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+        if self.order.len() > 4 * self.target_size {
+            let sequence = self.to_vec();
+            self.rebuild(&sequence);
+        }
+
+        let after_b = self.next(b);
+        self.split_before(a);
+        self.split_before(after_b);
+
+        let mut from = self.rank[self.segment_of[a]];
+        let mut to = self.rank[self.segment_of[b]];
+        if from > to {
+            // The path wraps around the end of `order`; rotate it to the front
+            self.order.rotate_left(from);
+            for (k, &s) in self.order.iter().enumerate() {
+                self.rank[s] = k;
+            }
+            to = self.rank[self.segment_of[b]];
+            from = 0;
+        }
+        self.reverse_segments(from, to);
+    }
+
+    fn to_vec(&self) -> Vec<usize> {
+        let mut sequence = Vec::with_capacity(self.len());
+        for &s in &self.order {
+            let segment = &self.segments[s];
+            if segment.reversed {
+                sequence.extend(segment.cities.iter().rev());
+            } else {
+                sequence.extend_from_slice(&segment.cities);
+            }
+        }
+        sequence
+    }
+}
+
+/// No child or parent in a `TreapTour`
+const NIL: usize = usize::MAX;
+
+/// Implicit treap tour
+/// A randomized balanced binary tree whose in-order sequence is the tour, with
+/// one node per city. A reversal splits out the path, toggles a lazy flag on
+/// its root and merges it back, so it is O(log n) expected whatever the path
+/// length; the price is that `next`, `prev` and `between` also walk the tree
+/// and are O(log n) instead of O(1). It pays off when reversals dominate, on
+/// 10^5 cities and more.
+#[derive(Debug, Clone)]
+pub struct TreapTour {
+    root: usize,
+    left: Vec<usize>,
+    right: Vec<usize>,
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    priority: Vec<u64>,
+    /// The subtree's sequence is reversed, not yet pushed down to its children
+    flipped: Vec<bool>,
+}
+
+impl TreapTour {
+    pub fn new(tour: &[usize]) -> Self {
+        let n = tour.len();
+        // A fixed hash of the city keeps the shape, and so the timings, reproducible
+        let priority = (0..n as u64)
+            .map(|city| {
+                let mut z = city.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                z ^ (z >> 31)
+            })
+            .collect();
+        let mut treap = TreapTour {
+            root: NIL,
+            left: vec![NIL; n],
+            right: vec![NIL; n],
+            parent: vec![NIL; n],
+            size: vec![1; n],
+            priority,
+            flipped: vec![false; n],
+        };
+        for &city in tour {
+            treap.root = treap.merge(treap.root, city);
+        }
+        treap.detach(treap.root);
+        treap
+    }
+
+    fn size_of(&self, node: usize) -> usize {
+        if node == NIL {
+            0
+        } else {
+            self.size[node]
+        }
+    }
+
+    fn detach(&mut self, node: usize) {
+        if node != NIL {
+            self.parent[node] = NIL;
+        }
+    }
+
+    /// Apply a pending reversal of `node` to its children
+    fn push(&mut self, node: usize) {
+        if self.flipped[node] {
+            self.flipped[node] = false;
+            core::mem::swap(&mut self.left[node], &mut self.right[node]);
+            for child in [self.left[node], self.right[node]] {
+                if child != NIL {
+                    self.flipped[child] = !self.flipped[child];
+                }
+            }
+        }
+    }
+
+    /// Recompute the size of `node` and point its children back at it
+    fn update(&mut self, node: usize) {
+        let (left, right) = (self.left[node], self.right[node]);
+        self.size[node] = 1 + self.size_of(left) + self.size_of(right);
+        for child in [left, right] {
+            if child != NIL {
+                self.parent[child] = node;
+            }
+        }
+    }
+
+    /// Split the subtree of `node` into its first `k` cities and the rest
+    fn split(&mut self, node: usize, k: usize) -> (usize, usize) {
+        if node == NIL {
+            return (NIL, NIL);
+        }
+        self.push(node);
+        let left_size = self.size_of(self.left[node]);
+        if k <= left_size {
+            let (first, rest) = self.split(self.left[node], k);
+            self.left[node] = rest;
+            self.update(node);
+            (first, node)
+        } else {
+            let (first, rest) = self.split(self.right[node], k - left_size - 1);
+            self.right[node] = first;
+            self.update(node);
+            (node, rest)
+        }
+    }
+
+    /// Concatenate the sequences of subtrees `a` and `b`
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        if self.priority[a] > self.priority[b] {
+            self.push(a);
+            self.right[a] = self.merge(self.right[a], b);
+            self.update(a);
+            a
+        } else {
+            self.push(b);
+            self.left[b] = self.merge(a, self.left[b]);
+            self.update(b);
+            b
+        }
+    }
+
+    /// Whether the children of `node` are swapped once every pending flag
+    /// above and at it is applied
+    fn swapped(&self, node: usize) -> bool {
+        let mut swapped = false;
+        let mut current = node;
+        while current != NIL {
+            swapped ^= self.flipped[current];
+            current = self.parent[current];
+        }
+        swapped
+    }
+
+    /// Index of `city` in the tour
+    /// Time complexity: O(log n) expected
+    fn position(&self, city: usize) -> usize {
+        let mut swapped = self.swapped(city);
+        let left_of = |node: usize, swapped: bool| if swapped { self.right[node] } else { self.left[node] };
+        let mut position = self.size_of(left_of(city, swapped));
+        let mut child = city;
+        while self.parent[child] != NIL {
+            let node = self.parent[child];
+            swapped ^= self.flipped[child];
+            if left_of(node, swapped) != child {
+                position += self.size_of(left_of(node, swapped)) + 1;
+            }
+            child = node;
+        }
+        position
+    }
+
+    /// City at index `k` of the tour
+    /// Time complexity: O(log n) expected
+    fn city_at(&self, mut k: usize) -> usize {
+        let (mut node, mut swapped) = (self.root, false);
+        loop {
+            swapped ^= self.flipped[node];
+            let (left, right) = if swapped {
+                (self.right[node], self.left[node])
+            } else {
+                (self.left[node], self.right[node])
+            };
+            let left_size = self.size_of(left);
+            if k < left_size {
+                node = left;
+            } else if k == left_size {
+                return node;
+            } else {
+                k -= left_size + 1;
+                node = right;
+            }
+        }
+    }
+}
+
+impl Tour for TreapTour {
+    fn len(&self) -> usize {
+        self.size_of(self.root)
+    }
+
+    fn next(&self, city: usize) -> usize {
+        self.city_at((self.position(city) + 1) % self.len())
+    }
+
+    fn prev(&self, city: usize) -> usize {
+        let n = self.len();
+        self.city_at((self.position(city) + n - 1) % n)
+    }
+
+    fn between(&self, a: usize, b: usize, c: usize) -> bool {
+        cyclic_between(self.position(a), self.position(b), self.position(c))
+    }
+
+    fn reverse(&mut self, a: usize, b: usize) {
+        let (i, j) = (self.position(a), self.position(b));
+        // A path wrapping around the end is reversed through its complement
+        let (from, to) = if i <= j { (i, j + 1) } else { (j + 1, i) };
+        let (head, rest) = self.split(self.root, from);
+        let (middle, tail) = self.split(rest, to - from);
+        if middle != NIL {
+            self.flipped[middle] = !self.flipped[middle];
+        }
+        let front = self.merge(head, middle);
+        self.root = self.merge(front, tail);
+        self.detach(self.root);
+    }
+
+    fn to_vec(&self) -> Vec<usize> {
+        let mut sequence = Vec::with_capacity(self.len());
+        // In-order walk carrying the pending flips down
+        let mut stack = Vec::new();
+        let (mut node, mut swapped) = (self.root, false);
+        loop {
+            while node != NIL {
+                let inner = swapped ^ self.flipped[node];
+                stack.push((node, inner));
+                node = if inner { self.right[node] } else { self.left[node] };
+                swapped = inner;
+            }
+            let Some((top, inner)) = stack.pop() else { break };
+            sequence.push(top);
+            node = if inner { self.left[top] } else { self.right[top] };
+            swapped = inner;
+        }
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_tour;
+    use crate::utils::Lcg;
+
+    /// Canonical form of a cycle: start at city 0, walk towards the smaller neighbour
+    fn canonical(tour: &[usize]) -> Vec<usize> {
+        let n = tour.len();
+        let start = tour.iter().position(|&c| c == 0).unwrap();
+        let forward: Vec<usize> = (0..n).map(|k| tour[(start + k) % n]).collect();
+        let backward: Vec<usize> = (0..n).map(|k| tour[(start + n - k) % n]).collect();
+        forward.min(backward)
+    }
+
+    /// Reference reversal of the path from `a` forward to `b` on a plain vector
+    fn reverse_path(tour: &[usize], a: usize, b: usize) -> Vec<usize> {
+        let n = tour.len();
+        let i = tour.iter().position(|&c| c == a).unwrap();
+        let length = (tour.iter().position(|&c| c == b).unwrap() + n - i) % n + 1;
+        let mut result = tour.to_vec();
+        for k in 0..length {
+            result[(i + k) % n] = tour[(i + length - 1 - k) % n];
+        }
+        result
+    }
+
+    /// `next`, `prev` and `between` around `c` agree with `to_vec`
+    fn check_neighbours(tour: &impl Tour, c: usize) {
+        let n = tour.len();
+        let (x, y) = (tour.next(c), tour.prev(c));
+        let sequence = tour.to_vec();
+        let i = sequence.iter().position(|&city| city == c).unwrap();
+        assert_eq!(x, sequence[(i + 1) % n]);
+        assert_eq!(y, sequence[(i + n - 1) % n]);
+        assert!(tour.between(c, c, y) && tour.between(y, c, x));
+    }
+
+    #[test]
+    fn test_tour_representations_match_reference_reversal() {
+        let n = 200;
+        let initial = random_tour::generate_tour(n, 1);
+        let mut array = ArrayTour::new(&initial);
+        let mut list = TwoLevelList::new(&initial);
+        let mut treap = TreapTour::new(&initial);
+        let mut rng = Lcg::new(99);
+
+        for _ in 0..2_000 {
+            let a = (rng.next() * n as f64) as usize;
+            let b = (rng.next() * n as f64) as usize;
+
+            let expected_array = canonical(&reverse_path(&array.to_vec(), a, b));
+            let expected_list = canonical(&reverse_path(&list.to_vec(), a, b));
+            let expected_treap = canonical(&reverse_path(&treap.to_vec(), a, b));
+            array.reverse(a, b);
+            list.reverse(a, b);
+            treap.reverse(a, b);
+            assert_eq!(canonical(&array.to_vec()), expected_array);
+            assert_eq!(canonical(&list.to_vec()), expected_list);
+            assert_eq!(canonical(&treap.to_vec()), expected_treap);
+
+            let c = (rng.next() * n as f64) as usize;
+            check_neighbours(&list, c);
+            check_neighbours(&treap, c);
+        }
+    }
+}
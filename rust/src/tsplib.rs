@@ -0,0 +1,174 @@
+//! TSPLIB and Concorde file formats
+
+use crate::problem::Metric;
+use crate::utils::Point;
+use std::fmt::Write;
+
+/// Write points as a TSPLIB `.tsp` instance readable by Concorde
+/// Concorde's EUC_2D metric rounds distances to integers, so coordinates are
+/// multiplied by `scale` (e.g. 1e6 for points in the unit square)
+pub fn write_tsp(name: &str, points: &[Point], scale: f64) -> String {
+    let mut out = String::new();
+    writeln!(out, "NAME : {}", name).unwrap();
+    writeln!(out, "TYPE : TSP").unwrap();
+    writeln!(out, "DIMENSION : {}", points.len()).unwrap();
+    writeln!(out, "EDGE_WEIGHT_TYPE : EUC_2D").unwrap();
+    writeln!(out, "NODE_COORD_SECTION").unwrap();
+    for (i, p) in points.iter().enumerate() {
+        writeln!(out, "{} {} {}", i + 1, p.x * scale, p.y * scale).unwrap();
+    }
+    writeln!(out, "EOF").unwrap();
+    out
+}
+
+/// Parse a TSPLIB `.tsp` instance with a NODE_COORD_SECTION
+/// Coordinates are returned as-is; ids are 0-based positions in the file
+pub fn parse_tsp(content: &str) -> Result<Vec<Point>, String> {
+    let mut dimension = None;
+    let mut points = Vec::new();
+    let mut lines = content.lines();
+
+    for line in lines.by_ref() {
+        let line = line.trim();
+        if line.starts_with("NODE_COORD_SECTION") {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            if key.trim() == "DIMENSION" {
+                dimension = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    for line in lines {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() {
+            continue;
+        }
+        if fields[0] == "EOF" || fields.len() < 3 {
+            break;
+        }
+        let coordinate = |s: &str| {
+            s.parse::<f64>()
+                .map_err(|_| format!("invalid coordinate in NODE_COORD_SECTION: {}", s))
+        };
+        let (x, y) = (coordinate(fields[1])?, coordinate(fields[2])?);
+        points.push(Point { x, y, angle: 0.0, id: points.len() });
+    }
+
+    if points.is_empty() {
+        return Err("missing or empty NODE_COORD_SECTION".to_string());
+    }
+    if let Some(n) = dimension {
+        if n != points.len() {
+            return Err(format!("DIMENSION is {} but {} nodes were read", n, points.len()));
+        }
+    }
+    Ok(points)
+}
+
+/// Metric named by the `EDGE_WEIGHT_TYPE` header
+/// `EUC_2D` selects TSPLIB's integer rounding; anything else, or no header,
+/// falls back to exact Euclidean distances
+pub fn parse_metric(content: &str) -> Metric {
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with("NODE_COORD_SECTION") {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            if key.trim() == "EDGE_WEIGHT_TYPE" && value.trim() == "EUC_2D" {
+                return Metric::Euc2d;
+            }
+        }
+    }
+    Metric::Euclidean
+}
+
+/// Path of the known-optimal tour stored next to an instance (`name.opt.tour`)
+pub fn optimal_tour_path(instance: &std::path::Path) -> std::path::PathBuf {
+    instance.with_extension("opt.tour")
+}
+
+/// Load the known-optimal tour for an instance file, if one exists alongside it
+pub fn load_optimal_tour(instance: &std::path::Path) -> Option<Result<Vec<usize>, String>> {
+    let content = std::fs::read_to_string(optimal_tour_path(instance)).ok()?;
+    Some(parse_tour(&content))
+}
+
+/// Parse a TSPLIB `.tour` file (1-based ids in TOUR_SECTION, terminated by -1)
+/// Returns 0-based city indices
+pub fn parse_tour(content: &str) -> Result<Vec<usize>, String> {
+    let mut in_section = false;
+    let mut tour = Vec::new();
+
+    for token in content.split_whitespace() {
+        if !in_section {
+            in_section = token == "TOUR_SECTION";
+            continue;
+        }
+        if token == "-1" || token == "EOF" {
+            break;
+        }
+        let id: usize = token
+            .parse()
+            .map_err(|_| format!("invalid city id in TOUR_SECTION: {}", token))?;
+        if id == 0 {
+            return Err("TSPLIB city ids are 1-based, found 0".to_string());
+        }
+        tour.push(id - 1);
+    }
+
+    if !in_section {
+        return Err("missing TOUR_SECTION".to_string());
+    }
+    Ok(tour)
+}
+
+/// Parse a Concorde `.sol` file: the city count followed by 0-based city indices
+pub fn parse_sol(content: &str) -> Result<Vec<usize>, String> {
+    let mut numbers = content.split_whitespace().map(|token| {
+        token
+            .parse::<usize>()
+            .map_err(|_| format!("invalid number in solution file: {}", token))
+    });
+
+    let n = numbers.next().ok_or("empty solution file")??;
+    let tour = numbers.collect::<Result<Vec<usize>, String>>()?;
+    if tour.len() != n {
+        return Err(format!("solution declares {} cities but lists {}", n, tour.len()));
+    }
+    Ok(tour)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::generate_normalized_points;
+
+    #[test]
+    fn test_tsplib_round_trips() {
+        let points = generate_normalized_points(5, 10, 1);
+        let tsp = write_tsp("sample", &points, 1000.0);
+        assert!(tsp.contains("DIMENSION : 5"));
+        assert_eq!(tsp.lines().filter(|l| l.starts_with(char::is_numeric)).count(), 5);
+
+        let tour = "NAME : sample.tour\nTYPE : TOUR\nDIMENSION : 4\nTOUR_SECTION\n1\n3\n2\n4\n-1\nEOF\n";
+        assert_eq!(parse_tour(tour).unwrap(), vec![0, 2, 1, 3]);
+        assert!(parse_tour("TOUR_SECTION\n0\n-1\n").is_err());
+
+        assert_eq!(parse_metric(&tsp), Metric::Euc2d);
+        assert_eq!(parse_metric("NAME : x\nNODE_COORD_SECTION\n"), Metric::Euclidean);
+
+        let parsed = parse_tsp(&tsp).unwrap();
+        assert_eq!(parsed.len(), 5);
+        assert!((parsed[2].x - points[2].x * 1000.0).abs() < 1e-9);
+        assert_eq!(
+            optimal_tour_path(std::path::Path::new("data/berlin52.tsp")),
+            std::path::PathBuf::from("data/berlin52.opt.tour")
+        );
+
+        assert_eq!(parse_sol("4\n0 2 1\n3\n").unwrap(), vec![0, 2, 1, 3]);
+        assert!(parse_sol("5\n0 2 1 3\n").is_err());
+    }
+}
@@ -0,0 +1,356 @@
+//! Hyperparameter tuning for simulated annealing and the genetic algorithm
+//!
+//! A tuner draws configurations from a `SearchSpace` and scores each by its
+//! mean tour length relative to nearest neighbor over a family of instances,
+//! keeping the best one found within a time budget. Every configuration gets
+//! the same random stream on the same instance, so score differences come from
+//! the parameters rather than from luck.
+
+use crate::determinism;
+use crate::error::SonarError;
+use crate::genetic::GeneticSolver;
+use crate::nearest_neighbor;
+use crate::registry::{ANNEALING_ITERATIONS, COOLING_RATE, INITIAL_TEMPERATURE};
+use crate::registry::{GENERATIONS, MUTATION_RATE, POPULATION_SIZE};
+use crate::simulated_annealing::{AnnealingSolver, MoveKind};
+use crate::stepwise::SteppableSolver;
+use crate::utils::calculate_tour_length;
+use crate::rng::BoxedRng;
+use rand::Rng;
+use std::fmt::Debug;
+use std::time::{Duration, Instant};
+
+/// Configurations sampled per successive halving bracket
+const BRACKET: usize = 16;
+
+/// Parameters of one algorithm and the ranges to search them in
+pub trait SearchSpace {
+    type Params: Copy + Debug;
+
+    /// Registry key of the algorithm, the table name of its TOML
+    const KEY: &'static str;
+
+    /// The parameters the algorithm runs with today, scored as the baseline
+    fn defaults(&self) -> Self::Params;
+
+    fn sample(&self, rng: &mut BoxedRng) -> Self::Params;
+
+    /// Tour of `graph` found with `params`, from the nearest neighbor tour
+    /// `initial` where the algorithm improves a tour
+    fn solve(
+        &self,
+        params: &Self::Params,
+        graph: &[Vec<f64>],
+        initial: &[usize],
+        rng: BoxedRng,
+    ) -> Result<Vec<usize>, SonarError>;
+
+    /// `(name, value)` of every parameter, values written as TOML
+    fn fields(&self, params: &Self::Params) -> Vec<(&'static str, String)>;
+}
+
+/// Uniform in log space between the bounds, for scale parameters
+fn log_uniform(rng: &mut BoxedRng, (low, high): (f64, f64)) -> f64 {
+    (low.ln() + rng.gen::<f64>() * (high.ln() - low.ln())).exp()
+}
+
+/// Simulated annealing with a fixed iteration count
+#[derive(Debug, Clone, Copy)]
+pub struct AnnealingSpace {
+    pub max_iterations: usize,
+    /// Range of the initial temperature, in units of the instance's distances
+    pub initial_temperature: (f64, f64),
+    /// Range of the cooling rate, searched by its distance from 1
+    pub cooling_rate: (f64, f64),
+}
+
+impl Default for AnnealingSpace {
+    fn default() -> Self {
+        AnnealingSpace {
+            max_iterations: ANNEALING_ITERATIONS.value,
+            initial_temperature: (1e-4, 10.0),
+            cooling_rate: (0.99, 0.99999),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnnealingParams {
+    pub max_iterations: usize,
+    pub initial_temperature: f64,
+    pub cooling_rate: f64,
+}
+
+impl SearchSpace for AnnealingSpace {
+    type Params = AnnealingParams;
+    const KEY: &'static str = "simulated-annealing";
+
+    fn defaults(&self) -> AnnealingParams {
+        AnnealingParams {
+            max_iterations: self.max_iterations,
+            initial_temperature: INITIAL_TEMPERATURE.value,
+            cooling_rate: COOLING_RATE.value,
+        }
+    }
+
+    fn sample(&self, rng: &mut BoxedRng) -> AnnealingParams {
+        let (low, high) = self.cooling_rate;
+        AnnealingParams {
+            max_iterations: self.max_iterations,
+            initial_temperature: log_uniform(rng, self.initial_temperature),
+            cooling_rate: 1.0 - log_uniform(rng, (1.0 - high, 1.0 - low)),
+        }
+    }
+
+    fn solve(
+        &self,
+        params: &AnnealingParams,
+        graph: &[Vec<f64>],
+        initial: &[usize],
+        rng: BoxedRng,
+    ) -> Result<Vec<usize>, SonarError> {
+        let moves = [(MoveKind::Reversal, 1.0)];
+        let AnnealingParams { max_iterations, initial_temperature, cooling_rate } = *params;
+        let solver = AnnealingSolver::new(
+            graph,
+            initial,
+            max_iterations,
+            initial_temperature,
+            cooling_rate,
+            &moves,
+            max_iterations.max(1),
+        )?;
+        Ok(solver.with_rng(rng).run())
+    }
+
+    fn fields(&self, params: &AnnealingParams) -> Vec<(&'static str, String)> {
+        vec![
+            ("max_iterations", params.max_iterations.to_string()),
+            ("initial_temperature", format!("{:?}", params.initial_temperature)),
+            ("cooling_rate", format!("{:?}", params.cooling_rate)),
+        ]
+    }
+}
+
+/// The genetic algorithm with a fixed number of generations
+#[derive(Debug, Clone, Copy)]
+pub struct GeneticSpace {
+    pub generations: usize,
+    pub population_size: (usize, usize),
+    pub mutation_rate: (f64, f64),
+}
+
+impl Default for GeneticSpace {
+    fn default() -> Self {
+        GeneticSpace { generations: GENERATIONS.value, population_size: (10, 200), mutation_rate: (0.001, 0.5) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeneticParams {
+    pub population_size: usize,
+    pub generations: usize,
+    pub mutation_rate: f64,
+}
+
+impl SearchSpace for GeneticSpace {
+    type Params = GeneticParams;
+    const KEY: &'static str = "genetic";
+
+    fn defaults(&self) -> GeneticParams {
+        GeneticParams {
+            population_size: POPULATION_SIZE.value,
+            generations: self.generations,
+            mutation_rate: MUTATION_RATE.value,
+        }
+    }
+
+    fn sample(&self, rng: &mut BoxedRng) -> GeneticParams {
+        let (low, high) = self.population_size;
+        GeneticParams {
+            population_size: rng.gen_range(low..=high),
+            generations: self.generations,
+            mutation_rate: log_uniform(rng, self.mutation_rate),
+        }
+    }
+
+    fn solve(
+        &self,
+        params: &GeneticParams,
+        graph: &[Vec<f64>],
+        _initial: &[usize],
+        rng: BoxedRng,
+    ) -> Result<Vec<usize>, SonarError> {
+        let GeneticParams { population_size, generations, mutation_rate } = *params;
+        let solver = GeneticSolver::new(graph, graph.len(), population_size, generations, mutation_rate)?;
+        Ok(solver.with_rng(rng).run())
+    }
+
+    fn fields(&self, params: &GeneticParams) -> Vec<(&'static str, String)> {
+        vec![
+            ("population_size", params.population_size.to_string()),
+            ("generations", params.generations.to_string()),
+            ("mutation_rate", format!("{:?}", params.mutation_rate)),
+        ]
+    }
+}
+
+/// How configurations are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Score every sampled configuration on the whole family
+    Random,
+    /// Score brackets of `BRACKET` configurations on one instance, then keep
+    /// the better half for twice as many instances, until one is left or the
+    /// family is used up; weak configurations cost one run instead of one per
+    /// instance
+    SuccessiveHalving,
+}
+
+/// Best configuration a tuner found
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tuned<P> {
+    pub params: P,
+    /// Mean tour length of `params` over the family, relative to nearest neighbor
+    pub score: f64,
+    /// The same for the default parameters
+    pub baseline: f64,
+    /// Solver runs spent
+    pub runs: usize,
+}
+
+/// Instances to tune on, with their nearest neighbor tours and lengths
+struct Family<'g> {
+    graphs: &'g [Vec<Vec<f64>>],
+    initial: Vec<Vec<usize>>,
+    reference: Vec<f64>,
+}
+
+impl Family<'_> {
+    /// Length of the tour `params` finds on instance `index`, relative to nearest neighbor
+    fn ratio<S: SearchSpace>(&self, space: &S, params: &S::Params, index: usize) -> Result<f64, SonarError> {
+        let graph = &self.graphs[index];
+        let rng = determinism::seeded(index as u64);
+        let tour = space.solve(params, graph, &self.initial[index], rng)?;
+        Ok(calculate_tour_length(&tour, graph) / self.reference[index].max(f64::MIN_POSITIVE))
+    }
+}
+
+/// Search `space` for the parameters with the shortest tours on `graphs`
+/// The default parameters are scored first, so the result is never worse than
+/// them. The budget is checked before every solver run, so the tuner may
+/// overrun it by one run, and always scores at least the defaults.
+/// Time complexity: O(budget), plus one solver run per instance for the defaults
+pub fn tune<S: SearchSpace>(
+    space: &S,
+    graphs: &[Vec<Vec<f64>>],
+    strategy: Strategy,
+    budget: Duration,
+) -> Result<Tuned<S::Params>, SonarError> {
+    if graphs.is_empty() {
+        return Err(SonarError::EmptyInput);
+    }
+    let deadline = Instant::now() + budget;
+    let initial = graphs
+        .iter()
+        .map(|graph| nearest_neighbor::generate_tour(graph.len(), graph, 0))
+        .collect::<Result<Vec<_>, _>>()?;
+    let reference = graphs.iter().zip(&initial).map(|(graph, tour)| calculate_tour_length(tour, graph)).collect();
+    let family = Family { graphs, initial, reference };
+    let m = graphs.len();
+
+    let defaults = space.defaults();
+    let mut total = 0.0;
+    for index in 0..m {
+        total += family.ratio(space, &defaults, index)?;
+    }
+    let baseline = total / m as f64;
+    let mut best = Tuned { params: defaults, score: baseline, baseline, runs: m };
+    let mut rng = determinism::rng(0);
+    let expired = || Instant::now() >= deadline;
+
+    match strategy {
+        Strategy::Random => 'search: while !expired() {
+            let params = space.sample(&mut rng);
+            let mut total = 0.0;
+            for index in 0..m {
+                if expired() {
+                    break 'search;
+                }
+                total += family.ratio(space, &params, index)?;
+                best.runs += 1;
+            }
+            if total / (m as f64) < best.score {
+                best.params = params;
+                best.score = total / m as f64;
+            }
+        },
+        Strategy::SuccessiveHalving => 'search: while !expired() {
+            // (params, sum of ratios over the instances scored so far)
+            let mut candidates: Vec<(S::Params, f64)> = (0..BRACKET).map(|_| (space.sample(&mut rng), 0.0)).collect();
+            let mut scored = 0;
+            loop {
+                let upto = if candidates.len() == 1 { m } else { (2 * scored).clamp(1, m) };
+                for (params, total) in &mut candidates {
+                    for index in scored..upto {
+                        if expired() {
+                            break 'search;
+                        }
+                        *total += family.ratio(space, params, index)?;
+                        best.runs += 1;
+                    }
+                }
+                scored = upto;
+                candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+                if scored == m {
+                    break;
+                }
+                candidates.truncate(candidates.len().div_ceil(2));
+            }
+            let (params, total) = candidates[0];
+            if total / (m as f64) < best.score {
+                best.params = params;
+                best.score = total / m as f64;
+            }
+        },
+    }
+    Ok(best)
+}
+
+/// `params` as a TOML table named after the algorithm's registry key
+pub fn to_toml<S: SearchSpace>(space: &S, params: &S::Params) -> String {
+    let mut toml = format!("[{}]\n", S::KEY);
+    for (name, value) in space.fields(params) {
+        toml.push_str(&format!("{} = {}\n", name, value));
+    }
+    toml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_tuning_never_loses_to_the_defaults() {
+        let graphs: Vec<_> = (1..=3)
+            .map(|seed| create_distance_matrix(&generate_normalized_points(40, 30, seed)))
+            .collect();
+        let space = AnnealingSpace { max_iterations: 2000, ..AnnealingSpace::default() };
+        for strategy in [Strategy::Random, Strategy::SuccessiveHalving] {
+            let tuned = tune(&space, &graphs, strategy, Duration::from_millis(200)).unwrap();
+            assert!(tuned.score <= tuned.baseline && tuned.runs > graphs.len(), "{:?}", tuned);
+            let (low, high) = space.cooling_rate;
+            assert!(tuned.params == space.defaults() || (low..=high).contains(&tuned.params.cooling_rate));
+        }
+
+        let genetic = GeneticSpace { generations: 5, ..GeneticSpace::default() };
+        let tuned = tune(&genetic, &graphs, Strategy::Random, Duration::ZERO).unwrap();
+        assert_eq!((tuned.params, tuned.runs), (genetic.defaults(), 3));
+        assert_eq!(
+            to_toml(&genetic, &tuned.params),
+            "[genetic]\npopulation_size = 50\ngenerations = 5\nmutation_rate = 0.1\n"
+        );
+        assert_eq!(tune(&genetic, &[], Strategy::Random, Duration::ZERO), Err(SonarError::EmptyInput));
+    }
+}
@@ -0,0 +1,118 @@
+//! 2h-opt (2.5-opt) local search: 2-opt combined with single-node insertion
+
+use alloc::{vec, vec::Vec};
+use crate::error::{validate_graph, validate_tour, SonarError};
+use crate::neighbors::candidate_lists;
+use crate::or_one::relocate;
+
+/// Apply 2h-opt improvement to a tour using candidate lists of size `k`
+/// For every tour edge (a, b) and candidate c of a with successor d, two moves
+/// are evaluated: the 2-opt exchange (a, c)(b, d), and moving the single city b
+/// between c and d. The better improving move is applied.
+/// Time complexity: O(n * k) evaluations per iteration, plus O(d) per applied
+/// move that reverses or shifts d positions
+pub fn improve(tour: &[usize], graph: &[Vec<f64>], k: usize, max_iterations: usize) -> Result<Vec<usize>, SonarError> {
+    validate_graph(graph)?;
+    validate_tour(tour, graph.len())?;
+
+    let n = tour.len();
+    let mut current_tour = tour.to_vec();
+    if n < 5 {
+        return Ok(current_tour);
+    }
+
+    let candidates = candidate_lists(graph, k);
+    let mut position = vec![0; n];
+    for (p, &city) in current_tour.iter().enumerate() {
+        position[city] = p;
+    }
+    let mut improved = true;
+    let mut iterations = 0;
+
+    while improved && iterations < max_iterations {
+        improved = false;
+        iterations += 1;
+
+        for i in 0..n {
+            let a = current_tour[i];
+            let b = current_tour[(i + 1) % n];
+            let b_next = current_tour[(i + 2) % n];
+
+            for &c in &candidates[a] {
+                if c == b {
+                    continue;
+                }
+                let j = position[c];
+                let d = current_tour[(j + 1) % n];
+
+                // 2-opt: a -> c ... b -> d
+                let two_opt_gain = if d != a {
+                    graph[a][b] + graph[c][d] - graph[a][c] - graph[b][d]
+                } else {
+                    0.0
+                };
+
+                // Node insertion: a -> b_next, c -> b -> d
+                let insertion_gain = graph[a][b] + graph[b][b_next] + graph[c][d]
+                    - graph[a][b_next] - graph[c][b] - graph[b][d];
+
+                if two_opt_gain.max(insertion_gain) <= 1e-10 {
+                    continue;
+                }
+
+                if two_opt_gain >= insertion_gain {
+                    reverse_cyclic(&mut current_tour, &mut position, (i + 1) % n, j);
+                } else {
+                    relocate(&mut current_tour, &mut position, (i + 1) % n, j);
+                }
+                improved = true;
+                break;
+            }
+        }
+    }
+
+    Ok(current_tour)
+}
+
+/// Reverse the cyclic range of positions from `i` to `j` inclusive, updating
+/// `position` for the cities it moves
+fn reverse_cyclic(tour: &mut [usize], position: &mut [usize], mut i: usize, mut j: usize) {
+    let n = tour.len();
+    let length = (j + n - i) % n + 1;
+    for _ in 0..length / 2 {
+        tour.swap(i, j);
+        position[tour[i]] = i;
+        position[tour[j]] = j;
+        i = (i + 1) % n;
+        j = (j + n - 1) % n;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_tour;
+    use crate::two_opt;
+    use crate::utils::{calculate_tour_length, create_distance_matrix, generate_normalized_points};
+
+    #[test]
+    fn test_two_h_opt_improves_tour() {
+        let points = generate_normalized_points(200, 40, 6);
+        let graph = create_distance_matrix(&points);
+        let initial = random_tour::generate_tour(points.len(), 3);
+
+        let improved = improve(&initial, &graph, 8, 1_000).unwrap();
+
+        assert_eq!(improved.len(), points.len());
+        let mut seen = std::collections::HashSet::new();
+        for &id in &improved {
+            assert!(seen.insert(id));
+        }
+
+        let two_opt_tour = two_opt::improve(&initial, &graph, 1_000).unwrap();
+        let length = calculate_tour_length(&improved, &graph);
+        assert!(length < calculate_tour_length(&initial, &graph));
+        // Should land in the same quality range as full 2-opt
+        assert!(length < calculate_tour_length(&two_opt_tour, &graph) * 1.15);
+    }
+}
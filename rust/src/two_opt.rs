@@ -1,17 +1,47 @@
 //! 2-Opt local search improvement algorithm
 
+use alloc::vec::Vec;
+use crate::error::{validate_graph, validate_size, validate_tour, SonarError};
+use crate::stepwise::SteppableSolver;
+use crate::tour::Tour;
+
 /// Apply 2-opt improvement to a tour
 /// Iteratively swaps edge pairs to reduce tour length
 /// Time complexity: O(n^2) per iteration
-pub fn improve(tour: &[usize], graph: &[Vec<f64>], max_iterations: usize) -> Vec<usize> {
-    let n = tour.len();
-    let mut improved = true;
-    let mut iterations = 0;
-    let mut current_tour = tour.to_vec();
+pub fn improve(tour: &[usize], graph: &[Vec<f64>], max_iterations: usize) -> Result<Vec<usize>, SonarError> {
+    Ok(TwoOptSolver::new(tour, graph, max_iterations)?.run())
+}
 
-    while improved && iterations < max_iterations {
-        improved = false;
-        iterations += 1;
+/// 2-opt improvement run one full sweep per `step`
+/// Finishes after a sweep without improvement or after `max_iterations` sweeps.
+pub struct TwoOptSolver<'a> {
+    graph: &'a [Vec<f64>],
+    tour: Vec<usize>,
+    max_iterations: usize,
+    iterations: usize,
+    improved: bool,
+}
+
+impl<'a> TwoOptSolver<'a> {
+    pub fn new(tour: &[usize], graph: &'a [Vec<f64>], max_iterations: usize) -> Result<Self, SonarError> {
+        validate_graph(graph)?;
+        validate_tour(tour, graph.len())?;
+        Ok(TwoOptSolver { graph, tour: tour.to_vec(), max_iterations, iterations: 0, improved: true })
+    }
+}
+
+impl SteppableSolver for TwoOptSolver<'_> {
+    /// One sweep over all edge pairs
+    /// Time complexity: O(n^2)
+    fn step(&mut self) -> &[usize] {
+        if self.is_finished() {
+            return &self.tour;
+        }
+        let graph = self.graph;
+        let current_tour = &mut self.tour;
+        let n = current_tour.len();
+        self.improved = false;
+        self.iterations += 1;
 
         for i in 0..n - 1 {
             for j in i + 2..n {
@@ -28,13 +58,124 @@ pub fn improve(tour: &[usize], graph: &[Vec<f64>], max_iterations: usize) -> Vec
                 if new_distance < current_distance {
                     // Reverse the segment between i+1 and j
                     current_tour[i + 1..=j].reverse();
+                    self.improved = true;
+                }
+            }
+        }
+
+        &self.tour
+    }
+
+    fn tour(&self) -> &[usize] {
+        &self.tour
+    }
+
+    fn is_finished(&self) -> bool {
+        !self.improved || self.iterations >= self.max_iterations
+    }
+}
+
+/// 2-opt restricted to moves whose both removed edges start within `radius`
+/// tour positions of position `center`, repeated until no move improves
+/// For repairing a tour in place after a local change, such as an inserted
+/// city or a seam where two sub-tours were joined, where a full sweep would
+/// mostly re-examine edges that cannot have changed. Reversals stay inside the
+/// window, so cities outside it keep their positions. Only the window's cities
+/// are checked against the matrix; the whole tour and matrix are validated in
+/// debug builds alone, as that would cost O(n^2) per repair.
+/// Time complexity: O(radius^2) per pass
+pub fn improve_window(
+    tour: &mut [usize],
+    graph: &[Vec<f64>],
+    center: usize,
+    radius: usize,
+) -> Result<(), SonarError> {
+    validate_size(tour.len(), graph)?;
+    let n = tour.len();
+    if center >= n {
+        return Err(SonarError::InvalidParameter { name: "center", reason: "must be a tour position" });
+    }
+    debug_assert!(validate_graph(graph).is_ok() && validate_tour(tour, n).is_ok());
+    if n < 4 {
+        return Ok(());
+    }
+    let window = radius.saturating_mul(2).saturating_add(1).min(n);
+    let start = if window == n { 0 } else { (center + n - radius % n) % n };
+    let at = |k: usize| (start + k) % n;
+    for k in 0..=window {
+        let city = tour[at(k)];
+        if city >= n {
+            return Err(SonarError::InvalidTour);
+        }
+        if graph[city].len() != n {
+            return Err(SonarError::NonSquareMatrix { row: city, len: graph[city].len(), expected: n });
+        }
+    }
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for a in 0..window {
+            for b in a + 2..window {
+                // With the whole tour in the window these two edges are adjacent
+                if window == n && a == 0 && b == n - 1 {
+                    continue;
+                }
+                let (i, i_next, j, j_next) = (tour[at(a)], tour[at(a + 1)], tour[at(b)], tour[at(b + 1)]);
+                let delta = graph[i][j] + graph[i_next][j_next] - graph[i][i_next] - graph[j][j_next];
+                if delta < -1e-12 {
+                    // Reverse positions a+1..=b of the window
+                    let (mut lo, mut hi) = (a + 1, b);
+                    while lo < hi {
+                        tour.swap(at(lo), at(hi));
+                        lo += 1;
+                        hi -= 1;
+                    }
                     improved = true;
                 }
             }
         }
     }
+    Ok(())
+}
 
-    current_tour
+/// Apply 2-opt improvement on any `Tour` representation
+/// Same neighbourhood as `improve`, but moves are applied through `Tour::reverse`,
+/// so a `TwoLevelList` makes each reversal O(sqrt n) instead of O(n)
+/// Time complexity: O(n^2) evaluations per iteration
+pub fn improve_tour<T: Tour>(tour: &mut T, graph: &[Vec<f64>], max_iterations: usize) -> Result<(), SonarError> {
+    validate_graph(graph)?;
+    validate_size(tour.len(), graph)?;
+
+    let n = tour.len();
+    let mut improved = true;
+    let mut iterations = 0;
+
+    while improved && iterations < max_iterations {
+        improved = false;
+        iterations += 1;
+
+        for a in 0..n {
+            for c in 0..n {
+                let a_next = tour.next(a);
+                let c_next = tour.next(c);
+                if c == a || c == a_next || c_next == a {
+                    continue;
+                }
+
+                let current_distance = graph[a][a_next] + graph[c][c_next];
+                let new_distance = graph[a][c] + graph[a_next][c_next];
+
+                if new_distance < current_distance - 1e-12 {
+                    // a -> a_next ... c -> c_next becomes a -> c ... a_next -> c_next
+                    tour.reverse(a_next, c);
+                    improved = true;
+                }
+            }
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -55,11 +196,71 @@ mod tests {
 
         // Suboptimal tour with crossing edges
         let suboptimal = vec![0, 2, 1, 3];
-        let improved = improve(&suboptimal, &graph, 100);
+        let improved = improve(&suboptimal, &graph, 100).unwrap();
 
         let original_length = calculate_tour_length(&suboptimal, &graph);
         let improved_length = calculate_tour_length(&improved, &graph);
 
         assert!(improved_length <= original_length);
     }
+
+    #[test]
+    fn test_stepping_matches_improve() {
+        use crate::utils::generate_normalized_points;
+
+        let points = generate_normalized_points(60, 20, 3);
+        let graph = create_distance_matrix(&points);
+        let initial: Vec<usize> = (0..points.len()).collect();
+
+        let mut solver = TwoOptSolver::new(&initial, &graph, 100).unwrap();
+        let mut last_length = calculate_tour_length(&initial, &graph);
+        while !solver.is_finished() {
+            let length = calculate_tour_length(solver.step(), &graph);
+            assert!(length <= last_length);
+            last_length = length;
+        }
+        assert_eq!(solver.tour(), improve(&initial, &graph, 100).unwrap());
+    }
+
+    #[test]
+    fn test_window_repair_only_touches_the_window() {
+        use crate::utils::generate_normalized_points;
+
+        let points = generate_normalized_points(100, 40, 9);
+        let graph = create_distance_matrix(&points);
+        let good = improve(&(0..100).collect::<Vec<_>>(), &graph, 1_000).unwrap();
+        // Scramble a stretch that wraps around the end of the tour
+        let mut damaged = good.clone();
+        for (a, b) in [(97, 2), (98, 0), (99, 3)] {
+            damaged.swap(a, b);
+        }
+        let mut repaired = damaged.clone();
+        improve_window(&mut repaired, &graph, 0, 8).unwrap();
+        assert!(calculate_tour_length(&repaired, &graph) < calculate_tour_length(&damaged, &graph));
+        assert_eq!(repaired[9..92], damaged[9..92]);
+
+        // A window over the whole tour is plain 2-opt to convergence
+        let mut full = damaged.clone();
+        improve_window(&mut full, &graph, 50, 100).unwrap();
+        let full_length = calculate_tour_length(&full, &graph);
+        assert!(calculate_tour_length(&improve(&full, &graph, 10).unwrap(), &graph) > full_length - 1e-9);
+        assert!(improve_window(&mut damaged, &graph, 100, 8).is_err());
+    }
+
+    #[test]
+    fn test_two_opt_on_two_level_list() {
+        use crate::tour::{Tour, TwoLevelList};
+        use crate::utils::generate_normalized_points;
+
+        let points = generate_normalized_points(150, 40, 5);
+        let graph = create_distance_matrix(&points);
+        let initial: Vec<usize> = (0..points.len()).collect();
+
+        let mut list = TwoLevelList::new(&initial);
+        improve_tour(&mut list, &graph, 100).unwrap();
+        let improved = list.to_vec();
+
+        assert_eq!(improved.len(), points.len());
+        assert!(calculate_tour_length(&improved, &graph) < calculate_tour_length(&initial, &graph));
+    }
 }
@@ -1,84 +1,311 @@
 //! Shared utilities for TSP algorithms
 
-use std::f64::consts::PI;
+use alloc::{collections::BinaryHeap, vec, vec::Vec, string::String, format};
+use crate::error::SonarError;
+use crate::math;
+use crate::scalar::Scalar;
+// Moved to `rng`; kept here for existing callers
+pub use crate::rng::Lcg;
+use crate::rng::SonarRng;
+use core::f64::consts::PI;
 
 /// A point with x, y coordinates, angle from center, and id
+/// Coordinates are `f64` unless another `Scalar` is chosen
 #[derive(Debug, Clone)]
-pub struct Point {
-    pub x: f64,
-    pub y: f64,
-    pub angle: f64,
+pub struct Point<T = f64> {
+    pub x: T,
+    pub y: T,
+    pub angle: T,
     pub id: usize,
 }
 
+/// Dense matrix of pairwise distances, `graph[i][j]` from city i to city j
+pub type DistanceMatrix<T = f64> = Vec<Vec<T>>;
+
 /// Calculate Euclidean distance between two points
-pub fn distance(start: &Point, end: &Point) -> f64 {
-    let dx = end.x - start.x;
-    let dy = end.y - start.y;
-    (dx * dx + dy * dy).sqrt()
+pub fn distance<T: Scalar>(start: &Point<T>, end: &Point<T>) -> T {
+    distance_coords(start.x, start.y, end.x, end.y)
 }
 
 /// Calculate distance between two points given coordinates
-pub fn distance_coords(x1: f64, y1: f64, x2: f64, y2: f64) -> f64 {
+pub fn distance_coords<T: Scalar>(x1: T, y1: T, x2: T, y2: T) -> T {
     let dx = x2 - x1;
     let dy = y2 - y1;
     (dx * dx + dy * dy).sqrt()
 }
 
+/// Side of the square tiles `symmetric_matrix` fills at a time
+/// 64 x 64 f64 entries (32KB) plus their mirror stay resident in L1/L2
+const MATRIX_TILE: usize = 64;
+
+/// Below this many cities the matrix is built on one thread; rayon's task
+/// overhead would outweigh the work
+#[cfg(feature = "std")]
+const PARALLEL_MATRIX_MIN: usize = 256;
+
 /// Create a distance matrix from an array of points
-pub fn create_distance_matrix(points: &[Point]) -> Vec<Vec<f64>> {
-    let n = points.len();
-    let mut graph = vec![vec![0.0; n]; n];
+/// Time complexity: O(n^2), n * (n - 1) / 2 distance computations
+pub fn create_distance_matrix<T: Scalar>(points: &[Point<T>]) -> DistanceMatrix<T> {
+    symmetric_matrix(points.len(), |i, j| distance(&points[i], &points[j]))
+}
+
+/// Build the matrix of a symmetric distance with a zero diagonal
+/// `distance(i, j)` is only called for i < j; each value is mirrored into the
+/// lower triangle. With `std`, rows of the upper triangle are computed in
+/// parallel and the mirror is copied in parallel too; otherwise the work is
+/// done tile by tile so the column-wise mirror writes stay within a cache-sized
+/// block instead of striding across the whole matrix.
+/// Time complexity: O(n^2), n * (n - 1) / 2 calls to `distance`
+pub fn symmetric_matrix<T: Scalar, F: Fn(usize, usize) -> T + Sync>(n: usize, distance: F) -> DistanceMatrix<T> {
+    let mut graph = vec![vec![T::zero(); n]; n];
+    #[cfg(feature = "std")]
+    if n >= PARALLEL_MATRIX_MIN {
+        use rayon::prelude::*;
+        graph.par_iter_mut().enumerate().for_each(|(i, row)| {
+            for j in i + 1..n {
+                row[j] = distance(i, j);
+            }
+        });
+        mirror_upper_triangle(&mut graph, 0);
+        return graph;
+    }
+    for row_start in (0..n).step_by(MATRIX_TILE) {
+        let row_end = (row_start + MATRIX_TILE).min(n);
+        for col_start in (row_start..n).step_by(MATRIX_TILE) {
+            let col_end = (col_start + MATRIX_TILE).min(n);
+            for i in row_start..row_end {
+                for j in col_start.max(i + 1)..col_end {
+                    let d = distance(i, j);
+                    graph[i][j] = d;
+                    graph[j][i] = d;
+                }
+            }
+        }
+    }
+    graph
+}
+
+/// Copy the upper triangle of `rows`, the matrix rows from `start` on, into
+/// their lower triangle; columns before `start` are left alone
+/// The lower half of the rows takes the block below the top half's diagonal
+/// block from the top rows, in parallel, then both halves recurse
+/// independently.
+#[cfg(feature = "std")]
+fn mirror_upper_triangle<T: Scalar>(rows: &mut [Vec<T>], start: usize) {
+    use rayon::prelude::*;
+    if rows.len() <= MATRIX_TILE {
+        for i in 1..rows.len() {
+            for j in 0..i {
+                let d = rows[j][start + i];
+                rows[i][start + j] = d;
+            }
+        }
+        return;
+    }
+    let (top, bottom) = rows.split_at_mut(rows.len() / 2);
+    let middle = start + top.len();
+    bottom.par_chunks_mut(MATRIX_TILE).enumerate().for_each(|(chunk, block)| {
+        let first = middle + chunk * MATRIX_TILE;
+        for col_start in (0..top.len()).step_by(MATRIX_TILE) {
+            let col_end = (col_start + MATRIX_TILE).min(top.len());
+            for (k, row) in block.iter_mut().enumerate() {
+                for j in col_start..col_end {
+                    row[start + j] = top[j][first + k];
+                }
+            }
+        }
+    });
+    rayon::join(|| mirror_upper_triangle(top, start), || mirror_upper_triangle(bottom, middle));
+}
+
+/// Mark the edges set in `forbidden` as absent by making them infinite
+/// Obstacles and turn restrictions can be modelled this way. The solvers
+/// assume symmetric costs, so an edge forbidden in either direction is
+/// forbidden in both.
+/// Time complexity: O(n^2)
+pub fn forbid_edges(graph: &mut [Vec<f64>], forbidden: &[Vec<bool>]) -> Result<(), SonarError> {
+    let n = graph.len();
+    if forbidden.len() != n {
+        return Err(SonarError::SizeMismatch { expected: n, found: forbidden.len() });
+    }
+    if let Some((row, mask)) = forbidden.iter().enumerate().find(|(_, mask)| mask.len() != n) {
+        return Err(SonarError::NonSquareMatrix { row, len: mask.len(), expected: n });
+    }
     for i in 0..n {
         for j in 0..n {
-            if i != j {
-                graph[i][j] = distance(&points[i], &points[j]);
+            if forbidden[i][j] {
+                graph[i][j] = f64::INFINITY;
+                graph[j][i] = f64::INFINITY;
             }
         }
     }
-    graph
+    Ok(())
 }
 
 /// Calculate the total length of a tour
-pub fn calculate_tour_length(tour: &[usize], graph: &[Vec<f64>]) -> f64 {
+pub fn calculate_tour_length<T: Scalar>(tour: &[usize], graph: &[Vec<T>]) -> T {
     let n = tour.len();
-    let mut total_distance = 0.0;
+    let mut total_distance = T::zero();
     for i in 0..n {
-        total_distance += graph[tour[i]][tour[(i + 1) % n]];
+        total_distance = total_distance + graph[tour[i]][tour[(i + 1) % n]];
     }
     total_distance
 }
 
-/// Calculate the Minimum Spanning Tree weight using Prim's algorithm
-/// Used as a lower bound for TSP
-pub fn calculate_mst(graph: &[Vec<f64>]) -> f64 {
-    let n = graph.len();
-    let mut selected = vec![false; n];
-    selected[0] = true;
-    let mut edge_count = 0;
-    let mut mst_weight = 0.0;
-
-    while edge_count < n - 1 {
-        let mut min = f64::INFINITY;
-        let mut y = 0;
-
-        for i in 0..n {
-            if selected[i] {
-                for j in 0..n {
-                    if !selected[j] && graph[i][j] > 0.0 && graph[i][j] < min {
-                        min = graph[i][j];
-                        y = j;
-                    }
+/// Sum values with Kahan compensated summation
+/// The rounding error stays bounded independently of the number of values
+pub fn compensated_sum<T: Scalar>(values: impl IntoIterator<Item = T>) -> T {
+    let mut total = T::zero();
+    let mut compensation = T::zero();
+    for value in values {
+        let value = value - compensation;
+        let next = total + value;
+        compensation = (next - total) - value;
+        // An infinite value makes the compensation NaN; the total is infinite anyway
+        if compensation.partial_cmp(&compensation).is_none() {
+            compensation = T::zero();
+        }
+        total = next;
+    }
+    total
+}
+
+/// Calculate the total length of a tour with compensated summation
+/// Naive summation over 10^5-10^6 edges drifts by more than the difference
+/// between close results, so use this whenever lengths from different
+/// algorithms are compared.
+/// Time complexity: O(n)
+pub fn calculate_tour_length_compensated<T: Scalar>(tour: &[usize], graph: &[Vec<T>]) -> T {
+    let n = tour.len();
+    compensated_sum((0..n).map(|i| graph[tour[i]][tour[(i + 1) % n]]))
+}
+
+/// A minimum spanning tree (or forest): its weight and its edges as
+/// `(parent, child)` pairs, in the order the children joined
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanningTree {
+    pub weight: f64,
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// Minimum spanning tree of the complete graph, by Prim's algorithm rooted at city 0
+/// Time complexity: O(n^2)
+pub fn minimum_spanning_tree(graph: &[Vec<f64>]) -> SpanningTree {
+    minimum_spanning_tree_by(graph.len(), |i, j| graph[i][j])
+}
+
+/// Minimum spanning tree of the complete graph on `n` cities with edge weights
+/// `cost(i, j)`, by Prim's algorithm rooted at city 0
+/// Every city outside the tree keeps its cheapest edge into it, so each step
+/// is a single O(n) scan; on a dense graph this beats any heap.
+/// Time complexity: O(n^2) calls to `cost`
+pub fn minimum_spanning_tree_by(n: usize, cost: impl Fn(usize, usize) -> f64) -> SpanningTree {
+    let mut tree = SpanningTree { weight: 0.0, edges: Vec::with_capacity(n.saturating_sub(1)) };
+    let mut in_tree = vec![false; n];
+    // Cheapest known edge into the tree for every city: (cost, tree end)
+    let mut cheapest = vec![(f64::INFINITY, 0); n];
+    if n == 0 {
+        return tree;
+    }
+    cheapest[0].0 = 0.0;
+    for step in 0..n {
+        let u = (0..n)
+            .filter(|&k| !in_tree[k])
+            .min_by(|&a, &b| cheapest[a].0.total_cmp(&cheapest[b].0))
+            .unwrap();
+        in_tree[u] = true;
+        if step > 0 {
+            tree.weight += cheapest[u].0;
+            tree.edges.push((cheapest[u].1, u));
+        }
+        for v in 0..n {
+            if !in_tree[v] {
+                let c = cost(u, v);
+                if c < cheapest[v].0 {
+                    cheapest[v] = (c, u);
                 }
             }
         }
+    }
+    tree
+}
+
+/// A city offered to the tree at `cost` through an edge from `parent`
+/// Ordered so `BinaryHeap` pops the cheapest offer first, ties by city.
+struct Offer {
+    cost: f64,
+    city: usize,
+    parent: usize,
+}
+
+impl Ord for Offer {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        other.cost.total_cmp(&self.cost).then(other.city.cmp(&self.city))
+    }
+}
+
+impl PartialOrd for Offer {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-        mst_weight += min;
-        selected[y] = true;
-        edge_count += 1;
+impl PartialEq for Offer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
     }
+}
+
+impl Eq for Offer {}
 
-    mst_weight
+/// Minimum spanning tree over candidate edges only, by Prim's algorithm with a binary heap
+/// `candidates[i]` lists the cities i has an edge to, such as its k nearest
+/// neighbors; every edge is usable in both directions and weighs
+/// `distance(i, j)`, so no O(n^2) matrix is needed. If the candidate graph is
+/// disconnected, the result is a minimum spanning forest, one tree per component.
+/// Time complexity: O(E log E) for E candidate edges
+pub fn minimum_spanning_tree_candidates(
+    candidates: &[Vec<usize>],
+    distance: impl Fn(usize, usize) -> f64,
+) -> SpanningTree {
+    let n = candidates.len();
+    let mut adjacency = candidates.to_vec();
+    for (i, list) in candidates.iter().enumerate() {
+        for &j in list {
+            adjacency[j].push(i);
+        }
+    }
+    let mut tree = SpanningTree { weight: 0.0, edges: Vec::with_capacity(n.saturating_sub(1)) };
+    let mut in_tree = vec![false; n];
+    let mut heap = BinaryHeap::new();
+    for root in 0..n {
+        if in_tree[root] {
+            continue;
+        }
+        heap.push(Offer { cost: 0.0, city: root, parent: root });
+        while let Some(Offer { cost, city, parent }) = heap.pop() {
+            if in_tree[city] {
+                continue;
+            }
+            in_tree[city] = true;
+            if city != parent {
+                tree.weight += cost;
+                tree.edges.push((parent, city));
+            }
+            for &next in &adjacency[city] {
+                if !in_tree[next] {
+                    heap.push(Offer { cost: distance(city, next), city: next, parent: city });
+                }
+            }
+        }
+    }
+    tree
+}
+
+/// Weight of the minimum spanning tree, used as a lower bound for TSP
+/// Time complexity: O(n^2)
+pub fn calculate_mst(graph: &[Vec<f64>]) -> f64 {
+    minimum_spanning_tree(graph).weight
 }
 
 /// Calculate efficiency as (optimal_length / solution_length) * 100
@@ -89,71 +316,193 @@ pub fn calculate_efficiency(solution_length: f64, optimal_length: f64) -> f64 {
     (optimal_length / solution_length) * 100.0
 }
 
-/// Simple LCG random number generator for reproducibility
-pub struct Lcg {
-    seed: u64,
+/// Radius of the disc the generated points are drawn from, around (0.5, 0.5)
+const POINTS_RADIUS: f64 = 0.45;
+
+/// Whether grid cell `(gx, gy)` has its centre in the disc
+fn in_disc(gx: usize, gy: usize, grid_step: f64) -> bool {
+    let dx = (gx as f64 + 0.5) * grid_step - 0.5;
+    let dy = (gy as f64 + 0.5) * grid_step - 0.5;
+    math::sqrt(dx * dx + dy * dy) <= POINTS_RADIUS
 }
 
-impl Lcg {
-    pub fn new(seed: u64) -> Self {
-        Lcg { seed }
+/// Grid cell `(gx, gy)` as `(x, y, angle)` if its centre lies in the disc
+fn disc_position(gx: usize, gy: usize, grid_step: f64) -> Option<(f64, f64, f64)> {
+    if !in_disc(gx, gy, grid_step) {
+        return None;
     }
-
-    pub fn next(&mut self) -> f64 {
-        self.seed = (self.seed.wrapping_mul(1103515245).wrapping_add(12345)) % (1 << 31);
-        self.seed as f64 / (1u64 << 31) as f64
+    let x = (gx as f64 + 0.5) * grid_step;
+    let y = (gy as f64 + 0.5) * grid_step;
+    let mut angle = math::atan2(y - 0.5, x - 0.5);
+    if angle < 0.0 {
+        angle += 2.0 * PI;
     }
+    Some((x, y, angle))
+}
+
+/// How `generate_normalized_points_version` picks grid positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PointsVersion {
+    /// Shuffle every position in the disc and take the first n, as the JS
+    /// generator does; O(grid_size^2) time and memory
+    #[default]
+    V1,
+    /// Draw n distinct positions by sparse index sampling without listing the
+    /// others; O(grid_size + n log n) time and O(grid_size + n) memory. Same
+    /// positions are possible, but a seed gives a different sample than `V1`.
+    V2,
 }
 
 /// Generate random points on a normalized grid (0-1 range)
 pub fn generate_normalized_points(num_points: usize, grid_size: usize, seed: u64) -> Vec<Point> {
+    generate_normalized_points_version(num_points, grid_size, seed, PointsVersion::V1)
+}
+
+/// `generate_normalized_points` with the sampling of the given version
+pub fn generate_normalized_points_version(
+    num_points: usize,
+    grid_size: usize,
+    seed: u64,
+    version: PointsVersion,
+) -> Vec<Point> {
     let mut rng = Lcg::new(seed);
-    let center = 0.5;
-    let max_radius = 0.45;
     let grid_step = 1.0 / grid_size as f64;
+    let positions = match version {
+        PointsVersion::V1 => {
+            // Generate all valid grid positions within the circular area
+            let mut valid_positions: Vec<(f64, f64, f64)> = Vec::new();
+            for gx in 0..grid_size {
+                for gy in 0..grid_size {
+                    valid_positions.extend(disc_position(gx, gy, grid_step));
+                }
+            }
 
-    // Generate all valid grid positions within the circular area
-    let mut valid_positions: Vec<(f64, f64, f64)> = Vec::new();
+            // Fisher-Yates shuffle
+            let n = valid_positions.len();
+            for i in (1..n).rev() {
+                valid_positions.swap(i, rng.below(i + 1));
+            }
+            valid_positions.truncate(num_points);
+            valid_positions
+        }
+        PointsVersion::V2 => sample_disc_positions(num_points, grid_size, &mut rng),
+    };
+
+    positions
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (x, y, angle))| Point { x, y, angle, id: idx })
+        .collect()
+}
 
+/// Up to `count` distinct disc positions in random order, for `PointsVersion::V2`
+/// The positions in a column form one run of rows, so each column is stored as
+/// its first row and the number of positions before it; a partial Fisher-Yates
+/// shuffle over position indices keeps only the displaced entries in a map.
+fn sample_disc_positions(count: usize, grid_size: usize, rng: &mut Lcg) -> Vec<(f64, f64, f64)> {
+    let grid_step = 1.0 / grid_size as f64;
+    let inside = |gx: usize, gy: usize| in_disc(gx, gy, grid_step);
+    let mut first_rows = Vec::with_capacity(grid_size);
+    let mut starts = Vec::with_capacity(grid_size + 1);
+    starts.push(0);
     for gx in 0..grid_size {
-        for gy in 0..grid_size {
-            let x = (gx as f64 + 0.5) * grid_step;
-            let y = (gy as f64 + 0.5) * grid_step;
-            let dx = x - center;
-            let dy = y - center;
-            let dist_from_center = (dx * dx + dy * dy).sqrt();
-
-            if dist_from_center <= max_radius {
-                let mut angle = dy.atan2(dx);
-                if angle < 0.0 {
-                    angle += 2.0 * PI;
-                }
-                valid_positions.push((x, y, angle));
-            }
+        // Estimate the run from the circle's chord, then settle it exactly
+        let dx = (gx as f64 + 0.5) * grid_step - 0.5;
+        let half = math::sqrt((POINTS_RADIUS * POINTS_RADIUS - dx * dx).max(0.0)) / grid_step;
+        let middle = grid_size as f64 / 2.0 - 0.5;
+        // Rows nearest the centre; a column missing them misses the disc
+        let (near, far) = ((grid_size - 1) / 2, grid_size / 2);
+        if !inside(gx, near) && !inside(gx, far) {
+            first_rows.push(0);
+            starts.push(starts[gx]);
+            continue;
+        }
+        let mut low = ((middle - half).max(0.0) as usize).min(grid_size);
+        while low > 0 && inside(gx, low - 1) {
+            low -= 1;
+        }
+        while low < grid_size && !inside(gx, low) {
+            low += 1;
+        }
+        let mut high = low;
+        while high < grid_size && inside(gx, high) {
+            high += 1;
         }
+        first_rows.push(low);
+        starts.push(starts[gx] + high - low);
     }
 
-    // Fisher-Yates shuffle
-    let n = valid_positions.len();
-    for i in (1..n).rev() {
-        let j = (rng.next() * (i + 1) as f64) as usize;
-        valid_positions.swap(i, j);
+    let total = starts[grid_size];
+    let count = count.min(total);
+    let mut displaced = alloc::collections::BTreeMap::new();
+    let mut positions = Vec::with_capacity(count);
+    for i in 0..count {
+        let j = i + rng.below(total - i);
+        let at_i = displaced.remove(&i).unwrap_or(i);
+        let index = if j == i { at_i } else { displaced.insert(j, at_i).unwrap_or(j) };
+        let gx = starts.partition_point(|&start| start <= index) - 1;
+        positions.push(disc_position(gx, first_rows[gx] + index - starts[gx], grid_step).unwrap());
     }
+    positions
+}
 
-    // Select num_points from shuffled positions
-    let count = num_points.min(valid_positions.len());
-    valid_positions
-        .into_iter()
-        .take(count)
+/// Rescale points into the unit square (aspect ratio preserved, centred on 0.5, 0.5)
+/// and recompute their angles around the centre, matching `generate_normalized_points`
+pub fn normalize_points(points: &[Point]) -> Vec<Point> {
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for p in points {
+        min_x = min_x.min(p.x);
+        min_y = min_y.min(p.y);
+        max_x = max_x.max(p.x);
+        max_y = max_y.max(p.y);
+    }
+    let extent = (max_x - min_x).max(max_y - min_y);
+    let scale = if extent > 0.0 { 1.0 / extent } else { 1.0 };
+    let offset_x = 0.5 - (max_x - min_x) * scale / 2.0;
+    let offset_y = 0.5 - (max_y - min_y) * scale / 2.0;
+
+    points
+        .iter()
         .enumerate()
-        .map(|(idx, (x, y, angle))| Point { x, y, angle, id: idx })
+        .map(|(idx, p)| {
+            let x = (p.x - min_x) * scale + offset_x;
+            let y = (p.y - min_y) * scale + offset_y;
+            let mut angle = math::atan2(y - 0.5, x - 0.5);
+            if angle < 0.0 {
+                angle += 2.0 * PI;
+            }
+            Point { x, y, angle, id: idx }
+        })
         .collect()
 }
 
+/// Quote and escape a string for hand-written JSON output
+pub fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// The tests build `f64` points unless they name another scalar
+    type Point = super::Point<f64>;
+
     #[test]
     fn test_distance() {
         let p1 = Point { x: 0.0, y: 0.0, angle: 0.0, id: 0 };
@@ -174,4 +523,114 @@ mod tests {
         let length = calculate_tour_length(&tour, &graph);
         assert!((length - 4.0).abs() < 0.0001);
     }
+
+    #[test]
+    fn test_symmetric_matrix_matches_every_pair() {
+        // Sizes around the parallel threshold and the tile edges
+        for n in [0, 1, 65, 255, 256, 301, 700] {
+            let points = generate_normalized_points(n, 40, n as u64);
+            let graph = create_distance_matrix(&points);
+            assert_eq!(graph.len(), n);
+            for (i, row) in graph.iter().enumerate() {
+                for (j, &d) in row.iter().enumerate() {
+                    let expected = if i == j { 0.0 } else { distance(&points[i], &points[j]) };
+                    assert_eq!(d, expected, "n={} ({}, {})", n, i, j);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_spanning_trees_agree() {
+        let points = generate_normalized_points(60, 20, 9);
+        let graph = create_distance_matrix(&points);
+        let dense = minimum_spanning_tree(&graph);
+        assert_eq!(dense.edges.len(), 59);
+        let edge_sum: f64 = dense.edges.iter().map(|&(a, b)| graph[a][b]).sum();
+        assert!((edge_sum - dense.weight).abs() < 1e-9);
+        assert_eq!(calculate_mst(&graph), dense.weight);
+
+        let all: Vec<Vec<usize>> = (0..60).map(|i| (0..60).filter(|&j| j != i).collect()).collect();
+        let sparse = minimum_spanning_tree_candidates(&all, |i, j| graph[i][j]);
+        assert_eq!(sparse.edges.len(), 59);
+        assert!((sparse.weight - dense.weight).abs() < 1e-9);
+
+        // Two components without a candidate edge between them
+        let split = vec![vec![1], vec![], vec![3], vec![]];
+        let forest = minimum_spanning_tree_candidates(&split, |_, _| 1.0);
+        assert_eq!((forest.edges.len(), forest.weight), (2, 2.0));
+        assert_eq!(minimum_spanning_tree(&[]).edges, []);
+    }
+
+    #[test]
+    fn test_compensated_tour_length_does_not_drift() {
+        // One long edge followed by many edges too short to register naively in f32
+        let n = 10_001;
+        let mut graph = vec![vec![0.0f32; n]; n];
+        graph[0][1] = 1.0e4;
+        for i in 1..n {
+            graph[i][(i + 1) % n] = 1.0e-4;
+        }
+        let tour: Vec<usize> = (0..n).collect();
+        assert_eq!(calculate_tour_length(&tour, &graph), 1.0e4);
+        assert!((calculate_tour_length_compensated(&tour, &graph) - 10001.0).abs() < 1e-2);
+
+        let unreachable = vec![vec![0.0, f64::INFINITY], vec![1.0, 0.0]];
+        assert_eq!(calculate_tour_length_compensated(&[0, 1], &unreachable), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_tour_length_in_f32() {
+        let square: Vec<super::Point<f32>> = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+            .iter()
+            .enumerate()
+            .map(|(id, &(x, y))| super::Point { x, y, angle: 0.0, id })
+            .collect();
+        let graph: DistanceMatrix<f32> = create_distance_matrix(&square);
+        assert!((graph[0][2] - 2f32.sqrt()).abs() < 1e-6);
+        assert_eq!(calculate_tour_length(&[0, 1, 2, 3], &graph), 4.0f32);
+    }
+
+    #[test]
+    fn test_distance_matrix_spanning_several_tiles() {
+        let points = generate_normalized_points(150, 20, 4);
+        let graph = create_distance_matrix(&points);
+        for i in 0..points.len() {
+            assert_eq!(graph[i][i], 0.0);
+            for j in 0..points.len() {
+                assert_eq!(graph[i][j], graph[j][i]);
+                if i < j {
+                    assert_eq!(graph[i][j], distance(&points[i], &points[j]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_string_escapes() {
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+    }
+
+    #[test]
+    fn test_sampled_points_cover_the_same_disc() {
+        let key = |p: &Point| ((p.x * 1e9) as i64, (p.y * 1e9) as i64);
+        for grid_size in [7, 20, 41] {
+            // Asking for every position gives both versions the same set
+            let mut all = generate_normalized_points(usize::MAX, grid_size, 3).iter().map(key).collect::<Vec<_>>();
+            let sampled = generate_normalized_points_version(usize::MAX, grid_size, 3, PointsVersion::V2);
+            let mut sampled_keys = sampled.iter().map(key).collect::<Vec<_>>();
+            all.sort();
+            sampled_keys.sort();
+            assert_eq!(all, sampled_keys);
+        }
+
+        let points = generate_normalized_points_version(1000, 2000, 5, PointsVersion::V2);
+        let again = generate_normalized_points_version(1000, 2000, 5, PointsVersion::V2);
+        assert!(points.iter().zip(&again).all(|(a, b)| key(a) == key(b)));
+        let mut keys = points.iter().map(key).collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), 1000);
+        assert!(points.iter().all(|p| ((p.x - 0.5).powi(2) + (p.y - 0.5).powi(2)).sqrt() <= 0.45));
+    }
 }
@@ -0,0 +1,214 @@
+//! WebAssembly bindings - the web demo's algorithms compiled from this crate
+//!
+//! The npm package is the `web/` crate next to this one, built with
+//! `wasm-pack build --target web rust/web`. Points cross the boundary as a
+//! flat `Float64Array` of `x, y, angle` triples, the layout
+//! `generateNormalizedPoints` returns, and tours come back as `Uint32Array`s of point indices. With the same seed the
+//! points, and so every deterministic tour, match the Rust benchmark; the
+//! stochastic methods take their own seed instead of drawing from `Math.random`.
+//! Seeds are `u32`s, so JS passes them as plain numbers rather than `BigInt`s.
+//! Each export is built only with the algorithm families it needs, so a page
+//! that only sweeps can ship `--no-default-features --features web`.
+
+#[cfg(feature = "metaheuristics")]
+use crate::determinism;
+use crate::error::SonarError;
+use crate::fixed;
+#[cfg(feature = "metaheuristics")]
+use crate::genetic::GeneticSolver;
+#[cfg(feature = "metaheuristics")]
+use crate::simulated_annealing::{AnnealingSolver, MoveKind};
+#[cfg(feature = "metaheuristics")]
+use crate::stepwise::SteppableSolver;
+use crate::utils::{self, create_distance_matrix, Point};
+use crate::{angular_sort, sonar_visit};
+#[cfg(feature = "exact")]
+use crate::brute_force;
+#[cfg(feature = "constructive")]
+use crate::{greedy_edge, nearest_neighbor};
+#[cfg(feature = "local-search")]
+use crate::{two_opt, zigzag};
+use wasm_bindgen::prelude::*;
+
+fn js_error(error: SonarError) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+/// Points from `x, y, angle` triples
+fn points_from(coords: &[f64]) -> Result<Vec<Point>, JsError> {
+    if !coords.len().is_multiple_of(3) {
+        return Err(JsError::new("points must be x, y, angle triples"));
+    }
+    Ok(coords
+        .chunks_exact(3)
+        .enumerate()
+        .map(|(id, c)| Point { x: c[0], y: c[1], angle: c[2], id })
+        .collect())
+}
+
+fn tour_from(tour: &[u32]) -> Vec<usize> {
+    tour.iter().map(|&city| city as usize).collect()
+}
+
+fn tour_to(tour: Vec<usize>) -> Vec<u32> {
+    tour.into_iter().map(|city| city as u32).collect()
+}
+
+/// `utils::generate_normalized_points` as `x, y, angle` triples
+#[wasm_bindgen(js_name = generateNormalizedPoints)]
+pub fn generate_normalized_points(num_points: usize, grid_size: usize, seed: u32) -> Vec<f64> {
+    utils::generate_normalized_points(num_points, grid_size, u64::from(seed))
+        .iter()
+        .flat_map(|p| [p.x, p.y, p.angle])
+        .collect()
+}
+
+#[wasm_bindgen(js_name = calculateTourLength)]
+pub fn calculate_tour_length(points: &[f64], tour: &[u32]) -> Result<f64, JsError> {
+    let graph = create_distance_matrix(&points_from(points)?);
+    Ok(utils::calculate_tour_length(&tour_from(tour), &graph))
+}
+
+/// `calculateTourLength` in `fixed::Fixed` arithmetic, bit-identical to the
+/// same length over a native `Fixed` matrix whatever the browser's float
+/// library does
+#[wasm_bindgen(js_name = calculateTourLengthFixed)]
+pub fn calculate_tour_length_fixed(points: &[f64], tour: &[u32]) -> Result<f64, JsError> {
+    let graph = create_distance_matrix(&fixed::to_fixed(&points_from(points)?));
+    Ok(utils::calculate_tour_length(&tour_from(tour), &graph).to_f64())
+}
+
+#[wasm_bindgen(js_name = sonarVisit)]
+pub fn sonar_visit(points: &[f64], grid_size: usize) -> Result<Vec<u32>, JsError> {
+    sonar_visit::generate_tour(&points_from(points)?, grid_size).map(tour_to).map_err(js_error)
+}
+
+#[wasm_bindgen(js_name = angularSort)]
+pub fn angular_sort(points: &[f64]) -> Result<Vec<u32>, JsError> {
+    angular_sort::generate_tour(&points_from(points)?).map(tour_to).map_err(js_error)
+}
+
+#[cfg(feature = "constructive")]
+#[wasm_bindgen(js_name = nearestNeighbor)]
+pub fn nearest_neighbor(points: &[f64], start_city: usize) -> Result<Vec<u32>, JsError> {
+    let points = points_from(points)?;
+    let graph = create_distance_matrix(&points);
+    nearest_neighbor::generate_tour(points.len(), &graph, start_city).map(tour_to).map_err(js_error)
+}
+
+#[cfg(feature = "constructive")]
+#[wasm_bindgen(js_name = greedyEdge)]
+pub fn greedy_edge(points: &[f64]) -> Result<Vec<u32>, JsError> {
+    let points = points_from(points)?;
+    let graph = create_distance_matrix(&points);
+    greedy_edge::generate_tour(points.len(), &graph).map(tour_to).map_err(js_error)
+}
+
+#[cfg(feature = "local-search")]
+#[wasm_bindgen(js_name = twoOpt)]
+pub fn two_opt(points: &[f64], tour: &[u32], max_iterations: usize) -> Result<Vec<u32>, JsError> {
+    let graph = create_distance_matrix(&points_from(points)?);
+    two_opt::improve(&tour_from(tour), &graph, max_iterations).map(tour_to).map_err(js_error)
+}
+
+#[cfg(feature = "local-search")]
+#[wasm_bindgen]
+pub fn zigzag(points: &[f64], tour: &[u32]) -> Result<Vec<u32>, JsError> {
+    let points = points_from(points)?;
+    let graph = create_distance_matrix(&points);
+    zigzag::optimize(&tour_from(tour), &points, &graph).map(tour_to).map_err(js_error)
+}
+
+#[cfg(feature = "metaheuristics")]
+#[wasm_bindgen(js_name = simulatedAnnealing)]
+pub fn simulated_annealing(
+    points: &[f64],
+    tour: &[u32],
+    max_iterations: usize,
+    initial_temperature: f64,
+    cooling_rate: f64,
+    seed: u32,
+) -> Result<Vec<u32>, JsError> {
+    let graph = create_distance_matrix(&points_from(points)?);
+    let moves = [(MoveKind::Reversal, 1.0)];
+    let tour = tour_from(tour);
+    let mut solver = AnnealingSolver::new(
+        &graph,
+        &tour,
+        max_iterations,
+        initial_temperature,
+        cooling_rate,
+        &moves,
+        max_iterations.max(1),
+    )
+    .map_err(js_error)?
+    .with_rng(determinism::seeded(u64::from(seed)));
+    Ok(tour_to(solver.run()))
+}
+
+#[cfg(feature = "metaheuristics")]
+#[wasm_bindgen(js_name = geneticAlgorithm)]
+pub fn genetic_algorithm(
+    points: &[f64],
+    population_size: usize,
+    generations: usize,
+    mutation_rate: f64,
+    seed: u32,
+) -> Result<Vec<u32>, JsError> {
+    let points = points_from(points)?;
+    let graph = create_distance_matrix(&points);
+    let mut solver = GeneticSolver::new(&graph, points.len(), population_size, generations, mutation_rate)
+        .map_err(js_error)?
+        .with_rng(determinism::seeded(u64::from(seed)));
+    Ok(tour_to(solver.run()))
+}
+
+/// Exhaustive search, O(n!); feasible up to about 10 points
+#[cfg(feature = "exact")]
+#[wasm_bindgen(js_name = bruteForce)]
+pub fn brute_force(points: &[f64]) -> Result<Vec<u32>, JsError> {
+    let points = points_from(points)?;
+    let graph = create_distance_matrix(&points);
+    brute_force::brute_force_exact(&graph, points.len()).map(|(tour, _)| tour_to(tour)).map_err(js_error)
+}
+
+/// Held-Karp dynamic programming, O(2^n * n^2); feasible up to `brute_force::HELD_KARP_MAX_N` points
+#[cfg(feature = "exact")]
+#[wasm_bindgen(js_name = heldKarp)]
+pub fn held_karp(points: &[f64]) -> Result<Vec<u32>, JsError> {
+    let points = points_from(points)?;
+    let graph = create_distance_matrix(&points);
+    brute_force::held_karp(&graph, points.len()).map(|(tour, _)| tour_to(tour)).map_err(js_error)
+}
+
+#[cfg(all(test, feature = "local-search", feature = "exact", feature = "metaheuristics"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings_match_native_calls() {
+        let points = generate_normalized_points(9, 10, 2);
+        let native = utils::generate_normalized_points(9, 10, 2);
+        assert_eq!(points.len(), 27);
+        let graph = create_distance_matrix(&native);
+
+        let tour = sonar_visit(&points, 10).unwrap();
+        assert_eq!(tour_from(&tour), sonar_visit::generate_tour(&native, 10).unwrap());
+        let improved = two_opt(&points, &tour, 100).unwrap();
+        assert_eq!(tour_from(&improved), two_opt::improve(&tour_from(&tour), &graph, 100).unwrap());
+
+        let fixed_length = calculate_tour_length_fixed(&points, &tour).unwrap();
+        assert!((fixed_length - calculate_tour_length(&points, &tour).unwrap()).abs() < 1e-8);
+
+        let optimal = held_karp(&points).unwrap();
+        let optimal_length = calculate_tour_length(&points, &optimal).unwrap();
+        let exact_length = calculate_tour_length(&points, &brute_force(&points).unwrap()).unwrap();
+        assert!((optimal_length - exact_length).abs() < 1e-9);
+
+        // Seeded metaheuristics repeat themselves
+        let annealed = simulated_annealing(&points, &tour, 500, 1.0, 0.999, 7).unwrap();
+        assert_eq!(annealed, simulated_annealing(&points, &tour, 500, 1.0, 0.999, 7).unwrap());
+        let evolved = genetic_algorithm(&points, 10, 5, 0.1, 3).unwrap();
+        assert_eq!(evolved, genetic_algorithm(&points, 10, 5, 0.1, 3).unwrap());
+    }
+}
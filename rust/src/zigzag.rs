@@ -1,5 +1,6 @@
 //! Zigzag local optimization algorithm
 
+use crate::error::{validate_points, validate_tour, SonarError};
 use crate::utils::Point;
 
 /// Calculate distance between two points
@@ -28,7 +29,15 @@ fn should_zigzag(i: usize, pts: &[Point]) -> bool {
 /// Apply zigzag optimization on a tour
 /// Checks if swapping adjacent point pairs reduces path length
 /// Time complexity: O(n)
-pub fn optimize(tour_indices: &[usize], points: &[Point], _graph: &[Vec<f64>]) -> Vec<usize> {
+pub fn optimize(tour_indices: &[usize], points: &[Point], _graph: &[Vec<f64>]) -> Result<Vec<usize>, SonarError> {
+    validate_points(points)?;
+    validate_tour(tour_indices, points.len())?;
+
+    // The pairwise sweep below starts at position 1 and would drop a lone city
+    if tour_indices.len() < 2 {
+        return Ok(tour_indices.to_vec());
+    }
+
     // Convert tour indices to points in tour order
     let tour_points: Vec<Point> = tour_indices
         .iter()
@@ -58,10 +67,10 @@ pub fn optimize(tour_indices: &[usize], points: &[Point], _graph: &[Vec<f64>]) -
 
     // Remove duplicates while preserving order
     let mut seen = std::collections::HashSet::new();
-    new_tour
+    Ok(new_tour
         .into_iter()
         .filter(|&id| seen.insert(id))
-        .collect()
+        .collect())
 }
 
 #[cfg(test)]
@@ -80,8 +89,8 @@ mod tests {
             Point { x: 0.0, y: 1.0, angle: PI / 2.0, id: 3 },
         ];
         let graph = create_distance_matrix(&points);
-        let angular_tour = angular_sort::generate_tour(&points);
-        let zigzag_tour = optimize(&angular_tour, &points, &graph);
+        let angular_tour = angular_sort::generate_tour(&points).unwrap();
+        let zigzag_tour = optimize(&angular_tour, &points, &graph).unwrap();
 
         let angular_length = calculate_tour_length(&angular_tour, &graph);
         let zigzag_length = calculate_tour_length(&zigzag_tour, &graph);
@@ -0,0 +1,3 @@
+//! npm package of the web demo's algorithms, see `tsp_algorithms::web`
+
+pub use tsp_algorithms::web::*;
@@ -0,0 +1,42 @@
+/**
+ * Parity driver: runs the JS algorithms on one seeded instance for the Rust
+ * parity check (`benchmark parity`) and prints one line per result:
+ *   <key> <length> <tour indices...>
+ * The first line, `points <x y angle ...>`, lists the generated points.
+ * Run with: node tests/parity-driver.js <numPoints> <gridSize> <seed>
+ * @see https://github.com/konard/sonar
+ */
+
+const TSPUtils = require('../algorithms/utils.js');
+const TwoOpt = require('../algorithms/two-opt.js');
+const Zigzag = require('../algorithms/zigzag.js');
+const SonarVisit = require('../algorithms/sonar-visit.js');
+const AngularSort = require('../algorithms/angular-sort.js');
+const NearestNeighbor = require('../algorithms/nearest-neighbor.js');
+const GreedyEdge = require('../algorithms/greedy-edge.js');
+const BruteForce = require('../algorithms/brute-force.js');
+
+const [numPoints, gridSize, seed] = process.argv.slice(2).map(Number);
+const points = TSPUtils.generateNormalizedPoints(numPoints, gridSize, seed);
+const graph = TSPUtils.createDistanceMatrix(points);
+
+const lines = [`points ${points.map(p => `${p.x} ${p.y} ${p.angle}`).join(' ')}`];
+const emit = (key, tour) => {
+  lines.push(`${key} ${TSPUtils.calculateTourLength(tour, graph)} ${tour.join(' ')}`);
+};
+
+const sonar = SonarVisit.generateTour(points, gridSize);
+emit('sonar-visit', sonar);
+emit('sonar-visit-zigzag', Zigzag.optimize(sonar, points, graph));
+const angular = AngularSort.generateTour(points);
+emit('angular-sort', angular);
+emit('angular-sort-zigzag', Zigzag.optimize(angular, points, graph));
+const nearest = NearestNeighbor.generateTour(points, graph);
+emit('nearest-neighbor', nearest);
+emit('greedy-edge', GreedyEdge.generateTour(points, graph));
+emit('two-opt', TwoOpt.improve(nearest, graph, 100));
+if (points.length <= BruteForce.getMaxFeasibleN()) {
+  emit('brute-force', BruteForce.findOptimal(graph, points.length).tour);
+}
+
+console.log(lines.join('\n'));